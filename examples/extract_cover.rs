@@ -0,0 +1,18 @@
+use mp4ameta::{ImgFmt, Tag};
+
+/// Extract the first cover art image next to the audio file.
+fn main() {
+    let mut tag = Tag::read_from_path("music.m4a").expect("error reading tag");
+
+    let Some(artwork) = tag.take_artwork() else {
+        println!("no cover art found");
+        return;
+    };
+
+    let extension = match artwork.fmt {
+        ImgFmt::Bmp => "bmp",
+        ImgFmt::Jpeg => "jpg",
+        ImgFmt::Png => "png",
+    };
+    std::fs::write(format!("cover.{extension}"), artwork.data).expect("error writing cover art");
+}
@@ -256,8 +256,7 @@ impl Userdata {{
 
     /// Sets the {n} (`{ais}`)
     pub fn set_{vi}(&mut self, {vi}: u16) {{
-        let vec: Vec<u8> = {vi}.to_be_bytes().to_vec();
-        self.set_data({ai}, Data::BeSigned(vec));
+        self.set_data({ai}, Data::u16_be({vi}));
     }}
 
     /// Removes the {n} (`{ais}`).
@@ -299,8 +298,7 @@ impl Userdata {{
 
     /// Sets the {n} (`{ais}`)
     pub fn set_{vi}(&mut self, {vi}: u32) {{
-        let vec: Vec<u8> = {vi}.to_be_bytes().to_vec();
-        self.set_data({ai}, Data::BeSigned(vec));
+        self.set_data({ai}, Data::u32_be({vi}));
     }}
 
     /// Removes the {n} (`{ais}`).
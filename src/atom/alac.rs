@@ -0,0 +1,110 @@
+//! alac atom
+//!
+//! ```md
+//! 4 bytes ?
+//! 2 bytes ?
+//! 2 bytes data reference index
+//! 8 bytes ?
+//! 2 bytes channel count
+//! 2 bytes sample size
+//! 4 bytes ?
+//! 4 bytes sample rate
+//! │
+//! └─ alac atom (magic cookie)
+//!    4 bytes len
+//!    4 bytes ident
+//!    1 byte version
+//!    3 bytes flags
+//!    4 bytes frame length
+//!    1 byte compatible version
+//!    1 byte bit depth
+//!    1 byte pb
+//!    1 byte mb
+//!    1 byte kb
+//!    1 byte channel count
+//!    2 bytes max run
+//!    4 bytes max frame bytes
+//!    4 bytes average bitrate
+//!    4 bytes sample rate
+//! ```
+
+use crate::{ChannelConfig, SampleRate};
+
+use super::*;
+
+pub const HEADER_SIZE: u64 = 28;
+const COOKIE_SIZE: u64 = 28;
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Alac {
+    pub channel_config: Option<ChannelConfig>,
+    pub sample_rate: Option<SampleRate>,
+    pub avg_bitrate: Option<u32>,
+    /// The bit depth of the magic cookie, or otherwise of the sample entry.
+    pub bit_depth: Option<u8>,
+    /// The number of frames per packet of the magic cookie.
+    pub frames_per_packet: Option<u32>,
+    /// The ALAC specific config of the magic cookie, without version and flags.
+    pub cookie: Option<Vec<u8>>,
+}
+
+impl Atom for Alac {
+    const FOURCC: Fourcc = APPLE_LOSSLESS_AUDIO;
+}
+
+impl ParseAtom for Alac {
+    fn parse_atom(
+        reader: &mut (impl Read + Seek),
+        _cfg: &ParseConfig<'_>,
+        size: Size,
+    ) -> crate::Result<Self> {
+        let bounds = find_bounds(reader, size)?;
+        let mut alac = Self::default();
+
+        expect_min_size("Apple lossless audio (alac)", size, HEADER_SIZE)?;
+
+        // use cursor over a buffer to avoid syscalls
+        let mut buf = vec![0; bounds.content_len() as usize];
+        reader.read_exact(&mut buf)?;
+
+        let channel_count = u16::from_be_bytes([buf[16], buf[17]]);
+        let sample_size = u16::from_be_bytes([buf[18], buf[19]]);
+        let sample_rate = u16::from_be_bytes([buf[24], buf[25]]);
+        alac.channel_config = ChannelConfig::from_channel_count(channel_count as u8);
+        alac.sample_rate = SampleRate::from_hz(sample_rate as u32);
+        alac.bit_depth = Some(sample_size as u8).filter(|b| *b != 0);
+
+        let mut cursor = std::io::Cursor::new(&mut buf);
+        cursor.skip(HEADER_SIZE as i64)?;
+
+        let mut parsed_bytes = HEADER_SIZE;
+        while parsed_bytes < size.content_len() {
+            let remaining_bytes = size.content_len() - parsed_bytes;
+            let head = head::parse(&mut cursor, remaining_bytes)?;
+
+            match head.fourcc() {
+                APPLE_LOSSLESS_AUDIO if head.content_len() >= COOKIE_SIZE => {
+                    let pos = cursor.position() as usize;
+                    let cookie = &cursor.get_ref()[pos..pos + COOKIE_SIZE as usize];
+                    let be_u32 =
+                        |i: usize| u32::from_be_bytes(cookie[i..i + 4].try_into().unwrap());
+
+                    alac.bit_depth = Some(cookie[9]).filter(|b| *b != 0).or(alac.bit_depth);
+                    alac.channel_config =
+                        ChannelConfig::from_channel_count(cookie[13]).or(alac.channel_config);
+                    alac.avg_bitrate = Some(be_u32(20)).filter(|b| *b != 0);
+                    alac.sample_rate = SampleRate::from_hz(be_u32(24)).or(alac.sample_rate);
+                    alac.frames_per_packet = Some(be_u32(4)).filter(|f| *f != 0);
+                    alac.cookie = Some(cookie[4..].to_vec());
+
+                    cursor.skip(head.content_len() as i64)?;
+                }
+                _ => cursor.skip(head.content_len() as i64)?,
+            }
+
+            parsed_bytes += head.len();
+        }
+
+        Ok(alac)
+    }
+}
@@ -47,14 +47,13 @@ impl<T: SimpleCollectChanges> CollectChanges for T {
         match &self.state() {
             State::Existing(bounds) => {
                 let len_diff = self.existing(level + 1, bounds, changes);
-                if len_diff != 0 {
-                    changes.push(Change::UpdateLen(UpdateAtomLen {
-                        bounds,
-                        fourcc: Self::FOURCC,
-                        len_diff,
-                    }));
+                if len_diff == 0 {
+                    return 0;
                 }
-                len_diff
+                let update = UpdateAtomLen { bounds, fourcc: Self::FOURCC, len_diff };
+                let head_len_diff = update.head_len_diff();
+                changes.push(Change::UpdateLen(update));
+                len_diff + head_len_diff
             }
             State::Remove(bounds) => {
                 changes.push(Change::Remove(RemoveAtom { bounds, level: level + 1 }));
@@ -164,7 +163,7 @@ impl Change<'_> {
 
     pub fn len_diff(&self) -> i64 {
         match self {
-            Self::UpdateLen(_) => 0,
+            Self::UpdateLen(c) => c.head_len_diff(),
             Self::UpdateChunkOffset(_) => 0,
             Self::Remove(c) => -(c.bounds.len() as i64),
             Self::Replace(c) => (c.atom.len() as i64) - (c.bounds.len() as i64),
@@ -195,9 +194,23 @@ pub struct UpdateAtomLen<'a> {
 }
 
 impl UpdateAtomLen<'_> {
+    /// Whether the head has to be extended to a 64 bit length, because the updated length doesn't
+    /// fit into 32 bits anymore.
+    fn extends_head(&self) -> bool {
+        !self.bounds.ext() && self.bounds.len() as i64 + self.len_diff > u32::MAX as i64
+    }
+
+    /// The length difference of the head itself, caused by extending it.
+    pub fn head_len_diff(&self) -> i64 {
+        match self.extends_head() {
+            true => (Head::EXT_SIZE - Head::NORMAL_SIZE) as i64,
+            false => 0,
+        }
+    }
+
     pub fn update_len(&self, writer: &mut impl Write) -> crate::Result<()> {
-        let len = (self.bounds.len() as i64 + self.len_diff) as u64;
-        let head = Head::new(self.bounds.ext(), len, self.fourcc);
+        let len = (self.bounds.len() as i64 + self.len_diff + self.head_len_diff()) as u64;
+        let head = Head::new(self.bounds.ext() || self.extends_head(), len, self.fourcc);
         head::write(writer, head)?;
         Ok(())
     }
@@ -371,15 +384,18 @@ macro_rules! atom_ref {
 
 atom_ref!(
     Moov<'a> nowrite,
+    Mvhd,
     Udta<'a>,
     Chpl<'a>,
     Meta<'a>,
     Hdlr,
     Ilst<'a>,
+    Free,
     Trak,
     Tref,
     Chap,
     Mdia,
+    Mdhd,
     Minf,
     Dinf,
     Dref,
@@ -66,8 +66,14 @@ impl ParseAtom for Chpl<'_> {
         for _ in 0..num_entries {
             let start = cursor.read_be_u64()?;
 
+            // Titles written by some tools use legacy encodings, so invalid utf-8 is replaced
+            // instead of failing to read the whole tag.
             let str_len = cursor.read_u8()?;
-            let title = cursor.read_utf8(str_len as u64)?;
+            let title = cursor.read_u8_vec(str_len as u64)?;
+            let title = match String::from_utf8(title) {
+                Ok(s) => s,
+                Err(e) => String::from_utf8_lossy(e.as_bytes()).into_owned(),
+            };
 
             chpl.push(ChplItem { start, title });
         }
@@ -113,7 +119,7 @@ impl WriteAtom for Chpl<'_> {
             ChplData::Borrowed(timescale, chapters) => {
                 writer.write_u8(chapters.len() as u8)?;
                 for c in chapters.iter() {
-                    let start = unscale_duration(*timescale, c.start);
+                    let start = c.start_ticks_in(*timescale);
                     writer.write_be_u64(start)?;
 
                     let title_len = title_len(&c.title);
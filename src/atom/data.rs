@@ -1,5 +1,7 @@
 use std::fmt;
+use std::sync::Arc;
 
+use crate::util::BytesPreview;
 use crate::{Img, ImgBuf, ImgFmt, ImgMut, ImgRef};
 
 use super::*;
@@ -109,28 +111,279 @@ pub enum Data {
     /// A big-endian signed integer.
     /// This is often used for track/disc numbers and standard genre codes.
     BeSigned(Vec<u8>),
-    /// A value containing an unknown data type code and data.
+    /// A value containing an unknown data type code and data, or utf-8 text ([`Utf8`]) that isn't
+    /// valid utf-8.
+    ///
+    /// [`Utf8`]: Data::Utf8
     Unknown {
         /// The data type code.
         code: u32,
         /// The data.
         data: Vec<u8>,
     },
+    /// Data whose payload was skipped while reading, see [`ReadConfig::skip_idents`]. When
+    /// writing, the payload is copied from the file, which therefore has to be the one it was
+    /// read from.
+    ///
+    /// [`ReadConfig::skip_idents`]: crate::ReadConfig::skip_idents
+    Skipped(SkippedData),
+    /// Data whose payload is copied from its source when writing, see [`DataSource`].
+    Streamed {
+        /// The data type code.
+        code: u32,
+        /// The source of the payload.
+        source: DataSource,
+    },
+}
+
+/// Opens a reader positioned at the start of a streamed payload.
+type OpenReader = dyn Fn() -> std::io::Result<Box<dyn Read>> + Send + Sync;
+
+/// The source of the payload of [`Data::Streamed`].
+///
+/// A payload read from a reader is copied in chunks straight into the written file, so it never
+/// has to be held in memory as a whole.
+///
+/// # Example
+/// ```no_run
+/// use mp4ameta::{Data, DataSource, ImgFmt, Tag, ident};
+///
+/// let mut tag = Tag::read_from_path("music.m4a").unwrap();
+/// let source = DataSource::from_path("cover.jpg").unwrap();
+/// tag.set_data(ident::ARTWORK, Data::streamed_image(ImgFmt::Jpeg, source));
+/// tag.write_to_path("music.m4a").unwrap();
+/// ```
+#[derive(Clone)]
+pub enum DataSource {
+    /// The payload is held in memory.
+    InMemory(Vec<u8>),
+    /// The payload is read from a reader, which is opened each time it is written.
+    FromReader {
+        /// Opens a reader positioned at the start of the payload.
+        open: Arc<OpenReader>,
+        /// The length of the payload in bytes.
+        len: u64,
+    },
+}
+
+impl DataSource {
+    /// Creates a source reading the payload of the length from the readers returned by `open`.
+    pub fn from_reader<R: Read + 'static>(
+        len: u64,
+        open: impl Fn() -> std::io::Result<R> + Send + Sync + 'static,
+    ) -> Self {
+        let open = move || open().map(|r| Box::new(r) as Box<dyn Read>);
+        Self::FromReader { open: Arc::new(open), len }
+    }
+
+    /// Creates a source reading the payload from the file at the path. The file has to remain
+    /// unchanged until the data is written.
+    pub fn from_path(path: impl AsRef<Path>) -> std::io::Result<Self> {
+        let path = path.as_ref().to_owned();
+        let len = std::fs::metadata(&path)?.len();
+        Ok(Self::from_reader(len, move || File::open(&path)))
+    }
+
+    /// Returns the length of the payload in bytes.
+    pub fn len(&self) -> u64 {
+        match self {
+            Self::InMemory(v) => v.len() as u64,
+            Self::FromReader { len, .. } => *len,
+        }
+    }
+
+    /// Returns true if the payload is of length 0.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Reads the whole payload without keeping it, to check that it can be read and has the
+    /// expected length before anything is written.
+    pub(crate) fn verify(&self) -> crate::Result<()> {
+        match self {
+            Self::InMemory(_) => Ok(()),
+            Self::FromReader { .. } => self.write_to(&mut std::io::sink()),
+        }
+    }
+
+    /// Copies the payload to the writer, in chunks if it is read from a reader.
+    fn write_to(&self, writer: &mut impl Write) -> crate::Result<()> {
+        let (open, len) = match self {
+            Self::InMemory(v) => return Ok(writer.write_all(v)?),
+            Self::FromReader { open, len } => (open, *len),
+        };
+        let mut reader = open()?.take(len);
+        let copied = std::io::copy(&mut reader, writer)?;
+        if copied != len {
+            return Err(crate::Error::new(
+                crate::ErrorKind::InvalidData,
+                format!("Streamed data is {copied} bytes long, instead of the expected {len}"),
+            ));
+        }
+        Ok(())
+    }
+}
+
+impl PartialEq for DataSource {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Self::InMemory(a), Self::InMemory(b)) => a == b,
+            (Self::FromReader { open: a, len: l1 }, Self::FromReader { open: b, len: l2 }) => {
+                Arc::ptr_eq(a, b) && l1 == l2
+            }
+            _ => false,
+        }
+    }
+}
+
+impl Eq for DataSource {}
+
+impl fmt::Debug for DataSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InMemory(d) => write!(f, "DataSource::InMemory({:?})", BytesPreview(d)),
+            Self::FromReader { len, .. } => write!(f, "DataSource::FromReader {{ len: {len} }}"),
+        }
+    }
+}
+
+/// The location of a data payload that was skipped while reading.
+///
+/// The location refers to the file as it was read. Writing the tag to the same file may move the
+/// data atom, so the tag has to be read again before it can be written or loaded once more.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SkippedData {
+    /// The data type code.
+    code: u32,
+    /// The position of the data atom in the file.
+    pos: u64,
+    /// The length of the head of the data atom.
+    head_len: u64,
+    /// The length of the payload.
+    len: u64,
+}
+
+impl SkippedData {
+    /// Returns the data type code.
+    pub const fn code(&self) -> u32 {
+        self.code
+    }
+
+    /// Returns the length of the skipped payload in bytes.
+    pub const fn len(&self) -> u64 {
+        self.len
+    }
+
+    /// Returns true if the skipped payload is of length 0.
+    pub const fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Returns true if the data type code is one of an image.
+    pub const fn is_image(&self) -> bool {
+        matches!(self.code, JPEG | PNG | BMP)
+    }
+
+    /// Returns the image format, if the data type code is one of an image.
+    pub const fn image_fmt(&self) -> Option<ImgFmt> {
+        match self.code {
+            JPEG => Some(ImgFmt::Jpeg),
+            PNG => Some(ImgFmt::Png),
+            BMP => Some(ImgFmt::Bmp),
+            _ => None,
+        }
+    }
+
+    /// Reads the skipped data from the file it was read from.
+    pub fn load(&self, reader: &mut (impl Read + Seek)) -> crate::Result<Data> {
+        let parse_cfg = ParseConfig {
+            cfg: &ReadConfig::DEFAULT,
+            write: false,
+            skip_data: false,
+        };
+        Data::Skipped(self.clone()).read_skipped(reader, &parse_cfg)
+    }
+}
+
+/// A handle to an artwork image (`covr`) whose data was skipped while reading, see
+/// [`ReadConfig::skip_artwork`]. The image data is only read when calling [`ArtworkRef::load`].
+///
+/// # Example
+/// ```no_run
+/// use std::fs::File;
+/// use mp4ameta::{ReadConfig, Tag};
+///
+/// let cfg = ReadConfig { skip_artwork: true, ..Default::default() };
+/// let tag = Tag::read_with_path("music.m4a", &cfg).unwrap();
+///
+/// if let Some(artwork) = tag.artwork_lazy() {
+///     let mut file = File::open("music.m4a").unwrap();
+///     let image = artwork.load(&mut file).unwrap();
+///     assert_eq!(image.data.len() as u64, artwork.len());
+/// }
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ArtworkRef {
+    fmt: ImgFmt,
+    data: SkippedData,
+}
+
+impl ArtworkRef {
+    /// Returns the artwork handle of the skipped data, if it is an image.
+    pub fn new(data: &SkippedData) -> Option<Self> {
+        Some(Self { fmt: data.image_fmt()?, data: data.clone() })
+    }
+
+    /// Returns the image format.
+    pub const fn fmt(&self) -> &ImgFmt {
+        &self.fmt
+    }
+
+    /// Returns the position of the image data in the file.
+    pub const fn offset(&self) -> u64 {
+        self.data.pos + self.data.head_len + HEADER_SIZE
+    }
+
+    /// Returns the length of the image data in bytes.
+    pub const fn len(&self) -> u64 {
+        self.data.len
+    }
+
+    /// Returns true if the image data is of length 0.
+    pub const fn is_empty(&self) -> bool {
+        self.data.len == 0
+    }
+
+    /// Reads the image from the file the tag was read from.
+    pub fn load(&self, reader: &mut (impl Read + Seek)) -> crate::Result<ImgBuf> {
+        let data = self.data.load(reader)?;
+        data.into_image().ok_or_else(|| {
+            crate::Error::new(crate::ErrorKind::InvalidData, "Skipped artwork is not an image")
+        })
+    }
 }
 
 impl fmt::Debug for Data {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
-            Self::Reserved(d) => write!(f, "Data::Reserved({d:?})"),
+            Self::Reserved(d) => write!(f, "Data::Reserved({:?})", BytesPreview(d)),
             Self::Utf8(d) => write!(f, "Data::Utf8({d:?})"),
             Self::Utf16(d) => write!(f, "Data::Utf16({d:?})"),
-            Self::Jpeg(_) => write!(f, "Data::Jpeg"),
-            Self::Png(_) => write!(f, "Data::Png"),
-            Self::BeSigned(d) => write!(f, "Data::BeSigned({d:?})"),
-            Self::Bmp(_) => write!(f, "Data::Bmp"),
-            Self::Unknown { code, data } => {
-                f.debug_struct("Data::Unknown").field("code", code).field("data", data).finish()
-            }
+            Self::Jpeg(d) => write!(f, "Data::Jpeg({:?})", BytesPreview(d)),
+            Self::Png(d) => write!(f, "Data::Png({:?})", BytesPreview(d)),
+            Self::BeSigned(d) => write!(f, "Data::BeSigned({:?})", BytesPreview(d)),
+            Self::Bmp(d) => write!(f, "Data::Bmp({:?})", BytesPreview(d)),
+            Self::Skipped(d) => write!(f, "Data::Skipped({d:?})"),
+            Self::Streamed { code, source } => f
+                .debug_struct("Data::Streamed")
+                .field("code", code)
+                .field("source", source)
+                .finish(),
+            Self::Unknown { code, data } => f
+                .debug_struct("Data::Unknown")
+                .field("code", code)
+                .field("data", &BytesPreview(data))
+                .finish(),
         }
     }
 }
@@ -156,24 +409,10 @@ impl Data {
         cfg: &ParseConfig<'_>,
         size: Size,
     ) -> crate::Result<Data> {
-        let mut buf = [0; 8];
-        reader.read_exact(&mut buf)?;
-
-        let [version, b2, b1, b0, _locale @ ..] = buf;
-        if version != 0 {
-            return Err(crate::Error::new(
-                crate::ErrorKind::UnknownVersion(version),
-                "Unknown data atom (data) version",
-            ));
-        }
-        let datatype = u32::from_be_bytes([0, b2, b1, b0]);
-
-        expect_min_size("Data (data)", size, HEADER_SIZE)?;
-
-        let len = size.content_len() - HEADER_SIZE;
+        let (datatype, len) = parse_header(reader, size)?;
         Ok(match datatype {
             RESERVED => Data::Reserved(reader.read_u8_vec(len)?),
-            UTF8 => Data::Utf8(reader.read_utf8(len)?),
+            UTF8 => read_string(reader, cfg, len)?,
             UTF16 => Data::Utf16(reader.read_be_utf16(len)?),
             JPEG => Data::Jpeg(read_image(reader, cfg.cfg.read_image_data, len)?),
             PNG => Data::Png(read_image(reader, cfg.cfg.read_image_data, len)?),
@@ -186,10 +425,42 @@ impl Data {
         })
     }
 
-    pub fn write(&self, writer: &mut impl Write) -> crate::Result<()> {
-        head::write(writer, Head::new(false, self.len(), DATA))?;
+    /// Skips the payload of the data atom, only recording its location.
+    pub fn skip(reader: &mut (impl Read + Seek), size: Size) -> crate::Result<Data> {
+        let head_len = size.head_len();
+        let pos = reader.stream_position()? - head_len;
+        let (code, len) = parse_header(reader, size)?;
+        reader.skip(len as i64)?;
+        Ok(Data::Skipped(SkippedData { code, pos, head_len, len }))
+    }
 
-        let datatype = match self {
+    /// Reads the payload of skipped data from the file it was read from. Other data is returned
+    /// as is.
+    pub fn read_skipped(
+        self,
+        reader: &mut (impl Read + Seek),
+        cfg: &ParseConfig<'_>,
+    ) -> crate::Result<Data> {
+        let Self::Skipped(skipped) = self else { return Ok(self) };
+
+        reader.seek(SeekFrom::Start(skipped.pos))?;
+        let data = head::parse(reader, u64::MAX)
+            .ok()
+            .filter(|h| h.fourcc() == DATA)
+            .map(|h| Data::parse(reader, cfg, h.size()))
+            .transpose()?;
+        match data {
+            Some(d) if d.data_type() == skipped.code && d.data_len() == skipped.len => Ok(d),
+            _ => Err(crate::Error::new(
+                crate::ErrorKind::InvalidData,
+                "Skipped data could not be found in the file, the tag has to be read again after writing it and can only be written to the file it was read from",
+            )),
+        }
+    }
+
+    /// Returns the data type code that is written to the data atom.
+    pub(crate) fn data_type(&self) -> u32 {
+        match self {
             Self::Reserved(_) => RESERVED,
             Self::Utf8(_) => UTF8,
             Self::Utf16(_) => UTF16,
@@ -198,9 +469,33 @@ impl Data {
             Self::BeSigned(_) => BE_SIGNED,
             Self::Bmp(_) => BMP,
             Self::Unknown { code, .. } => *code,
-        };
+            Self::Skipped(d) => d.code,
+            Self::Streamed { code, .. } => *code,
+        }
+    }
+
+    /// Returns true if this is utf-8 text containing invalid utf-8, which is read as
+    /// [`Data::Unknown`].
+    pub(crate) fn is_invalid_utf8(&self) -> bool {
+        matches!(self, Self::Unknown { code: UTF8, data } if std::str::from_utf8(data).is_err())
+    }
+
+    /// Returns true if the data type code is one of an image, including data whose payload isn't
+    /// in memory.
+    pub(crate) fn has_image_type(&self) -> bool {
+        matches!(self.data_type(), JPEG | PNG | BMP)
+    }
+
+    pub fn write(&self, writer: &mut impl Write) -> crate::Result<()> {
+        if let Self::Skipped(_) = self {
+            return Err(crate::Error::new(
+                crate::ErrorKind::InvalidData,
+                "The payload of skipped data has to be read before writing",
+            ));
+        }
+        head::write(writer, Head::new(false, self.len(), DATA))?;
 
-        writer.write_all(&datatype.to_be_bytes())?;
+        writer.write_all(&self.data_type().to_be_bytes())?;
         writer.write_all(&[0; 4])?; // locale indicator
         match self {
             Self::Reserved(v) => writer.write_all(v)?,
@@ -211,6 +506,8 @@ impl Data {
             Self::BeSigned(v) => writer.write_all(v)?,
             Self::Bmp(v) => writer.write_all(v)?,
             Self::Unknown { data, .. } => writer.write_all(data)?,
+            Self::Streamed { source, .. } => source.write_to(writer)?,
+            Self::Skipped(_) => unreachable!(),
         }
 
         Ok(())
@@ -221,6 +518,94 @@ impl Data {
     }
 }
 
+/// Parses the data type code and locale of a data atom, returning the data type code and the
+/// length of the payload.
+fn parse_header(reader: &mut impl Read, size: Size) -> crate::Result<(u32, u64)> {
+    let mut buf = [0; 8];
+    reader.read_exact(&mut buf)?;
+
+    let [version, b2, b1, b0, _locale @ ..] = buf;
+    if version != 0 {
+        return Err(crate::Error::new(
+            crate::ErrorKind::UnknownVersion(version),
+            "Unknown data atom (data) version",
+        ));
+    }
+    expect_min_size("Data (data)", size, HEADER_SIZE)?;
+
+    let datatype = u32::from_be_bytes([0, b2, b1, b0]);
+    Ok((datatype, size.content_len() - HEADER_SIZE))
+}
+
+impl Data {
+    /// Creates image data of the format, whose payload is copied from the source when writing.
+    pub fn streamed_image(fmt: ImgFmt, source: DataSource) -> Self {
+        let code = match fmt {
+            ImgFmt::Jpeg => JPEG,
+            ImgFmt::Png => PNG,
+            ImgFmt::Bmp => BMP,
+        };
+        Self::Streamed { code, source }
+    }
+
+    /// Creates a big-endian signed integer ([`BeSigned`]) containing a 16-bit integer.
+    ///
+    /// ```
+    /// use mp4ameta::Data;
+    ///
+    /// assert_eq!(Data::u16_be(0x1234), Data::BeSigned(vec![0x12, 0x34]));
+    /// ```
+    ///
+    /// [`BeSigned`]: Data::BeSigned
+    pub fn u16_be(value: u16) -> Self {
+        Self::BeSigned(value.to_be_bytes().to_vec())
+    }
+
+    /// Creates a big-endian signed integer ([`BeSigned`]) containing a 32-bit integer.
+    ///
+    /// ```
+    /// use mp4ameta::Data;
+    ///
+    /// assert_eq!(Data::u32_be(0x12345678), Data::BeSigned(vec![0x12, 0x34, 0x56, 0x78]));
+    /// ```
+    ///
+    /// [`BeSigned`]: Data::BeSigned
+    pub fn u32_be(value: u32) -> Self {
+        Self::BeSigned(value.to_be_bytes().to_vec())
+    }
+
+    /// Creates a big-endian signed integer ([`BeSigned`]) containing a 64-bit integer.
+    ///
+    /// ```
+    /// use mp4ameta::Data;
+    ///
+    /// let bytes = vec![0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xfe];
+    /// assert_eq!(Data::i64_be(-2), Data::BeSigned(bytes));
+    /// ```
+    ///
+    /// [`BeSigned`]: Data::BeSigned
+    pub fn i64_be(value: i64) -> Self {
+        Self::BeSigned(value.to_be_bytes().to_vec())
+    }
+
+    /// Creates the 8 byte [`Reserved`] data used by the track (`trkn`) and disc (`disk`) number
+    /// atoms. It consists of 2 reserved bytes, a 16-bit number, a 16-bit total and another 2
+    /// reserved bytes.
+    ///
+    /// ```
+    /// use mp4ameta::Data;
+    ///
+    /// assert_eq!(Data::number_and_total(3, 12), Data::Reserved(vec![0, 0, 0, 3, 0, 12, 0, 0]));
+    /// ```
+    ///
+    /// [`Reserved`]: Data::Reserved
+    pub fn number_and_total(number: u16, total: u16) -> Self {
+        let [n0, n1] = number.to_be_bytes();
+        let [t0, t1] = total.to_be_bytes();
+        Self::Reserved(vec![0, 0, n0, n1, t0, t1, 0, 0])
+    }
+}
+
 impl Data {
     /// Returns the length of the raw data (without version, datatype and locale header) in bytes.
     pub fn data_len(&self) -> u64 {
@@ -233,6 +618,8 @@ impl Data {
             Self::BeSigned(v) => v.len(),
             Self::Bmp(v) => v.len(),
             Self::Unknown { data, .. } => data.len(),
+            Self::Skipped(d) => return d.len,
+            Self::Streamed { source, .. } => return source.len(),
         }) as u64
     }
 
@@ -535,3 +922,21 @@ fn read_image(reader: &mut (impl Read + Seek), parse: bool, len: u64) -> crate::
         Ok(Vec::new())
     }
 }
+
+/// Reads a utf-8 string, falling back to the configured encoding if it contains invalid utf-8.
+/// Without a fallback encoding, invalid utf-8 is kept as is in [`Data::Unknown`], so it's
+/// preserved when writing and can be reported by [`Tag::validate`](crate::Tag::validate).
+fn read_string(
+    reader: &mut (impl Read + Seek),
+    cfg: &ParseConfig<'_>,
+    len: u64,
+) -> crate::Result<Data> {
+    let data = reader.read_u8_vec(len)?;
+    Ok(match String::from_utf8(data) {
+        Ok(s) => Data::Utf8(s),
+        Err(e) => match cfg.cfg.fallback_encoding {
+            Some(encoding) => Data::Utf8(encoding.decode(e.as_bytes())),
+            None => Data::Unknown { code: UTF8, data: e.into_bytes() },
+        },
+    })
+}
@@ -0,0 +1,169 @@
+use super::*;
+
+/// The size of the buffer used to move data inside the file.
+const MOVE_BUF_LEN: u64 = 1024 * 1024;
+
+/// Moves the movie (`moov`) atom in front of the first media data (`mdat`) atom, so the file can
+/// be streamed progressively. Free space directly preceding the `mdat` atom is reused, free space
+/// directly following the `moov` atom is removed.
+///
+/// Returns whether the `moov` atom was moved.
+pub fn move_moov_to_front(file: &mut impl StorageFile) -> crate::Result<bool> {
+    let mut reader = BufReader::new(&mut *file);
+
+    let file_len = reader.seek(SeekFrom::End(0))?;
+    reader.seek(SeekFrom::Start(0))?;
+
+    let ftyp = Ftyp::parse(&mut reader, file_len)?;
+
+    let mut atoms = Vec::new();
+    let mut parsed_bytes = ftyp.size.len();
+    while parsed_bytes < file_len {
+        let head = match head::parse_top_level(&mut reader, file_len - parsed_bytes) {
+            Ok(h) => h,
+            // trailing garbage is moved along with the atoms following the moov atom
+            Err(e) if is_trailing_garbage(&e) && atoms.iter().any(|(f, _)| *f == MOVIE) => break,
+            Err(e) => return Err(e),
+        };
+        let bounds = find_bounds(&mut reader, head.size())?;
+        reader.skip(head.content_len() as i64)?;
+
+        atoms.push((head.fourcc(), bounds));
+        parsed_bytes += head.len();
+    }
+
+    let Some(mdat_idx) = atoms.iter().position(|(f, _)| *f == MEDIA_DATA) else {
+        return Ok(false);
+    };
+    let Some(moov_idx) = atoms.iter().position(|(f, _)| *f == MOVIE) else {
+        return Ok(false);
+    };
+    if moov_idx < mdat_idx {
+        return Ok(false);
+    }
+
+    // A wide atom directly preceding the mdat atom is reserved for expanding its size to 64 bits,
+    // so it's moved along with it.
+    let mdat_idx = match mdat_idx.checked_sub(1) {
+        Some(i) if atoms[i].0 == WIDE => i,
+        _ => mdat_idx,
+    };
+    let mdat_pos = atoms[mdat_idx].1.pos();
+    let moov_bounds = atoms[moov_idx].1.clone();
+    let moov_pos = moov_bounds.pos();
+    let moov_len = moov_bounds.len();
+
+    let insert_pos = (atoms[..mdat_idx].iter().rev())
+        .take_while(|(f, _)| *f == FREE)
+        .last()
+        .map_or(mdat_pos, |(_, b)| b.pos());
+    let tail_pos = (atoms[moov_idx + 1..].iter())
+        .take_while(|(f, _)| *f == FREE)
+        .last()
+        .map_or(moov_bounds.end(), |(_, b)| b.end());
+
+    // Reuse the free space in front of the mdat atom if the moov atom fits, otherwise shift
+    // everything up to the old moov position.
+    let free_len = mdat_pos - insert_pos;
+    let (leftover_free_len, mdat_shift) =
+        if free_len == moov_len || free_len >= moov_len + Head::NORMAL_SIZE {
+            (free_len - moov_len, 0)
+        } else {
+            (0, moov_len as i64 - free_len as i64)
+        };
+    let tail_shift = mdat_shift - (tail_pos - moov_pos) as i64;
+    let offset_shift = |offset: u64| {
+        if offset >= tail_pos {
+            tail_shift
+        } else if offset >= mdat_pos {
+            mdat_shift
+        } else {
+            0
+        }
+    };
+
+    // read the moov atom and update the chunk offsets
+    reader.seek(SeekFrom::Start(moov_pos))?;
+    let mut moov_data = vec![0; moov_len as usize];
+    reader.read_exact(&mut moov_data)?;
+
+    reader.seek(SeekFrom::Start(moov_pos))?;
+    let head = head::parse(&mut reader, moov_len)?;
+    let parse_cfg = ParseConfig {
+        cfg: &ReadConfig::NONE,
+        write: true,
+        skip_data: false,
+    };
+    let moov = Moov::parse(&mut reader, &parse_cfg, head.size())?;
+
+    for stbl in moov.trak.iter().filter_map(|trak| {
+        trak.mdia.as_ref().and_then(|a| a.minf.as_ref()).and_then(|a| a.stbl.as_ref())
+    }) {
+        if let Some(co64) = &stbl.co64
+            && let State::Existing(bounds) = &co64.state
+        {
+            let offsets = co64.offsets.get_or_read(&mut reader)?;
+            let pos = bounds.content_pos() + co64::HEADER_SIZE - moov_pos;
+            shift_offsets(&mut moov_data[pos as usize..], &offsets, offset_shift)?;
+        }
+        if let Some(stco) = &stbl.stco
+            && let State::Existing(bounds) = &stco.state
+        {
+            let offsets = stco.offsets.get_or_read(&mut reader)?;
+            let pos = bounds.content_pos() + stco::HEADER_SIZE - moov_pos;
+            shift_offsets(&mut moov_data[pos as usize..], &offsets, offset_shift)?;
+        }
+    }
+
+    // no more reading from here on
+    drop(reader);
+
+    move_data(file, mdat_pos, mdat_shift, moov_pos - mdat_pos)?;
+    move_data(file, tail_pos, tail_shift, file_len - tail_pos)?;
+
+    file.seek(SeekFrom::Start(insert_pos))?;
+    file.write_all(&moov_data)?;
+    if leftover_free_len > 0 {
+        head::write(file, Head::new(false, leftover_free_len, FREE))?;
+    }
+
+    file.set_len((file_len as i64 + tail_shift) as u64)?;
+    file.flush()?;
+
+    Ok(true)
+}
+
+fn shift_offsets<T: ChunkOffsetInt>(
+    mut writer: &mut [u8],
+    offsets: &[T],
+    offset_shift: impl Fn(u64) -> i64,
+) -> crate::Result<()> {
+    for o in offsets.iter().copied() {
+        o.shift(offset_shift(o.into())).write(&mut writer)?;
+    }
+    Ok(())
+}
+
+/// Moves `len` bytes starting at `pos` by `shift` bytes.
+fn move_data(file: &mut impl StorageFile, pos: u64, shift: i64, len: u64) -> crate::Result<()> {
+    if shift == 0 || len == 0 {
+        return Ok(());
+    }
+
+    let mut buf = vec![0; len.min(MOVE_BUF_LEN) as usize];
+    let mut moved = 0;
+    while moved < len {
+        let chunk_len = (len - moved).min(MOVE_BUF_LEN);
+        // copy back to front when moving towards the end, so nothing is overwritten before it's read
+        let chunk_pos = if shift > 0 { pos + len - moved - chunk_len } else { pos + moved };
+        let chunk = &mut buf[..chunk_len as usize];
+
+        file.seek(SeekFrom::Start(chunk_pos))?;
+        file.read_exact(chunk)?;
+        file.seek(SeekFrom::Start((chunk_pos as i64 + shift) as u64))?;
+        file.write_all(chunk)?;
+
+        moved += chunk_len;
+    }
+    Ok(())
+}
@@ -0,0 +1,95 @@
+use super::*;
+
+/// A free space (`free`) atom, containing nothing but padding.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Free {
+    pub state: State,
+    pub content_len: u64,
+}
+
+impl Atom for Free {
+    const FOURCC: Fourcc = FREE;
+}
+
+impl ParseAtom for Free {
+    fn parse_atom(
+        reader: &mut (impl Read + Seek),
+        _cfg: &ParseConfig<'_>,
+        size: Size,
+    ) -> crate::Result<Self> {
+        let bounds = find_bounds(reader, size)?;
+        reader.skip(size.content_len() as i64)?;
+        Ok(Self {
+            state: State::Existing(bounds),
+            content_len: size.content_len(),
+        })
+    }
+}
+
+impl AtomSize for Free {
+    fn size(&self) -> Size {
+        Size::from(self.content_len)
+    }
+}
+
+impl WriteAtom for Free {
+    fn write_atom(&self, writer: &mut impl Write, _changes: &[Change<'_>]) -> crate::Result<()> {
+        self.write_head(writer)?;
+
+        const ZEROS: [u8; 1024] = [0; 1024];
+        let mut remaining = self.content_len;
+        while remaining > 0 {
+            let len = remaining.min(ZEROS.len() as u64);
+            writer.write_all(&ZEROS[..len as usize])?;
+            remaining -= len;
+        }
+        Ok(())
+    }
+}
+
+impl LeafAtomCollectChanges for Free {
+    fn state(&self) -> &State {
+        &self.state
+    }
+
+    fn atom_ref(&self) -> AtomRef<'_> {
+        AtomRef::Free(self)
+    }
+}
+
+impl Free {
+    /// Creates a new free atom that will be inserted with the total length `len`.
+    pub fn with_len(len: u64) -> Self {
+        Self {
+            state: State::Insert,
+            content_len: len - Head::NORMAL_SIZE,
+        }
+    }
+
+    /// Resizes this free atom to the total length `len`.
+    pub fn resize(&mut self, len: u64) {
+        self.content_len = len - Head::NORMAL_SIZE;
+        self.state.replace_existing();
+    }
+
+    /// Attempts to resize this free atom by the negated length difference, so that the combined
+    /// length of the free atom and the preceding changes stays the same. Returns whether the
+    /// length difference could be absorbed.
+    pub fn absorb(&mut self, len_diff: i64) -> bool {
+        let State::Existing(bounds) = &self.state else {
+            return false;
+        };
+
+        let new_len = bounds.len() as i64 - len_diff;
+        if new_len == 0 {
+            self.state.remove_existing();
+            true
+        } else if (Head::NORMAL_SIZE as i64..=u32::MAX as i64).contains(&new_len) {
+            self.content_len = new_len as u64 - Head::NORMAL_SIZE;
+            self.state.replace_existing();
+            true
+        } else {
+            false
+        }
+    }
+}
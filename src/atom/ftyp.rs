@@ -4,6 +4,7 @@ use super::*;
 pub struct Ftyp {
     pub size: Size,
     pub string: String,
+    pub file_type: FileType,
 }
 
 impl Ftyp {
@@ -13,8 +14,20 @@ impl Ftyp {
             return Err(crate::Error::new(ErrorKind::NoFtyp, "No filetype atom found."));
         }
 
-        let string = reader.read_utf8(head.content_len())?;
+        expect_min_size("Filetype (ftyp)", head.size(), 8)?;
 
-        Ok(Ftyp { size: head.size(), string })
+        let data = reader.read_u8_vec(head.content_len())?;
+        let file_type = FileType {
+            major_brand: Fourcc(data[0..4].try_into().unwrap()),
+            minor_version: u32::from_be_bytes(data[4..8].try_into().unwrap()),
+            compatible_brands: data[8..]
+                .chunks_exact(4)
+                .map(|c| Fourcc(c.try_into().unwrap()))
+                .collect(),
+        };
+        let string = String::from_utf8(data)
+            .map_err(|_| crate::Error::new(ErrorKind::Utf8StringDecoding, "invalid utf-8 data"))?;
+
+        Ok(Ftyp { size: head.size(), string, file_type })
     }
 }
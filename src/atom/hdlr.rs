@@ -50,6 +50,12 @@ impl LeafAtomCollectChanges for Hdlr {
 }
 
 impl Hdlr {
+    /// Returns the handler type, for example `soun` for audio tracks.
+    pub fn handler_type(&self) -> Option<Fourcc> {
+        let b = self.data.get(8..12)?;
+        Some(Fourcc([b[0], b[1], b[2], b[3]]))
+    }
+
     pub fn meta() -> Self {
         Self {
             state: State::Insert,
@@ -101,6 +101,20 @@ impl Head {
 /// 8 bytes optional extended length
 /// ```
 pub fn parse(reader: &mut impl Read, remaining_bytes: u64) -> crate::Result<Head> {
+    parse_inner(reader, remaining_bytes, false)
+}
+
+/// Attempts to parse the head of a top level atom, see [`parse`]. A length of 0 means that the
+/// atom extends to the end of the file, which is common for a trailing media data (`mdat`) atom.
+pub fn parse_top_level(reader: &mut impl Read, remaining_bytes: u64) -> crate::Result<Head> {
+    parse_inner(reader, remaining_bytes, true)
+}
+
+fn parse_inner(
+    reader: &mut impl Read,
+    remaining_bytes: u64,
+    top_level: bool,
+) -> crate::Result<Head> {
     let mut buf = [[0u8; 4]; 2];
 
     // SAFETY: the buffer has the same size and alignment
@@ -112,6 +126,9 @@ pub fn parse(reader: &mut impl Read, remaining_bytes: u64) -> crate::Result<Head
 
     let mut len = u32::from_be_bytes(buf[0]) as u64;
     let fourcc = Fourcc(buf[1]);
+    if len == 0 && top_level {
+        len = remaining_bytes;
+    }
 
     let ext = if len == 1 {
         match reader.read_be_u64() {
@@ -221,3 +238,111 @@ pub fn find_bounds(reader: &mut impl Seek, size: Size) -> crate::Result<AtomBoun
     let pos = reader.stream_position()? - size.head_len();
     Ok(AtomBounds { pos, size })
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn round_trip_head(head: Head) -> Head {
+        let mut buf = Vec::new();
+        write(&mut buf, head).unwrap();
+        assert_eq!(buf.len() as u64, head.head_len());
+        parse(&mut Cursor::new(&buf), u64::MAX).unwrap()
+    }
+
+    #[test]
+    fn head_round_trip() {
+        let head = Head::from(Size::from(24), FREE);
+        assert!(!head.ext());
+        assert_eq!(round_trip_head(head), head);
+
+        let head = Head::from(Size::from(u32::MAX as u64), MEDIA_DATA);
+        assert!(head.ext());
+        assert_eq!(round_trip_head(head), head);
+
+        let head = Head::from(Size::new(true, 24), FREE);
+        assert_eq!(round_trip_head(head), head);
+    }
+
+    #[test]
+    fn zero_len_extends_to_end() {
+        let buf = [0, 0, 0, 0, b'm', b'd', b'a', b't'];
+        let head = parse_top_level(&mut Cursor::new(&buf), 40).unwrap();
+        assert_eq!(head, Head::new(false, 40, MEDIA_DATA));
+
+        assert!(parse(&mut Cursor::new(&buf), 40).is_err());
+        assert!(parse_top_level(&mut Cursor::new(&buf), 4).is_err());
+    }
+
+    #[test]
+    fn update_len_extends_head() {
+        let len = u32::MAX as u64 - 8;
+        let udta = Udta {
+            state: State::Existing(AtomBounds { pos: 0, size: Size::new(false, len - 8) }),
+            free: Some(Free::with_len(16)),
+            ..Default::default()
+        };
+
+        let mut changes = Vec::new();
+        let len_diff = udta.collect_changes(0, 0, &mut changes);
+        assert_eq!(len_diff, 16 + 8);
+
+        let update = changes.iter().find(|c| matches!(c, Change::UpdateLen(_))).unwrap();
+        assert_eq!(update.len_diff(), 8);
+
+        let mut buf = Vec::new();
+        match update {
+            Change::UpdateLen(u) => u.update_len(&mut buf).unwrap(),
+            _ => unreachable!(),
+        }
+        let head = parse(&mut Cursor::new(&buf), u64::MAX).unwrap();
+        assert!(head.ext());
+        assert_eq!(head.len(), len + 16 + 8);
+        assert_eq!(head.fourcc(), USER_DATA);
+    }
+
+    #[test]
+    fn atom_tree_round_trip() {
+        let udta = Udta {
+            meta: Some(Meta {
+                hdlr: Some(Hdlr::meta()),
+                ilst: Some(Ilst {
+                    data: Cow::Owned(vec![MetaItem::new(
+                        ident::TITLE.into(),
+                        vec![Data::Utf8("title".to_owned())],
+                    )]),
+                    ..Default::default()
+                }),
+                free: Some(Free::with_len(16)),
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+
+        let mut buf = Vec::new();
+        udta.write(&mut buf, &[]).unwrap();
+        assert_eq!(buf.len() as u64, udta.len());
+
+        let cfg = ParseConfig {
+            cfg: &ReadConfig::DEFAULT,
+            write: true,
+            skip_data: false,
+        };
+        let mut reader = Cursor::new(&buf);
+        let head = parse(&mut reader, buf.len() as u64).unwrap();
+        assert_eq!(head.fourcc(), USER_DATA);
+        let parsed = Udta::parse(&mut reader, &cfg, head.size()).unwrap();
+
+        let meta = parsed.meta.as_ref().unwrap();
+        assert_eq!(meta.hdlr.as_ref().unwrap().data, Hdlr::meta().data);
+        assert_eq!(
+            meta.ilst.as_ref().unwrap().data,
+            udta.meta.as_ref().unwrap().ilst.as_ref().unwrap().data
+        );
+        assert_eq!(meta.free.as_ref().unwrap().content_len, 8);
+
+        let mut rewritten = Vec::new();
+        parsed.write(&mut rewritten, &[]).unwrap();
+        assert_eq!(rewritten, buf);
+    }
+}
@@ -1,5 +1,6 @@
 use std::array::TryFromSliceError;
-use std::borrow::Cow;
+use std::borrow::{Borrow, Cow};
+use std::collections::HashMap;
 use std::convert::TryInto;
 use std::fmt::{self, Write};
 use std::marker::PhantomData;
@@ -26,6 +27,8 @@ pub(crate) const CHAPTER_REFERENCE: Fourcc = Fourcc(*b"chap");
 pub(crate) const MEDIA: Fourcc = Fourcc(*b"mdia");
 /// (`mdhd`)
 pub(crate) const MEDIA_HEADER: Fourcc = Fourcc(*b"mdhd");
+/// (`elng`) Extended language tag of a track, in the BCP 47 format.
+pub(crate) const EXTENDED_LANGUAGE: Fourcc = Fourcc(*b"elng");
 /// (`minf`)
 pub(crate) const MEDIA_INFORMATION: Fourcc = Fourcc(*b"minf");
 /// (`gmhd`)
@@ -56,8 +59,44 @@ pub(crate) const SAMPLE_TABLE_SAMPLE_DESCRIPTION: Fourcc = Fourcc(*b"stsd");
 pub(crate) const MP4_AUDIO: Fourcc = Fourcc(*b"mp4a");
 /// (`text`)
 pub(crate) const TEXT_MEDIA: Fourcc = Fourcc(*b"text");
+/// (`alac`) Sample entry and magic cookie of apple lossless audio.
+pub(crate) const APPLE_LOSSLESS_AUDIO: Fourcc = Fourcc(*b"alac");
+/// (`ac-3`) Sample entry of dolby digital audio.
+pub(crate) const AC3_AUDIO: Fourcc = Fourcc(*b"ac-3");
+/// (`ec-3`) Sample entry of dolby digital plus audio.
+pub(crate) const EAC3_AUDIO: Fourcc = Fourcc(*b"ec-3");
 /// (`esds`)
 pub(crate) const ELEMENTARY_STREAM_DESCRIPTION: Fourcc = Fourcc(*b"esds");
+/// (`drms`) Sample entry of FairPlay protected audio.
+pub(crate) const DRM_AUDIO: Fourcc = Fourcc(*b"drms");
+/// (`drmi`) Sample entry of FairPlay protected video.
+pub(crate) const DRM_VIDEO: Fourcc = Fourcc(*b"drmi");
+/// (`enca`) Sample entry of encrypted audio.
+pub(crate) const ENCRYPTED_AUDIO: Fourcc = Fourcc(*b"enca");
+/// (`encv`) Sample entry of encrypted video.
+pub(crate) const ENCRYPTED_VIDEO: Fourcc = Fourcc(*b"encv");
+/// (`sinf`) Protection scheme information of an encrypted sample entry.
+pub(crate) const PROTECTION_SCHEME_INFO: Fourcc = Fourcc(*b"sinf");
+/// (`btrt`) Bitrate of a sample entry.
+pub(crate) const BITRATE: Fourcc = Fourcc(*b"btrt");
+/// (`soun`) Handler type of audio tracks.
+pub(crate) const SOUND_HANDLER: Fourcc = Fourcc(*b"soun");
+/// (`avc1`) Sample entry of H.264 video.
+pub(crate) const AVC_VIDEO: Fourcc = Fourcc(*b"avc1");
+/// (`hvc1`) Sample entry of H.265 video, with parameter sets in the sample entry.
+pub(crate) const HEVC_VIDEO: Fourcc = Fourcc(*b"hvc1");
+/// (`hev1`) Sample entry of H.265 video, with parameter sets in the samples.
+pub(crate) const HEVC_VIDEO_IN_BAND: Fourcc = Fourcc(*b"hev1");
+/// (`mp4v`) Sample entry of MPEG-4 visual.
+pub(crate) const MP4_VIDEO: Fourcc = Fourcc(*b"mp4v");
+/// (`pasp`) Pixel aspect ratio of a visual sample entry.
+pub(crate) const PIXEL_ASPECT_RATIO: Fourcc = Fourcc(*b"pasp");
+/// (`jpeg`) Sample entry of jpeg images, for example chapter images.
+pub(crate) const JPEG_IMAGE: Fourcc = Fourcc(*b"jpeg");
+/// (`png `) Sample entry of png images, for example chapter images.
+pub(crate) const PNG_IMAGE: Fourcc = Fourcc(*b"png ");
+/// (`href`) Hypertext link of a text sample, for example a chapter url.
+pub(crate) const HYPERTEXT_LINK: Fourcc = Fourcc(*b"href");
 /// (`udta`) Identifier of an atom containing user metadata.
 pub(crate) const USER_DATA: Fourcc = Fourcc(*b"udta");
 /// (`chpl`)
@@ -76,6 +115,8 @@ pub(crate) const MEAN: Fourcc = Fourcc(*b"mean");
 pub(crate) const NAME: Fourcc = Fourcc(*b"name");
 /// (`free`)
 pub(crate) const FREE: Fourcc = Fourcc(*b"free");
+/// (`wide`)
+pub(crate) const WIDE: Fourcc = Fourcc(*b"wide");
 
 /// (`----`)
 pub const FREEFORM: Fourcc = Fourcc(*b"----");
@@ -162,6 +203,22 @@ pub const PURCHASE_DATE: Fourcc = Fourcc(*b"purd");
 /// (`pgap`)
 pub const GAPLESS_PLAYBACK: Fourcc = Fourcc(*b"pgap");
 
+// iTunes store
+/// (`apID`)
+pub const ACCOUNT_ID: Fourcc = Fourcc(*b"apID");
+/// (`atID`)
+pub const ARTIST_ID: Fourcc = Fourcc(*b"atID");
+/// (`cnID`)
+pub const CATALOG_ID: Fourcc = Fourcc(*b"cnID");
+/// (`ownr`)
+pub const OWNER: Fourcc = Fourcc(*b"ownr");
+/// (`plID`)
+pub const PLAYLIST_ID: Fourcc = Fourcc(*b"plID");
+/// (`sfID`)
+pub const STOREFRONT_ID: Fourcc = Fourcc(*b"sfID");
+/// (`xid `)
+pub const XID: Fourcc = Fourcc(*b"xid ");
+
 // Work, Movement
 /// (`©mvn`)
 pub const MOVEMENT: Fourcc = Fourcc(*b"\xa9mvn");
@@ -174,6 +231,14 @@ pub const WORK: Fourcc = Fourcc(*b"\xa9wrk");
 /// (`shwm`)
 pub const SHOW_MOVEMENT: Fourcc = Fourcc(*b"shwm");
 
+// QuickTime credits
+/// (`©aut`)
+pub const AUTHOR: Fourcc = Fourcc(*b"\xa9aut");
+/// (`©dir`)
+pub const DIRECTOR: Fourcc = Fourcc(*b"\xa9dir");
+/// (`©prd`)
+pub const PRODUCER: Fourcc = Fourcc(*b"\xa9prd");
+
 // Sort order
 /// (`soaa`)
 pub const ALBUM_ARTIST_SORT_ORDER: Fourcc = Fourcc(*b"soaa");
@@ -196,8 +261,24 @@ pub const APPLE_ITUNES_MEAN: &str = "com.apple.iTunes";
 pub const ISRC: FreeformIdentStatic = FreeformIdent::new_static(APPLE_ITUNES_MEAN, "ISRC");
 /// (`----:com.apple.iTunes:LYRICIST`)
 pub const LYRICIST: FreeformIdentStatic = FreeformIdent::new_static(APPLE_ITUNES_MEAN, "LYRICIST");
+/// (`----:com.apple.iTunes:iTunSMPB`) Gapless playback information: encoder delay, padding and
+/// the original sample count.
+pub const ITUNES_SMPB: FreeformIdentStatic =
+    FreeformIdent::new_static(APPLE_ITUNES_MEAN, "iTunSMPB");
 /// (`----:com.apple.iTunes:LABEL`)
 pub const LABEL: FreeformIdentStatic = FreeformIdent::new_static(APPLE_ITUNES_MEAN, "LABEL");
+/// (`----:com.apple.iTunes:MusicBrainz Track Id`) The MusicBrainz recording id.
+pub const MUSICBRAINZ_TRACK_ID: FreeformIdentStatic =
+    FreeformIdent::new_static(APPLE_ITUNES_MEAN, "MusicBrainz Track Id");
+/// (`----:com.apple.iTunes:MusicBrainz Album Id`) The MusicBrainz release id.
+pub const MUSICBRAINZ_ALBUM_ID: FreeformIdentStatic =
+    FreeformIdent::new_static(APPLE_ITUNES_MEAN, "MusicBrainz Album Id");
+/// (`----:com.apple.iTunes:MusicBrainz Artist Id`) The MusicBrainz artist ids, one per artist.
+pub const MUSICBRAINZ_ARTIST_ID: FreeformIdentStatic =
+    FreeformIdent::new_static(APPLE_ITUNES_MEAN, "MusicBrainz Artist Id");
+/// (`----:com.apple.iTunes:MusicBrainz Release Group Id`) The MusicBrainz release group id.
+pub const MUSICBRAINZ_RELEASE_GROUP_ID: FreeformIdentStatic =
+    FreeformIdent::new_static(APPLE_ITUNES_MEAN, "MusicBrainz Release Group Id");
 
 /// A trait providing information about an identifier.
 pub trait Ident: PartialEq<DataIdent> {
@@ -217,6 +298,72 @@ pub fn idents_match(a: &impl Ident, b: &impl Ident) -> bool {
 #[derive(Clone, Copy, Default, PartialEq, Eq, Hash)]
 pub struct Fourcc(pub [u8; 4]);
 
+impl Fourcc {
+    /// Creates a fourcc from a string of 4 latin-1 characters, such as `©nam`. Each character is
+    /// stored as a single byte, the same way [`Fourcc`] is displayed.
+    ///
+    /// # Panics
+    /// Panics if the string doesn't contain exactly 4 characters, or contains a character that
+    /// isn't latin-1 (above `U+00FF`). In a const context this is a compile error, see
+    /// [`fourcc!`](crate::fourcc).
+    ///
+    /// # Example
+    /// ```
+    /// use mp4ameta::{Fourcc, ident};
+    ///
+    /// assert_eq!(Fourcc::from_latin1("©nam"), ident::TITLE);
+    /// assert_eq!(Fourcc::from_latin1("covr"), ident::ARTWORK);
+    /// ```
+    pub const fn from_latin1(s: &str) -> Self {
+        let bytes = s.as_bytes();
+        let mut fourcc = [0; 4];
+        let mut i = 0;
+        let mut n = 0;
+        while i < bytes.len() {
+            assert!(n < 4, "A fourcc must contain exactly 4 characters");
+            let b = bytes[i];
+            if b < 0x80 {
+                fourcc[n] = b;
+                i += 1;
+            } else {
+                // 2 byte utf-8 sequences starting with 0xc2 or 0xc3 encode U+0080 to U+00FF
+                assert!(b == 0xc2 || b == 0xc3, "A fourcc must only contain latin-1 characters");
+                fourcc[n] = ((b & 0x1f) << 6) | (bytes[i + 1] & 0x3f);
+                i += 2;
+            }
+            n += 1;
+        }
+        assert!(n == 4, "A fourcc must contain exactly 4 characters");
+        Fourcc(fourcc)
+    }
+}
+
+/// Creates a [`Fourcc`] from a string literal of 4 latin-1 characters at compile time, allowing
+/// the `©` character of the standard metadata items.
+///
+/// # Example
+/// ```
+/// use mp4ameta::{Fourcc, fourcc, ident};
+///
+/// const TITLE: Fourcc = fourcc!("©nam");
+/// assert_eq!(TITLE, ident::TITLE);
+/// assert_eq!(fourcc!("covr"), Fourcc(*b"covr"));
+/// ```
+///
+/// Strings that don't contain exactly 4 latin-1 characters fail to compile:
+/// ```compile_fail
+/// let fourcc = mp4ameta::fourcc!("©na");
+/// ```
+/// ```compile_fail
+/// let fourcc = mp4ameta::fourcc!("nam€");
+/// ```
+#[macro_export]
+macro_rules! fourcc {
+    ($s:literal) => {
+        const { $crate::Fourcc::from_latin1($s) }
+    };
+}
+
 impl Deref for Fourcc {
     type Target = [u8; 4];
 
@@ -231,6 +378,14 @@ impl DerefMut for Fourcc {
     }
 }
 
+// The derived `Hash` and `Eq` implementations only use the inner bytes, so they are consistent
+// with the ones of `[u8; 4]`.
+impl Borrow<[u8; 4]> for Fourcc {
+    fn borrow(&self) -> &[u8; 4] {
+        &self.0
+    }
+}
+
 impl PartialEq<DataIdent> for Fourcc {
     fn eq(&self, other: &DataIdent) -> bool {
         match other {
@@ -456,4 +611,103 @@ impl DataIdent {
     pub const fn fourcc(bytes: [u8; 4]) -> Self {
         Self::Fourcc(Fourcc(bytes))
     }
+
+    /// Returns a reference to the 4 bytes of the identifier, if it is of type
+    /// [`DataIdent::Fourcc`].
+    pub const fn fourcc_ref(&self) -> Option<&[u8; 4]> {
+        match self {
+            Self::Fourcc(Fourcc(bytes)) => Some(bytes),
+            Self::Freeform { .. } => None,
+        }
+    }
+}
+
+/// A map keyed by metadata item identifiers. Values of [`DataIdent::Fourcc`] identifiers can be
+/// looked up by their raw bytes, without constructing a [`DataIdent`].
+///
+/// # Example
+/// ```
+/// use mp4ameta::{IdentMap, ident};
+///
+/// let mut map = IdentMap::new();
+/// map.insert(ident::TITLE, "title");
+/// map.insert(ident::ISRC, "isrc");
+///
+/// assert_eq!(map.get_fourcc(b"\xa9nam"), Some(&"title"));
+/// assert_eq!(map.get(&ident::ISRC), Some(&"isrc"));
+/// assert_eq!(map.get(&ident::ALBUM), None);
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct IdentMap<V> {
+    fourccs: HashMap<Fourcc, V>,
+    freeforms: Vec<(DataIdent, V)>,
+}
+
+impl<V> Default for IdentMap<V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<V> IdentMap<V> {
+    /// Creates an empty map.
+    pub fn new() -> Self {
+        Self { fourccs: HashMap::new(), freeforms: Vec::new() }
+    }
+
+    /// Returns the number of entries.
+    pub fn len(&self) -> usize {
+        self.fourccs.len() + self.freeforms.len()
+    }
+
+    /// Returns true if the map contains no entries.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Inserts the value for the identifier, returning the previous value if there was one.
+    pub fn insert(&mut self, ident: impl Into<DataIdent>, value: V) -> Option<V> {
+        match ident.into() {
+            DataIdent::Fourcc(fourcc) => self.fourccs.insert(fourcc, value),
+            ident => match self.freeforms.iter_mut().find(|(i, _)| *i == ident) {
+                Some((_, v)) => Some(std::mem::replace(v, value)),
+                None => {
+                    self.freeforms.push((ident, value));
+                    None
+                }
+            },
+        }
+    }
+
+    /// Returns a reference to the value of the identifier.
+    pub fn get(&self, ident: &impl Ident) -> Option<&V> {
+        match ident.fourcc() {
+            Some(fourcc) => self.fourccs.get(&fourcc),
+            None => self.freeforms.iter().find(|(i, _)| ident == i).map(|(_, v)| v),
+        }
+    }
+
+    /// Returns a mutable reference to the value of the identifier.
+    pub fn get_mut(&mut self, ident: &impl Ident) -> Option<&mut V> {
+        match ident.fourcc() {
+            Some(fourcc) => self.fourccs.get_mut(&fourcc),
+            None => self.freeforms.iter_mut().find(|(i, _)| ident == i).map(|(_, v)| v),
+        }
+    }
+
+    /// Returns a reference to the value of the [`DataIdent::Fourcc`] identifier with the bytes.
+    pub fn get_fourcc(&self, fourcc: &[u8; 4]) -> Option<&V> {
+        self.fourccs.get(fourcc)
+    }
+
+    /// Removes and returns the value of the identifier.
+    pub fn remove(&mut self, ident: &impl Ident) -> Option<V> {
+        match ident.fourcc() {
+            Some(fourcc) => self.fourccs.remove(&fourcc),
+            None => {
+                let idx = self.freeforms.iter().position(|(i, _)| ident == i)?;
+                Some(self.freeforms.remove(idx).1)
+            }
+        }
+    }
 }
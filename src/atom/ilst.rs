@@ -74,3 +74,83 @@ impl LeafAtomCollectChanges for Ilst<'_> {
         AtomRef::Ilst(self)
     }
 }
+
+/// The order in which iTunes writes the metadata items. Freeform items follow after these, and
+/// unknown items come last.
+const ITUNES_ORDER: [Fourcc; 45] = [
+    TITLE,
+    ARTIST,
+    ALBUM_ARTIST,
+    ALBUM,
+    GROUPING,
+    COMPOSER,
+    COMMENT,
+    STANDARD_GENRE,
+    CUSTOM_GENRE,
+    YEAR,
+    TRACK_NUMBER,
+    DISC_NUMBER,
+    BPM,
+    COMPILATION,
+    GAPLESS_PLAYBACK,
+    ADVISORY_RATING,
+    MEDIA_TYPE,
+    LYRICS,
+    DESCRIPTION,
+    TV_SHOW_NAME,
+    TV_EPISODE_NAME,
+    TV_NETWORK_NAME,
+    TV_EPISODE,
+    TV_SEASON,
+    MOVEMENT,
+    MOVEMENT_INDEX,
+    MOVEMENT_COUNT,
+    WORK,
+    SHOW_MOVEMENT,
+    CATEGORY,
+    KEYWORD,
+    PODCAST,
+    PODCAST_URL,
+    PODCAST_EPISODE_GLOBAL_UNIQUE_ID,
+    PURCHASE_DATE,
+    COPYRIGHT,
+    PUBLISHER,
+    ENCODER,
+    TITLE_SORT_ORDER,
+    ARTIST_SORT_ORDER,
+    ALBUM_ARTIST_SORT_ORDER,
+    ALBUM_SORT_ORDER,
+    COMPOSER_SORT_ORDER,
+    TV_SHOW_NAME_SORT_ORDER,
+    ARTWORK,
+];
+
+/// Sorts the items into the order iTunes writes them in, keeping the relative order of items with
+/// the same rank.
+pub fn sort_itunes_canonical(items: &mut [MetaItem]) {
+    items.sort_by_key(|item| match &item.ident {
+        DataIdent::Fourcc(f) => {
+            ITUNES_ORDER.iter().position(|o| o == f).unwrap_or(ITUNES_ORDER.len() + 1)
+        }
+        DataIdent::Freeform { .. } => ITUNES_ORDER.len(),
+    });
+}
+
+/// Sorts the items by the bytes of their identifier, freeform items by their mean and name.
+pub fn sort_alphabetical(items: &mut [MetaItem]) {
+    items.sort_by(|a, b| match (&a.ident, &b.ident) {
+        (DataIdent::Fourcc(a), DataIdent::Fourcc(b)) => a.cmp(b),
+        (DataIdent::Fourcc(a), DataIdent::Freeform { .. }) => a.cmp(&FREEFORM),
+        (DataIdent::Freeform { .. }, DataIdent::Fourcc(b)) => FREEFORM.cmp(b),
+        (
+            DataIdent::Freeform { mean: mean_a, name: name_a },
+            DataIdent::Freeform { mean: mean_b, name: name_b },
+        ) => (mean_a, name_a).cmp(&(mean_b, name_b)),
+    });
+}
+
+/// Sorts the items into the order of the identifiers, keeping the relative order of items that
+/// aren't listed after the listed ones.
+pub fn sort_custom(items: &mut [MetaItem], order: &[DataIdent]) {
+    items.sort_by_key(|item| order.iter().position(|o| *o == item.ident).unwrap_or(order.len()));
+}
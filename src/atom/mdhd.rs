@@ -12,10 +12,18 @@ const UNSPECIFIED_LANGUAGE: u16 = i16::MAX as u16;
 
 #[derive(Clone, Debug, Default, PartialEq, Eq)]
 pub struct Mdhd {
+    pub state: State,
     pub version: u8,
     pub flags: [u8; 3],
+    /// The creation time in seconds since 1904
+    pub creation_time: u64,
+    /// The modification time in seconds since 1904
+    pub modification_time: u64,
     pub timescale: u32,
     pub duration: u64,
+    /// The ISO 639-2/T language code, `None` if unspecified.
+    pub language: Option<String>,
+    pub quality: u16,
 }
 
 #[derive(Default)]
@@ -64,7 +72,11 @@ impl ParseAtom for Mdhd {
         _cfg: &ParseConfig<'_>,
         size: Size,
     ) -> crate::Result<Self> {
-        let mut mdhd = Self::default();
+        let bounds = find_bounds(reader, size)?;
+        let mut mdhd = Self {
+            state: State::Existing(bounds),
+            ..Default::default()
+        };
 
         let (version, flags) = head::parse_full(reader)?;
         mdhd.version = version;
@@ -76,16 +88,24 @@ impl ParseAtom for Mdhd {
 
                 let mut buf = MdhdBufV0::default();
                 reader.read_exact(buf.bytes_mut())?;
+                mdhd.creation_time = u32::from_be_bytes(buf.creation_time) as u64;
+                mdhd.modification_time = u32::from_be_bytes(buf.modification_time) as u64;
                 mdhd.timescale = u32::from_be_bytes(buf.timescale);
                 mdhd.duration = u32::from_be_bytes(buf.duration) as u64;
+                mdhd.language = decode_language(u16::from_be_bytes(buf.language));
+                mdhd.quality = u16::from_be_bytes(buf.quality);
             }
             1 => {
                 expect_size("Media header (mdhd) version 1", size, HEADER_SIZE_V1 as u64)?;
 
                 let mut buf = MdhdBufV1::default();
                 reader.read_exact(buf.bytes_mut())?;
+                mdhd.creation_time = u64::from_be_bytes(buf.creation_time);
+                mdhd.modification_time = u64::from_be_bytes(buf.modification_time);
                 mdhd.timescale = u32::from_be_bytes(buf.timescale);
                 mdhd.duration = u64::from_be_bytes(buf.duration);
+                mdhd.language = decode_language(u16::from_be_bytes(buf.language));
+                mdhd.quality = u16::from_be_bytes(buf.quality);
             }
             _ => {
                 return unknown_version("media header (mdhd)", version);
@@ -114,19 +134,23 @@ impl WriteAtom for Mdhd {
         match self.version {
             0 => {
                 let mut buf = MdhdBufV0 {
+                    creation_time: u32::to_be_bytes(self.creation_time as u32),
+                    modification_time: u32::to_be_bytes(self.modification_time as u32),
                     timescale: u32::to_be_bytes(self.timescale),
                     duration: u32::to_be_bytes(self.duration as u32),
-                    language: u16::to_be_bytes(UNSPECIFIED_LANGUAGE),
-                    ..Default::default()
+                    language: u16::to_be_bytes(encode_language(self.language.as_deref())),
+                    quality: u16::to_be_bytes(self.quality),
                 };
                 writer.write_all(buf.bytes_mut())?;
             }
             1 => {
                 let mut buf = MdhdBufV1 {
+                    creation_time: u64::to_be_bytes(self.creation_time),
+                    modification_time: u64::to_be_bytes(self.modification_time),
                     timescale: u32::to_be_bytes(self.timescale),
                     duration: u64::to_be_bytes(self.duration),
-                    language: u16::to_be_bytes(UNSPECIFIED_LANGUAGE),
-                    ..Default::default()
+                    language: u16::to_be_bytes(encode_language(self.language.as_deref())),
+                    quality: u16::to_be_bytes(self.quality),
                 };
                 writer.write_all(buf.bytes_mut())?;
             }
@@ -141,3 +165,34 @@ impl WriteAtom for Mdhd {
         Ok(())
     }
 }
+
+impl LeafAtomCollectChanges for Mdhd {
+    fn state(&self) -> &State {
+        &self.state
+    }
+
+    fn atom_ref(&self) -> AtomRef<'_> {
+        AtomRef::Mdhd(self)
+    }
+}
+
+/// Returns whether the language is a valid ISO 639-2/T code of three lowercase ASCII letters.
+pub fn is_valid_language(language: &str) -> bool {
+    language.len() == 3 && language.bytes().all(|c| c.is_ascii_lowercase())
+}
+
+/// Decodes a packed language code, 3 times 5 bits storing the characters offset by `0x60`.
+fn decode_language(packed: u16) -> Option<String> {
+    if packed & 0x7fff == UNSPECIFIED_LANGUAGE || packed == 0 {
+        return None;
+    }
+    let chars = [10, 5, 0].map(|shift| ((packed >> shift) & 0x1f) as u8 + 0x60);
+    Some(chars.iter().map(|&c| c as char).collect())
+}
+
+fn encode_language(language: Option<&str>) -> u16 {
+    let Some(language) = language.filter(|l| is_valid_language(l)) else {
+        return UNSPECIFIED_LANGUAGE;
+    };
+    language.bytes().fold(0, |packed, c| (packed << 5) | (c - 0x60) as u16)
+}
@@ -5,6 +5,8 @@ pub struct Mdia {
     pub state: State,
     pub mdhd: Mdhd,
     pub hdlr: Option<Hdlr>,
+    /// The extended language tag (`elng`), which is only read.
+    pub elng: Option<String>,
     pub minf: Option<Minf>,
 }
 
@@ -22,7 +24,10 @@ impl ParseAtom for Mdia {
         let mut parsed_bytes = 0;
         let mut mdhd = None;
         let mut hdlr = None;
+        let mut elng = None;
         let mut minf = None;
+        let parse_hdlr = cfg.write || cfg.cfg.read_audio_info || cfg.cfg.read_video_info;
+        let parse_elng = cfg.cfg.read_audio_info || cfg.cfg.read_video_info;
 
         while parsed_bytes < size.content_len() {
             let remaining_bytes = size.content_len() - parsed_bytes;
@@ -30,14 +35,22 @@ impl ParseAtom for Mdia {
 
             match head.fourcc() {
                 MEDIA_HEADER => mdhd = Some(Mdhd::parse(reader, cfg, head.size())?),
-                HANDLER_REFERENCE if cfg.write => {
+                HANDLER_REFERENCE if parse_hdlr => {
                     hdlr = Some(Hdlr::parse(reader, cfg, head.size())?)
                 }
+                EXTENDED_LANGUAGE if parse_elng => elng = parse_elng_tag(reader, head.size())?,
                 MEDIA_INFORMATION => minf = Some(Minf::parse(reader, cfg, head.size())?),
                 _ => reader.skip(head.content_len() as i64)?,
             }
 
             parsed_bytes += head.len();
+
+            // the optional extended language tag precedes the media information
+            if mdhd.is_some() && minf.is_some() && (hdlr.is_some() || !parse_hdlr) {
+                // the remaining atoms aren't needed
+                reader.skip((size.content_len() - parsed_bytes) as i64)?;
+                break;
+            }
         }
 
         let mdhd = mdhd.ok_or_else(|| {
@@ -47,12 +60,31 @@ impl ParseAtom for Mdia {
             )
         })?;
 
-        let mdia = Self { state: State::Existing(bounds), mdhd, hdlr, minf };
+        let mdia = Self {
+            state: State::Existing(bounds),
+            mdhd,
+            hdlr,
+            elng,
+            minf,
+        };
 
         Ok(mdia)
     }
 }
 
+/// Parses the extended language tag (`elng`), a null terminated string following the version and
+/// flags.
+fn parse_elng_tag(reader: &mut (impl Read + Seek), size: Size) -> crate::Result<Option<String>> {
+    expect_min_size("Extended language (elng)", size, 4)?;
+    head::parse_full(reader)?;
+
+    let mut buf = vec![0; size.content_len() as usize - 4];
+    reader.read_exact(&mut buf)?;
+    let end = buf.iter().position(|&b| b == 0).unwrap_or(buf.len());
+    buf.truncate(end);
+    Ok(String::from_utf8(buf).ok().filter(|l| !l.is_empty()))
+}
+
 impl AtomSize for Mdia {
     fn size(&self) -> Size {
         let content_len = self.mdhd.len() + self.hdlr.len_or_zero() + self.minf.len_or_zero();
@@ -85,7 +117,8 @@ impl SimpleCollectChanges for Mdia {
         bounds: &'a AtomBounds,
         changes: &mut Vec<Change<'a>>,
     ) -> i64 {
-        self.hdlr.collect_changes(bounds.end(), level, changes)
+        self.mdhd.collect_changes(bounds.end(), level, changes)
+            + self.hdlr.collect_changes(bounds.end(), level, changes)
             + self.minf.collect_changes(bounds.end(), level, changes)
     }
 
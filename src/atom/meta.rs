@@ -7,6 +7,7 @@ pub struct Meta<'a> {
     pub state: State,
     pub hdlr: Option<Hdlr>,
     pub ilst: Option<Ilst<'a>>,
+    pub free: Option<Free>,
 }
 
 impl Atom for Meta<'_> {
@@ -39,14 +40,20 @@ impl ParseAtom for Meta<'_> {
             let head = head::parse(reader, remaining_bytes)?;
 
             match head.fourcc() {
-                HANDLER_REFERENCE if cfg.write => {
-                    meta.hdlr = Some(Hdlr::parse(reader, cfg, head.size())?)
-                }
+                // also parsed when reading, to check that it is present
+                HANDLER_REFERENCE => meta.hdlr = Some(Hdlr::parse(reader, cfg, head.size())?),
                 ITEM_LIST => meta.ilst = Some(Ilst::parse(reader, cfg, head.size())?),
+                FREE if cfg.write => meta.free = Some(Free::parse(reader, cfg, head.size())?),
                 _ => reader.skip(head.content_len() as i64)?,
             }
 
             parsed_bytes += head.len();
+
+            if !cfg.write && meta.hdlr.is_some() && meta.ilst.is_some() {
+                // the remaining atoms aren't needed
+                reader.skip((size.content_len() - parsed_bytes) as i64)?;
+                break;
+            }
         }
 
         Ok(meta)
@@ -55,7 +62,10 @@ impl ParseAtom for Meta<'_> {
 
 impl AtomSize for Meta<'_> {
     fn size(&self) -> Size {
-        let content_len = HEADER_SIZE + self.hdlr.len_or_zero() + self.ilst.len_or_zero();
+        let content_len = HEADER_SIZE
+            + self.hdlr.len_or_zero()
+            + self.ilst.len_or_zero()
+            + self.free.len_or_zero();
         Size::from(content_len)
     }
 }
@@ -70,6 +80,9 @@ impl WriteAtom for Meta<'_> {
         if let Some(a) = &self.ilst {
             a.write(writer, changes)?;
         }
+        if let Some(a) = &self.free {
+            a.write(writer, changes)?;
+        }
         Ok(())
     }
 }
@@ -87,9 +100,30 @@ impl SimpleCollectChanges for Meta<'_> {
     ) -> i64 {
         self.hdlr.collect_changes(bounds.content_pos() + HEADER_SIZE, level, changes)
             + self.ilst.collect_changes(bounds.end(), level, changes)
+            + self.free.collect_changes(bounds.end(), level, changes)
     }
 
     fn atom_ref(&self) -> AtomRef<'_> {
         AtomRef::Meta(self)
     }
 }
+
+impl Meta<'_> {
+    /// Whether this atom won't contain anything but a handler and free space after the changes
+    /// are applied.
+    pub fn is_empty(&self) -> bool {
+        if self.ilst.as_ref().is_some_and(|a| !a.state.is_remove()) {
+            return false;
+        }
+        match &self.state {
+            State::Existing(bounds) => {
+                let known_len = HEADER_SIZE
+                    + self.hdlr.prev_len_or_zero()
+                    + self.ilst.prev_len_or_zero()
+                    + self.free.prev_len_or_zero();
+                bounds.content_len() == known_len
+            }
+            _ => true,
+        }
+    }
+}
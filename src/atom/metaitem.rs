@@ -50,11 +50,33 @@ impl MetaItem {
         let mut name: Option<String> = None;
         let mut parsed_bytes = 0;
 
+        // The identifier of freeform items is only known after parsing the `mean` and `name`
+        // atoms, so their data is skipped first and read afterwards if needed.
+        let skip_idents = &cfg.cfg.skip_idents;
+        let skip = match head.fourcc() {
+            FREEFORM => {
+                cfg.skip_data || skip_idents.iter().any(|i| matches!(i, DataIdent::Freeform { .. }))
+            }
+            fourcc => cfg.skips(&DataIdent::Fourcc(fourcc)),
+        };
+
         while parsed_bytes < head.content_len() {
             let remaining_bytes = head.content_len() - parsed_bytes;
-            let head = head::parse(reader, remaining_bytes)?;
-
+            let head = head::parse(reader, remaining_bytes).map_err(|e| match e.kind {
+                ErrorKind::AtomSizeOutOfBounds if head.fourcc() == ARTWORK => crate::Error::new(
+                    ErrorKind::InvalidAtomSize,
+                    format!(
+                        "Artwork (covr) data exceeds the bounds of its item: {}",
+                        e.description
+                    ),
+                ),
+                _ => e,
+            })?;
+
+            // some encoders don't adhere to the `mean`, `name`, `data` order, so the child atoms
+            // are matched by their identifier
             match head.fourcc() {
+                DATA if skip => data.push(Data::skip(reader, head.size())?),
                 DATA => data.push(Data::parse(reader, cfg, head.size())?),
                 MEAN => {
                     let (version, _) = head::parse_full(reader)?;
@@ -85,6 +107,14 @@ impl MetaItem {
             (fourcc, _, _) => DataIdent::Fourcc(fourcc),
         };
 
+        if skip && !cfg.skips(&ident) {
+            let end = reader.stream_position()?;
+            let data: crate::Result<Vec<_>> =
+                data.into_iter().map(|d| d.read_skipped(reader, cfg)).collect();
+            reader.seek(SeekFrom::Start(end))?;
+            return Ok(MetaItem { ident, data: data? });
+        }
+
         Ok(MetaItem { ident, data })
     }
 
@@ -122,3 +152,55 @@ impl MetaItem {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn parse_freeform() {
+        #[rustfmt::skip]
+        let buf = [
+            &[0, 0, 0, 74][..], b"----",
+            &[0, 0, 0, 28], b"mean", &[0, 0, 0, 0], b"com.apple.iTunes",
+            &[0, 0, 0, 16], b"name", &[0, 0, 0, 0], b"ISRC",
+            &[0, 0, 0, 22], b"data", &[0, 0, 0, 1], &[0, 0, 0, 0], b"US1234",
+        ]
+        .concat();
+
+        let mut reader = Cursor::new(&buf);
+        let head = head::parse(&mut reader, buf.len() as u64).unwrap();
+        let cfg = ParseConfig {
+            cfg: &ReadConfig::DEFAULT,
+            write: false,
+            skip_data: false,
+        };
+        let item = MetaItem::parse(&mut reader, &cfg, head).unwrap();
+
+        assert_eq!(item.ident, DataIdent::freeform("com.apple.iTunes", "ISRC"));
+        assert_eq!(item.data, [Data::Utf8("US1234".to_owned())]);
+    }
+
+    #[test]
+    fn skip_extended_data() {
+        #[rustfmt::skip]
+        let buf = [
+            &[0, 0, 0, 36][..], b"covr",
+            &[0, 0, 0, 1], b"data", &[0, 0, 0, 0, 0, 0, 0, 28], &[0, 0, 0, 14], &[0, 0, 0, 0],
+            &[0x89, b'P', b'N', b'G'],
+        ]
+        .concat();
+
+        let mut reader = Cursor::new(&buf);
+        let head = head::parse(&mut reader, buf.len() as u64).unwrap();
+        let read_cfg = ReadConfig { skip_artwork: true, ..ReadConfig::DEFAULT };
+        let cfg = ParseConfig { cfg: &read_cfg, write: false, skip_data: false };
+        let item = MetaItem::parse(&mut reader, &cfg, head).unwrap();
+
+        let Data::Skipped(skipped) = &item.data[0] else { panic!("data wasn't skipped") };
+        let artwork = ArtworkRef::new(skipped).unwrap();
+        assert_eq!(artwork.len(), 4);
+        assert_eq!(artwork.offset(), 32);
+        assert_eq!(skipped.load(&mut reader).unwrap(), Data::Png(b"\x89PNG".to_vec()));
+    }
+}
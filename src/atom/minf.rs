@@ -41,6 +41,12 @@ impl ParseAtom for Minf {
             }
 
             parsed_bytes += head.len();
+
+            if minf.stbl.is_some() && !cfg.write {
+                // the remaining atoms aren't needed
+                reader.skip((size.content_len() - parsed_bytes) as i64)?;
+                break;
+            }
         }
 
         Ok(minf)
@@ -23,6 +23,10 @@
 //! │           ├─ stsd
 //! │           │  ├─ mp4a
 //! │           │  │  └─ esds
+//! │           │  ├─ alac
+//! │           │  │  └─ alac
+//! │           │  ├─ avc1, hvc1, hev1, mp4v
+//! │           │  │  └─ pasp
 //! │           │  └─ text
 //! │           ├─ stts
 //! │           ├─ stsc
@@ -31,25 +35,35 @@
 //! │           └─ co64
 //! └─ udta
 //!    ├─ chpl
-//!    └─ meta
-//!       ├─ hdlr
-//!       └─ ilst
-//!          ├─ **** (any fourcc)
-//!          │  └─ data
-//!          └─ ---- (freeform fourcc)
-//!             ├─ mean
-//!             ├─ name
-//!             └─ data
+//!    ├─ meta
+//!    │  ├─ hdlr
+//!    │  ├─ ilst
+//!    │  │  ├─ **** (any fourcc)
+//!    │  │  │  └─ data
+//!    │  │  └─ ---- (freeform fourcc)
+//!    │  │     ├─ mean
+//!    │  │     ├─ name
+//!    │  │     └─ data
+//!    │  └─ free
+//!    └─ free
+//! free
 //! ```
 
 use std::borrow::Cow;
 use std::convert::TryFrom;
+use std::fmt;
 use std::fs::File;
 use std::io::{BufReader, BufWriter, Cursor, Read, Seek, SeekFrom, Write};
 use std::num::NonZeroU32;
 use std::ops::Deref;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{Duration, SystemTime};
 
-use crate::{AudioInfo, Chapter, ErrorKind, Tag, Userdata};
+use crate::{
+    AudioInfo, Chapter, Codec, ErrorKind, FileType, Img, ImgBuf, ImgFmt, Tag, Timestamp, TrackInfo,
+    Userdata, VideoInfo,
+};
 
 use change::{
     AtomRef, Change, ChunkOffsetInt, ChunkOffsets, CollectChanges, LeafAtomCollectChanges,
@@ -60,11 +74,13 @@ use ident::*;
 use state::State;
 use util::*;
 
+use alac::Alac;
 use chap::Chap;
-use chpl::{Chpl, ChplData};
+use chpl::{Chpl, ChplData, ChplItem};
 use co64::Co64;
 use dinf::Dinf;
 use dref::Dref;
+use free::Free;
 use ftyp::Ftyp;
 use gmhd::Gmhd;
 use gmin::Gmin;
@@ -72,6 +88,7 @@ use hdlr::Hdlr;
 use ilst::Ilst;
 use mdat::Mdat;
 use mdhd::Mdhd;
+pub(crate) use mdhd::is_valid_language;
 use mdia::Mdia;
 use meta::Meta;
 use minf::Minf;
@@ -90,25 +107,49 @@ use trak::Trak;
 use tref::Tref;
 use udta::Udta;
 use url::*;
+use visual::Visual;
 
-pub use data::Data;
+pub use data::{ArtworkRef, Data, DataSource, SkippedData};
 pub use metaitem::MetaItem;
 
+pub(crate) use faststart::move_moov_to_front;
+pub(crate) use util::{scale_duration, unscale_duration};
+
 /// A module for working with identifiers.
+///
+/// All identifiers of the atoms and freeform items that are supported, are publicly reachable
+/// through this module.
+///
+/// ```
+/// use mp4ameta::ident::{ARTIST, ISRC, LYRICIST};
+/// use mp4ameta::{Data, Tag};
+///
+/// let mut tag = Tag::default();
+/// tag.set_data(ARTIST, Data::Utf8("artist".to_owned()));
+/// tag.set_data(ISRC, Data::Utf8("isrc".to_owned()));
+/// tag.set_data(LYRICIST, Data::Utf8("lyricist".to_owned()));
+///
+/// assert_eq!(tag.data_of(&ARTIST).next(), Some(&Data::Utf8("artist".to_owned())));
+/// assert_eq!(tag.strings_of(&ISRC).next(), Some("isrc"));
+/// assert_eq!(tag.strings_of(&LYRICIST).next(), Some("lyricist"));
+/// ```
 pub mod ident;
 
 #[macro_use]
 mod util;
 mod change;
+mod faststart;
 mod head;
 mod state;
 
+mod alac;
 mod chap;
 mod chpl;
 mod co64;
 mod data;
 mod dinf;
 mod dref;
+mod free;
 mod ftyp;
 mod gmhd;
 mod gmin;
@@ -135,6 +176,7 @@ mod trak;
 mod tref;
 mod udta;
 mod url;
+mod visual;
 
 trait Atom: Sized {
     const FOURCC: Fourcc;
@@ -212,6 +254,16 @@ impl<T: AtomSize> LenOrZero for Option<T> {
     }
 }
 
+trait PrevLenOrZero {
+    fn prev_len_or_zero(&self) -> u64;
+}
+
+impl<T: SimpleCollectChanges> PrevLenOrZero for Option<T> {
+    fn prev_len_or_zero(&self) -> u64 {
+        self.as_ref().map_or(0, |a| SimpleCollectChanges::state(a).prev_len())
+    }
+}
+
 trait PushAndGet<T> {
     fn push_and_get(&mut self, item: T) -> &mut T;
 }
@@ -267,6 +319,14 @@ pub struct ReadConfig {
     /// Wheter image data will be read, mostly for performance reasons.
     /// If disabled, images will still show up as empty [`Data`].
     pub read_image_data: bool,
+    /// Wheter the payload of artwork images (`covr`) will be skipped, the same as if it was
+    /// contained in [`ReadConfig::skip_idents`].
+    pub skip_artwork: bool,
+    /// The metadata items whose data payloads will be skipped, only recording their location and
+    /// size as [`Data::Skipped`]. This avoids reading large payloads into memory, while still
+    /// preserving them when writing the tag to the same file. Since writing may move the skipped
+    /// payloads, the tag has to be read again before writing it another time.
+    pub skip_idents: Vec<DataIdent>,
     /// Wheter chapter list information will be read.
     pub read_chapter_list: bool,
     /// Wheter chapter track information will be read.
@@ -274,8 +334,15 @@ pub struct ReadConfig {
     /// Wheter audio information will be read.
     /// Even if disabled, the [`AudioInfo::duration`] will be read.
     pub read_audio_info: bool,
+    /// Wheter video information will be read.
+    pub read_video_info: bool,
     /// The timescale that is used to scale time for chapter list (chpl) atoms.
     pub chpl_timescale: ChplTimescale,
+    /// The encoding used to decode utf-8 strings containing invalid utf-8, such as Latin-1 or
+    /// Shift-JIS text written by some older tools. If `None` such strings are kept as is in
+    /// [`Data::Unknown`].
+    /// An encoding can only be specified with the `encoding_rs` feature.
+    pub fallback_encoding: Option<FallbackEncoding>,
 }
 
 impl ReadConfig {
@@ -283,10 +350,44 @@ impl ReadConfig {
     pub const DEFAULT: ReadConfig = ReadConfig {
         read_meta_items: true,
         read_image_data: true,
+        skip_artwork: false,
+        skip_idents: Vec::new(),
         read_chapter_list: true,
         read_chapter_track: true,
         read_audio_info: true,
+        read_video_info: true,
         chpl_timescale: ChplTimescale::DEFAULT,
+        fallback_encoding: None,
+    };
+
+    /// A configuration that only reads the audio information, skipping the user data (`udta`)
+    /// containing the metadata items and chapter list.
+    pub const AUDIO_INFO_ONLY: ReadConfig = ReadConfig {
+        read_meta_items: false,
+        read_image_data: false,
+        skip_artwork: false,
+        skip_idents: Vec::new(),
+        read_chapter_list: false,
+        read_chapter_track: false,
+        read_audio_info: true,
+        read_video_info: false,
+        chpl_timescale: ChplTimescale::DEFAULT,
+        fallback_encoding: None,
+    };
+
+    /// A configuration that only reads the metadata items, including image data, and the chapter
+    /// list, skipping the sample tables of the tracks.
+    pub const METADATA_ONLY: ReadConfig = ReadConfig {
+        read_meta_items: true,
+        read_image_data: true,
+        skip_artwork: false,
+        skip_idents: Vec::new(),
+        read_chapter_list: true,
+        read_chapter_track: false,
+        read_audio_info: false,
+        read_video_info: false,
+        chpl_timescale: ChplTimescale::DEFAULT,
+        fallback_encoding: None,
     };
 
     /// A configuration that would read no data at all.
@@ -303,66 +404,185 @@ impl ReadConfig {
     pub const NONE: ReadConfig = ReadConfig {
         read_meta_items: false,
         read_image_data: false,
+        skip_artwork: false,
+        skip_idents: Vec::new(),
         read_chapter_list: false,
         read_chapter_track: false,
         read_audio_info: false,
+        read_video_info: false,
         chpl_timescale: ChplTimescale::DEFAULT,
+        fallback_encoding: None,
     };
 }
 
+impl ReadConfig {
+    /// Returns whether the data payloads of the metadata item will be skipped.
+    fn skips(&self, ident: &DataIdent) -> bool {
+        (self.skip_artwork && ARTWORK == *ident) || self.skip_idents.contains(ident)
+    }
+}
+
 impl Default for ReadConfig {
     fn default() -> Self {
         Self::DEFAULT.clone()
     }
 }
 
+/// The encoding used to decode strings containing invalid utf-8, see
+/// [`ReadConfig::fallback_encoding`]. It can only be created with the `encoding_rs` feature.
+///
+/// ```
+/// # #[cfg(feature = "encoding_rs")] {
+/// use mp4ameta::{FallbackEncoding, ReadConfig};
+///
+/// let cfg = ReadConfig {
+///     fallback_encoding: Some(FallbackEncoding::new(encoding_rs::SHIFT_JIS)),
+///     ..ReadConfig::DEFAULT
+/// };
+/// # }
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct FallbackEncoding(
+    #[cfg(feature = "encoding_rs")] &'static encoding_rs::Encoding,
+    #[cfg(not(feature = "encoding_rs"))] std::convert::Infallible,
+);
+
+#[cfg(feature = "encoding_rs")]
+impl FallbackEncoding {
+    /// Creates a fallback encoding using the encoding.
+    pub const fn new(encoding: &'static encoding_rs::Encoding) -> Self {
+        Self(encoding)
+    }
+
+    /// Returns the encoding.
+    pub const fn encoding(&self) -> &'static encoding_rs::Encoding {
+        self.0
+    }
+}
+
+#[cfg(feature = "encoding_rs")]
+impl From<&'static encoding_rs::Encoding> for FallbackEncoding {
+    fn from(encoding: &'static encoding_rs::Encoding) -> Self {
+        Self(encoding)
+    }
+}
+
+impl FallbackEncoding {
+    /// Decodes the bytes, replacing malformed sequences.
+    #[cfg(feature = "encoding_rs")]
+    fn decode(&self, bytes: &[u8]) -> String {
+        self.0.decode_without_bom_handling(bytes).0.into_owned()
+    }
+
+    /// Can't be called, since the encoding can't be created without the `encoding_rs` feature.
+    #[cfg(not(feature = "encoding_rs"))]
+    fn decode(&self, _: &[u8]) -> String {
+        match self.0 {}
+    }
+}
+
 pub struct ParseConfig<'a> {
     cfg: &'a ReadConfig,
     write: bool,
+    /// Whether the data payloads of all metadata items will be skipped.
+    skip_data: bool,
+}
+
+impl ParseConfig<'_> {
+    /// Returns whether the data payloads of the metadata item will be skipped.
+    fn skips(&self, ident: &DataIdent) -> bool {
+        self.skip_data || self.cfg.skips(ident)
+    }
 }
 
 pub(crate) fn read_tag(reader: &mut (impl Read + Seek), cfg: &ReadConfig) -> crate::Result<Tag> {
-    let parse_cfg = ParseConfig { cfg, write: false };
+    read_tag_and_layout(reader, cfg, false).map(|(tag, _)| tag)
+}
+
+/// Reads the tag, and if `layout` is set, the layout of the file for the next write in the same
+/// pass. The layout is only complete if `cfg` reads the metadata items and chapters.
+fn read_tag_and_layout<'a>(
+    reader: &mut (impl Read + Seek),
+    cfg: &ReadConfig,
+    layout: bool,
+) -> crate::Result<(Tag, Option<WriteTarget<'a>>)> {
+    let parse_cfg = ParseConfig { cfg, write: layout, skip_data: false };
 
     let file_len = reader.seek(SeekFrom::End(0))?;
     reader.seek(SeekFrom::Start(0))?;
 
     let ftyp = Ftyp::parse(reader, file_len)?;
 
+    // the top level atoms are traversed completely, to sum up the lengths of all mdat atoms
     let mut parsed_bytes = ftyp.size.len();
-    let mut moov = loop {
-        if parsed_bytes >= file_len {
-            return Err(crate::Error::new(
-                ErrorKind::AtomNotFound(MOVIE),
-                "Missing necessary data, no movie (moov) atom found",
-            ));
-        }
-
+    let mut moov = None;
+    let mut mdat_len = 0;
+    let mut mdat_bounds = None;
+    let mut moov_end = 0;
+    let mut mdat_after_moov = false;
+    let mut trailing_free = None;
+    let mut prev_fourcc = FILETYPE;
+    let mut multiple_moov = false;
+    while parsed_bytes < file_len {
         let remaining_bytes = file_len - parsed_bytes;
-        let head = head::parse(reader, remaining_bytes)?;
-        if head.fourcc() == MOVIE {
-            break Moov::parse(reader, &parse_cfg, head.size())?;
+        let head = match head::parse_top_level(reader, remaining_bytes) {
+            Ok(h) => h,
+            // Trailing garbage after the movie atom, such as a partial atom head or one with an
+            // invalid or out of bounds size, is ignored.
+            Err(e) if moov.is_some() && is_trailing_garbage(&e) => break,
+            Err(e) => return Err(e),
+        };
+        match head.fourcc() {
+            MOVIE if moov.is_none() => {
+                moov = Some(Moov::parse(reader, &parse_cfg, head.size())?);
+                moov_end = parsed_bytes + head.len();
+            }
+            MEDIA_DATA => {
+                mdat_len += head.content_len();
+                mdat_bounds = Some(Mdat::read_bounds(reader, head.size())?);
+                mdat_after_moov |= moov.is_some();
+            }
+            // padding directly following the moov atom can absorb a changed length
+            FREE if layout && prev_fourcc == MOVIE => {
+                trailing_free = Some(Free::parse(reader, &parse_cfg, head.size())?)
+            }
+            fourcc => {
+                multiple_moov |= fourcc == MOVIE;
+                reader.skip(head.content_len() as i64)?;
+            }
         }
-
-        reader.skip(head.content_len() as i64)?;
+        prev_fourcc = head.fourcc();
         parsed_bytes += head.len();
+    }
+    let Some(mut moov) = moov else {
+        return Err(crate::Error::new(
+            ErrorKind::AtomNotFound(MOVIE),
+            "Missing necessary data, no movie (moov) atom found",
+        ));
     };
 
-    let mvhd = moov.mvhd;
+    // The sample descriptions are only parsed if track information was requested.
+    let encrypted = moov.trak.iter().any(|trak| {
+        let stbl = trak.mdia.as_ref().and_then(|a| a.minf.as_ref()).and_then(|a| a.stbl.as_ref());
+        stbl.and_then(|a| a.stsd.as_ref()).is_some_and(|a| a.encrypted)
+    });
+
+    let mvhd = &moov.mvhd;
     let duration = scale_duration(mvhd.timescale, mvhd.duration);
 
-    let meta_items = moov
-        .udta
-        .as_mut()
-        .and_then(|a| a.meta.take())
-        .and_then(|a| a.ilst)
-        .map(|a| a.data.into_owned())
-        .unwrap_or_default();
+    // the layout keeps the user data, which is replaced during the next write
+    let mut udta = match layout {
+        true => moov.udta.clone(),
+        false => moov.udta.take(),
+    };
+    let meta = udta.as_mut().and_then(|a| a.meta.take());
+    let meta_hdlr_missing = meta.as_ref().is_some_and(|a| a.hdlr.is_none());
+    let meta_items = meta.and_then(|a| a.ilst).map(|a| a.data.into_owned()).unwrap_or_default();
 
     // chapter list atom
     let mut chapter_list = Vec::new();
     if cfg.read_chapter_list
-        && let Some(udta) = moov.udta
+        && let Some(udta) = udta
         && let Some(mut chpl) = udta.chpl.and_then(Chpl::into_owned)
     {
         let chpl_timescale = cfg.chpl_timescale.fixed_or_mvhd(mvhd.timescale);
@@ -371,127 +591,564 @@ pub(crate) fn read_tag(reader: &mut (impl Read + Seek), cfg: &ReadConfig) -> cra
         chapter_list.extend(chpl.into_iter().map(|c| Chapter {
             start: scale_duration(chpl_timescale, c.start),
             title: c.title,
+            start_ticks: c.start,
+            timescale: chpl_timescale,
+            ..Default::default()
         }));
     }
 
     // chapter tracks
-    let mut chapter_track = Vec::new();
-    if cfg.read_chapter_track {
-        // https://developer.apple.com/documentation/quicktime-file-format/chapter_lists
-        // > If more than one enabled track includes a 'chap' track reference,
-        // > QuickTime uses the first chapter list that it finds.
-        let traks = &moov.trak;
-        let chapter_trak = traks.iter().find_map(|trak| {
-            let chap = trak.tref.as_ref().and_then(|tref| tref.chap.as_ref())?;
-            traks.iter().find(|trak| chap.chapter_ids.contains(&trak.tkhd.id))
-        });
-        if let Some(trak) = chapter_trak {
-            let Some(mdia) = &trak.mdia else {
-                return Err(crate::Error::new(
-                    ErrorKind::AtomNotFound(MEDIA),
-                    "Media (mdia) atom of chapter track not found",
-                ));
-            };
-            let Some(stbl) = mdia.minf.as_ref().and_then(|a| a.stbl.as_ref()) else {
-                return Err(crate::Error::new(
-                    ErrorKind::AtomNotFound(SAMPLE_TABLE),
-                    "Sample table (stbl) of chapter track not found",
-                ));
-            };
-            let Some(stsc) = &stbl.stsc else {
-                return Err(crate::Error::new(
-                    ErrorKind::AtomNotFound(SAMPLE_TABLE_SAMPLE_TO_CHUNK),
-                    "Sample table sample to chunk (stsc) atom of chapter track not found",
-                ));
-            };
-            let Some(stsz) = &stbl.stsz else {
-                return Err(crate::Error::new(
-                    ErrorKind::AtomNotFound(SAMPLE_TABLE_SAMPLE_SIZE),
-                    "Sample table sample size (stsz) atom of chapter track not found",
-                ));
-            };
-            let Some(stts) = &stbl.stts else {
-                return Err(crate::Error::new(
-                    ErrorKind::AtomNotFound(SAMPLE_TABLE_TIME_TO_SAMPLE),
-                    "Sample table time to sample (stts) atom of chapter track not found",
-                ));
-            };
-            let timescale = mdia.mdhd.timescale;
-
-            let stsc_items = stsc.items.get_or_read(reader)?;
-            let stsz_sizes = stsz.sizes.get_or_read(reader)?;
-            let stts_items = stts.items.get_or_read(reader)?;
-
-            chapter_track.reserve(stsz_sizes.len());
-
-            if let Some(co64) = &stbl.co64 {
-                let co64_offsets = co64.offsets.get_or_read(reader)?;
-
-                read_track_chapters(
-                    reader,
-                    &mut chapter_track,
-                    timescale,
-                    &co64_offsets,
-                    &stsc_items,
-                    stsz.uniform_sample_size,
-                    &stsz_sizes,
-                    &stts_items,
-                )
-                .map_err(|mut e| {
-                    let mut desc = e.description.into_owned();
-                    desc.insert_str(0, "Error reading chapters: ");
-                    e.description = desc.into();
-                    e
-                })?;
-            } else if let Some(stco) = &stbl.stco {
-                let stco_offsets = stco.offsets.get_or_read(reader)?;
-
-                chapter_track.reserve(stco.offsets.len());
-                read_track_chapters(
-                    reader,
-                    &mut chapter_track,
-                    timescale,
-                    &stco_offsets,
-                    stsc_items.as_ref(),
-                    stsz.uniform_sample_size,
-                    stsz_sizes.as_ref(),
-                    stts_items.as_ref(),
-                )
-                .map_err(|mut e| {
-                    let mut desc = e.description.into_owned();
-                    desc.insert_str(0, "Error reading chapters: ");
-                    e.description = desc.into();
-                    e
-                })?;
+    let chapter_track = match cfg.read_chapter_track {
+        true => read_chapter_track(reader, &moov.trak, cfg.read_image_data)?,
+        false => Vec::new(),
+    };
+
+    let tracks = match cfg.read_audio_info || cfg.read_video_info {
+        true => read_tracks(reader, &moov.trak, mvhd.timescale, cfg)?,
+        false => Vec::new(),
+    };
+
+    // the first enabled track, or otherwise the first track, with the respective information
+    let first = |f: fn(&TrackInfo) -> bool| {
+        let mut iter = tracks.iter().filter(|t| f(t));
+        iter.clone().find(|t| t.enabled).or_else(|| iter.next())
+    };
+    let mut info = first(|t| t.audio.is_some()).and_then(|t| t.audio.clone()).unwrap_or_default();
+    if info.timescale == 0 {
+        info.duration = duration;
+        info.duration_ticks = mvhd.duration;
+        info.timescale = mvhd.timescale;
+    }
+    let video_info = first(|t| t.video.is_some()).and_then(|t| t.video.clone());
+
+    // Estimate the bitrate from the size of the media data, which is only meaningful if it
+    // doesn't contain any video.
+    if info.avg_bitrate.is_none_or(|b| b == 0)
+        && video_info.is_none()
+        && let Some(bitrate) = estimate_bitrate(mdat_len, info.duration)
+    {
+        info.avg_bitrate = Some(bitrate);
+        info.bitrate_estimated = true;
+    }
+
+    let userdata = Userdata {
+        meta_items,
+        chapter_list,
+        chapter_track,
+        ..Default::default()
+    };
+    if let Some((delay, padding, count)) =
+        userdata.strings_of(&ITUNES_SMPB).find_map(parse_itunes_smpb)
+    {
+        info.encoder_delay = Some(delay);
+        info.encoder_padding = Some(padding);
+        info.exact_sample_count = count;
+    }
+
+    let tag = Tag {
+        ftyp: ftyp.string,
+        file_type: ftyp.file_type,
+        info,
+        video_info,
+        tracks,
+        mdat_len,
+        created_at: Some(Timestamp::from_secs(mvhd.creation_time)),
+        modified_at: Some(Timestamp::from_secs(mvhd.modification_time)),
+        userdata,
+        meta_hdlr_missing,
+        encrypted,
+    };
+
+    let target = match mdat_bounds {
+        Some(mdat_bounds) if layout && !multiple_moov => Some(WriteTarget {
+            moov,
+            mdat_bounds,
+            moov_end,
+            mdat_after_moov,
+            trailing_free,
+        }),
+        _ => None,
+    };
+
+    Ok((tag, target))
+}
+
+/// Reads the tag from the file at the path, and caches the layout of the file for the next write
+/// to the same path, see [`Tag::read_for_update`].
+pub(crate) fn read_tag_for_update(path: &Path, cfg: &ReadConfig) -> crate::Result<Tag> {
+    let file = File::open(path)?;
+    let metadata = file.metadata()?;
+    let mut reader = BufReader::new(file);
+    let (tag, target) = read_tag_and_layout(&mut reader, cfg, true)?;
+
+    // If the layout is incomplete, the next write parses the file again and reports any error.
+    if let Ok(modified) = metadata.modified()
+        && let Some(target) = target
+    {
+        let layout = CachedLayout {
+            path: path.to_path_buf(),
+            len: metadata.len(),
+            modified,
+            target,
+        };
+        *tag.userdata.layout.0.lock().unwrap_or_else(|e| e.into_inner()) = Some(layout);
+    }
+
+    Ok(tag)
+}
+
+/// Returns whether the error of parsing a top level atom head indicates that the remaining bytes
+/// aren't a valid atom.
+fn is_trailing_garbage(e: &crate::Error) -> bool {
+    match &e.kind {
+        ErrorKind::InvalidAtomSize | ErrorKind::AtomSizeOutOfBounds => true,
+        ErrorKind::Io(e) => e.kind() == std::io::ErrorKind::UnexpectedEof,
+        _ => false,
+    }
+}
+
+/// Returns whether the error was caused by the reader, rather than by malformed data or the end
+/// of the file.
+fn is_reader_error(e: &crate::Error) -> bool {
+    match &e.kind {
+        ErrorKind::Io(e) => e.kind() != std::io::ErrorKind::UnexpectedEof,
+        _ => false,
+    }
+}
+
+/// Reads only the artwork images (`covr`), skipping all other atoms by their size.
+pub(crate) fn read_artworks(reader: &mut (impl Read + Seek)) -> crate::Result<Vec<ImgBuf>> {
+    let Some(content_len) = find_item_list(reader)? else {
+        return Ok(Vec::new());
+    };
+
+    let read_cfg = ReadConfig {
+        read_meta_items: true,
+        read_image_data: true,
+        ..ReadConfig::NONE
+    };
+    let parse_cfg = ParseConfig { cfg: &read_cfg, write: false, skip_data: false };
+    let mut artworks = Vec::new();
+    let mut parsed_bytes = 0;
+    while parsed_bytes < content_len {
+        let head = head::parse(reader, content_len - parsed_bytes)?;
+        match head.fourcc() {
+            ARTWORK => {
+                let item = MetaItem::parse(reader, &parse_cfg, head)?;
+                artworks.extend(item.data.into_iter().filter_map(Data::into_image));
             }
+            _ => reader.skip(head.content_len() as i64)?,
         }
+        parsed_bytes += head.len();
     }
 
-    let mut info = AudioInfo { duration, ..Default::default() };
-    if cfg.read_audio_info {
-        let mp4a = moov.trak.into_iter().find_map(|trak| {
-            trak.mdia
-                .and_then(|a| a.minf)
-                .and_then(|a| a.stbl)
-                .and_then(|a| a.stsd)
-                .and_then(|a| a.mp4a)
+    Ok(artworks)
+}
+
+/// Reads only the identifiers of the metadata items and the total length of their data payloads,
+/// skipping the payloads and all other atoms by their size.
+pub(crate) fn scan_idents(
+    reader: &mut (impl Read + Seek),
+) -> crate::Result<Vec<(DataIdent, usize)>> {
+    let Some(content_len) = find_item_list(reader)? else {
+        return Ok(Vec::new());
+    };
+
+    let read_cfg = ReadConfig { read_meta_items: true, ..ReadConfig::NONE };
+    let parse_cfg = ParseConfig { cfg: &read_cfg, write: false, skip_data: true };
+    let mut idents = Vec::new();
+    let mut parsed_bytes = 0;
+    while parsed_bytes < content_len {
+        let head = head::parse(reader, content_len - parsed_bytes)?;
+        let item = MetaItem::parse(reader, &parse_cfg, head)?;
+        let len: u64 = item.data.iter().map(Data::data_len).sum();
+        idents.push((item.ident, len as usize));
+        parsed_bytes += head.len();
+    }
+
+    Ok(idents)
+}
+
+/// Skips the atoms down to the item list (`ilst`), leaving the reader at its content and returning
+/// its content length, or `None` if there is no item list.
+fn find_item_list(reader: &mut (impl Read + Seek)) -> crate::Result<Option<u64>> {
+    let file_len = reader.seek(SeekFrom::End(0))?;
+    reader.seek(SeekFrom::Start(0))?;
+
+    let ftyp = Ftyp::parse(reader, file_len)?;
+
+    let mut content_len = file_len - ftyp.size.len();
+    for fourcc in [MOVIE, USER_DATA, METADATA, ITEM_LIST] {
+        let Some(head) = find_child(reader, content_len, fourcc)? else {
+            return Ok(None);
+        };
+        content_len = head.content_len();
+        if fourcc == METADATA {
+            let (version, _) = head::parse_full(reader)?;
+            if version != 0 {
+                return unknown_version("metadata (meta)", version);
+            }
+            expect_min_size("Metadata (meta)", head.size(), meta::HEADER_SIZE)?;
+            content_len -= meta::HEADER_SIZE;
+        }
+    }
+
+    Ok(Some(content_len))
+}
+
+/// Skips sibling atoms until one with the fourcc is found, leaving the reader at its content.
+fn find_child(
+    reader: &mut (impl Read + Seek),
+    content_len: u64,
+    fourcc: Fourcc,
+) -> crate::Result<Option<Head>> {
+    let mut parsed_bytes = 0;
+    while parsed_bytes < content_len {
+        let head = head::parse(reader, content_len - parsed_bytes)?;
+        if head.fourcc() == fourcc {
+            return Ok(Some(head));
+        }
+        reader.skip(head.content_len() as i64)?;
+        parsed_bytes += head.len();
+    }
+    Ok(None)
+}
+
+/// Returns the index of the chapter text track, and of the chapter image track if present.
+fn find_chapter_traks(traks: &[Trak]) -> Option<(usize, Option<usize>)> {
+    // https://developer.apple.com/documentation/quicktime-file-format/chapter_lists
+    // > If more than one enabled track includes a 'chap' track reference,
+    // > QuickTime uses the first chapter list that it finds.
+    traks.iter().find_map(|trak| {
+        let chap = trak.tref.as_ref().and_then(|tref| tref.chap.as_ref())?;
+        let is_chapter_trak = |t: &Trak, image: bool| {
+            chap.chapter_ids.contains(&t.tkhd.id) && chapter_image_fmt(t).is_some() == image
+        };
+        let text_idx = traks.iter().position(|t| is_chapter_trak(t, false))?;
+        let image_idx = traks.iter().position(|t| is_chapter_trak(t, true));
+        Some((text_idx, image_idx))
+    })
+}
+
+fn read_tracks(
+    reader: &mut (impl Read + Seek),
+    traks: &[Trak],
+    mvhd_timescale: u32,
+    cfg: &ReadConfig,
+) -> crate::Result<Vec<TrackInfo>> {
+    let mut tracks = Vec::with_capacity(traks.len());
+    for trak in traks {
+        let mdia = trak.mdia.as_ref();
+        let handler_type = mdia.and_then(|a| a.hdlr.as_ref()).and_then(Hdlr::handler_type);
+        let duration = match mvhd_timescale {
+            0 => Duration::ZERO,
+            t => scale_duration(t, trak.tkhd.duration),
+        };
+
+        let mut track = TrackInfo {
+            id: trak.tkhd.id,
+            handler_type,
+            enabled: trak.tkhd.flags[2] & 0x01 != 0,
+            language: mdia.and_then(|a| a.mdhd.language.clone()),
+            extended_language: mdia.and_then(|a| a.elng.clone()),
+            duration,
+            ..Default::default()
+        };
+        if cfg.read_audio_info {
+            track.audio = read_audio_info(reader, trak)?;
+        }
+        if cfg.read_video_info {
+            track.video = read_video_info(reader, trak)?;
+        }
+        tracks.push(track);
+    }
+    Ok(tracks)
+}
+
+/// Estimates the average bitrate in bits per second from the length of the media data.
+fn estimate_bitrate(mdat_len: u64, duration: Duration) -> Option<u32> {
+    let nanos = duration.as_nanos();
+    if mdat_len == 0 || nanos == 0 {
+        return None;
+    }
+    let bitrate = mdat_len as u128 * 8 * 1_000_000_000 / nanos;
+    u32::try_from(bitrate).ok()
+}
+
+/// Parses the encoder delay, padding and, if not zero, the original sample count of the gapless
+/// playback information (`iTunSMPB`), which consists of space separated hexadecimal numbers.
+///
+/// ```md
+/// 00000000 00000840 000001E4 00000000000A0E5C 00000000 ...
+/// ```
+fn parse_itunes_smpb(smpb: &str) -> Option<(u32, u32, Option<u64>)> {
+    let mut fields = smpb.split_whitespace().skip(1);
+    let delay = u32::from_str_radix(fields.next()?, 16).ok()?;
+    let padding = u32::from_str_radix(fields.next()?, 16).ok()?;
+    let count = fields.next().and_then(|f| u64::from_str_radix(f, 16).ok()).filter(|&c| c != 0);
+    Some((delay, padding, count))
+}
+
+/// Reads the audio information of a track with a sound handler or a supported audio sample entry.
+fn read_audio_info(
+    reader: &mut (impl Read + Seek),
+    trak: &Trak,
+) -> crate::Result<Option<AudioInfo>> {
+    let Some(mdia) = &trak.mdia else {
+        return Ok(None);
+    };
+    let Some(stbl) = mdia.minf.as_ref().and_then(|a| a.stbl.as_ref()) else {
+        return Ok(None);
+    };
+    let Some(stsd) = &stbl.stsd else {
+        return Ok(None);
+    };
+    let is_sound = mdia.hdlr.as_ref().and_then(Hdlr::handler_type) == Some(SOUND_HANDLER);
+    if !is_sound && stsd.mp4a.is_none() && stsd.alac.is_none() {
+        return Ok(None);
+    }
+
+    let mut info = AudioInfo { codec: stsd.codec, ..Default::default() };
+    if mdia.mdhd.timescale != 0 && mdia.mdhd.duration != 0 {
+        info.duration = scale_duration(mdia.mdhd.timescale, mdia.mdhd.duration);
+        info.duration_ticks = mdia.mdhd.duration;
+        info.timescale = mdia.mdhd.timescale;
+    }
+    if let Some(i) = &stsd.mp4a {
+        info.channel_config = i.channel_config;
+        info.sample_rate = i.sample_rate;
+        if let Some(aac) = i.aac {
+            info.spectral_band_replication = aac.sbr;
+            info.parametric_stereo = aac.ps;
+            info.extension_sample_rate = aac.ext_sample_rate;
+        }
+        info.max_bitrate = i.max_bitrate;
+        info.avg_bitrate = i.avg_bitrate;
+        info.bitrate_kind = i.bitrate_kind;
+        info.bit_depth = i.bit_depth;
+    } else if let Some(i) = &stsd.alac {
+        info.channel_config = i.channel_config;
+        info.sample_rate = i.sample_rate;
+        info.avg_bitrate = i.avg_bitrate;
+        info.bit_depth = i.bit_depth;
+        info.frames_per_packet = i.frames_per_packet;
+        info.alac_cookie = i.cookie.clone();
+    }
+
+    // only the counts of the sample size table are needed, so it isn't read
+    if let Some(stts) = &stbl.stts {
+        let items = stts.items.get_or_read(reader)?;
+        let total = items.iter().map(|i| i.sample_count as u64 * i.sample_duration as u64).sum();
+        info.total_samples = Some(total);
+        info.frame_count = Some(items.iter().map(|i| i.sample_count as u64).sum());
+    }
+    if let Some(stsz) = &stbl.stsz {
+        info.frame_count = Some(stsz.sample_count as u64);
+    }
+
+    Ok(Some(info))
+}
+
+/// Reads the video information of a track with a supported visual sample entry.
+fn read_video_info(
+    reader: &mut (impl Read + Seek),
+    trak: &Trak,
+) -> crate::Result<Option<VideoInfo>> {
+    let Some(mdia) = &trak.mdia else {
+        return Ok(None);
+    };
+    let Some(stbl) = mdia.minf.as_ref().and_then(|a| a.stbl.as_ref()) else {
+        return Ok(None);
+    };
+    let Some(visual) = stbl.stsd.as_ref().and_then(|a| a.visual.as_ref()) else {
+        return Ok(None);
+    };
+
+    // fall back to the track header, which stores the presentation size in 16.16 fixed point
+    let width = match visual.width {
+        0 => (trak.tkhd.width >> 16) as u16,
+        w => w,
+    };
+    let height = match visual.height {
+        0 => (trak.tkhd.height >> 16) as u16,
+        h => h,
+    };
+    let display_width = match visual.pixel_aspect_ratio {
+        Some((h, v)) => (width as u64 * h as u64 / v as u64).min(u16::MAX as u64) as u16,
+        None => width,
+    };
+
+    let timescale = mdia.mdhd.timescale;
+    let mut frame_rate = None;
+    if let Some(stts) = &stbl.stts {
+        let items = stts.items.get_or_read(reader)?;
+        let mut durations = items.iter().filter(|i| i.sample_count > 0).map(|i| i.sample_duration);
+        if let Some(d) = durations.next()
+            && d != 0
+            && timescale != 0
+            && durations.all(|d2| d2 == d)
+        {
+            frame_rate = Some(timescale as f64 / d as f64);
+        }
+    }
+    let duration = match timescale {
+        0 => None,
+        t => Some(scale_duration(t, mdia.mdhd.duration)),
+    };
+
+    Ok(Some(VideoInfo {
+        width,
+        height,
+        display_width,
+        codec: visual.codec,
+        frame_rate,
+        duration,
+    }))
+}
+
+fn chapter_image_fmt(trak: &Trak) -> Option<ImgFmt> {
+    let mdia = trak.mdia.as_ref()?;
+    let stbl = mdia.minf.as_ref()?.stbl.as_ref()?;
+    stbl.stsd.as_ref()?.image.clone()
+}
+
+fn read_chapter_track(
+    reader: &mut (impl Read + Seek),
+    traks: &[Trak],
+    read_images: bool,
+) -> crate::Result<Vec<Chapter>> {
+    let mut chapter_track = Vec::new();
+    let Some((text_idx, image_idx)) = find_chapter_traks(traks) else {
+        return Ok(chapter_track);
+    };
+
+    let (timescale, samples) = read_track_samples(reader, &traks[text_idx])?;
+    chapter_track.reserve(samples.len());
+    for s in samples.iter() {
+        let (title, url) = read_chapter_sample(reader, s).map_err(|mut e| {
+            let mut desc = e.description.into_owned();
+            desc.insert_str(0, "Error reading chapters: ");
+            e.description = desc.into();
+            e
+        })?;
+        let start = scale_duration(timescale, s.time);
+        chapter_track.push(Chapter {
+            start,
+            title,
+            url,
+            image: None,
+            start_ticks: s.time,
+            timescale,
         });
-        if let Some(i) = mp4a {
-            info.channel_config = i.channel_config;
-            info.sample_rate = i.sample_rate;
-            info.max_bitrate = i.max_bitrate;
-            info.avg_bitrate = i.avg_bitrate;
+    }
+
+    if read_images
+        && let Some(idx) = image_idx
+        && let Some(fmt) = chapter_image_fmt(&traks[idx])
+    {
+        let (image_timescale, image_samples) = read_track_samples(reader, &traks[idx])?;
+        for (i, c) in chapter_track.iter_mut().enumerate() {
+            // image samples usually correspond to the text samples, otherwise use the last image
+            // starting before the chapter
+            let sample = match image_samples.len() == samples.len() {
+                true => image_samples.get(i),
+                false => (image_samples.iter().rev())
+                    .find(|s| scale_duration(image_timescale, s.time) <= c.start),
+            };
+            if let Some(s) = sample {
+                reader.seek(SeekFrom::Start(s.offset))?;
+                c.image = Some(Img::new(fmt.clone(), reader.read_u8_vec(s.size as u64)?));
+            }
         }
     }
 
-    let userdata = Userdata { meta_items, chapter_list, chapter_track };
-    Ok(Tag { ftyp: ftyp.string, info, userdata })
+    Ok(chapter_track)
 }
 
-fn read_track_chapters<T: ChunkOffsetInt>(
+/// A sample of a track, with its start time in the media timescale, and its position in the file.
+struct Sample {
+    time: u64,
+    offset: u64,
+    size: u32,
+}
+
+/// Reads the sample table of a chapter track and returns the media timescale and the samples.
+fn read_track_samples(
     reader: &mut (impl Read + Seek),
-    chapters: &mut Vec<Chapter>,
-    timescale: u32,
+    trak: &Trak,
+) -> crate::Result<(u32, Vec<Sample>)> {
+    let Some(mdia) = &trak.mdia else {
+        return Err(crate::Error::new(
+            ErrorKind::AtomNotFound(MEDIA),
+            "Media (mdia) atom of chapter track not found",
+        ));
+    };
+    let Some(stbl) = mdia.minf.as_ref().and_then(|a| a.stbl.as_ref()) else {
+        return Err(crate::Error::new(
+            ErrorKind::AtomNotFound(SAMPLE_TABLE),
+            "Sample table (stbl) of chapter track not found",
+        ));
+    };
+    let Some(stsc) = &stbl.stsc else {
+        return Err(crate::Error::new(
+            ErrorKind::AtomNotFound(SAMPLE_TABLE_SAMPLE_TO_CHUNK),
+            "Sample table sample to chunk (stsc) atom of chapter track not found",
+        ));
+    };
+    let Some(stsz) = &stbl.stsz else {
+        return Err(crate::Error::new(
+            ErrorKind::AtomNotFound(SAMPLE_TABLE_SAMPLE_SIZE),
+            "Sample table sample size (stsz) atom of chapter track not found",
+        ));
+    };
+    let Some(stts) = &stbl.stts else {
+        return Err(crate::Error::new(
+            ErrorKind::AtomNotFound(SAMPLE_TABLE_TIME_TO_SAMPLE),
+            "Sample table time to sample (stts) atom of chapter track not found",
+        ));
+    };
+    let timescale = mdia.mdhd.timescale;
+
+    let stsc_items = stsc.items.get_or_read(reader)?;
+    let stsz_sizes = stsz.sizes.get_or_read(reader)?;
+    let stts_items = stts.items.get_or_read(reader)?;
+
+    let mut samples = Vec::with_capacity(stsz_sizes.len());
+    let res = if let Some(co64) = &stbl.co64 {
+        let co64_offsets = co64.offsets.get_or_read(reader)?;
+        collect_samples(
+            &mut samples,
+            &co64_offsets,
+            &stsc_items,
+            stsz.uniform_sample_size,
+            &stsz_sizes,
+            &stts_items,
+        )
+    } else if let Some(stco) = &stbl.stco {
+        let stco_offsets = stco.offsets.get_or_read(reader)?;
+        collect_samples(
+            &mut samples,
+            &stco_offsets,
+            &stsc_items,
+            stsz.uniform_sample_size,
+            &stsz_sizes,
+            &stts_items,
+        )
+    } else {
+        Ok(())
+    };
+    res.map_err(|mut e| {
+        let mut desc = e.description.into_owned();
+        desc.insert_str(0, "Error reading chapters: ");
+        e.description = desc.into();
+        e
+    })?;
+
+    Ok((timescale, samples))
+}
+
+fn collect_samples<T: ChunkOffsetInt>(
+    samples: &mut Vec<Sample>,
     offsets: &[T],
     stsc: &[StscItem],
     stsz_uniform_size: u32,
@@ -542,8 +1199,7 @@ fn read_track_chapters<T: ChunkOffsetInt>(
                     ));
                 };
 
-                let title = read_chapter_title(reader, current_offset)?;
-                chapters.push(Chapter { start: scale_duration(timescale, time), title });
+                samples.push(Sample { time, offset: current_offset, size });
 
                 time += duration as u64;
 
@@ -557,8 +1213,12 @@ fn read_track_chapters<T: ChunkOffsetInt>(
     Ok(())
 }
 
-fn read_chapter_title(reader: &mut (impl Read + Seek), offset: u64) -> crate::Result<String> {
-    reader.seek(SeekFrom::Start(offset))?;
+/// Reads the title and the url of a chapter text sample.
+fn read_chapter_sample(
+    reader: &mut (impl Read + Seek),
+    sample: &Sample,
+) -> crate::Result<(String, Option<String>)> {
+    reader.seek(SeekFrom::Start(sample.offset))?;
     let len = reader.read_be_u16()?;
     let bom = reader.read_be_u16()?;
 
@@ -572,7 +1232,30 @@ fn read_chapter_title(reader: &mut (impl Read + Seek), offset: u64) -> crate::Re
         }
     };
 
-    Ok(title)
+    // the text may be followed by modifier atoms, like a hypertext link (`href`)
+    let remaining_len = (sample.size as u64).saturating_sub(2 + len as u64);
+    if remaining_len < 8 {
+        return Ok((title, None));
+    }
+    let modifiers = reader.read_u8_vec(remaining_len)?;
+    let mut url = None;
+    let mut rest = modifiers.as_slice();
+    while rest.len() >= 8 {
+        let atom_len = u32::from_be_bytes([rest[0], rest[1], rest[2], rest[3]]) as usize;
+        if atom_len < 8 || atom_len > rest.len() {
+            break;
+        }
+        // content: start char (u16), end char (u16), url len (u8), url
+        let content = &rest[8..atom_len];
+        if rest[4..8] == *HYPERTEXT_LINK && content.len() >= 5 {
+            let url_len = (content[4] as usize).min(content.len() - 5);
+            url = Some(String::from_utf8_lossy(&content[5..5 + url_len]).into_owned());
+            break;
+        }
+        rest = &rest[atom_len..];
+    }
+
+    Ok((title, url))
 }
 
 /// Configure which metadata is (over)written.
@@ -591,15 +1274,45 @@ pub struct WriteConfig {
     pub write_chapter_track: bool,
     /// The timescale that is used to scale time for chapter list (chpl) atoms.
     pub chpl_timescale: ChplTimescale,
+    /// The number of bytes of free space (`free`) that are reserved when the file has to be
+    /// rewritten, so that later edits can be done in place. Space freed by shrinking metadata is
+    /// also kept as padding instead of rewriting the file, as long as the padding doesn't grow
+    /// beyond this size. Zero means no padding is added, which results in the smallest file.
+    /// Values smaller than an atom head (8 bytes) are rounded up.
+    pub padding: usize,
+    /// Where the padding is placed.
+    pub padding_position: PaddingPosition,
+    /// Whether to keep the encoder (`©too`) already present in the file, if the written metadata
+    /// doesn't contain one. This preserves the original encoder provenance unless it's explicitly
+    /// set.
+    pub preserve_encoder: bool,
+    /// Whether to move the movie (`moov`) atom in front of the media data (`mdat`) atom, if it is
+    /// located after it. This allows the file to be streamed progressively, but requires the
+    /// media data to be rewritten.
+    pub faststart: bool,
+    /// The order in which the metadata items are written.
+    pub item_order: ItemOrder,
+    /// Whether to back up the original file before modifying it. This only applies when writing
+    /// to a path. If the backup can't be created the write is aborted.
+    pub backup: Option<BackupMode>,
 }
 
 impl WriteConfig {
+    /// The default amount of padding in bytes.
+    pub const DEFAULT_PADDING: usize = 2048;
+
     /// The default configuration for writing tags.
     pub const DEFAULT: WriteConfig = WriteConfig {
         write_meta_items: true,
         write_chapter_list: true,
         write_chapter_track: true,
         chpl_timescale: ChplTimescale::DEFAULT,
+        padding: Self::DEFAULT_PADDING,
+        padding_position: PaddingPosition::Udta,
+        preserve_encoder: false,
+        faststart: false,
+        item_order: ItemOrder::Preserve,
+        backup: None,
     };
 
     /// A configuration that would write no data at all.
@@ -617,6 +1330,12 @@ impl WriteConfig {
         write_chapter_list: false,
         write_chapter_track: false,
         chpl_timescale: ChplTimescale::DEFAULT,
+        padding: 0,
+        padding_position: PaddingPosition::Udta,
+        preserve_encoder: false,
+        faststart: false,
+        item_order: ItemOrder::Preserve,
+        backup: None,
     };
 }
 
@@ -626,6 +1345,99 @@ impl Default for WriteConfig {
     }
 }
 
+/// The order in which metadata items are written.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub enum ItemOrder {
+    /// Write the items in the order they are stored in the tag.
+    #[default]
+    Preserve,
+    /// Sort the items into the order iTunes writes them in. Some software, like older iPod
+    /// firmware, only reads items in this order. Freeform (`----`) items follow after the known
+    /// items and unknown items come last, keeping their relative order.
+    ItunesCanonical,
+    /// Sort the items by the bytes of their identifier, freeform (`----`) items by their mean and
+    /// name.
+    Alphabetical,
+    /// Write the items in the order of the identifiers. Items that aren't listed always follow
+    /// after the listed ones, keeping their relative order.
+    ///
+    /// ```
+    /// use mp4ameta::{DataIdent, ItemOrder, ident};
+    ///
+    /// // write the title first, directly followed by the artwork, then all other items
+    /// let order = ItemOrder::Custom(vec![DataIdent::from(ident::TITLE), ident::ARTWORK.into()]);
+    /// ```
+    Custom(Vec<DataIdent>),
+}
+
+/// Where a backup of the original file is created.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum BackupMode {
+    /// Next to the original file, with the extension appended to its name, for example
+    /// `Sibling(".bak".into())` backs up `music.m4a` to `music.m4a.bak`.
+    Sibling(Cow<'static, str>),
+    /// Inside the directory, using the name of the original file. The directory is created if it
+    /// doesn't exist.
+    Directory(PathBuf),
+}
+
+impl BackupMode {
+    /// Returns the path of the backup of the file at the path.
+    pub(crate) fn backup_path(&self, path: &Path) -> crate::Result<PathBuf> {
+        let Some(file_name) = path.file_name() else {
+            return Err(crate::Error::new(
+                ErrorKind::Io(std::io::ErrorKind::InvalidInput.into()),
+                "Unable to create backup, the path has no file name",
+            ));
+        };
+        Ok(match self {
+            Self::Sibling(ext) => {
+                let mut backup_name = file_name.to_owned();
+                backup_name.push(ext.as_ref());
+                path.with_file_name(backup_name)
+            }
+            Self::Directory(dir) => dir.join(file_name),
+        })
+    }
+
+    /// Copies the file at the path to its backup location, unless an up to date backup, with the
+    /// same size and modification time, already exists.
+    pub(crate) fn backup(&self, path: &Path) -> crate::Result<()> {
+        let backup_path = self.backup_path(path)?;
+        if let Self::Directory(dir) = self {
+            std::fs::create_dir_all(dir)?;
+        }
+
+        let metadata = std::fs::metadata(path)?;
+        let modified = metadata.modified()?;
+        if let Ok(backup_metadata) = std::fs::metadata(&backup_path)
+            && backup_metadata.len() == metadata.len()
+            && backup_metadata.modified().ok() == Some(modified)
+        {
+            return Ok(());
+        }
+
+        std::fs::copy(path, &backup_path).map_err(|e| {
+            let description = format!("Error creating backup at {}: {e}", backup_path.display());
+            crate::Error::new(ErrorKind::Io(e), description)
+        })?;
+        File::options().write(true).open(&backup_path)?.set_modified(modified)?;
+
+        Ok(())
+    }
+}
+
+/// The position of the free space (`free`) atom that is reserved as padding.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum PaddingPosition {
+    /// Inside the user data (`udta`) atom, following the metadata (`meta`) atom. If there is no
+    /// user data, the padding is placed after the movie (`moov`) atom instead.
+    #[default]
+    Udta,
+    /// Directly following the movie (`moov`) atom.
+    AfterMoov,
+}
+
 #[derive(Debug)]
 struct MovedData {
     new_pos: u64,
@@ -655,46 +1467,177 @@ impl StorageFile for Cursor<Vec<u8>> {
     }
 }
 
-pub(crate) fn write_tag(
-    file: &mut impl StorageFile,
-    cfg: &WriteConfig,
-    userdata: &Userdata,
-) -> crate::Result<()> {
-    let mut reader = BufReader::new(&mut *file);
+/// The way in which a tag was written to a file.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum WriteOutcome {
+    /// The metadata was patched in place, by reusing existing free space or because the movie
+    /// (`moov`) atom is located at the end of the file. The media data and chunk offsets weren't
+    /// touched.
+    InPlace,
+    /// The metadata didn't fit into the existing space, so everything following it, including the
+    /// media data, had to be rewritten.
+    Rewritten,
+}
+
+/// A description of what writing a tag would do, computed without modifying the file.
+///
+/// Moving the movie (`moov`) atom to the front, as done by [`WriteConfig::faststart`], isn't taken
+/// into account.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct WritePlan {
+    /// The way in which the tag would be written.
+    pub outcome: WriteOutcome,
+    /// The serialized length of the user data (`udta`) atom containing the metadata, or 0 if it
+    /// would be removed.
+    pub metadata_len: u64,
+    /// The length of the existing free space (`free`) atoms, that could absorb a changed metadata
+    /// length.
+    pub available_padding: u64,
+    /// The amount by which the chunk offsets would be shifted, 0 if the media data stays in place.
+    pub chunk_offset_shift: i64,
+    /// The estimated number of bytes that would be written, including moved data.
+    pub rewritten_len: u64,
+}
 
+/// The parsed structure of a file that is about to be written.
+struct WriteTarget<'a> {
+    moov: Moov<'a>,
+    mdat_bounds: AtomBounds,
+    moov_end: u64,
+    mdat_after_moov: bool,
+    trailing_free: Option<Free>,
+}
+
+impl WriteTarget<'_> {
+    /// Returns whether the movie (`moov`) and item list (`ilst`) atoms are still found at their
+    /// parsed positions, since the modification time of a file might not change on every write.
+    fn heads_match(&self, mut file: &File) -> bool {
+        let ilst = (self.moov.udta.as_ref())
+            .and_then(|a| a.meta.as_ref())
+            .and_then(|a| a.ilst.as_ref())
+            .map(|a| (ITEM_LIST, &a.state));
+        let matches = [(MOVIE, &self.moov.state)].into_iter().chain(ilst).all(|(fourcc, state)| {
+            let State::Existing(bounds) = state else {
+                return false;
+            };
+            file.seek(SeekFrom::Start(bounds.pos())).is_ok()
+                && head::parse(&mut file, u64::MAX)
+                    .is_ok_and(|h| h.fourcc() == fourcc && h.len() == bounds.len())
+        });
+        file.rewind().is_ok() && matches
+    }
+}
+
+/// The layout of a file, parsed by [`Tag::read_for_update`] while reading the tag.
+struct CachedLayout {
+    path: PathBuf,
+    len: u64,
+    modified: SystemTime,
+    target: WriteTarget<'static>,
+}
+
+/// The cached layout of the file a tag was read from, which is taken by the next write to the
+/// same path.
+#[derive(Default)]
+pub(crate) struct LayoutCache(Mutex<Option<CachedLayout>>);
+
+impl LayoutCache {
+    /// Takes the cached layout, if it belongs to the file at the path, the file hasn't been
+    /// modified since it was read, and the layout was parsed for the same atoms that will be
+    /// written.
+    fn take(&self, path: &Path, file: &File, cfg: &WriteConfig) -> Option<WriteTarget<'static>> {
+        let layout = self.0.lock().unwrap_or_else(|e| e.into_inner()).take()?;
+        if !(cfg.write_meta_items && cfg.write_chapter_list && cfg.write_chapter_track) {
+            return None;
+        }
+        let metadata = file.metadata().ok()?;
+        let unmodified = layout.path == path
+            && layout.len == metadata.len()
+            && metadata.modified().ok() == Some(layout.modified)
+            && layout.target.heads_match(file);
+        unmodified.then_some(layout.target)
+    }
+}
+
+// A cloned tag could be written to another file, so the layout isn't cloned.
+impl Clone for LayoutCache {
+    fn clone(&self) -> Self {
+        Self::default()
+    }
+}
+
+// The cached layout doesn't affect the contents of a tag.
+impl PartialEq for LayoutCache {
+    fn eq(&self, _: &Self) -> bool {
+        true
+    }
+}
+
+impl Eq for LayoutCache {}
+
+impl fmt::Debug for LayoutCache {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("LayoutCache").finish_non_exhaustive()
+    }
+}
+
+fn parse_write_target<'a>(
+    reader: &mut (impl Read + Seek),
+    cfg: &WriteConfig,
+) -> crate::Result<WriteTarget<'a>> {
     let old_file_len = reader.seek(SeekFrom::End(0))?;
     reader.seek(SeekFrom::Start(0))?;
 
-    let ftyp = Ftyp::parse(&mut reader, old_file_len)?;
+    let ftyp = Ftyp::parse(reader, old_file_len)?;
 
     let mut moov = None;
     let mut mdat_bounds = None;
+    let mut moov_end = 0;
+    let mut mdat_after_moov = false;
+    let mut trailing_free = None;
     {
         let read_cfg = ReadConfig {
             read_meta_items: cfg.write_meta_items,
             read_chapter_list: cfg.write_chapter_list,
             read_chapter_track: cfg.write_chapter_track,
-            read_audio_info: false,
-            read_image_data: false,
-            chpl_timescale: ChplTimescale::default(),
+            ..ReadConfig::NONE
         };
 
         let mut parsed_bytes = ftyp.size.len();
+        let mut prev_fourcc = FILETYPE;
         while parsed_bytes < old_file_len {
             let remaining_bytes = old_file_len - parsed_bytes;
-            let head = head::parse(&mut reader, remaining_bytes)?;
-            let parse_cfg = ParseConfig { cfg: &read_cfg, write: true };
+            let head = match head::parse_top_level(reader, remaining_bytes) {
+                Ok(h) => h,
+                // trailing garbage is preserved as is
+                Err(e) if moov.is_some() && mdat_bounds.is_some() && is_trailing_garbage(&e) => {
+                    break;
+                }
+                Err(e) => return Err(e),
+            };
+            let parse_cfg = ParseConfig { cfg: &read_cfg, write: true, skip_data: false };
             match head.fourcc() {
-                MOVIE => moov = Some(Moov::parse(&mut reader, &parse_cfg, head.size())?),
-                MEDIA_DATA => mdat_bounds = Some(Mdat::read_bounds(&mut reader, head.size())?),
+                MOVIE => {
+                    moov = Some(Moov::parse(reader, &parse_cfg, head.size())?);
+                    moov_end = parsed_bytes + head.len();
+                }
+                MEDIA_DATA => {
+                    mdat_bounds = Some(Mdat::read_bounds(reader, head.size())?);
+                    mdat_after_moov |= moov.is_some();
+                }
+                // padding directly following the moov atom can absorb a changed length
+                FREE if prev_fourcc == MOVIE => {
+                    trailing_free = Some(Free::parse(reader, &parse_cfg, head.size())?)
+                }
                 _ => reader.skip(head.content_len() as i64)?,
             }
 
+            prev_fourcc = head.fourcc();
             parsed_bytes += head.len();
         }
     }
 
-    let Some(mut moov) = moov else {
+    let Some(moov) = moov else {
         return Err(crate::Error::new(
             crate::ErrorKind::AtomNotFound(MOVIE),
             "Missing necessary data, no movie (moov) atom found",
@@ -707,47 +1650,161 @@ pub(crate) fn write_tag(
         ));
     };
 
+    Ok(WriteTarget {
+        moov,
+        mdat_bounds,
+        moov_end,
+        mdat_after_moov,
+        trailing_free,
+    })
+}
+
+/// The changes that have to be applied to a file in order to write a tag, sorted by their
+/// position.
+struct WriteChanges<'a> {
+    changes: Vec<Change<'a>>,
+    outcome: WriteOutcome,
+    /// The length of the existing padding, that could absorb a changed length.
+    available_padding: u64,
+    /// The length of the user data (`udta`) atom once the changes are applied.
+    udta_len: u64,
+    /// The position of the media data (`mdat`) atom before the changes are applied.
+    mdat_pos: u64,
+}
+
+/// Updates the atom hierarchy and collects the changes that have to be applied to the file.
+fn collect_write_changes<'a>(
+    reader: &mut (impl Read + Seek),
+    target: &'a mut WriteTarget<'a>,
+    userdata: &'a Userdata,
+    cfg: &WriteConfig,
+) -> crate::Result<WriteChanges<'a>> {
+    let WriteTarget {
+        moov,
+        mdat_bounds,
+        moov_end,
+        mdat_after_moov,
+        trailing_free,
+    } = target;
+    let mdat_bounds: &'a AtomBounds = mdat_bounds;
+
+    let available_padding = {
+        let udta = moov.udta.as_ref();
+        let meta_free = udta.and_then(|a| a.meta.as_ref()).and_then(|a| a.free.as_ref());
+        let udta_free = udta.and_then(|a| a.free.as_ref());
+        [meta_free, udta_free, trailing_free.as_ref()].into_iter().flatten().map(|a| a.len()).sum()
+    };
+
     // update atom hierarchy
     let mut changes = Vec::new();
     if cfg.write_meta_items || cfg.write_chapter_list || cfg.write_chapter_track {
-        update_userdata(&mut reader, &mut changes, &mut moov, &mdat_bounds, userdata, cfg)?;
+        update_userdata(reader, &mut changes, moov, mdat_bounds, userdata, cfg)?;
     }
+    update_timestamps(&mut moov.mvhd, userdata)?;
+    update_audio_language(&mut moov.trak, userdata);
+
+    // Try to absorb the length difference with existing padding, so that nothing following the
+    // moov atom has to be moved. Existing padding only grows up to the configured padding.
+    let max_padding = (cfg.padding as i64).max(Head::NORMAL_SIZE as i64);
+    let modifies_mdat =
+        changes.iter().any(|c| matches!(c, Change::RemoveMdat(..) | Change::AppendMdat(..)));
+    let absorbed = !modifies_mdat && {
+        let len_diff = moov.collect_changes(0, 0, &mut Vec::new());
+        let (meta_free, udta_free) = match &mut moov.udta {
+            Some(udta) if udta.state.is_existing() => {
+                let meta = udta.meta.as_mut().filter(|m| m.state.is_existing());
+                (meta.and_then(|m| m.free.as_mut()), udta.free.as_mut())
+            }
+            _ => (None, None),
+        };
+        len_diff == 0
+            || (len_diff > 0 || cfg.padding > 0)
+                && [meta_free, udta_free, trailing_free.as_mut()]
+                    .into_iter()
+                    .flatten()
+                    .filter(|free| len_diff > 0 || free.len() as i64 - len_diff <= max_padding)
+                    .any(|free| free.absorb(len_diff))
+    };
 
-    for trak in moov.trak.iter() {
-        if !trak.state.is_existing() {
-            continue;
-        }
-
-        let Some(stbl) = (trak.mdia.as_ref())
-            .filter(|mdia| mdia.state.is_existing())
-            .and_then(|mdia| mdia.minf.as_ref())
-            .filter(|minf| minf.state.is_existing())
-            .and_then(|minf| minf.stbl.as_ref())
-            .filter(|stbl| stbl.state.is_existing())
-        else {
-            continue;
+    // Turn space freed by shrinking metadata into padding, instead of moving the media data.
+    let absorbed = absorbed
+        || !modifies_mdat && *mdat_after_moov && cfg.padding > 0 && {
+            let freed_len = -moov.collect_changes(0, 0, &mut Vec::new());
+            let udta = moov.udta.as_mut().filter(|a| a.state.is_existing());
+            let free = match (cfg.padding_position, udta) {
+                (PaddingPosition::Udta, Some(udta)) => &mut udta.free,
+                _ => &mut *trailing_free,
+            };
+            let fits =
+                (Head::NORMAL_SIZE as i64..=max_padding.min(u32::MAX as i64)).contains(&freed_len);
+            let insert = fits && free.is_none();
+            if insert {
+                *free = Some(Free::with_len(freed_len as u64));
+            }
+            insert
         };
 
-        if let Some(co64) = &stbl.co64
-            && let State::Existing(bounds) = &co64.state
-        {
-            let offsets = co64.offsets.get_or_read(&mut reader)?;
-            let offsets = ChunkOffsets::Co64(offsets);
-            let update = UpdateChunkOffsets { bounds, offsets };
-            changes.push(Change::UpdateChunkOffset(update));
-        }
-        if let Some(stco) = &stbl.stco
-            && let State::Existing(bounds) = &stco.state
-        {
-            let offsets = stco.offsets.get_or_read(&mut reader)?;
-            let offsets = ChunkOffsets::Stco(offsets);
-            let update = UpdateChunkOffsets { bounds, offsets };
-            changes.push(Change::UpdateChunkOffset(update));
+    // reserve padding for future edits if the file has to be rewritten anyway
+    if (modifies_mdat || (!absorbed && *mdat_after_moov)) && cfg.padding > 0 {
+        let padding = (cfg.padding as u64).max(Head::NORMAL_SIZE);
+        let udta = moov.udta.as_mut().filter(|a| !a.state.is_remove());
+        let free = match (cfg.padding_position, udta) {
+            (PaddingPosition::Udta, Some(udta)) => &mut udta.free,
+            _ => &mut *trailing_free,
+        };
+        match free {
+            Some(free) => free.resize(padding),
+            None => *free = Some(Free::with_len(padding)),
         }
     }
 
     // collect changes
-    moov.collect_changes(0, 0, &mut changes);
+    let moov: &'a Moov<'a> = moov;
+    let trailing_free: &'a Option<Free> = trailing_free;
+    let len_diff = moov.collect_changes(0, 0, &mut changes)
+        + trailing_free.collect_changes(*moov_end, 0, &mut changes);
+
+    // Chunk offsets only need to be updated if the media data is shifted.
+    let outcome = if modifies_mdat || (len_diff != 0 && *mdat_after_moov) {
+        WriteOutcome::Rewritten
+    } else {
+        WriteOutcome::InPlace
+    };
+
+    if outcome == WriteOutcome::Rewritten {
+        for trak in moov.trak.iter() {
+            if !trak.state.is_existing() {
+                continue;
+            }
+
+            let Some(stbl) = (trak.mdia.as_ref())
+                .filter(|mdia| mdia.state.is_existing())
+                .and_then(|mdia| mdia.minf.as_ref())
+                .filter(|minf| minf.state.is_existing())
+                .and_then(|minf| minf.stbl.as_ref())
+                .filter(|stbl| stbl.state.is_existing())
+            else {
+                continue;
+            };
+
+            if let Some(co64) = &stbl.co64
+                && let State::Existing(bounds) = &co64.state
+            {
+                let offsets = co64.offsets.get_or_read(reader)?;
+                let offsets = ChunkOffsets::Co64(offsets);
+                let update = UpdateChunkOffsets { bounds, offsets };
+                changes.push(Change::UpdateChunkOffset(update));
+            }
+            if let Some(stco) = &stbl.stco
+                && let State::Existing(bounds) = &stco.state
+            {
+                let offsets = stco.offsets.get_or_read(reader)?;
+                let offsets = ChunkOffsets::Stco(offsets);
+                let update = UpdateChunkOffsets { bounds, offsets };
+                changes.push(Change::UpdateChunkOffset(update));
+            }
+        }
+    }
 
     changes.sort_by(|a, b| {
         a.old_pos().cmp(&b.old_pos()).then_with(|| {
@@ -764,6 +1821,63 @@ pub(crate) fn write_tag(
         })
     });
 
+    let udta_len_diff = moov.udta.collect_changes(0, 0, &mut Vec::new());
+    let udta_len = (moov.udta.prev_len_or_zero() as i64 + udta_len_diff) as u64;
+
+    Ok(WriteChanges {
+        changes,
+        outcome,
+        available_padding,
+        udta_len,
+        mdat_pos: mdat_bounds.pos(),
+    })
+}
+
+pub(crate) fn write_tag(
+    file: &mut impl StorageFile,
+    cfg: &WriteConfig,
+    userdata: &Userdata,
+) -> crate::Result<WriteOutcome> {
+    write_tag_with_target(file, cfg, userdata, None)
+}
+
+/// Writes the tag to the file at the path, reusing the cached layout if it's still valid.
+pub(crate) fn write_tag_to_path(
+    path: &Path,
+    cfg: &WriteConfig,
+    userdata: &Userdata,
+) -> crate::Result<WriteOutcome> {
+    let mut file = std::fs::OpenOptions::new().read(true).write(true).open(path)?;
+    let target = userdata.layout.take(path, &file, cfg);
+    write_tag_with_target(&mut file, cfg, userdata, target)
+}
+
+fn write_tag_with_target(
+    file: &mut impl StorageFile,
+    cfg: &WriteConfig,
+    userdata: &Userdata,
+    target: Option<WriteTarget<'_>>,
+) -> crate::Result<WriteOutcome> {
+    // the file is modified in place, so streamed payloads must not fail halfway through
+    if cfg.write_meta_items {
+        let data = userdata.meta_items.iter().flat_map(|a| a.data.iter());
+        for source in data.filter_map(|d| match d {
+            Data::Streamed { source, .. } => Some(source),
+            _ => None,
+        }) {
+            source.verify()?;
+        }
+    }
+
+    let mut reader = BufReader::new(&mut *file);
+
+    let mut target = match target {
+        Some(t) => t,
+        None => parse_write_target(&mut reader, cfg)?,
+    };
+    let WriteChanges { changes, mut outcome, .. } =
+        collect_write_changes(&mut reader, &mut target, userdata, cfg)?;
+
     // read moved data
     let old_file_len = reader.seek(SeekFrom::End(0))?;
     let mut moved_data = Vec::new();
@@ -797,7 +1911,8 @@ pub(crate) fn write_tag(
     let new_file_len = (old_file_len as i64 + len_diff) as u64;
     file.set_len(new_file_len)?;
 
-    let writer = &mut BufWriter::new(file);
+    let mut buf_writer = BufWriter::new(&mut *file);
+    let writer = &mut buf_writer;
 
     // write moved data
     for d in moved_data {
@@ -815,24 +1930,229 @@ pub(crate) fn write_tag(
         let new_pos = c.old_pos() as i64 + pos_shift;
         writer.seek(SeekFrom::Start(new_pos as u64))?;
 
-        match c {
-            Change::UpdateLen(u) => u.update_len(writer)?,
-            Change::UpdateChunkOffset(u) => u.offsets.update_offsets(writer, shifting_changes)?,
-            Change::Remove(_) => (),
-            Change::Replace(r) => r.atom.write(writer, shifting_changes)?,
-            Change::Insert(i) => i.atom.write(writer, shifting_changes)?,
-            Change::RemoveMdat(_, _) => (),
-            Change::AppendMdat(_, d) => writer.write_all(d)?,
-        }
+        write_change(writer, c, shifting_changes)?;
 
         pos_shift += c.len_diff();
     }
 
     writer.flush()?;
+    drop(buf_writer);
+
+    if cfg.faststart && faststart::move_moov_to_front(file)? {
+        outcome = WriteOutcome::Rewritten;
+    }
+
+    Ok(outcome)
+}
+
+pub(crate) fn plan_write(
+    reader: &mut (impl Read + Seek),
+    cfg: &WriteConfig,
+    userdata: &Userdata,
+) -> crate::Result<WritePlan> {
+    let mut target = parse_write_target(reader, cfg)?;
+    let WriteChanges {
+        changes,
+        outcome,
+        available_padding,
+        udta_len,
+        mdat_pos,
+    } = collect_write_changes(reader, &mut target, userdata, cfg)?;
+
+    let file_len = reader.seek(SeekFrom::End(0))?;
+    let mut rewritten_len = 0;
+    let mut chunk_offset_shift = 0;
+    let mut current_shift = 0;
+    let mut changes_iter = changes.iter().peekable();
+    while let Some(change) = changes_iter.next() {
+        if change.old_pos() <= mdat_pos {
+            chunk_offset_shift += change.len_diff();
+        }
+        current_shift += change.len_diff();
+
+        // the change itself and the data following it, if it has to be moved
+        rewritten_len += (change.old_end() - change.old_pos()) as i64 + change.len_diff();
+        if current_shift != 0 {
+            let data_end = changes_iter.peek().map_or(file_len, |next| next.old_pos());
+            rewritten_len += (data_end - change.old_end()) as i64;
+        }
+    }
+    if outcome == WriteOutcome::InPlace {
+        chunk_offset_shift = 0;
+    }
+
+    Ok(WritePlan {
+        outcome,
+        metadata_len: udta_len,
+        available_padding,
+        chunk_offset_shift,
+        rewritten_len: rewritten_len as u64,
+    })
+}
+
+/// Writes a copy of the source with the updated metadata to the destination. The source is
+/// streamed in bounded chunks, only the changed atoms are held in memory.
+pub(crate) fn write_tag_copy(
+    src: &mut (impl Read + Seek),
+    dst: &mut impl Write,
+    cfg: &WriteConfig,
+    userdata: &Userdata,
+) -> crate::Result<()> {
+    let mut reader = BufReader::new(src);
+
+    let mut target = parse_write_target(&mut reader, cfg)?;
+    let WriteChanges { changes, .. } =
+        collect_write_changes(&mut reader, &mut target, userdata, cfg)?;
+
+    let src_len = reader.seek(SeekFrom::End(0))?;
+    let mut writer = BufWriter::new(dst);
+
+    let append_idx = changes.iter().position(|c| matches!(c, Change::AppendMdat(..)));
+    let end = append_idx.unwrap_or(changes.len());
+    let shifting_changes = &changes[..end];
+
+    let mut pos = 0;
+    for c in changes.iter() {
+        copy_range(&mut reader, &mut writer, pos, c.old_pos())?;
+        write_change(&mut writer, c, shifting_changes)?;
+        pos = c.old_end();
+    }
+    copy_range(&mut reader, &mut writer, pos, src_len)?;
+
+    writer.flush()?;
+
+    Ok(())
+}
+
+/// A part of a copy of the source with the updated metadata, see [`write_copy_segments`].
+#[cfg(feature = "tokio")]
+pub(crate) enum CopySegment {
+    /// The unchanged bytes of the source in the range.
+    Source(std::ops::Range<u64>),
+    /// The changed atoms.
+    Data(Vec<u8>),
+}
 
+/// Returns the segments of a copy of the source with the updated metadata, so that the unchanged
+/// parts of the source can be copied by the caller, for example asynchronously. Only the changed
+/// atoms are held in memory.
+#[cfg(feature = "tokio")]
+pub(crate) fn write_copy_segments(
+    reader: &mut (impl Read + Seek),
+    cfg: &WriteConfig,
+    userdata: &Userdata,
+) -> crate::Result<(Vec<CopySegment>, WriteOutcome)> {
+    let mut target = parse_write_target(reader, cfg)?;
+    let WriteChanges { changes, outcome, .. } =
+        collect_write_changes(reader, &mut target, userdata, cfg)?;
+
+    let src_len = reader.seek(SeekFrom::End(0))?;
+
+    let append_idx = changes.iter().position(|c| matches!(c, Change::AppendMdat(..)));
+    let end = append_idx.unwrap_or(changes.len());
+    let shifting_changes = &changes[..end];
+
+    let mut segments = Vec::new();
+    let mut pos = 0;
+    for c in changes.iter() {
+        if pos < c.old_pos() {
+            segments.push(CopySegment::Source(pos..c.old_pos()));
+        }
+        let mut data = Vec::new();
+        write_change(&mut data, c, shifting_changes)?;
+        segments.push(CopySegment::Data(data));
+        pos = c.old_end();
+    }
+    if pos < src_len {
+        segments.push(CopySegment::Source(pos..src_len));
+    }
+
+    Ok((segments, outcome))
+}
+
+/// Copies the bytes from `pos` to `end` of the reader to the writer.
+fn copy_range(
+    reader: &mut (impl Read + Seek),
+    writer: &mut impl Write,
+    pos: u64,
+    end: u64,
+) -> crate::Result<()> {
+    if end <= pos {
+        return Ok(());
+    }
+
+    reader.seek(SeekFrom::Start(pos))?;
+    let copied = std::io::copy(&mut reader.take(end - pos), writer)?;
+    if copied != end - pos {
+        return Err(crate::Error::new(
+            crate::ErrorKind::Io(std::io::ErrorKind::UnexpectedEof.into()),
+            "Unexpected end of file while copying data",
+        ));
+    }
     Ok(())
 }
 
+fn write_change(
+    writer: &mut impl Write,
+    change: &Change<'_>,
+    shifting_changes: &[Change<'_>],
+) -> crate::Result<()> {
+    match change {
+        Change::UpdateLen(u) => u.update_len(writer)?,
+        Change::UpdateChunkOffset(u) => u.offsets.update_offsets(writer, shifting_changes)?,
+        Change::Remove(_) => (),
+        Change::Replace(r) => r.atom.write(writer, shifting_changes)?,
+        Change::Insert(i) => i.atom.write(writer, shifting_changes)?,
+        Change::RemoveMdat(_, _) => (),
+        Change::AppendMdat(_, d) => writer.write_all(d)?,
+    }
+    Ok(())
+}
+
+/// Overwrites the timestamps of the movie header (`mvhd`), if any are set.
+fn update_timestamps(mvhd: &mut Mvhd, userdata: &Userdata) -> crate::Result<()> {
+    let times = [
+        (&mut mvhd.creation_time, userdata.created_at),
+        (&mut mvhd.modification_time, userdata.modified_at),
+    ];
+    let mut changed = false;
+    for (time, new) in times {
+        let Some(new) = new else { continue };
+        if mvhd.version == 0 && new.secs() > u32::MAX as u64 {
+            return Err(crate::Error::new(
+                ErrorKind::InvalidData,
+                format!(
+                    "Timestamp {} doesn't fit into a version 0 movie header (mvhd)",
+                    new.secs()
+                ),
+            ));
+        }
+        changed |= *time != new.secs();
+        *time = new.secs();
+    }
+    if changed {
+        mvhd.state.replace_existing();
+    }
+    Ok(())
+}
+
+/// Overwrites the language of the media header (`mdhd`) of the first enabled audio track, or the
+/// first audio track if none is enabled.
+fn update_audio_language(trak: &mut [Trak], userdata: &Userdata) {
+    let Some(language) = &userdata.audio_language else { return };
+    let is_audio = |t: &Trak| {
+        let hdlr = t.mdia.as_ref().and_then(|a| a.hdlr.as_ref());
+        hdlr.and_then(Hdlr::handler_type) == Some(SOUND_HANDLER)
+    };
+    let enabled = trak.iter().position(|t| is_audio(t) && t.tkhd.flags[2] & 0x01 != 0);
+    let Some(i) = enabled.or_else(|| trak.iter().position(is_audio)) else { return };
+    let Some(mdia) = trak[i].mdia.as_mut() else { return };
+    if mdia.mdhd.language.as_ref() != Some(language) {
+        mdia.mdhd.language = Some(language.clone());
+        mdia.mdhd.state.replace_existing();
+    }
+}
+
 fn update_userdata<'a>(
     reader: &mut (impl Read + Seek),
     changes: &mut Vec<Change<'a>>,
@@ -845,12 +2165,54 @@ fn update_userdata<'a>(
 
     // item list (ilst)
     if cfg.write_meta_items {
-        let meta = udta.meta.get_or_insert_default();
-        meta.hdlr.get_or_insert_with(Hdlr::meta);
+        let preserve_encoder =
+            cfg.preserve_encoder && !userdata.meta_items.iter().any(|i| ENCODER == i.ident);
+        let prev_encoder = (udta.meta.as_ref().and_then(|a| a.ilst.as_ref()))
+            .filter(|_| preserve_encoder)
+            .and_then(|a| a.data.iter().find(|i| ENCODER == i.ident).cloned());
+
+        let mut meta_items = match prev_encoder {
+            Some(encoder) => {
+                let mut items = userdata.meta_items.clone();
+                items.push(encoder);
+                Cow::Owned(items)
+            }
+            None => Cow::Borrowed(userdata.meta_items.as_slice()),
+        };
+        // copy the payloads of skipped data from the file
+        if meta_items.iter().flat_map(|i| i.data.iter()).any(|d| matches!(d, Data::Skipped(_))) {
+            let read_cfg = ReadConfig::DEFAULT;
+            let parse_cfg = ParseConfig { cfg: &read_cfg, write: false, skip_data: false };
+            let pos = reader.stream_position()?;
+            for item in meta_items.to_mut().iter_mut() {
+                for d in item.data.iter_mut() {
+                    if let Data::Skipped(_) = d {
+                        *d = std::mem::replace(d, Data::Reserved(Vec::new()))
+                            .read_skipped(reader, &parse_cfg)?;
+                    }
+                }
+            }
+            reader.seek(SeekFrom::Start(pos))?;
+        }
+        match &cfg.item_order {
+            ItemOrder::Preserve => (),
+            ItemOrder::ItunesCanonical => ilst::sort_itunes_canonical(meta_items.to_mut()),
+            ItemOrder::Alphabetical => ilst::sort_alphabetical(meta_items.to_mut()),
+            ItemOrder::Custom(order) => ilst::sort_custom(meta_items.to_mut(), order),
+        }
 
-        let ilst = meta.ilst.get_or_insert_default();
-        ilst.state.replace_existing();
-        ilst.data = Cow::Borrowed(&userdata.meta_items);
+        if meta_items.is_empty() {
+            if let Some(ilst) = udta.meta.as_mut().and_then(|a| a.ilst.as_mut()) {
+                ilst.state.remove_existing();
+            }
+        } else {
+            let meta = udta.meta.get_or_insert_default();
+            meta.hdlr.get_or_insert_with(Hdlr::meta);
+
+            let ilst = meta.ilst.get_or_insert_default();
+            ilst.state.replace_existing();
+            ilst.data = meta_items;
+        }
     }
 
     // chapter list
@@ -861,37 +2223,68 @@ fn update_userdata<'a>(
                 chpl.state.remove_existing();
             }
             _ => {
+                let mut starts: Vec<_> = userdata.chapter_list.iter().map(|c| c.start).collect();
+                starts.sort_unstable();
+                if let Some(w) = starts.windows(2).find(|w| w[0] == w[1]) {
+                    return Err(crate::Error::new(
+                        ErrorKind::InvalidChapterList,
+                        format!("Multiple chapters of the chapter list start at {:?}", w[0]),
+                    ));
+                }
+
                 let chpl_timescale = cfg.chpl_timescale.fixed_or_mvhd(moov.mvhd.timescale);
                 let chpl = udta.chpl.get_or_insert_default();
                 chpl.state.replace_existing();
-                chpl.data = ChplData::Borrowed(chpl_timescale, &userdata.chapter_list);
+                chpl.data = if userdata.chapter_list.is_sorted_by_key(|c| c.start) {
+                    ChplData::Borrowed(chpl_timescale, &userdata.chapter_list)
+                } else {
+                    let mut items = (userdata.chapter_list.iter())
+                        .map(|c| ChplItem {
+                            start: c.start_ticks_in(chpl_timescale),
+                            title: c.title.clone(),
+                        })
+                        .collect::<Vec<_>>();
+                    items.sort_by_key(|c| c.start);
+                    ChplData::Owned(items)
+                };
             }
         }
     }
 
+    // remove empty shells
+    if udta.meta.as_ref().is_some_and(Meta::is_empty) {
+        match udta.meta.as_mut() {
+            Some(meta) if meta.state.is_existing() => meta.state.remove_existing(),
+            _ => udta.meta = None,
+        }
+    }
+    if udta.is_empty() {
+        match udta.state.is_existing() {
+            true => udta.state.remove_existing(),
+            false => moov.udta = None,
+        }
+    }
+
     // chapter tracks
     'chapter_track: {
         if !cfg.write_chapter_track {
             break 'chapter_track;
         }
 
-        // https://developer.apple.com/documentation/quicktime-file-format/chapter_lists
-        // > If more than one enabled track includes a 'chap' track reference,
-        // > QuickTime uses the first chapter list that it finds.
-        let chapter_trak_idx = moov.trak.iter().find_map(|trak| {
-            let chap = trak.tref.as_ref().and_then(|tref| tref.chap.as_ref())?;
-            moov.trak.iter().position(|trak| chap.chapter_ids.contains(&trak.tkhd.id))
-        });
+        let chapter_trak_idxs = find_chapter_traks(&moov.trak);
+        let chapter_trak_idx = chapter_trak_idxs.map(|(text_idx, _)| text_idx);
 
         if userdata.chapter_track.is_empty() {
-            let Some(idx) = chapter_trak_idx else {
+            let Some((idx, image_idx)) = chapter_trak_idxs else {
                 // avoid doing redundant work
                 break 'chapter_track;
             };
 
-            // remove chapter track
-            let chapter_trak = &mut moov.trak[idx];
-            chapter_trak.state.remove_existing();
+            // remove chapter track, and the chapter image track
+            moov.trak[idx].state.remove_existing();
+            if let Some(image_idx) = image_idx {
+                moov.trak[image_idx].state.remove_existing();
+            }
 
             // remove all chap track references
             for trak in moov.trak.iter_mut() {
@@ -919,24 +2312,49 @@ fn update_userdata<'a>(
             break 'chapter_track;
         }
 
+        // Leave an unchanged chapter track untouched, so the track atoms and the media data are
+        // preserved byte for byte. A malformed chapter track is simply regenerated, while errors
+        // of the reader itself are reported. Chapter images aren't written, so the image track is
+        // always left untouched.
+        let prev_chapters = match chapter_trak_idx {
+            Some(_) => match read_chapter_track(reader, &moov.trak, false) {
+                Ok(chapters) => Some(chapters),
+                Err(e) if is_reader_error(&e) => return Err(e),
+                Err(_) => None,
+            },
+            None => None,
+        };
+        if let Some(prev_chapters) = prev_chapters
+            && prev_chapters.len() == userdata.chapter_track.len()
+            && prev_chapters
+                .iter()
+                .zip(userdata.chapter_track.iter())
+                .all(|(p, c)| p.start == c.start && p.title == c.title && p.url == c.url)
+        {
+            break 'chapter_track;
+        }
+
         // generate chapter track sample table
         let mut new_chapter_media_data = Vec::new();
+        // keep the media timescale of an existing chapter track, which the sample durations are
+        // stored in
+        let existing_mdhd = (chapter_trak_idx.and_then(|i| moov.trak[i].mdia.as_ref()))
+            .map(|mdia| &mdia.mdhd)
+            .filter(|mdhd| mdhd.timescale != 0);
+        let (chapter_timescale, media_duration) = match existing_mdhd {
+            Some(mdhd) => (mdhd.timescale, mdhd.duration),
+            None => (moov.mvhd.timescale, moov.mvhd.duration),
+        };
         let duration = moov.mvhd.duration;
-        let chapter_timescale = moov.mvhd.timescale;
         let chunk_offsets = vec![mdat_bounds.end()];
         let mut sample_sizes = Vec::with_capacity(userdata.chapter_track.len());
         let mut time_to_samples = Vec::with_capacity(userdata.chapter_track.len());
         let mut chapters_iter = userdata.chapter_track.iter().peekable();
         while let Some(c) = chapters_iter.next() {
+            let start = c.start_ticks_in(chapter_timescale);
             let c_duration = match chapters_iter.peek() {
-                Some(next) => {
-                    let c_duration = next.start.saturating_sub(c.start);
-                    unscale_duration(chapter_timescale, c_duration)
-                }
-                None => {
-                    let start = unscale_duration(chapter_timescale, c.start);
-                    duration.saturating_sub(start)
-                }
+                Some(next) => next.start_ticks_in(chapter_timescale).saturating_sub(start),
+                None => media_duration.saturating_sub(start),
             };
 
             time_to_samples.push(SttsItem {
@@ -950,12 +2368,23 @@ fn update_userdata<'a>(
                 0, 0, 1, 0, // content
             ];
             let title_len = c.title.len().min(u16::MAX as usize);
-            let sample_size = 2 + title_len + ENCD.len();
+            let url_len = c.url.as_ref().map_or(0, |u| u.len().min(u8::MAX as usize));
+            let href_len = c.url.as_ref().map_or(0, |_| 8 + 2 + 2 + 1 + url_len);
+            let sample_size = 2 + title_len + ENCD.len() + href_len;
             sample_sizes.push(sample_size as u32);
 
             new_chapter_media_data.write_be_u16(title_len as u16).ok();
             new_chapter_media_data.write_utf8(&c.title[..title_len]).ok();
             new_chapter_media_data.extend(ENCD);
+            if let Some(url) = &c.url {
+                // the link spans the whole title
+                new_chapter_media_data.write_be_u32(href_len as u32).ok();
+                new_chapter_media_data.extend(*HYPERTEXT_LINK);
+                new_chapter_media_data.write_be_u16(0).ok();
+                new_chapter_media_data.write_be_u16(title_len as u16).ok();
+                new_chapter_media_data.write_u8(url_len as u8).ok();
+                new_chapter_media_data.extend(&url.as_bytes()[..url_len]);
+            }
         }
 
         let chapter_trak = match chapter_trak_idx {
@@ -974,7 +2403,13 @@ fn update_userdata<'a>(
                 // add chapter track
                 moov.trak.push_and_get(Trak {
                     state: State::Insert,
-                    tkhd: Tkhd { version: 0, flags: [0, 0, 0], id: new_id, duration },
+                    tkhd: Tkhd {
+                        version: 0,
+                        flags: [0, 0, 0],
+                        id: new_id,
+                        duration,
+                        ..Default::default()
+                    },
                     ..Default::default()
                 })
             }
@@ -1126,3 +2561,66 @@ fn remove_chapter_media_data<T: ChunkOffsetInt>(
 
     Ok(())
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    /// A file that counts the bytes read from it.
+    struct CountingFile {
+        inner: Cursor<Vec<u8>>,
+        read: u64,
+    }
+
+    impl Read for CountingFile {
+        fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+            let n = self.inner.read(buf)?;
+            self.read += n as u64;
+            Ok(n)
+        }
+    }
+
+    impl Write for CountingFile {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.inner.write(buf)
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            self.inner.flush()
+        }
+    }
+
+    impl Seek for CountingFile {
+        fn seek(&mut self, pos: SeekFrom) -> std::io::Result<u64> {
+            self.inner.seek(pos)
+        }
+    }
+
+    impl StorageFile for CountingFile {
+        fn set_len(&mut self, len: u64) -> crate::Result<()> {
+            self.inner.set_len(len)
+        }
+    }
+
+    #[test]
+    fn cached_layout() {
+        let data = std::fs::read("files/sample.m4a").unwrap();
+        let read_cfg = ReadConfig::DEFAULT;
+        let cfg = WriteConfig::DEFAULT;
+
+        let mut parsed = CountingFile { inner: Cursor::new(data.clone()), read: 0 };
+        let mut tag = read_tag(&mut parsed, &read_cfg).unwrap();
+        tag.set_title("cached layout");
+        write_tag_with_target(&mut parsed, &cfg, &tag, None).unwrap();
+
+        // the layout is parsed while reading the tag, so the file is only scanned once
+        let mut cached = CountingFile { inner: Cursor::new(data), read: 0 };
+        let (mut tag, target) = read_tag_and_layout(&mut cached, &read_cfg, true).unwrap();
+        assert!(target.is_some());
+        tag.set_title("cached layout");
+        write_tag_with_target(&mut cached, &cfg, &tag, target).unwrap();
+
+        assert_eq!(parsed.inner.get_ref(), cached.inner.get_ref());
+        assert!(cached.read < parsed.read, "{} >= {}", cached.read, parsed.read);
+    }
+}
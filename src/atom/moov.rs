@@ -23,6 +23,11 @@ impl ParseAtom for Moov<'_> {
         let mut mvhd = None;
         let mut trak = Vec::new();
         let mut udta = None;
+        let parse_trak = cfg.write
+            || cfg.cfg.read_chapter_track
+            || cfg.cfg.read_audio_info
+            || cfg.cfg.read_video_info;
+        let parse_udta = cfg.cfg.read_meta_items || cfg.cfg.read_chapter_list;
 
         while parsed_bytes < size.content_len() {
             let remaining_bytes = size.content_len() - parsed_bytes;
@@ -30,16 +35,18 @@ impl ParseAtom for Moov<'_> {
 
             match head.fourcc() {
                 MOVIE_HEADER => mvhd = Some(Mvhd::parse(reader, cfg, head.size())?),
-                TRACK if cfg.write || cfg.cfg.read_chapter_track || cfg.cfg.read_audio_info => {
-                    trak.push(Trak::parse(reader, cfg, head.size())?)
-                }
-                USER_DATA if cfg.cfg.read_meta_items || cfg.cfg.read_chapter_list => {
-                    udta = Some(Udta::parse(reader, cfg, head.size())?)
-                }
+                TRACK if parse_trak => trak.push(Trak::parse(reader, cfg, head.size())?),
+                USER_DATA if parse_udta => udta = Some(Udta::parse(reader, cfg, head.size())?),
                 _ => reader.skip(head.content_len() as i64)?,
             }
 
             parsed_bytes += head.len();
+
+            if mvhd.is_some() && !parse_trak && (udta.is_some() || !parse_udta) {
+                // the remaining atoms aren't needed
+                reader.skip((size.content_len() - parsed_bytes) as i64)?;
+                break;
+            }
         }
 
         let mvhd = mvhd.ok_or_else(|| {
@@ -75,7 +82,8 @@ impl SimpleCollectChanges for Moov<'_> {
         bounds: &'a AtomBounds,
         changes: &mut Vec<Change<'a>>,
     ) -> i64 {
-        self.trak.iter().map(|a| a.collect_changes(bounds.end(), level, changes)).sum::<i64>()
+        self.mvhd.collect_changes(bounds.end(), level, changes)
+            + self.trak.iter().map(|a| a.collect_changes(bounds.end(), level, changes)).sum::<i64>()
             + self.udta.collect_changes(bounds.end(), level, changes)
     }
 
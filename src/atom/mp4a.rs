@@ -10,6 +10,13 @@
 //! 4 bytes ?
 //! 4 bytes sample rate
 //! │
+//! ├─ btrt atom (optional)
+//! │  4 bytes len
+//! │  4 bytes ident
+//! │  4 bytes buffer size
+//! │  4 bytes maximum bitrate
+//! │  4 bytes average bitrate
+//! │
 //! └─ esds atom
 //!    4 bytes len
 //!    4 bytes ident
@@ -47,12 +54,28 @@
 
 use std::cmp::min;
 
-use crate::{ChannelConfig, SampleRate};
+use crate::{BitrateKind, ChannelConfig, Codec, SampleRate};
 
 use super::*;
 
 pub const HEADER_SIZE: u64 = 28;
 
+/// MPEG-4 audio object type indication
+const MPEG4_AUDIO: u8 = 0x40;
+/// MPEG-2 AAC low complexity object type indication
+const MPEG2_AAC_LC: u8 = 0x67;
+
+/// AAC low complexity audio object type
+const AOT_AAC_LC: u32 = 2;
+/// Spectral band replication audio object type
+const AOT_SBR: u32 = 5;
+/// Parametric stereo audio object type
+const AOT_PS: u32 = 29;
+/// Sync extension of backward compatible SBR signaling
+const SYNC_EXTENSION_SBR: u32 = 0x2b7;
+/// Sync extension of backward compatible PS signaling
+const SYNC_EXTENSION_PS: u32 = 0x548;
+
 /// Es descriptor  tag
 const ELEMENTARY_STREAM_DESCRIPTOR: u8 = 0x03;
 /// Decoder config descriptor tag
@@ -66,6 +89,14 @@ pub struct Mp4a {
     pub sample_rate: Option<SampleRate>,
     pub max_bitrate: Option<u32>,
     pub avg_bitrate: Option<u32>,
+    pub bitrate_kind: BitrateKind,
+    pub bit_depth: Option<u8>,
+    /// The codec signaled by the audio specific config, if it is a known one.
+    pub codec: Option<Codec>,
+    /// The SBR and PS signaling of the audio specific config.
+    pub aac: Option<AacConfig>,
+    /// Whether the entry contains protection scheme information (`sinf`).
+    pub protected: bool,
 }
 
 impl Atom for Mp4a {
@@ -85,20 +116,56 @@ impl ParseAtom for Mp4a {
         let mut buf = vec![0; bounds.content_len() as usize];
         reader.read_exact(&mut buf)?;
 
+        if buf.len() >= 20 {
+            let sample_size = u16::from_be_bytes([buf[18], buf[19]]);
+            mp4a.bit_depth = Some(sample_size as u8).filter(|b| *b != 0);
+        }
+
         let mut cursor = std::io::Cursor::new(&mut buf);
         cursor.skip(HEADER_SIZE as i64)?;
 
-        let remaining_bytes = size.content_len() - HEADER_SIZE;
-        let head = head::parse(&mut cursor, remaining_bytes)?;
-        if head.fourcc() != ELEMENTARY_STREAM_DESCRIPTION {
+        let mut esds = false;
+        let mut btrt_kind = BitrateKind::Unknown;
+        let mut parsed_bytes = HEADER_SIZE;
+        while parsed_bytes < size.content_len() {
+            let remaining_bytes = size.content_len() - parsed_bytes;
+            let head = head::parse(&mut cursor, remaining_bytes)?;
+
+            match head.fourcc() {
+                ELEMENTARY_STREAM_DESCRIPTION if !esds => {
+                    let pos = cursor.position();
+                    parse_esds(&mut cursor, &mut mp4a, head.size())?;
+                    cursor.set_position(pos + head.content_len());
+                    esds = true;
+                }
+                BITRATE if head.content_len() >= 12 => {
+                    cursor.skip(4)?;
+                    let max = cursor.read_be_u32()?;
+                    let avg = cursor.read_be_u32()?;
+                    btrt_kind = BitrateKind::from_bitrates(max, avg);
+                    cursor.skip(head.content_len() as i64 - 12)?;
+                }
+                PROTECTION_SCHEME_INFO => {
+                    mp4a.protected = true;
+                    cursor.skip(head.content_len() as i64)?;
+                }
+                _ => cursor.skip(head.content_len() as i64)?,
+            }
+
+            parsed_bytes += head.len();
+        }
+
+        if mp4a.bitrate_kind == BitrateKind::Unknown {
+            mp4a.bitrate_kind = btrt_kind;
+        }
+
+        if !esds && !mp4a.protected {
             return Err(crate::Error::new(
                 crate::ErrorKind::AtomNotFound(ELEMENTARY_STREAM_DESCRIPTION),
                 "Missing esds atom",
             ));
         }
 
-        parse_esds(&mut cursor, &mut mp4a, head.size())?;
-
         Ok(mp4a)
     }
 }
@@ -189,15 +256,30 @@ fn parse_es_desc(reader: &mut (impl Read + Seek), info: &mut Mp4a, len: u64) ->
 /// └──decoder specific descriptor
 /// ```
 fn parse_dc_desc(reader: &mut (impl Read + Seek), info: &mut Mp4a, len: u64) -> crate::Result<()> {
-    reader.skip(5)?;
-    info.max_bitrate = Some(reader.read_be_u32()?);
-    info.avg_bitrate = Some(reader.read_be_u32()?);
+    let object_type = reader.read_u8()?;
+    reader.skip(4)?;
+    let max_bitrate = reader.read_be_u32()?;
+    info.max_bitrate = Some(max_bitrate);
+    let avg_bitrate = reader.read_be_u32()?;
+    info.avg_bitrate = Some(avg_bitrate);
+    info.bitrate_kind = BitrateKind::from_bitrates(max_bitrate, avg_bitrate);
+
+    // MPEG-2 AAC low complexity
+    if object_type == MPEG2_AAC_LC {
+        info.codec = Some(Codec::AacLc);
+    }
 
     let mut parsed_bytes = 13;
     while parsed_bytes < len {
         let (tag, head_len, desc_len) = parse_desc_head(reader)?;
 
         match tag {
+            DECODER_SPECIFIC_DESCRIPTOR if object_type == MPEG4_AUDIO => {
+                let asc = reader.read_u8_vec(desc_len)?;
+                parse_ds_desc(&mut std::io::Cursor::new(&asc), info, desc_len)?;
+                info.aac = aac_config(&asc);
+                info.codec = info.aac.map(|c| c.codec);
+            }
             DECODER_SPECIFIC_DESCRIPTOR => parse_ds_desc(reader, info, desc_len)?,
             _ => {
                 reader.skip(desc_len as i64)?;
@@ -249,3 +331,164 @@ fn parse_desc_head(reader: &mut impl Read) -> crate::Result<(u8, u64, u64)> {
 
     Ok((tag, head_len, len))
 }
+
+/// The codec and the signaling of SBR and PS of an audio specific config.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct AacConfig {
+    pub codec: Codec,
+    /// Whether spectral band replication is present, `None` if it isn't signaled, in which case it
+    /// may still be signaled implicitly in the bitstream.
+    pub sbr: Option<bool>,
+    /// Whether parametric stereo is present, `None` if it isn't signaled.
+    pub ps: Option<bool>,
+    /// The sample rate of the SBR extension in Hz, if signaled.
+    pub ext_sample_rate: Option<u32>,
+}
+
+/// Parses the codec of an audio specific config, considering both explicit and backward
+/// compatible signaling of SBR and PS.
+///
+/// ```md
+/// 5 bits audio object type (+6 bits if 31)
+/// 4 bits frequency index (+24 bits if 15)
+/// 4 bits channel config
+/// if audio object type is SBR (5) or PS (29)
+///     4 bits extension frequency index (+24 bits if 15)
+///     5 bits audio object type
+/// if audio object type is AAC-LC (2)
+///     1 bit frame length flag
+///     1 bit depends on core coder (+14 bits core coder delay)
+///     1 bit extension flag
+///     11 bits sync extension (0x2b7)
+///     5 bits extension audio object type
+///     1 bit SBR present
+///     4 bits extension frequency index (+24 bits if 15)
+///     11 bits sync extension (0x548)
+///     1 bit PS present
+/// ```
+fn aac_config(asc: &[u8]) -> Option<AacConfig> {
+    let mut bits = BitReader { buf: asc, pos: 0 };
+    let read_aot = |bits: &mut BitReader<'_>| match bits.read(5)? {
+        31 => Some(32 + bits.read(6)?),
+        aot => Some(aot),
+    };
+    // returns the sample rate in Hz, if the index is known
+    let read_freq = |bits: &mut BitReader<'_>| match bits.read(4)? {
+        15 => bits.read(24).map(Some),
+        i => Some(SampleRate::try_from(i as u8).ok().map(|r| r.hz())),
+    };
+
+    let aot = read_aot(&mut bits)?;
+    read_freq(&mut bits)?;
+    let channel_config = bits.read(4)?;
+
+    let config = |sbr: Option<bool>, ps: Option<bool>, ext_sample_rate| {
+        let codec = match (sbr == Some(true), ps == Some(true)) {
+            (false, _) => Codec::AacLc,
+            (true, false) => Codec::HeAac,
+            (true, true) => Codec::HeAacV2,
+        };
+        AacConfig { codec, sbr, ps, ext_sample_rate }
+    };
+
+    // explicit signaling
+    if aot == AOT_SBR || aot == AOT_PS {
+        let ps = aot == AOT_PS;
+        let ext_sample_rate = read_freq(&mut bits)?;
+        if read_aot(&mut bits)? != AOT_AAC_LC {
+            return None;
+        }
+        return Some(config(Some(true), Some(ps), ext_sample_rate));
+    }
+    if aot != AOT_AAC_LC {
+        return None;
+    }
+
+    // backward compatible signaling, following the GA specific config, which contains a program
+    // config element if the channel config is 0
+    if channel_config == 0 {
+        return Some(config(None, None, None));
+    }
+    let Some(_frame_length_flag) = bits.read(1) else {
+        return Some(config(None, None, None));
+    };
+    if bits.read(1) == Some(1) {
+        bits.read(14);
+    }
+    bits.read(1);
+
+    if bits.remaining() >= 16
+        && bits.read(11) == Some(SYNC_EXTENSION_SBR)
+        && read_aot(&mut bits) == Some(AOT_SBR)
+        && let Some(sbr) = bits.read(1)
+    {
+        if sbr == 0 {
+            return Some(config(Some(false), Some(false), None));
+        }
+        let ext_sample_rate = read_freq(&mut bits).flatten();
+        let ps = match bits.remaining() >= 12 && bits.read(11) == Some(SYNC_EXTENSION_PS) {
+            true => bits.read(1).map(|ps| ps == 1),
+            false => None,
+        };
+        return Some(config(Some(true), ps, ext_sample_rate));
+    }
+
+    Some(config(None, None, None))
+}
+
+struct BitReader<'a> {
+    buf: &'a [u8],
+    pos: usize,
+}
+
+impl BitReader<'_> {
+    fn remaining(&self) -> usize {
+        self.buf.len() * 8 - self.pos
+    }
+
+    /// Reads up to 32 bits, returning `None` if there aren't enough bits left.
+    fn read(&mut self, n: usize) -> Option<u32> {
+        if n > self.remaining() {
+            self.pos = self.buf.len() * 8;
+            return None;
+        }
+        let mut val = 0;
+        for _ in 0..n {
+            let bit = (self.buf[self.pos / 8] >> (7 - self.pos % 8)) & 1;
+            val = (val << 1) | bit as u32;
+            self.pos += 1;
+        }
+        Some(val)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn aac_configs() {
+        let aac = |asc: &[u8]| aac_config(asc).map(|c| (c.codec, c.sbr, c.ps, c.ext_sample_rate));
+
+        // AAC-LC without any SBR signaling, SBR may be signaled implicitly
+        assert_eq!(aac(&[0x11, 0x90]), Some((Codec::AacLc, None, None, None)));
+        // AAC-LC with backward compatible signaling of absent SBR
+        let asc = [0x12, 0x08, 0x56, 0xe5, 0x00];
+        assert_eq!(aac(&asc), Some((Codec::AacLc, Some(false), Some(false), None)));
+        // HE-AAC with backward compatible signaling
+        let asc = [0x13, 0x90, 0x56, 0xe5, 0xa0];
+        assert_eq!(aac(&asc), Some((Codec::HeAac, Some(true), None, Some(44100))));
+        // HE-AACv2 with backward compatible signaling
+        let asc = [0x13, 0x88, 0x56, 0xe5, 0xa5, 0x48, 0x80];
+        assert_eq!(aac(&asc), Some((Codec::HeAacV2, Some(true), Some(true), Some(44100))));
+        // HE-AAC with explicit signaling
+        let asc = [0x2b, 0x92, 0x08, 0x00];
+        assert_eq!(aac(&asc), Some((Codec::HeAac, Some(true), Some(false), Some(44100))));
+        // HE-AACv2 with explicit signaling
+        let asc = [0xeb, 0x8a, 0x08, 0x00];
+        assert_eq!(aac(&asc), Some((Codec::HeAacV2, Some(true), Some(true), Some(44100))));
+        // AAC main
+        assert_eq!(aac(&[0x0a, 0x10]), None);
+        assert_eq!(aac(&[0x12]), None);
+    }
+}
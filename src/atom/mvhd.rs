@@ -8,12 +8,35 @@ const BUF_SIZE_V1: usize = HEADER_SIZE_V1 - 4;
 const_assert!(std::mem::size_of::<MvhdBufV0>() == BUF_SIZE_V0);
 const_assert!(std::mem::size_of::<MvhdBufV1>() == BUF_SIZE_V1);
 
-#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[derive(Clone, Debug, PartialEq, Eq)]
 pub struct Mvhd {
+    pub state: State,
     pub version: u8,
     pub flags: [u8; 3],
+    /// The creation time in seconds since 1904
+    pub creation_time: u64,
+    /// The modification time in seconds since 1904
+    pub modification_time: u64,
     pub timescale: u32,
     pub duration: u64,
+    /// The raw content following the timestamps, which is written back unchanged. Only read
+    /// when writing.
+    pub rest: Vec<u8>,
+}
+
+impl Default for Mvhd {
+    fn default() -> Self {
+        Self {
+            state: State::Insert,
+            version: 0,
+            flags: [0; 3],
+            creation_time: 0,
+            modification_time: 0,
+            timescale: 0,
+            duration: 0,
+            rest: vec![0; BUF_SIZE_V0 - 8],
+        }
+    }
 }
 
 #[derive(Default)]
@@ -77,10 +100,20 @@ impl Atom for Mvhd {
 impl ParseAtom for Mvhd {
     fn parse_atom(
         reader: &mut (impl Read + Seek),
-        _cfg: &ParseConfig<'_>,
+        cfg: &ParseConfig<'_>,
         size: Size,
     ) -> crate::Result<Self> {
-        let mut mvhd = Self::default();
+        let bounds = find_bounds(reader, size)?;
+        let mut mvhd = Self {
+            state: State::Existing(bounds),
+            version: 0,
+            flags: [0; 3],
+            creation_time: 0,
+            modification_time: 0,
+            timescale: 0,
+            duration: 0,
+            rest: Vec::new(),
+        };
 
         let (version, flags) = head::parse_full(reader)?;
         mvhd.version = version;
@@ -92,6 +125,11 @@ impl ParseAtom for Mvhd {
 
                 let mut buf = MvhdBufV0::default();
                 reader.read_exact(buf.bytes_mut())?;
+                mvhd.creation_time = u32::from_be_bytes(buf.creation_time) as u64;
+                mvhd.modification_time = u32::from_be_bytes(buf.modification_time) as u64;
+                if cfg.write {
+                    mvhd.rest = buf.bytes_mut()[8..].to_vec();
+                }
                 mvhd.timescale = u32::from_be_bytes(buf.timescale);
                 mvhd.duration = u32::from_be_bytes(buf.duration) as u64;
             }
@@ -100,6 +138,11 @@ impl ParseAtom for Mvhd {
 
                 let mut buf = MvhdBufV1::default();
                 reader.read_exact(buf.bytes_mut())?;
+                mvhd.creation_time = u64::from_be_bytes(buf.creation_time);
+                mvhd.modification_time = u64::from_be_bytes(buf.modification_time);
+                if cfg.write {
+                    mvhd.rest = buf.bytes_mut()[16..].to_vec();
+                }
                 mvhd.timescale = u32::from_be_bytes(buf.timescale);
                 mvhd.duration = u64::from_be_bytes(buf.duration);
             }
@@ -114,10 +157,44 @@ impl ParseAtom for Mvhd {
 
 impl AtomSize for Mvhd {
     fn size(&self) -> Size {
+        let time_len = if self.version == 1 { 8 } else { 4 };
+        Size::from(4 + 2 * time_len + self.rest.len() as u64)
+    }
+}
+
+impl WriteAtom for Mvhd {
+    fn write_atom(&self, writer: &mut impl Write, _changes: &[Change<'_>]) -> crate::Result<()> {
+        self.write_head(writer)?;
+        head::write_full(writer, self.version, self.flags)?;
+
         match self.version {
-            0 => Size::from(HEADER_SIZE_V0 as u64),
-            1 => Size::from(HEADER_SIZE_V1 as u64),
-            _ => Size::from(0),
+            0 => {
+                writer.write_be_u32(self.creation_time as u32)?;
+                writer.write_be_u32(self.modification_time as u32)?;
+            }
+            1 => {
+                writer.write_be_u64(self.creation_time)?;
+                writer.write_be_u64(self.modification_time)?;
+            }
+            v => {
+                return Err(crate::Error::new(
+                    crate::ErrorKind::UnknownVersion(self.version),
+                    format!("Unknown movie header (mvhd) version {v}"),
+                ));
+            }
         }
+        writer.write_all(&self.rest)?;
+
+        Ok(())
+    }
+}
+
+impl LeafAtomCollectChanges for Mvhd {
+    fn state(&self) -> &State {
+        &self.state
+    }
+
+    fn atom_ref(&self) -> AtomRef<'_> {
+        AtomRef::Mvhd(self)
     }
 }
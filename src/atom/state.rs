@@ -23,6 +23,18 @@ impl State {
         matches!(self, Self::Existing(_))
     }
 
+    pub fn is_remove(&self) -> bool {
+        matches!(self, Self::Remove(_))
+    }
+
+    /// The length of the atom before any changes are applied, or 0 if it will be inserted.
+    pub fn prev_len(&self) -> u64 {
+        match self {
+            Self::Existing(b) | Self::Replace(b) | Self::Remove(b) => b.len(),
+            Self::Insert => 0,
+        }
+    }
+
     pub fn replace_existing(&mut self) {
         if let Self::Existing(b) = self {
             *self = Self::Replace(b.clone())
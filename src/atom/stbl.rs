@@ -107,34 +107,54 @@ impl ParseAtom for Stbl {
             ..Default::default()
         };
         let mut parsed_bytes = 0;
+        let parse_stsd = cfg.write
+            || cfg.cfg.read_chapter_track
+            || cfg.cfg.read_audio_info
+            || cfg.cfg.read_video_info;
+        let parse_stts =
+            cfg.cfg.read_chapter_track || cfg.cfg.read_audio_info || cfg.cfg.read_video_info;
+        let parse_stsc = cfg.cfg.read_chapter_track;
+        let parse_stsz = cfg.cfg.read_chapter_track || cfg.cfg.read_audio_info;
+        let parse_stco = cfg.write || cfg.cfg.read_chapter_track;
 
         while parsed_bytes < size.content_len() {
             let remaining_bytes = size.content_len() - parsed_bytes;
             let head = head::parse(reader, remaining_bytes)?;
 
             match head.fourcc() {
-                SAMPLE_TABLE_SAMPLE_DESCRIPTION if cfg.write || cfg.cfg.read_audio_info => {
+                SAMPLE_TABLE_SAMPLE_DESCRIPTION if parse_stsd => {
                     stbl.stsd = Some(Stsd::parse(reader, cfg, head.size())?)
                 }
-                SAMPLE_TABLE_TIME_TO_SAMPLE if cfg.cfg.read_chapter_track => {
+                SAMPLE_TABLE_TIME_TO_SAMPLE if parse_stts => {
                     stbl.stts = Some(Stts::parse(reader, cfg, head.size())?)
                 }
-                SAMPLE_TABLE_SAMPLE_TO_CHUNK if cfg.cfg.read_chapter_track => {
+                SAMPLE_TABLE_SAMPLE_TO_CHUNK if parse_stsc => {
                     stbl.stsc = Some(Stsc::parse(reader, cfg, head.size())?)
                 }
-                SAMPLE_TABLE_SAMPLE_SIZE if cfg.cfg.read_chapter_track => {
+                SAMPLE_TABLE_SAMPLE_SIZE if parse_stsz => {
                     stbl.stsz = Some(Stsz::parse(reader, cfg, head.size())?)
                 }
-                SAMPLE_TABLE_CHUNK_OFFSET if cfg.write || cfg.cfg.read_chapter_track => {
+                SAMPLE_TABLE_CHUNK_OFFSET if parse_stco => {
                     stbl.stco = Some(Stco::parse(reader, cfg, head.size())?)
                 }
-                SAMPLE_TABLE_CHUNK_OFFSET_64 if cfg.write || cfg.cfg.read_chapter_track => {
+                SAMPLE_TABLE_CHUNK_OFFSET_64 if parse_stco => {
                     stbl.co64 = Some(Co64::parse(reader, cfg, head.size())?)
                 }
                 _ => reader.skip(head.content_len() as i64)?,
             }
 
             parsed_bytes += head.len();
+
+            if (stbl.stsd.is_some() || !parse_stsd)
+                && (stbl.stts.is_some() || !parse_stts)
+                && (stbl.stsc.is_some() || !parse_stsc)
+                && (stbl.stsz.is_some() || !parse_stsz)
+                && (stbl.stco.is_some() || stbl.co64.is_some() || !parse_stco)
+            {
+                // the remaining atoms aren't needed
+                reader.skip((size.content_len() - parsed_bytes) as i64)?;
+                break;
+            }
         }
 
         Ok(stbl)
@@ -6,7 +6,15 @@ pub const HEADER_SIZE: u64 = 8;
 pub struct Stsd {
     pub state: State,
     pub mp4a: Option<Mp4a>,
+    pub alac: Option<Alac>,
     pub text: Option<Text>,
+    pub visual: Option<Visual>,
+    /// Whether a sample entry is encrypted or protected by DRM.
+    pub encrypted: bool,
+    /// The image format of a video sample entry, for example of a chapter image track.
+    pub image: Option<ImgFmt>,
+    /// The codec of the first sample entry.
+    pub codec: Option<Codec>,
 }
 
 impl Atom for Stsd {
@@ -34,17 +42,53 @@ impl ParseAtom for Stsd {
             ..Default::default()
         };
         let mut parsed_bytes = HEADER_SIZE;
+        // the sample entries aren't needed for writing, unless the track information is read too
+        let parse_entries = !cfg.write || cfg.cfg.read_audio_info || cfg.cfg.read_video_info;
 
         while parsed_bytes < size.content_len() {
             let remaining_bytes = size.content_len() - parsed_bytes;
             let head = head::parse(reader, remaining_bytes)?;
 
             match head.fourcc() {
-                MP4_AUDIO if !cfg.write => stsd.mp4a = Some(Mp4a::parse(reader, cfg, head.size())?),
+                DRM_AUDIO | DRM_VIDEO | ENCRYPTED_AUDIO | ENCRYPTED_VIDEO if parse_entries => {
+                    stsd.encrypted = true;
+                    reader.skip(head.content_len() as i64)?;
+                }
+                MP4_AUDIO if parse_entries => {
+                    let mp4a = Mp4a::parse(reader, cfg, head.size())?;
+                    stsd.encrypted |= mp4a.protected;
+                    stsd.mp4a = Some(mp4a);
+                }
+                JPEG_IMAGE => {
+                    stsd.image = Some(ImgFmt::Jpeg);
+                    reader.skip(head.content_len() as i64)?;
+                }
+                PNG_IMAGE => {
+                    stsd.image = Some(ImgFmt::Png);
+                    reader.skip(head.content_len() as i64)?;
+                }
+                APPLE_LOSSLESS_AUDIO if parse_entries => {
+                    stsd.alac = Some(Alac::parse(reader, cfg, head.size())?)
+                }
+                AVC_VIDEO | HEVC_VIDEO | HEVC_VIDEO_IN_BAND | MP4_VIDEO if parse_entries => {
+                    stsd.visual = Some(Visual::parse(reader, head.fourcc(), head.size())?)
+                }
                 TEXT_MEDIA if cfg.write => stsd.text = Some(Text::parse(reader, cfg, head.size())?),
                 _ => reader.skip(head.content_len() as i64)?,
             }
 
+            if parse_entries && stsd.codec.is_none() {
+                stsd.codec = Some(match head.fourcc() {
+                    MP4_AUDIO => {
+                        stsd.mp4a.as_ref().and_then(|a| a.codec).unwrap_or(Codec::Other(MP4_AUDIO))
+                    }
+                    APPLE_LOSSLESS_AUDIO => Codec::Alac,
+                    AC3_AUDIO => Codec::Ac3,
+                    EAC3_AUDIO => Codec::Eac3,
+                    fourcc => Codec::Other(fourcc),
+                });
+            }
+
             parsed_bytes += head.len();
         }
 
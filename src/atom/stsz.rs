@@ -9,6 +9,8 @@ pub struct Stsz {
     /// If this field is set to zero, a list of sizes is read instead.
     pub uniform_sample_size: u32,
     pub sizes: Table<u32>,
+    /// The number of samples, which is also read if the sizes are uniform.
+    pub sample_count: u32,
 }
 
 impl Atom for Stsz {
@@ -49,6 +51,7 @@ impl ParseAtom for Stsz {
             state: State::Existing(bounds),
             uniform_sample_size,
             sizes,
+            sample_count: num_entries,
         })
     }
 }
@@ -21,6 +21,10 @@ pub struct Tkhd {
     pub id: u32,
     /// The duration in mvhd timescale units
     pub duration: u64,
+    /// The width as 16.16 fixed point number
+    pub width: u32,
+    /// The height as 16.16 fixed point number
+    pub height: u32,
 }
 
 #[derive(Default)]
@@ -97,6 +101,8 @@ impl ParseAtom for Tkhd {
                 reader.read_exact(buf.bytes_mut())?;
                 tkhd.id = u32::from_be_bytes(buf.id);
                 tkhd.duration = u32::from_be_bytes(buf.duration) as u64;
+                tkhd.width = u32::from_be_bytes(buf.track_width);
+                tkhd.height = u32::from_be_bytes(buf.track_height);
             }
             1 => {
                 expect_size("Track header (tkhd) version 1", size, HEADER_SIZE_V1 as u64)?;
@@ -105,6 +111,8 @@ impl ParseAtom for Tkhd {
                 reader.read_exact(buf.bytes_mut())?;
                 tkhd.id = u32::from_be_bytes(buf.id);
                 tkhd.duration = u64::from_be_bytes(buf.duration);
+                tkhd.width = u32::from_be_bytes(buf.track_width);
+                tkhd.height = u32::from_be_bytes(buf.track_height);
             }
             _ => {
                 return unknown_version("track header (tkhd)", version);
@@ -136,6 +144,8 @@ impl WriteAtom for Tkhd {
                     id: u32::to_be_bytes(self.id),
                     duration: u32::to_be_bytes(self.duration as u32),
                     matrix: MATRIX,
+                    track_width: u32::to_be_bytes(self.width),
+                    track_height: u32::to_be_bytes(self.height),
                     ..Default::default()
                 };
                 writer.write_all(buf.bytes_mut())?;
@@ -145,6 +155,8 @@ impl WriteAtom for Tkhd {
                     id: u32::to_be_bytes(self.id),
                     duration: u64::to_be_bytes(self.duration),
                     matrix: MATRIX,
+                    track_width: u32::to_be_bytes(self.width),
+                    track_height: u32::to_be_bytes(self.height),
                     ..Default::default()
                 };
                 writer.write_all(buf.bytes_mut())?;
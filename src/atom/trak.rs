@@ -23,6 +23,10 @@ impl ParseAtom for Trak {
         let mut tkhd = None;
         let mut tref = None;
         let mut mdia = None;
+        let parse_mdia = cfg.write
+            || cfg.cfg.read_chapter_track
+            || cfg.cfg.read_audio_info
+            || cfg.cfg.read_video_info;
 
         while parsed_bytes < size.content_len() {
             let remaining_bytes = size.content_len() - parsed_bytes;
@@ -33,13 +37,17 @@ impl ParseAtom for Trak {
                 TRACK_REFERENCE if cfg.cfg.read_chapter_track => {
                     tref = Some(Tref::parse(reader, cfg, head.size())?)
                 }
-                MEDIA if cfg.write || cfg.cfg.read_chapter_track || cfg.cfg.read_audio_info => {
-                    mdia = Some(Mdia::parse(reader, cfg, head.size())?)
-                }
+                MEDIA if parse_mdia => mdia = Some(Mdia::parse(reader, cfg, head.size())?),
                 _ => reader.skip(head.content_len() as i64)?,
             }
 
             parsed_bytes += head.len();
+
+            if tkhd.is_some() && (mdia.is_some() || !parse_mdia) && !cfg.cfg.read_chapter_track {
+                // the remaining atoms aren't needed
+                reader.skip((size.content_len() - parsed_bytes) as i64)?;
+                break;
+            }
         }
 
         let tkhd = tkhd.ok_or_else(|| {
@@ -5,6 +5,7 @@ pub struct Udta<'a> {
     pub state: State,
     pub chpl: Option<Chpl<'a>>,
     pub meta: Option<Meta<'a>>,
+    pub free: Option<Free>,
 }
 
 impl Atom for Udta<'_> {
@@ -35,10 +36,20 @@ impl ParseAtom for Udta<'_> {
                 METADATA if cfg.cfg.read_meta_items => {
                     udta.meta = Some(Meta::parse(reader, cfg, head.size())?)
                 }
+                FREE if cfg.write => udta.free = Some(Free::parse(reader, cfg, head.size())?),
                 _ => reader.skip(head.content_len() as i64)?,
             }
 
             parsed_bytes += head.len();
+
+            if !cfg.write
+                && (udta.meta.is_some() || !cfg.cfg.read_meta_items)
+                && (udta.chpl.is_some() || !cfg.cfg.read_chapter_list)
+            {
+                // the remaining atoms aren't needed
+                reader.skip((size.content_len() - parsed_bytes) as i64)?;
+                break;
+            }
         }
 
         Ok(udta)
@@ -47,7 +58,8 @@ impl ParseAtom for Udta<'_> {
 
 impl AtomSize for Udta<'_> {
     fn size(&self) -> Size {
-        let content_len = self.meta.len_or_zero() + self.chpl.len_or_zero();
+        let content_len =
+            self.meta.len_or_zero() + self.chpl.len_or_zero() + self.free.len_or_zero();
         Size::from(content_len)
     }
 }
@@ -61,6 +73,9 @@ impl WriteAtom for Udta<'_> {
         if let Some(a) = &self.meta {
             a.write(writer, changes)?;
         }
+        if let Some(a) = &self.free {
+            a.write(writer, changes)?;
+        }
         Ok(())
     }
 }
@@ -78,9 +93,30 @@ impl SimpleCollectChanges for Udta<'_> {
     ) -> i64 {
         self.chpl.collect_changes(bounds.end(), level, changes)
             + self.meta.collect_changes(bounds.end(), level, changes)
+            + self.free.collect_changes(bounds.end(), level, changes)
     }
 
     fn atom_ref(&self) -> AtomRef<'_> {
         AtomRef::Udta(self)
     }
 }
+
+impl Udta<'_> {
+    /// Whether this atom won't contain anything but free space after the changes are applied.
+    pub fn is_empty(&self) -> bool {
+        if self.chpl.as_ref().is_some_and(|a| !a.state.is_remove())
+            || self.meta.as_ref().is_some_and(|a| !a.state.is_remove())
+        {
+            return false;
+        }
+        match &self.state {
+            State::Existing(bounds) => {
+                let known_len = self.chpl.prev_len_or_zero()
+                    + self.meta.prev_len_or_zero()
+                    + self.free.prev_len_or_zero();
+                bounds.content_len() == known_len
+            }
+            _ => true,
+        }
+    }
+}
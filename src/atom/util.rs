@@ -1,4 +1,4 @@
-use std::io::{self, Read, Seek, SeekFrom, Write};
+use std::io::{self, Read, Seek, Write};
 use std::time::Duration;
 
 use crate::ErrorKind;
@@ -81,8 +81,10 @@ fn decode_utf16(buf: &mut String, code_units: impl Iterator<Item = u16>) -> crat
 impl<T: Read> ReadUtil for T {}
 
 pub trait SeekUtil: Seek {
+    /// Skips bytes relative to the current position. Unlike seeking, this keeps the buffer of a
+    /// [`BufReader`](std::io::BufReader) if the new position is inside of it.
     fn skip(&mut self, offset: i64) -> io::Result<()> {
-        self.seek(SeekFrom::Current(offset))?;
+        self.seek_relative(offset)?;
         Ok(())
     }
 }
@@ -0,0 +1,80 @@
+//! visual sample entry (`avc1`, `hvc1`, `hev1`, `mp4v`)
+//!
+//! ```md
+//! 6 bytes ?
+//! 2 bytes data reference index
+//! 16 bytes ?
+//! 2 bytes width
+//! 2 bytes height
+//! 4 bytes horizontal resolution
+//! 4 bytes vertical resolution
+//! 4 bytes ?
+//! 2 bytes frame count
+//! 32 bytes compressor name
+//! 2 bytes depth
+//! 2 bytes ?
+//! │
+//! └─ pasp atom
+//!    4 bytes len
+//!    4 bytes ident
+//!    4 bytes horizontal spacing
+//!    4 bytes vertical spacing
+//! ```
+
+use super::*;
+
+pub const HEADER_SIZE: u64 = 78;
+const PASP_SIZE: u64 = 8;
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Visual {
+    pub codec: Fourcc,
+    pub width: u16,
+    pub height: u16,
+    /// The horizontal and vertical spacing of the pixel aspect ratio (`pasp`) atom.
+    pub pixel_aspect_ratio: Option<(u32, u32)>,
+}
+
+impl Visual {
+    pub fn parse(
+        reader: &mut (impl Read + Seek),
+        codec: Fourcc,
+        size: Size,
+    ) -> crate::Result<Self> {
+        let mut visual = Self { codec, ..Default::default() };
+
+        expect_min_size(&format!("Visual sample entry ({codec})"), size, HEADER_SIZE)?;
+
+        // use cursor over a buffer to avoid syscalls
+        let mut buf = vec![0; size.content_len() as usize];
+        reader.read_exact(&mut buf)?;
+
+        visual.width = u16::from_be_bytes([buf[24], buf[25]]);
+        visual.height = u16::from_be_bytes([buf[26], buf[27]]);
+
+        let mut cursor = std::io::Cursor::new(&mut buf);
+        cursor.skip(HEADER_SIZE as i64)?;
+
+        let mut parsed_bytes = HEADER_SIZE;
+        while parsed_bytes < size.content_len() {
+            let remaining_bytes = size.content_len() - parsed_bytes;
+            let head = head::parse(&mut cursor, remaining_bytes)?;
+
+            match head.fourcc() {
+                PIXEL_ASPECT_RATIO if head.content_len() >= PASP_SIZE => {
+                    let h_spacing = cursor.read_be_u32()?;
+                    let v_spacing = cursor.read_be_u32()?;
+                    if h_spacing != 0 && v_spacing != 0 {
+                        visual.pixel_aspect_ratio = Some((h_spacing, v_spacing));
+                    }
+                    cursor.skip((head.content_len() - PASP_SIZE) as i64)?;
+                }
+                _ => cursor.skip(head.content_len() as i64)?,
+            }
+
+            parsed_bytes += head.len();
+        }
+
+        Ok(visual)
+    }
+}
@@ -1,3 +1,4 @@
+use std::array::TryFromSliceError;
 use std::borrow::Cow;
 use std::{error, fmt, io};
 
@@ -41,6 +42,21 @@ pub enum ErrorKind {
     Utf8StringDecoding,
     /// An invalid utf-16 string was found.
     Utf16StringDecoding,
+    /// A slice couldn't be converted into a fixed size value, such as a [`Fourcc`], because its
+    /// length didn't match.
+    InvalidSliceLength,
+    /// The chapters are invalid, because multiple chapters start at the same time or they aren't
+    /// sorted.
+    InvalidChapterList,
+    /// Data can't be stored as a metadata item, for example a big-endian signed integer of an
+    /// unsupported width.
+    InvalidData,
+    /// An image couldn't be decoded or encoded while transcoding it.
+    ImageTranscoding,
+    /// The image data isn't in one of the supported formats ([`ImgFmt`]).
+    ///
+    /// [`ImgFmt`]: crate::ImgFmt
+    UnsupportedImageFormat,
     /// An IO error has occurred.
     Io(io::Error),
 }
@@ -75,6 +91,12 @@ impl From<io::Error> for Error {
     }
 }
 
+impl From<TryFromSliceError> for Error {
+    fn from(err: TryFromSliceError) -> Error {
+        Error::new(ErrorKind::InvalidSliceLength, format!("Slice conversion error: {err}"))
+    }
+}
+
 impl fmt::Debug for Error {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         if self.description.is_empty() {
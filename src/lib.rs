@@ -94,10 +94,16 @@
 //! ```
 #![deny(rust_2018_idioms)]
 
-pub use crate::atom::ident::{self, DataIdent, Fourcc, FreeformIdent, Ident};
-pub use crate::atom::{ChplTimescale, Data, ReadConfig, StorageFile, WriteConfig};
+pub use crate::atom::ident::{self, DataIdent, Fourcc, FreeformIdent, Ident, IdentMap};
+pub use crate::atom::{
+    ArtworkRef, BackupMode, ChplTimescale, Data, DataSource, FallbackEncoding, ItemOrder,
+    PaddingPosition, ReadConfig, SkippedData, StorageFile, WriteConfig, WriteOutcome, WritePlan,
+};
 pub use crate::error::{Error, ErrorKind, Result};
-pub use crate::tag::{STANDARD_GENRES, Tag, Userdata};
+pub use crate::tag::{
+    Change, DiffMode, IntoItems, MergeOptions, MergePolicy, NormalizeOptions, STANDARD_GENRES, Tag,
+    TagDiff, Userdata, ValidationIssue,
+};
 pub use crate::types::*;
 
 pub(crate) use crate::atom::MetaItem;
@@ -0,0 +1,317 @@
+//! Async reading and writing with tokio.
+//!
+//! The sync parser and writer are reused instead of being duplicated. When reading, the atoms
+//! are fetched asynchronously into a [`Prefetched`] reader, and the sync parser is run on it. If
+//! the parser needs bytes that haven't been fetched, like the samples of a chapter track inside
+//! the media data, they are fetched and the parser is run again.
+
+use std::collections::BTreeMap;
+use std::fmt;
+use std::io::{self, Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncSeek, AsyncSeekExt, AsyncWriteExt};
+
+use crate::atom::{self, CopySegment};
+use crate::{ErrorKind, ReadConfig, Tag, Userdata, WriteConfig, WriteOutcome};
+
+/// The maximum length of a top level atom, other than the media data (`mdat`), that is fetched
+/// upfront.
+const MAX_PREFETCH_LEN: u64 = 64 * 1024 * 1024;
+/// The minimum number of bytes that are fetched when the parser needs bytes that are missing.
+const MIN_FETCH_LEN: u64 = 64 * 1024;
+
+/// A reader over the segments of a file that have been fetched.
+struct Prefetched {
+    len: u64,
+    pos: u64,
+    /// The non-overlapping segments by their start position.
+    segments: BTreeMap<u64, Vec<u8>>,
+}
+
+/// The error returned by [`Prefetched`] when reading bytes that haven't been fetched.
+#[derive(Debug)]
+struct Missing {
+    pos: u64,
+    len: u64,
+}
+
+impl fmt::Display for Missing {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} bytes at {} haven't been fetched", self.len, self.pos)
+    }
+}
+
+impl std::error::Error for Missing {}
+
+impl Prefetched {
+    /// Fetches the heads of all top level atoms and the contents of all but the media data
+    /// (`mdat`) and very large ones.
+    async fn new(reader: &mut (impl AsyncRead + AsyncSeek + Unpin)) -> io::Result<Self> {
+        let len = reader.seek(SeekFrom::End(0)).await?;
+        let mut prefetched = Self { len, pos: 0, segments: BTreeMap::new() };
+
+        let mut pos = 0;
+        while pos + 8 <= len {
+            // the head may span several segments, so it's read through the reader
+            let mut buf = [0; 16];
+            let head = &mut buf[..(len - pos).min(16) as usize];
+            prefetched.fetch(reader, pos, head.len() as u64).await?;
+            prefetched.pos = pos;
+            prefetched.read_exact(head)?;
+            let size = u32::from_be_bytes([head[0], head[1], head[2], head[3]]) as u64;
+            let size = match size {
+                0 => len - pos,
+                1 if head.len() == 16 => u64::from_be_bytes(head[8..16].try_into().unwrap()),
+                _ => size,
+            };
+            // leave malformed atoms to the parser
+            if size < 8 || size > len - pos {
+                break;
+            }
+            if &head[4..8] != b"mdat" && size <= MAX_PREFETCH_LEN {
+                prefetched.fetch(reader, pos, size).await?;
+            }
+            pos += size;
+        }
+
+        Ok(prefetched)
+    }
+
+    /// Fetches the `len` bytes at the position that haven't been fetched yet, stopping at the end
+    /// of the file.
+    async fn fetch(
+        &mut self,
+        reader: &mut (impl AsyncRead + AsyncSeek + Unpin),
+        mut pos: u64,
+        len: u64,
+    ) -> io::Result<()> {
+        let end = (pos + len).min(self.len);
+        while pos < end {
+            if let Some((s, seg)) = self.segments.range(..=pos).next_back()
+                && pos < s + seg.len() as u64
+            {
+                pos = s + seg.len() as u64;
+                continue;
+            }
+            let next = self.segments.range(pos..).next().map_or(end, |(s, _)| *s).min(end);
+            let mut buf = vec![0; (next - pos) as usize];
+            reader.seek(SeekFrom::Start(pos)).await?;
+            reader.read_exact(&mut buf).await?;
+            self.segments.insert(pos, buf);
+            pos = next;
+        }
+
+        Ok(())
+    }
+}
+
+impl Read for Prefetched {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if self.pos >= self.len || buf.is_empty() {
+            return Ok(0);
+        }
+        if let Some((start, seg)) = self.segments.range(..=self.pos).next_back()
+            && self.pos < start + seg.len() as u64
+        {
+            let offset = (self.pos - start) as usize;
+            let n = buf.len().min(seg.len() - offset);
+            buf[..n].copy_from_slice(&seg[offset..offset + n]);
+            self.pos += n as u64;
+            return Ok(n);
+        }
+        Err(io::Error::other(Missing { pos: self.pos, len: buf.len() as u64 }))
+    }
+}
+
+impl Seek for Prefetched {
+    fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+        let pos = match pos {
+            SeekFrom::Start(p) => Some(p),
+            SeekFrom::End(p) => self.len.checked_add_signed(p),
+            SeekFrom::Current(p) => self.pos.checked_add_signed(p),
+        };
+        self.pos = pos.ok_or_else(|| {
+            io::Error::new(io::ErrorKind::InvalidInput, "Seek to a negative position")
+        })?;
+        Ok(self.pos)
+    }
+}
+
+/// Returns the bytes that were missing, if the error was caused by reading bytes that haven't
+/// been fetched.
+fn missing(e: &crate::Error) -> Option<&Missing> {
+    match &e.kind {
+        ErrorKind::Io(e) => e.get_ref()?.downcast_ref(),
+        _ => None,
+    }
+}
+
+/// ### Async
+impl Tag {
+    /// Attempts to read a MPEG-4 audio tag from the async reader. The top level atoms, apart from
+    /// the media data (`mdat`), are fetched into memory, and parsed the same way as by
+    /// [`Tag::read_with`].
+    pub async fn read_with_async(
+        reader: &mut (impl AsyncRead + AsyncSeek + Unpin),
+        cfg: &ReadConfig,
+    ) -> crate::Result<Self> {
+        let mut prefetched = Prefetched::new(reader).await?;
+        loop {
+            prefetched.pos = 0;
+            let res = Self::read_with(&mut prefetched, cfg);
+            match res.as_ref().err().and_then(missing) {
+                Some(&Missing { pos, len }) => {
+                    prefetched.fetch(reader, pos, len.max(MIN_FETCH_LEN)).await?;
+                }
+                None => return res,
+            }
+        }
+    }
+
+    /// Attempts to read a MPEG-4 audio tag from the async reader, see [`Tag::read_with_async`].
+    pub async fn read_from_async(
+        reader: &mut (impl AsyncRead + AsyncSeek + Unpin),
+    ) -> crate::Result<Self> {
+        Self::read_with_async(reader, &ReadConfig::DEFAULT).await
+    }
+
+    /// Attempts to read a MPEG-4 audio tag from the file at the indicated path asynchronously,
+    /// see [`Tag::read_with_async`].
+    pub async fn read_with_path_async(
+        path: impl AsRef<Path>,
+        cfg: &ReadConfig,
+    ) -> crate::Result<Self> {
+        let mut file = tokio::fs::File::open(path).await?;
+        Self::read_with_async(&mut file, cfg).await
+    }
+
+    /// Attempts to read a MPEG-4 audio tag from the file at the indicated path asynchronously,
+    /// see [`Tag::read_with_async`].
+    pub async fn read_from_path_async(path: impl AsRef<Path>) -> crate::Result<Self> {
+        Self::read_with_path_async(path, &ReadConfig::DEFAULT).await
+    }
+}
+
+/// A temporary file that is removed when dropped, unless it has been persisted.
+struct TempFile {
+    path: Option<PathBuf>,
+}
+
+impl Drop for TempFile {
+    fn drop(&mut self) {
+        if let Some(path) = &self.path {
+            _ = std::fs::remove_file(path);
+        }
+    }
+}
+
+/// ### Async
+impl Userdata {
+    /// Attempts to write the MPEG-4 audio tag to the file at the path asynchronously.
+    ///
+    /// The top level atoms, apart from the media data (`mdat`), are fetched into memory, and the
+    /// changes are collected the same way as by [`Userdata::write_with`]. A copy with the changed
+    /// atoms is written to a temporary file next to it, while the unchanged parts, like the media
+    /// data, are copied in chunks. The temporary file then atomically replaces the original file.
+    /// So the file is never left partially written, even if the returned future is dropped
+    /// before it completes. The temporary file is removed in that case.
+    pub async fn write_with_path_async(
+        &self,
+        path: impl AsRef<Path>,
+        cfg: &WriteConfig,
+    ) -> crate::Result<WriteOutcome> {
+        let path = path.as_ref();
+        if let Some(backup) = &cfg.backup {
+            let (backup, path) = (backup.clone(), path.to_path_buf());
+            spawn_blocking(move || backup.backup(&path)).await?;
+        }
+
+        let mut src = tokio::fs::File::open(path).await?;
+        let mut prefetched = Prefetched::new(&mut src).await?;
+        let (segments, mut outcome) = loop {
+            prefetched.pos = 0;
+            let res = atom::write_copy_segments(&mut prefetched, cfg, self);
+            match res.as_ref().err().and_then(missing) {
+                Some(&Missing { pos, len }) => {
+                    prefetched.fetch(&mut src, pos, len.max(MIN_FETCH_LEN)).await?;
+                }
+                None => break res?,
+            }
+        };
+        drop(prefetched);
+
+        let Some(file_name) = path.file_name() else {
+            return Err(crate::Error::new(
+                ErrorKind::Io(io::ErrorKind::InvalidInput.into()),
+                "Unable to write the file, the path has no file name",
+            ));
+        };
+        let mut temp_name = std::ffi::OsString::from(".");
+        temp_name.push(file_name);
+        temp_name.push(".tmp");
+        let temp_path = path.with_file_name(temp_name);
+
+        let mut temp = TempFile { path: Some(temp_path.clone()) };
+        let mut temp_file = tokio::fs::OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(true)
+            .open(&temp_path)
+            .await?;
+        for segment in segments {
+            match segment {
+                CopySegment::Source(range) => {
+                    let len = range.end - range.start;
+                    src.seek(SeekFrom::Start(range.start)).await?;
+                    let copied = tokio::io::copy(&mut (&mut src).take(len), &mut temp_file).await?;
+                    if copied != len {
+                        return Err(crate::Error::new(
+                            ErrorKind::Io(io::ErrorKind::UnexpectedEof.into()),
+                            "Unexpected end of file while copying data",
+                        ));
+                    }
+                }
+                CopySegment::Data(data) => temp_file.write_all(&data).await?,
+            }
+        }
+        drop(src);
+
+        if cfg.faststart {
+            let mut file = temp_file.into_std().await;
+            let (moved, file) =
+                spawn_blocking(move || Ok((atom::move_moov_to_front(&mut file)?, file))).await?;
+            if moved {
+                outcome = WriteOutcome::Rewritten;
+            }
+            temp_file = tokio::fs::File::from_std(file);
+        }
+        temp_file.sync_all().await?;
+        drop(temp_file);
+
+        let permissions = tokio::fs::metadata(path).await?.permissions();
+        tokio::fs::set_permissions(&temp_path, permissions).await?;
+        tokio::fs::rename(&temp_path, path).await?;
+        temp.path = None;
+
+        Ok(outcome)
+    }
+
+    /// Attempts to write the MPEG-4 audio tag to the file at the path asynchronously, see
+    /// [`Userdata::write_with_path_async`]. This will overwrite any metadata previously present
+    /// on the file.
+    pub async fn write_to_path_async(&self, path: impl AsRef<Path>) -> crate::Result<WriteOutcome> {
+        self.write_with_path_async(path, &WriteConfig::DEFAULT).await
+    }
+}
+
+/// Runs the blocking function on a thread where blocking is acceptable, so that the runtime
+/// isn't blocked.
+async fn spawn_blocking<T: Send + 'static>(
+    f: impl FnOnce() -> crate::Result<T> + Send + 'static,
+) -> crate::Result<T> {
+    tokio::task::spawn_blocking(f).await.map_err(|e| {
+        crate::Error::new(ErrorKind::Io(io::Error::other(e)), "A blocking task failed")
+    })?
+}
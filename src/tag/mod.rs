@@ -1,23 +1,47 @@
 use std::fmt;
 use std::fs::File;
-use std::io::{BufReader, Read, Seek};
+use std::io::{BufReader, Cursor, Read, Seek};
 use std::ops::{Deref, DerefMut};
 use std::path::Path;
+use std::time::Duration;
 
-use crate::{AudioInfo, ReadConfig, atom, util};
+use crate::{
+    AudioInfo, Chapter, Data, DataIdent, FileType, ImgBuf, ReadConfig, Timestamp, TrackInfo,
+    VideoInfo, WriteConfig, WriteOutcome, atom, ident, util,
+};
 
 pub use userdata::*;
+pub use validate::ValidationIssue;
 
+#[cfg(feature = "tokio")]
+mod async_io;
 mod readonly;
 mod userdata;
+mod validate;
 
 /// A tag containing MPEG-4 audio metadata.
 #[derive(Clone, Debug, Default, PartialEq, Eq)]
 pub struct Tag {
     /// The filetype (`ftyp`) atom.
     pub ftyp: String,
+    /// The parsed contents of the filetype (`ftyp`) atom.
+    pub file_type: FileType,
     pub info: AudioInfo,
+    /// The video information, if the file contains a video track.
+    pub video_info: Option<VideoInfo>,
+    /// The information of all tracks.
+    pub tracks: Vec<TrackInfo>,
+    /// The summed content length of all media data (`mdat`) atoms.
+    pub mdat_len: u64,
+    /// The creation time stored in the movie header (`mvhd`).
+    pub created_at: Option<Timestamp>,
+    /// The modification time stored in the movie header (`mvhd`).
+    pub modified_at: Option<Timestamp>,
     pub userdata: Userdata,
+    /// Whether a metadata (`meta`) atom was found without a handler reference (`hdlr`) atom.
+    pub(crate) meta_hdlr_missing: bool,
+    /// Whether a track is encrypted or protected by DRM.
+    pub(crate) encrypted: bool,
 }
 
 impl Deref for Tag {
@@ -34,6 +58,17 @@ impl DerefMut for Tag {
     }
 }
 
+impl IntoIterator for Tag {
+    type Item = (DataIdent, Data);
+    type IntoIter = IntoItems;
+
+    /// Consumes `self` and returns an iterator over all metadata items of the [`Userdata`],
+    /// yielding each data value separately, in item order.
+    fn into_iter(self) -> Self::IntoIter {
+        self.userdata.into_iter()
+    }
+}
+
 impl fmt::Display for Tag {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         self.format_album_artists(f)?;
@@ -43,6 +78,9 @@ impl fmt::Display for Tag {
         self.format_composers(f)?;
         self.format_composer_sort_orders(f)?;
         self.format_lyricists(f)?;
+        self.format_authors(f)?;
+        self.format_directors(f)?;
+        self.format_producers(f)?;
         self.format_album(f)?;
         self.format_album_sort_order(f)?;
         self.format_title(f)?;
@@ -76,13 +114,20 @@ impl fmt::Display for Tag {
         self.format_duration(f)?;
         self.format_channel_config(f)?;
         self.format_sample_rate(f)?;
+        self.format_bit_depth(f)?;
+        self.format_codec(f)?;
         self.format_avg_bitrate(f)?;
         self.format_max_bitrate(f)?;
+        self.format_video_info(f)?;
         self.format_show_movement(f)?;
         self.format_gapless_playback(f)?;
         self.format_compilation(f)?;
         self.format_isrc(f)?;
         self.format_label(f)?;
+        self.format_musicbrainz_track_id(f)?;
+        self.format_musicbrainz_album_id(f)?;
+        self.format_musicbrainz_artist_ids(f)?;
+        self.format_musicbrainz_release_group_id(f)?;
         self.format_lyrics(f)?;
         self.format_chapter_list(f)?;
         self.format_chapter_track(f)?;
@@ -119,14 +164,232 @@ impl Tag {
         Self::read_with(reader, &ReadConfig::DEFAULT)
     }
 
+    /// Attempts to read a MPEG-4 audio tag from the byte slice, for example a file that has been
+    /// read into memory.
+    pub fn read_with_slice(data: &[u8], cfg: &ReadConfig) -> crate::Result<Self> {
+        Self::read_with(&mut Cursor::new(data), cfg)
+    }
+
+    /// Attempts to read a MPEG-4 audio tag from the byte slice, for example a file that has been
+    /// read into memory.
+    pub fn read_from_slice(data: &[u8]) -> crate::Result<Self> {
+        Self::read_with_slice(data, &ReadConfig::DEFAULT)
+    }
+
+    /// Attempts to read a MPEG-4 audio tag from the file at the indicated path, by mapping it into
+    /// memory instead of reading it. This avoids read calls and lets the operating system cache
+    /// the file.
+    ///
+    /// # Safety
+    /// The file must not be modified or truncated while it is being read, see
+    /// [`memmap2::Mmap`].
+    #[cfg(feature = "mmap")]
+    pub unsafe fn read_with_mmap(path: impl AsRef<Path>, cfg: &ReadConfig) -> crate::Result<Self> {
+        let file = File::open(path)?;
+        // SAFETY: upheld by the caller
+        let mmap = unsafe { memmap2::Mmap::map(&file)? };
+        Self::read_with_slice(&mmap, cfg)
+    }
+
+    /// Attempts to read a MPEG-4 audio tag from the file at the indicated path, by mapping it into
+    /// memory instead of reading it, see [`Tag::read_with_mmap`].
+    ///
+    /// # Safety
+    /// The file must not be modified or truncated while it is being read, see
+    /// [`memmap2::Mmap`].
+    #[cfg(feature = "mmap")]
+    pub unsafe fn read_from_mmap(path: impl AsRef<Path>) -> crate::Result<Self> {
+        // SAFETY: upheld by the caller
+        unsafe { Self::read_with_mmap(path, &ReadConfig::DEFAULT) }
+    }
+
     /// Attempts to read a MPEG-4 audio tag from the file at the indicated path.
     pub fn read_with_path(path: impl AsRef<Path>, cfg: &ReadConfig) -> crate::Result<Self> {
         let mut file = BufReader::new(File::open(path)?);
         Self::read_with(&mut file, cfg)
     }
 
+    /// Attempts to read a MPEG-4 audio tag from the file at the indicated path, in order to
+    /// update it.
+    ///
+    /// The layout of the file, such as the positions of the top level atoms, the metadata, the
+    /// padding and the chunk offsets, is parsed along with the tag and cached in it. The next
+    /// write to the same path, using [`Userdata::write_to_path`] or
+    /// [`Userdata::write_with_path`], reuses it instead of parsing the file again. If the length
+    /// or modification time of the file changed in the meantime, or the movie (`moov`) and item
+    /// list (`ilst`) atoms aren't found at their cached positions, the file is parsed again.
+    pub fn read_for_update(path: impl AsRef<Path>) -> crate::Result<Self> {
+        atom::read_tag_for_update(path.as_ref(), &ReadConfig::DEFAULT)
+    }
+
     /// Attempts to read a MPEG-4 audio tag from the file at the indicated path.
     pub fn read_from_path(path: impl AsRef<Path>) -> crate::Result<Self> {
         Self::read_with_path(path, &ReadConfig::DEFAULT)
     }
+
+    /// Attempts to read only the audio information and the filetype from the reader, see
+    /// [`ReadConfig::AUDIO_INFO_ONLY`]. The user data (`udta`) is skipped without being parsed, so
+    /// malformed metadata items don't cause an error.
+    pub fn read_audio_info_from(
+        reader: &mut (impl Read + Seek),
+    ) -> crate::Result<(AudioInfo, FileType)> {
+        let tag = Self::read_with(reader, &ReadConfig::AUDIO_INFO_ONLY)?;
+        Ok((tag.info, tag.file_type))
+    }
+
+    /// Attempts to read only the audio information and the filetype from the file at the
+    /// indicated path, see [`Tag::read_audio_info_from`].
+    pub fn read_audio_info_from_path(
+        path: impl AsRef<Path>,
+    ) -> crate::Result<(AudioInfo, FileType)> {
+        let mut file = BufReader::new(File::open(path)?);
+        Self::read_audio_info_from(&mut file)
+    }
+
+    /// Attempts to read only the metadata items and chapter list from the reader, see
+    /// [`ReadConfig::METADATA_ONLY`]. The sample tables of the tracks are skipped, so the audio
+    /// and video information, apart from the duration, isn't available.
+    pub fn read_metadata_only_from(reader: &mut (impl Read + Seek)) -> crate::Result<Self> {
+        Self::read_with(reader, &ReadConfig::METADATA_ONLY)
+    }
+
+    /// Attempts to read only the metadata items and chapter list from the file at the indicated
+    /// path, see [`Tag::read_metadata_only_from`].
+    pub fn read_metadata_only_from_path(path: impl AsRef<Path>) -> crate::Result<Self> {
+        Self::read_with_path(path, &ReadConfig::METADATA_ONLY)
+    }
+
+    /// Attempts to read only the artwork images (`covr`) from the reader. All other atoms are
+    /// skipped without being parsed.
+    pub fn read_artwork_from(reader: &mut (impl Read + Seek)) -> crate::Result<Vec<ImgBuf>> {
+        atom::read_artworks(reader)
+    }
+
+    /// Attempts to read only the artwork images (`covr`) from the file at the indicated path. All
+    /// other atoms are skipped without being parsed.
+    pub fn read_artwork_from_path(path: impl AsRef<Path>) -> crate::Result<Vec<ImgBuf>> {
+        let mut file = BufReader::new(File::open(path)?);
+        Self::read_artwork_from(&mut file)
+    }
+
+    /// Attempts to read only the identifiers of the metadata items from the reader, along with the
+    /// total length of their data payloads in bytes. Only the item headers and the `mean` and
+    /// `name` atoms of freeform items are parsed, the data payloads are skipped. This is much
+    /// cheaper than a full read for files with large artwork, and the identifiers can be used to
+    /// populate [`ReadConfig::skip_idents`] for a subsequent read.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use mp4ameta::{ReadConfig, Tag};
+    ///
+    /// let idents = Tag::scan_idents_from_path("music.m4a").unwrap();
+    /// let skip_idents = idents.into_iter().filter(|(_, len)| *len > 1024).map(|(i, _)| i);
+    /// let cfg = ReadConfig { skip_idents: skip_idents.collect(), ..Default::default() };
+    /// let tag = Tag::read_with_path("music.m4a", &cfg).unwrap();
+    /// ```
+    pub fn scan_idents_from(
+        reader: &mut (impl Read + Seek),
+    ) -> crate::Result<Vec<(DataIdent, usize)>> {
+        atom::scan_idents(reader)
+    }
+
+    /// Attempts to read only the identifiers of the metadata items from the file at the indicated
+    /// path, along with the total length of their data payloads in bytes, see
+    /// [`Tag::scan_idents_from`].
+    pub fn scan_idents_from_path(path: impl AsRef<Path>) -> crate::Result<Vec<(DataIdent, usize)>> {
+        let mut file = BufReader::new(File::open(path)?);
+        Self::scan_idents_from(&mut file)
+    }
+
+    /// Attempts to remove all metadata items from the file at the indicated path. Empty metadata
+    /// (`meta`) and user data (`udta`) atoms are removed as well, chapters are left intact.
+    pub fn remove_from_path(path: impl AsRef<Path>) -> crate::Result<WriteOutcome> {
+        let cfg = WriteConfig { write_meta_items: true, ..WriteConfig::NONE };
+        Userdata::default().write_with_path(path, &cfg)
+    }
+
+    /// Attempts to copy all metadata items (`ilst`) from the file at the source path to the file
+    /// at the destination path, replacing the metadata items of the destination. Missing user
+    /// data (`udta`) and metadata (`meta`) atoms are created. The chapters and the media data of
+    /// the destination are left intact, only chunk offsets are updated if the media data has to
+    /// be moved.
+    pub fn copy_metadata(
+        src: impl AsRef<Path>,
+        dst: impl AsRef<Path>,
+    ) -> crate::Result<WriteOutcome> {
+        let cfg = ReadConfig {
+            read_meta_items: true,
+            read_image_data: true,
+            ..ReadConfig::NONE
+        };
+        let userdata = Self::read_with_path(src, &cfg)?.userdata;
+
+        let cfg = WriteConfig {
+            write_meta_items: true,
+            write_chapter_list: false,
+            write_chapter_track: false,
+            ..WriteConfig::DEFAULT
+        };
+        userdata.write_with_path(dst, &cfg)
+    }
+
+    /// Returns the end of each chapter returned by [`Userdata::chapters`], see [`Chapter::ends`].
+    pub fn chapter_ends(&self) -> Vec<Duration> {
+        Chapter::ends(self.chapters(), self.info.duration)
+    }
+
+    /// Checks the chapters like [`Userdata::validate_chapters`], and additionally that all
+    /// chapters start within the duration of the audio. If the duration is unknown (zero) the
+    /// latter check is skipped.
+    pub fn validate_chapters(&self) -> crate::Result<()> {
+        self.userdata.validate_chapters()?;
+
+        let duration = self.info.duration;
+        if duration.is_zero() {
+            return Ok(());
+        }
+        let mut chapters =
+            self.userdata.chapter_list.iter().chain(self.userdata.chapter_track.iter());
+        if let Some(c) = chapters.find(|c| c.start >= duration) {
+            return Err(crate::Error::new(
+                crate::ErrorKind::InvalidChapterList,
+                format!(
+                    "Chapter '{}' starts at {:?}, not before the audio ends at {duration:?}",
+                    c.title, c.start
+                ),
+            ));
+        }
+        Ok(())
+    }
+
+    /// Returns a copy of this tag without any artwork images (`covr`). The image data is never
+    /// cloned, which keeps copies that are e.g. cached small.
+    ///
+    /// All other state is kept, except for the file layout cached by
+    /// [`Tag::read_for_update`], which is dropped by [`Clone`] as well, so writing the copy
+    /// parses the file again.
+    pub fn clone_without_artwork(&self) -> Self {
+        let meta_items = self.userdata.meta_items.iter();
+        Self {
+            ftyp: self.ftyp.clone(),
+            file_type: self.file_type.clone(),
+            info: self.info.clone(),
+            video_info: self.video_info.clone(),
+            tracks: self.tracks.clone(),
+            mdat_len: self.mdat_len,
+            created_at: self.created_at,
+            modified_at: self.modified_at,
+            meta_hdlr_missing: self.meta_hdlr_missing,
+            encrypted: self.encrypted,
+            userdata: Userdata {
+                meta_items: meta_items.filter(|a| ident::ARTWORK != a.ident).cloned().collect(),
+                chapter_list: self.userdata.chapter_list.clone(),
+                chapter_track: self.userdata.chapter_track.clone(),
+                created_at: self.userdata.created_at,
+                modified_at: self.userdata.modified_at,
+                audio_language: self.userdata.audio_language.clone(),
+                layout: self.userdata.layout.clone(),
+            },
+        }
+    }
 }
@@ -1,11 +1,15 @@
 use std::fmt;
 use std::time::Duration;
 
-use crate::{AudioInfo, ChannelConfig, SampleRate, Tag, util};
+use crate::{
+    AudioInfo, ChannelConfig, Codec, FileType, SampleRate, Tag, Timestamp, TrackInfo, VideoInfo,
+    util,
+};
 
 /// ### Audio information
 impl Tag {
-    /// Returns a reference of the audio information.
+    /// Returns a reference of the audio information of the first enabled audio track, or the
+    /// first audio track if none is enabled.
     pub fn audio_info(&self) -> &AudioInfo {
         &self.info
     }
@@ -39,13 +43,56 @@ impl Tag {
         self.info.sample_rate
     }
 
+    /// Returns the sample rate in Hz at which the audio is played back, see
+    /// [`AudioInfo::effective_sample_rate`].
+    pub fn effective_sample_rate(&self) -> Option<u32> {
+        self.info.effective_sample_rate()
+    }
+
     pub(crate) fn format_sample_rate(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        match self.sample_rate() {
-            Some(r) => writeln!(f, "sample rate: {r}"),
+        let Some(r) = self.sample_rate() else {
+            return Ok(());
+        };
+        match self.effective_sample_rate() {
+            Some(e) if e != r.hz() => writeln!(f, "sample rate: {r} (effective {e}Hz)"),
+            _ => writeln!(f, "sample rate: {r}"),
+        }
+    }
+
+    /// Returns the bit depth, see [`AudioInfo::bit_depth`].
+    pub fn bit_depth(&self) -> Option<u8> {
+        self.info.bit_depth
+    }
+
+    pub(crate) fn format_bit_depth(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.bit_depth() {
+            Some(b) => writeln!(f, "bit depth: {b}"),
+            None => Ok(()),
+        }
+    }
+
+    /// Returns the codec, see [`AudioInfo::codec`].
+    pub fn codec(&self) -> Option<Codec> {
+        self.info.codec
+    }
+
+    pub(crate) fn format_codec(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.codec() {
+            Some(c) => writeln!(f, "codec: {c}"),
             None => Ok(()),
         }
     }
 
+    /// Returns the number of frames per packet, see [`AudioInfo::frames_per_packet`].
+    pub fn frames_per_packet(&self) -> Option<u32> {
+        self.info.frames_per_packet
+    }
+
+    /// Returns the ALAC specific config, see [`AudioInfo::alac_cookie`].
+    pub fn alac_cookie(&self) -> Option<&[u8]> {
+        self.info.alac_cookie.as_deref()
+    }
+
     /// Returns the average bitrate.
     pub fn avg_bitrate(&self) -> Option<u32> {
         self.info.avg_bitrate
@@ -53,6 +100,9 @@ impl Tag {
 
     pub(crate) fn format_avg_bitrate(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self.avg_bitrate() {
+            Some(c) if self.info.bitrate_estimated => {
+                writeln!(f, "average bitrate: ~{}kbps (estimated)", c / 1024)
+            }
             Some(c) => writeln!(f, "average bitrate: {}kbps", c / 1024),
             None => Ok(()),
         }
@@ -71,10 +121,122 @@ impl Tag {
     }
 }
 
+/// ### Video information
+impl Tag {
+    /// Returns a reference of the video information of the first enabled video track, or the first
+    /// video track if none is enabled. `None` if there is no video track.
+    pub fn video_info(&self) -> Option<&VideoInfo> {
+        self.video_info.as_ref()
+    }
+
+    pub(crate) fn format_video_info(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let Some(v) = self.video_info() else {
+            return Ok(());
+        };
+        write!(f, "video: {} {}x{}", v.codec, v.width, v.height)?;
+        if v.display_width != v.width {
+            write!(f, " (displayed as {}x{})", v.display_width, v.height)?;
+        }
+        if let Some(r) = v.frame_rate {
+            write!(f, " {r:.3}fps")?;
+        }
+        writeln!(f)
+    }
+}
+
+/// ### Tracks
+impl Tag {
+    /// Returns the information of all tracks, in the order they are stored in the file.
+    ///
+    /// The [`AudioInfo`] of this tag is the one of the first enabled audio track, and the
+    /// [`VideoInfo`] the one of the first enabled video track.
+    pub fn tracks(&self) -> &[TrackInfo] {
+        &self.tracks
+    }
+
+    /// Returns the ISO 639-2/T language code of the first enabled audio track, or the first audio
+    /// track if none is enabled, for example `eng`, or `und` if it is undetermined. A language
+    /// set using [`Userdata::set_audio_language`](crate::Userdata::set_audio_language) takes
+    /// precedence over the one read from the file.
+    pub fn audio_language(&self) -> Option<&str> {
+        if let Some(l) = &self.userdata.audio_language {
+            return Some(l);
+        }
+        let mut audio = self.tracks.iter().filter(|t| t.audio.is_some());
+        let track = audio.clone().find(|t| t.enabled).or_else(|| audio.next())?;
+        Some(track.language.as_deref().unwrap_or("und"))
+    }
+
+    /// Returns the language of the first enabled audio track, or the first track if there is no
+    /// audio track. This is the extended language tag (`elng`) if present, for example `en-US`,
+    /// and otherwise the ISO 639-2/T language code decoded from the media header (`mdhd`), for
+    /// example `eng`. A language set using
+    /// [`Userdata::set_audio_language`](crate::Userdata::set_audio_language) takes precedence.
+    /// Returns `None` if the language is unspecified or undetermined (`und`).
+    pub fn language(&self) -> Option<String> {
+        let language = match &self.userdata.audio_language {
+            Some(l) => l,
+            None => {
+                let mut audio = self.tracks.iter().filter(|t| t.audio.is_some());
+                let track = (audio.clone().find(|t| t.enabled))
+                    .or_else(|| audio.next())
+                    .or_else(|| self.tracks.first())?;
+                track.extended_language.as_ref().or(track.language.as_ref())?
+            }
+        };
+        (language != "und").then(|| language.clone())
+    }
+}
+
+/// ### Encryption
+impl Tag {
+    /// Returns whether a track is encrypted or protected by DRM, such as the audio of a FairPlay
+    /// protected `.m4p` file. The metadata of such files is usually still readable.
+    ///
+    /// This is only detected if the sample descriptions were read, which is the case if any of
+    /// [`ReadConfig::read_audio_info`](crate::ReadConfig::read_audio_info),
+    /// [`ReadConfig::read_video_info`](crate::ReadConfig::read_video_info) or
+    /// [`ReadConfig::read_chapter_track`](crate::ReadConfig::read_chapter_track) is set.
+    pub fn is_encrypted(&self) -> bool {
+        self.encrypted
+    }
+}
+
+/// ### Timestamps
+impl Tag {
+    /// Returns the creation time of the movie header (`mvhd`). A value set using
+    /// [`Userdata::set_created_at`](crate::Userdata::set_created_at) takes precedence over the one
+    /// read from the file.
+    pub fn created_at(&self) -> Option<Timestamp> {
+        self.userdata.created_at.or(self.created_at)
+    }
+
+    /// Returns the modification time of the movie header (`mvhd`). A value set using
+    /// [`Userdata::set_modified_at`](crate::Userdata::set_modified_at) takes precedence over the
+    /// one read from the file.
+    pub fn modified_at(&self) -> Option<Timestamp> {
+        self.userdata.modified_at.or(self.modified_at)
+    }
+}
+
+/// ### Media data
+impl Tag {
+    /// Returns the summed content length of all media data (`mdat`) atoms in bytes.
+    pub fn mdat_len(&self) -> u64 {
+        self.mdat_len
+    }
+}
+
 /// ### Filetype
 impl Tag {
     /// returns the filetype (`ftyp`).
     pub fn filetype(&self) -> &str {
         self.ftyp.as_str()
     }
+
+    /// Returns the major brand, minor version and compatible brands of the filetype (`ftyp`).
+    /// The filetype atom is never modified when writing.
+    pub fn file_type(&self) -> &FileType {
+        &self.file_type
+    }
 }
@@ -0,0 +1,222 @@
+use std::fmt;
+
+use crate::{Data, DataIdent, Userdata};
+
+/// A change of the data of a metadata item between two tags, see [`Userdata::diff`]. The data is
+/// borrowed from the compared tags.
+///
+/// The [`Debug`](fmt::Debug) implementation summarizes binary data by its length instead of
+/// dumping it.
+#[derive(Clone, PartialEq, Eq)]
+pub enum Change<'a> {
+    /// The metadata item was added, with the contained data.
+    Added(Vec<&'a Data>),
+    /// The metadata item was removed, with the previously contained data.
+    Removed(Vec<&'a Data>),
+    /// The data of the metadata item was modified.
+    Modified {
+        /// The previously contained data.
+        old: Vec<&'a Data>,
+        /// The newly contained data.
+        new: Vec<&'a Data>,
+    },
+}
+
+impl fmt::Debug for Change<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Added(new) => f.debug_tuple("Added").field(&DataSummary(new)).finish(),
+            Self::Removed(old) => f.debug_tuple("Removed").field(&DataSummary(old)).finish(),
+            Self::Modified { old, new } => f
+                .debug_struct("Modified")
+                .field("old", &DataSummary(old))
+                .field("new", &DataSummary(new))
+                .finish(),
+        }
+    }
+}
+
+/// How the data of metadata items with multiple values is compared, see [`Userdata::diff_with`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum DiffMode {
+    /// The data is compared as a set, ignoring the order of the values.
+    #[default]
+    Unordered,
+    /// The data is compared value by value, so a different order is a change.
+    Positional,
+}
+
+/// The changes of the metadata items between two tags, see [`Userdata::diff`].
+///
+/// The [`Display`](fmt::Display) implementation lists one change per line, summarizing binary
+/// data by its length instead of dumping it.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct TagDiff<'a> {
+    changes: Vec<(DataIdent, Change<'a>)>,
+}
+
+impl<'a> TagDiff<'a> {
+    /// Returns true if there are no changes.
+    pub fn is_empty(&self) -> bool {
+        self.changes.is_empty()
+    }
+
+    /// Returns the number of changed metadata items.
+    pub fn len(&self) -> usize {
+        self.changes.len()
+    }
+
+    /// Returns an iterator over all changes.
+    pub fn iter(&self) -> impl Iterator<Item = &(DataIdent, Change<'a>)> {
+        self.changes.iter()
+    }
+
+    /// Returns an iterator over the identifiers of the added metadata items.
+    pub fn added(&self) -> impl Iterator<Item = &DataIdent> {
+        self.changes.iter().filter(|(_, c)| matches!(c, Change::Added(_))).map(|(i, _)| i)
+    }
+
+    /// Returns an iterator over the identifiers of the removed metadata items.
+    pub fn removed(&self) -> impl Iterator<Item = &DataIdent> {
+        self.changes.iter().filter(|(_, c)| matches!(c, Change::Removed(_))).map(|(i, _)| i)
+    }
+
+    /// Returns an iterator over the identifiers of the modified metadata items.
+    pub fn modified(&self) -> impl Iterator<Item = &DataIdent> {
+        self.changes.iter().filter(|(_, c)| matches!(c, Change::Modified { .. })).map(|(i, _)| i)
+    }
+}
+
+impl<'a> IntoIterator for TagDiff<'a> {
+    type Item = (DataIdent, Change<'a>);
+    type IntoIter = std::vec::IntoIter<(DataIdent, Change<'a>)>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.changes.into_iter()
+    }
+}
+
+impl fmt::Display for TagDiff<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (ident, change) in self.changes.iter() {
+            match change {
+                Change::Added(new) => writeln!(f, "+ {ident}: {:?}", DataSummary(new))?,
+                Change::Removed(old) => writeln!(f, "- {ident}: {:?}", DataSummary(old))?,
+                Change::Modified { old, new } => {
+                    writeln!(f, "~ {ident}: {:?} -> {:?}", DataSummary(old), DataSummary(new))?
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Formats strings as they are, and other data by its type and length.
+struct DataSummary<'a>(&'a [&'a Data]);
+
+impl fmt::Debug for DataSummary<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut list = f.debug_list();
+        for d in self.0 {
+            match d.string() {
+                Some(s) => list.entry(&s),
+                None => list.entry(&format_args!("<{} bytes>", d.data_len())),
+            };
+        }
+        list.finish()
+    }
+}
+
+/// ### Diff
+impl Userdata {
+    /// Computes the changes of the metadata items from this tag to the other one, ignoring the
+    /// order of items and of the values of multi-value items, see [`DiffMode::Unordered`].
+    ///
+    /// The data of all metadata items with the same identifier, including freeform identifiers,
+    /// is compared as a whole, artwork included by its bytes. The changes are ordered by the
+    /// first occurrence of their identifier in this tag, followed by the added ones in the
+    /// order of the other tag.
+    ///
+    /// # Example
+    /// ```
+    /// use mp4ameta::{Change, Data, Userdata, ident};
+    ///
+    /// let mut old = Userdata::default();
+    /// old.set_title("title");
+    /// old.set_album("album");
+    /// let mut new = old.clone();
+    /// new.set_title("new title");
+    /// new.remove_album();
+    ///
+    /// let diff = old.diff(&new);
+    /// assert_eq!(diff.len(), 2);
+    /// assert_eq!(diff.modified().collect::<Vec<_>>(), [&ident::TITLE.into()]);
+    /// assert_eq!(diff.removed().collect::<Vec<_>>(), [&ident::ALBUM.into()]);
+    /// let expected = "~ ©nam: [\"title\"] -> [\"new title\"]\n- ©alb: [\"album\"]\n";
+    /// assert_eq!(diff.to_string(), expected);
+    ///
+    /// assert!(old.diff(&old.clone()).is_empty());
+    /// ```
+    pub fn diff<'a>(&'a self, other: &'a Userdata) -> TagDiff<'a> {
+        self.diff_with(other, DiffMode::Unordered)
+    }
+
+    /// Computes the changes of the metadata items from this tag to the other one, comparing the
+    /// values of multi-value items as specified by the mode. The order of the items themselves is
+    /// always ignored. See [`Userdata::diff`].
+    pub fn diff_with<'a>(&'a self, other: &'a Userdata, mode: DiffMode) -> TagDiff<'a> {
+        let old = self.grouped_data();
+        let new = other.grouped_data();
+
+        let mut changes = Vec::new();
+        for (ident, old_data) in old.iter() {
+            let change = match new.iter().find(|(i, _)| i == ident) {
+                Some((_, new_data)) if data_eq(old_data, new_data, mode) => continue,
+                Some((_, new_data)) => {
+                    Change::Modified { old: old_data.clone(), new: new_data.clone() }
+                }
+                None => Change::Removed(old_data.clone()),
+            };
+            changes.push(((*ident).clone(), change));
+        }
+        for (ident, new_data) in new.iter() {
+            if !old.iter().any(|(i, _)| i == ident) {
+                let change = Change::Added(new_data.clone());
+                changes.push(((*ident).clone(), change));
+            }
+        }
+
+        TagDiff { changes }
+    }
+
+    /// Returns the data of all metadata items grouped by their identifier, in the order of the
+    /// first occurrence of each identifier.
+    pub(super) fn grouped_data(&self) -> Vec<(&DataIdent, Vec<&Data>)> {
+        let mut grouped: Vec<(&DataIdent, Vec<&Data>)> = Vec::new();
+        for (ident, data) in self.data() {
+            match grouped.iter_mut().find(|(i, _)| *i == ident) {
+                Some((_, d)) => d.push(data),
+                None => grouped.push((ident, vec![data])),
+            }
+        }
+        grouped
+    }
+}
+
+/// Returns whether the data is equal, ignoring the order of the values unless the mode is
+/// [`DiffMode::Positional`].
+fn data_eq(a: &[&Data], b: &[&Data], mode: DiffMode) -> bool {
+    if a.len() != b.len() {
+        return false;
+    }
+    match mode {
+        DiffMode::Positional => a == b,
+        DiffMode::Unordered => {
+            let mut matched = vec![false; b.len()];
+            a.iter().all(|d| {
+                let pos = (0..b.len()).find(|&i| !matched[i] && b[i] == *d);
+                pos.map(|i| matched[i] = true).is_some()
+            })
+        }
+    }
+}
@@ -100,6 +100,38 @@ impl Userdata {
     }
 }
 
+/// ### Keyword
+impl Userdata {
+    /// Returns the keyword (`keyw`).
+    pub fn keyword(&self) -> Option<&str> {
+        self.strings_of(&ident::KEYWORD).next()
+    }
+
+    /// Removes and returns the keyword (`keyw`).
+    pub fn take_keyword(&mut self) -> Option<String> {
+        self.take_strings_of(&ident::KEYWORD).next()
+    }
+
+    /// Sets the keyword (`keyw`).
+    pub fn set_keyword(&mut self, keyword: impl Into<String>) {
+        self.set_data(ident::KEYWORD, Data::Utf8(keyword.into()));
+    }
+
+    /// Removes the keyword (`keyw`).
+    pub fn remove_keyword(&mut self) {
+        self.remove_data_of(&ident::KEYWORD);
+    }
+
+    /// Returns the keyword formatted in an easily readable way.
+    #[allow(unused)]
+    pub(crate) fn format_keyword(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.keyword() {
+            Some(s) => writeln!(f, "keyword: {}", s),
+            None => Ok(()),
+        }
+    }
+}
+
 /// ### Lyrics
 impl Userdata {
     /// Returns the lyrics (`©lyr`).
@@ -356,38 +388,6 @@ impl Userdata {
     }
 }
 
-/// ### Year
-impl Userdata {
-    /// Returns the year (`©day`).
-    pub fn year(&self) -> Option<&str> {
-        self.strings_of(&ident::YEAR).next()
-    }
-
-    /// Removes and returns the year (`©day`).
-    pub fn take_year(&mut self) -> Option<String> {
-        self.take_strings_of(&ident::YEAR).next()
-    }
-
-    /// Sets the year (`©day`).
-    pub fn set_year(&mut self, year: impl Into<String>) {
-        self.set_data(ident::YEAR, Data::Utf8(year.into()));
-    }
-
-    /// Removes the year (`©day`).
-    pub fn remove_year(&mut self) {
-        self.remove_data_of(&ident::YEAR);
-    }
-
-    /// Returns the year formatted in an easily readable way.
-    #[allow(unused)]
-    pub(crate) fn format_year(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        match self.year() {
-            Some(s) => writeln!(f, "year: {}", s),
-            None => Ok(()),
-        }
-    }
-}
-
 /// ### Isrc
 impl Userdata {
     /// Returns the isrc (`----:com.apple.iTunes:ISRC`).
@@ -452,6 +452,102 @@ impl Userdata {
     }
 }
 
+/// ### Musicbrainz track id
+impl Userdata {
+    /// Returns the musicbrainz track id (`----:com.apple.iTunes:MusicBrainz Track Id`).
+    pub fn musicbrainz_track_id(&self) -> Option<&str> {
+        self.strings_of(&ident::MUSICBRAINZ_TRACK_ID).next()
+    }
+
+    /// Removes and returns the musicbrainz track id (`----:com.apple.iTunes:MusicBrainz Track Id`).
+    pub fn take_musicbrainz_track_id(&mut self) -> Option<String> {
+        self.take_strings_of(&ident::MUSICBRAINZ_TRACK_ID).next()
+    }
+
+    /// Sets the musicbrainz track id (`----:com.apple.iTunes:MusicBrainz Track Id`).
+    pub fn set_musicbrainz_track_id(&mut self, musicbrainz_track_id: impl Into<String>) {
+        self.set_data(ident::MUSICBRAINZ_TRACK_ID, Data::Utf8(musicbrainz_track_id.into()));
+    }
+
+    /// Removes the musicbrainz track id (`----:com.apple.iTunes:MusicBrainz Track Id`).
+    pub fn remove_musicbrainz_track_id(&mut self) {
+        self.remove_data_of(&ident::MUSICBRAINZ_TRACK_ID);
+    }
+
+    /// Returns the musicbrainz track id formatted in an easily readable way.
+    #[allow(unused)]
+    pub(crate) fn format_musicbrainz_track_id(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.musicbrainz_track_id() {
+            Some(s) => writeln!(f, "musicbrainz track id: {}", s),
+            None => Ok(()),
+        }
+    }
+}
+
+/// ### Musicbrainz album id
+impl Userdata {
+    /// Returns the musicbrainz album id (`----:com.apple.iTunes:MusicBrainz Album Id`).
+    pub fn musicbrainz_album_id(&self) -> Option<&str> {
+        self.strings_of(&ident::MUSICBRAINZ_ALBUM_ID).next()
+    }
+
+    /// Removes and returns the musicbrainz album id (`----:com.apple.iTunes:MusicBrainz Album Id`).
+    pub fn take_musicbrainz_album_id(&mut self) -> Option<String> {
+        self.take_strings_of(&ident::MUSICBRAINZ_ALBUM_ID).next()
+    }
+
+    /// Sets the musicbrainz album id (`----:com.apple.iTunes:MusicBrainz Album Id`).
+    pub fn set_musicbrainz_album_id(&mut self, musicbrainz_album_id: impl Into<String>) {
+        self.set_data(ident::MUSICBRAINZ_ALBUM_ID, Data::Utf8(musicbrainz_album_id.into()));
+    }
+
+    /// Removes the musicbrainz album id (`----:com.apple.iTunes:MusicBrainz Album Id`).
+    pub fn remove_musicbrainz_album_id(&mut self) {
+        self.remove_data_of(&ident::MUSICBRAINZ_ALBUM_ID);
+    }
+
+    /// Returns the musicbrainz album id formatted in an easily readable way.
+    #[allow(unused)]
+    pub(crate) fn format_musicbrainz_album_id(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.musicbrainz_album_id() {
+            Some(s) => writeln!(f, "musicbrainz album id: {}", s),
+            None => Ok(()),
+        }
+    }
+}
+
+/// ### Musicbrainz release group id
+impl Userdata {
+    /// Returns the musicbrainz release group id (`----:com.apple.iTunes:MusicBrainz Release Group Id`).
+    pub fn musicbrainz_release_group_id(&self) -> Option<&str> {
+        self.strings_of(&ident::MUSICBRAINZ_RELEASE_GROUP_ID).next()
+    }
+
+    /// Removes and returns the musicbrainz release group id (`----:com.apple.iTunes:MusicBrainz Release Group Id`).
+    pub fn take_musicbrainz_release_group_id(&mut self) -> Option<String> {
+        self.take_strings_of(&ident::MUSICBRAINZ_RELEASE_GROUP_ID).next()
+    }
+
+    /// Sets the musicbrainz release group id (`----:com.apple.iTunes:MusicBrainz Release Group Id`).
+    pub fn set_musicbrainz_release_group_id(&mut self, musicbrainz_release_group_id: impl Into<String>) {
+        self.set_data(ident::MUSICBRAINZ_RELEASE_GROUP_ID, Data::Utf8(musicbrainz_release_group_id.into()));
+    }
+
+    /// Removes the musicbrainz release group id (`----:com.apple.iTunes:MusicBrainz Release Group Id`).
+    pub fn remove_musicbrainz_release_group_id(&mut self) {
+        self.remove_data_of(&ident::MUSICBRAINZ_RELEASE_GROUP_ID);
+    }
+
+    /// Returns the musicbrainz release group id formatted in an easily readable way.
+    #[allow(unused)]
+    pub(crate) fn format_musicbrainz_release_group_id(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.musicbrainz_release_group_id() {
+            Some(s) => writeln!(f, "musicbrainz release group id: {}", s),
+            None => Ok(()),
+        }
+    }
+}
+
 /// ### Album sort order
 impl Userdata {
     /// Returns the album sort order (`soal`).
@@ -676,6 +772,70 @@ impl Userdata {
     }
 }
 
+/// ### Author
+impl Userdata {
+    /// Returns all authors (`©aut`).
+    pub fn authors(&self) -> impl Iterator<Item=&str> {
+        self.strings_of(&ident::AUTHOR)
+    }
+
+    /// Returns the first author (`©aut`).
+    pub fn author(&self) -> Option<&str> {
+        self.strings_of(&ident::AUTHOR).next()
+    }
+
+    /// Removes and returns all authors (`©aut`).
+    pub fn take_authors(&mut self) -> impl Iterator<Item=String> + '_ {
+        self.take_strings_of(&ident::AUTHOR)
+    }
+
+    /// Removes all and returns the first author (`©aut`).
+    pub fn take_author(&mut self) -> Option<String> {
+        self.take_strings_of(&ident::AUTHOR).next()
+    }
+
+    /// Sets all authors (`©aut`). This will remove all other authors.
+    pub fn set_authors(&mut self, authors: impl IntoIterator<Item = String>) {
+        let data = authors.into_iter().map(Data::Utf8);
+        self.set_all_data(ident::AUTHOR, data);
+    }
+
+    /// Sets the author (`©aut`). This will remove all other authors.
+    pub fn set_author(&mut self, author: impl Into<String>) {
+        self.set_data(ident::AUTHOR, Data::Utf8(author.into()));
+    }
+
+    /// Adds all authors (`©aut`).
+    pub fn add_authors(&mut self, authors: impl IntoIterator<Item = String>) {
+        let data = authors.into_iter().map(Data::Utf8);
+        self.add_all_data(ident::AUTHOR, data);
+    }
+
+    /// Adds an author (`©aut`).
+    pub fn add_author(&mut self, author: impl Into<String>) {
+        self.add_data(ident::AUTHOR, Data::Utf8(author.into()));
+    }
+
+    /// Removes all authors (`©aut`).
+    pub fn remove_authors(&mut self) {
+        self.remove_data_of(&ident::AUTHOR);
+    }
+
+    /// Returns all authors formatted in an easily readable way.
+    #[allow(unused)]
+    pub(crate) fn format_authors(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.authors().count() > 1 {
+            writeln!(f, "authors:")?;
+            for s in self.authors() {
+                writeln!(f, "    {}", s)?;
+            }
+        } else if let Some(s) = self.author() {
+            writeln!(f, "author: {}", s)?;
+        }
+        Ok(())
+    }
+}
+
 /// ### Category
 impl Userdata {
     /// Returns all categories (`catg`).
@@ -996,6 +1156,70 @@ impl Userdata {
     }
 }
 
+/// ### Director
+impl Userdata {
+    /// Returns all directors (`©dir`).
+    pub fn directors(&self) -> impl Iterator<Item=&str> {
+        self.strings_of(&ident::DIRECTOR)
+    }
+
+    /// Returns the first director (`©dir`).
+    pub fn director(&self) -> Option<&str> {
+        self.strings_of(&ident::DIRECTOR).next()
+    }
+
+    /// Removes and returns all directors (`©dir`).
+    pub fn take_directors(&mut self) -> impl Iterator<Item=String> + '_ {
+        self.take_strings_of(&ident::DIRECTOR)
+    }
+
+    /// Removes all and returns the first director (`©dir`).
+    pub fn take_director(&mut self) -> Option<String> {
+        self.take_strings_of(&ident::DIRECTOR).next()
+    }
+
+    /// Sets all directors (`©dir`). This will remove all other directors.
+    pub fn set_directors(&mut self, directors: impl IntoIterator<Item = String>) {
+        let data = directors.into_iter().map(Data::Utf8);
+        self.set_all_data(ident::DIRECTOR, data);
+    }
+
+    /// Sets the director (`©dir`). This will remove all other directors.
+    pub fn set_director(&mut self, director: impl Into<String>) {
+        self.set_data(ident::DIRECTOR, Data::Utf8(director.into()));
+    }
+
+    /// Adds all directors (`©dir`).
+    pub fn add_directors(&mut self, directors: impl IntoIterator<Item = String>) {
+        let data = directors.into_iter().map(Data::Utf8);
+        self.add_all_data(ident::DIRECTOR, data);
+    }
+
+    /// Adds an director (`©dir`).
+    pub fn add_director(&mut self, director: impl Into<String>) {
+        self.add_data(ident::DIRECTOR, Data::Utf8(director.into()));
+    }
+
+    /// Removes all directors (`©dir`).
+    pub fn remove_directors(&mut self) {
+        self.remove_data_of(&ident::DIRECTOR);
+    }
+
+    /// Returns all directors formatted in an easily readable way.
+    #[allow(unused)]
+    pub(crate) fn format_directors(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.directors().count() > 1 {
+            writeln!(f, "directors:")?;
+            for s in self.directors() {
+                writeln!(f, "    {}", s)?;
+            }
+        } else if let Some(s) = self.director() {
+            writeln!(f, "director: {}", s)?;
+        }
+        Ok(())
+    }
+}
+
 /// ### Grouping
 impl Userdata {
     /// Returns all groupings (`©grp`).
@@ -1060,70 +1284,6 @@ impl Userdata {
     }
 }
 
-/// ### Keyword
-impl Userdata {
-    /// Returns all keywords (`keyw`).
-    pub fn keywords(&self) -> impl Iterator<Item=&str> {
-        self.strings_of(&ident::KEYWORD)
-    }
-
-    /// Returns the first keyword (`keyw`).
-    pub fn keyword(&self) -> Option<&str> {
-        self.strings_of(&ident::KEYWORD).next()
-    }
-
-    /// Removes and returns all keywords (`keyw`).
-    pub fn take_keywords(&mut self) -> impl Iterator<Item=String> + '_ {
-        self.take_strings_of(&ident::KEYWORD)
-    }
-
-    /// Removes all and returns the first keyword (`keyw`).
-    pub fn take_keyword(&mut self) -> Option<String> {
-        self.take_strings_of(&ident::KEYWORD).next()
-    }
-
-    /// Sets all keywords (`keyw`). This will remove all other keywords.
-    pub fn set_keywords(&mut self, keywords: impl IntoIterator<Item = String>) {
-        let data = keywords.into_iter().map(Data::Utf8);
-        self.set_all_data(ident::KEYWORD, data);
-    }
-
-    /// Sets the keyword (`keyw`). This will remove all other keywords.
-    pub fn set_keyword(&mut self, keyword: impl Into<String>) {
-        self.set_data(ident::KEYWORD, Data::Utf8(keyword.into()));
-    }
-
-    /// Adds all keywords (`keyw`).
-    pub fn add_keywords(&mut self, keywords: impl IntoIterator<Item = String>) {
-        let data = keywords.into_iter().map(Data::Utf8);
-        self.add_all_data(ident::KEYWORD, data);
-    }
-
-    /// Adds an keyword (`keyw`).
-    pub fn add_keyword(&mut self, keyword: impl Into<String>) {
-        self.add_data(ident::KEYWORD, Data::Utf8(keyword.into()));
-    }
-
-    /// Removes all keywords (`keyw`).
-    pub fn remove_keywords(&mut self) {
-        self.remove_data_of(&ident::KEYWORD);
-    }
-
-    /// Returns all keywords formatted in an easily readable way.
-    #[allow(unused)]
-    pub(crate) fn format_keywords(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        if self.keywords().count() > 1 {
-            writeln!(f, "keywords:")?;
-            for s in self.keywords() {
-                writeln!(f, "    {}", s)?;
-            }
-        } else if let Some(s) = self.keyword() {
-            writeln!(f, "keyword: {}", s)?;
-        }
-        Ok(())
-    }
-}
-
 /// ### Lyricist
 impl Userdata {
     /// Returns all lyricists (`----:com.apple.iTunes:LYRICIST`).
@@ -1188,6 +1348,134 @@ impl Userdata {
     }
 }
 
+/// ### Producer
+impl Userdata {
+    /// Returns all producers (`©prd`).
+    pub fn producers(&self) -> impl Iterator<Item=&str> {
+        self.strings_of(&ident::PRODUCER)
+    }
+
+    /// Returns the first producer (`©prd`).
+    pub fn producer(&self) -> Option<&str> {
+        self.strings_of(&ident::PRODUCER).next()
+    }
+
+    /// Removes and returns all producers (`©prd`).
+    pub fn take_producers(&mut self) -> impl Iterator<Item=String> + '_ {
+        self.take_strings_of(&ident::PRODUCER)
+    }
+
+    /// Removes all and returns the first producer (`©prd`).
+    pub fn take_producer(&mut self) -> Option<String> {
+        self.take_strings_of(&ident::PRODUCER).next()
+    }
+
+    /// Sets all producers (`©prd`). This will remove all other producers.
+    pub fn set_producers(&mut self, producers: impl IntoIterator<Item = String>) {
+        let data = producers.into_iter().map(Data::Utf8);
+        self.set_all_data(ident::PRODUCER, data);
+    }
+
+    /// Sets the producer (`©prd`). This will remove all other producers.
+    pub fn set_producer(&mut self, producer: impl Into<String>) {
+        self.set_data(ident::PRODUCER, Data::Utf8(producer.into()));
+    }
+
+    /// Adds all producers (`©prd`).
+    pub fn add_producers(&mut self, producers: impl IntoIterator<Item = String>) {
+        let data = producers.into_iter().map(Data::Utf8);
+        self.add_all_data(ident::PRODUCER, data);
+    }
+
+    /// Adds an producer (`©prd`).
+    pub fn add_producer(&mut self, producer: impl Into<String>) {
+        self.add_data(ident::PRODUCER, Data::Utf8(producer.into()));
+    }
+
+    /// Removes all producers (`©prd`).
+    pub fn remove_producers(&mut self) {
+        self.remove_data_of(&ident::PRODUCER);
+    }
+
+    /// Returns all producers formatted in an easily readable way.
+    #[allow(unused)]
+    pub(crate) fn format_producers(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.producers().count() > 1 {
+            writeln!(f, "producers:")?;
+            for s in self.producers() {
+                writeln!(f, "    {}", s)?;
+            }
+        } else if let Some(s) = self.producer() {
+            writeln!(f, "producer: {}", s)?;
+        }
+        Ok(())
+    }
+}
+
+/// ### Musicbrainz artist id
+impl Userdata {
+    /// Returns all musicbrainz artist ids (`----:com.apple.iTunes:MusicBrainz Artist Id`).
+    pub fn musicbrainz_artist_ids(&self) -> impl Iterator<Item=&str> {
+        self.strings_of(&ident::MUSICBRAINZ_ARTIST_ID)
+    }
+
+    /// Returns the first musicbrainz artist id (`----:com.apple.iTunes:MusicBrainz Artist Id`).
+    pub fn musicbrainz_artist_id(&self) -> Option<&str> {
+        self.strings_of(&ident::MUSICBRAINZ_ARTIST_ID).next()
+    }
+
+    /// Removes and returns all musicbrainz artist ids (`----:com.apple.iTunes:MusicBrainz Artist Id`).
+    pub fn take_musicbrainz_artist_ids(&mut self) -> impl Iterator<Item=String> + '_ {
+        self.take_strings_of(&ident::MUSICBRAINZ_ARTIST_ID)
+    }
+
+    /// Removes all and returns the first musicbrainz artist id (`----:com.apple.iTunes:MusicBrainz Artist Id`).
+    pub fn take_musicbrainz_artist_id(&mut self) -> Option<String> {
+        self.take_strings_of(&ident::MUSICBRAINZ_ARTIST_ID).next()
+    }
+
+    /// Sets all musicbrainz artist ids (`----:com.apple.iTunes:MusicBrainz Artist Id`). This will remove all other musicbrainz artist ids.
+    pub fn set_musicbrainz_artist_ids(&mut self, musicbrainz_artist_ids: impl IntoIterator<Item = String>) {
+        let data = musicbrainz_artist_ids.into_iter().map(Data::Utf8);
+        self.set_all_data(ident::MUSICBRAINZ_ARTIST_ID, data);
+    }
+
+    /// Sets the musicbrainz artist id (`----:com.apple.iTunes:MusicBrainz Artist Id`). This will remove all other musicbrainz artist ids.
+    pub fn set_musicbrainz_artist_id(&mut self, musicbrainz_artist_id: impl Into<String>) {
+        self.set_data(ident::MUSICBRAINZ_ARTIST_ID, Data::Utf8(musicbrainz_artist_id.into()));
+    }
+
+    /// Adds all musicbrainz artist ids (`----:com.apple.iTunes:MusicBrainz Artist Id`).
+    pub fn add_musicbrainz_artist_ids(&mut self, musicbrainz_artist_ids: impl IntoIterator<Item = String>) {
+        let data = musicbrainz_artist_ids.into_iter().map(Data::Utf8);
+        self.add_all_data(ident::MUSICBRAINZ_ARTIST_ID, data);
+    }
+
+    /// Adds an musicbrainz artist id (`----:com.apple.iTunes:MusicBrainz Artist Id`).
+    pub fn add_musicbrainz_artist_id(&mut self, musicbrainz_artist_id: impl Into<String>) {
+        self.add_data(ident::MUSICBRAINZ_ARTIST_ID, Data::Utf8(musicbrainz_artist_id.into()));
+    }
+
+    /// Removes all musicbrainz artist ids (`----:com.apple.iTunes:MusicBrainz Artist Id`).
+    pub fn remove_musicbrainz_artist_ids(&mut self) {
+        self.remove_data_of(&ident::MUSICBRAINZ_ARTIST_ID);
+    }
+
+    /// Returns all musicbrainz artist ids formatted in an easily readable way.
+    #[allow(unused)]
+    pub(crate) fn format_musicbrainz_artist_ids(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.musicbrainz_artist_ids().count() > 1 {
+            writeln!(f, "musicbrainz artist ids:")?;
+            for s in self.musicbrainz_artist_ids() {
+                writeln!(f, "    {}", s)?;
+            }
+        } else if let Some(s) = self.musicbrainz_artist_id() {
+            writeln!(f, "musicbrainz artist id: {}", s)?;
+        }
+        Ok(())
+    }
+}
+
 /// ### Album artist sort order
 impl Userdata {
     /// Returns all album artist sort orders (`soaa`).
@@ -1442,6 +1730,37 @@ impl Userdata {
     }
 }
 
+/// ### Podcast
+impl Userdata {
+    /// Returns the podcast flag (`pcst`).
+    pub fn podcast(&self) -> bool {
+        let vec = match self.bytes_of(&ident::PODCAST).next() {
+            Some(v) => v,
+            None => return false,
+        };
+        vec.first().map(|&v| v == 1).unwrap_or(false)
+    }
+
+    /// Sets the podcast flag to true (`pcst`).
+    pub fn set_podcast(&mut self) {
+        self.set_data(ident::PODCAST, Data::BeSigned(vec![1]));
+    }
+
+    /// Removes the podcast flag (`pcst`).
+    pub fn remove_podcast(&mut self) {
+        self.remove_data_of(&ident::PODCAST)
+    }
+
+    /// Returns the podcast formatted in an easily readable way.
+    #[allow(unused)]
+    pub(crate) fn format_podcast(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.podcast() {
+            true => writeln!(f, "podcast"),
+            false => Ok(()),
+        }
+    }
+}
+
 /// ### Show movement
 impl Userdata {
     /// Returns the show movement flag (`shwm`).
@@ -1473,35 +1792,6 @@ impl Userdata {
     }
 }
 
-/// ### Bpm
-impl Userdata {
-    /// Returns the bpm (`tmpo`)
-    pub fn bpm(&self) -> Option<u16> {
-        let vec = self.bytes_of(&ident::BPM).next()?;
-        be_int!(vec, 0, u16)
-    }
-
-    /// Sets the bpm (`tmpo`)
-    pub fn set_bpm(&mut self, bpm: u16) {
-        let vec: Vec<u8> = bpm.to_be_bytes().to_vec();
-        self.set_data(ident::BPM, Data::BeSigned(vec));
-    }
-
-    /// Removes the bpm (`tmpo`).
-    pub fn remove_bpm(&mut self) {
-        self.remove_data_of(&ident::BPM);
-    }
-
-    /// Returns the bpm formatted in an easily readable way.
-    #[allow(unused)]
-    pub(crate) fn format_bpm(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        match self.bpm() {
-            Some(s) => writeln!(f, "bpm: {}", s),
-            None => Ok(()),
-        }
-    }
-}
-
 /// ### Movement count
 impl Userdata {
     /// Returns the movement count (`©mvc`)
@@ -1512,8 +1802,7 @@ impl Userdata {
 
     /// Sets the movement count (`©mvc`)
     pub fn set_movement_count(&mut self, movement_count: u16) {
-        let vec: Vec<u8> = movement_count.to_be_bytes().to_vec();
-        self.set_data(ident::MOVEMENT_COUNT, Data::BeSigned(vec));
+        self.set_data(ident::MOVEMENT_COUNT, Data::u16_be(movement_count));
     }
 
     /// Removes the movement count (`©mvc`).
@@ -1541,8 +1830,7 @@ impl Userdata {
 
     /// Sets the movement index (`©mvi`)
     pub fn set_movement_index(&mut self, movement_index: u16) {
-        let vec: Vec<u8> = movement_index.to_be_bytes().to_vec();
-        self.set_data(ident::MOVEMENT_INDEX, Data::BeSigned(vec));
+        self.set_data(ident::MOVEMENT_INDEX, Data::u16_be(movement_index));
     }
 
     /// Removes the movement index (`©mvi`).
@@ -1570,8 +1858,7 @@ impl Userdata {
 
     /// Sets the tv episode (`tves`)
     pub fn set_tv_episode(&mut self, tv_episode: u32) {
-        let vec: Vec<u8> = tv_episode.to_be_bytes().to_vec();
-        self.set_data(ident::TV_EPISODE, Data::BeSigned(vec));
+        self.set_data(ident::TV_EPISODE, Data::u32_be(tv_episode));
     }
 
     /// Removes the tv episode (`tves`).
@@ -1599,8 +1886,7 @@ impl Userdata {
 
     /// Sets the tv season (`tvsn`)
     pub fn set_tv_season(&mut self, tv_season: u32) {
-        let vec: Vec<u8> = tv_season.to_be_bytes().to_vec();
-        self.set_data(ident::TV_SEASON, Data::BeSigned(vec));
+        self.set_data(ident::TV_SEASON, Data::u32_be(tv_season));
     }
 
     /// Removes the tv season (`tvsn`).
@@ -0,0 +1,141 @@
+use super::is_single_value;
+use crate::{Data, DataIdent, IdentMap, Userdata};
+
+/// How the metadata items of another tag are merged into a tag, see [`Userdata::merge`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum MergePolicy {
+    /// Conflicts are resolved in favor of the existing values. Metadata items that only hold a
+    /// single value, such as the title, track number or artwork, are kept, while the values of
+    /// other metadata items, such as the artists, are added after the existing ones.
+    #[default]
+    KeepSelf,
+    /// Conflicts are resolved in favor of the other tag. Its metadata items replace the existing
+    /// ones, including all values of metadata items holding multiple values.
+    PreferOther,
+    /// Only metadata items that don't exist are taken from the other tag. Unlike with
+    /// [`MergePolicy::KeepSelf`], no values are added to existing metadata items.
+    OtherIfMissing,
+}
+
+/// Configure how the metadata items of another tag are merged into a tag, see
+/// [`Userdata::merge_with`].
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct MergeOptions {
+    /// The policy used for all metadata items without an override.
+    pub policy: MergePolicy,
+    /// The policies used for specific metadata items instead.
+    pub overrides: IdentMap<MergePolicy>,
+    /// Whether the existing artwork is only replaced if the artwork of the other tag has larger
+    /// dimensions, unless there is an override for the artwork. Images that can't be decoded are
+    /// treated as empty, but are still added if there is no existing artwork.
+    ///
+    /// This requires the `image` feature, without it the artwork is merged like any other item.
+    pub prefer_larger_artwork: bool,
+}
+
+impl MergeOptions {
+    /// Creates options using the policy for all metadata items.
+    pub fn new(policy: MergePolicy) -> Self {
+        Self { policy, ..Default::default() }
+    }
+}
+
+/// ### Merge
+impl Userdata {
+    /// Merges the metadata items of the other tag into this one using the policy, see
+    /// [`Userdata::merge_with`].
+    ///
+    /// # Example
+    /// ```
+    /// use mp4ameta::{MergePolicy, Userdata, ident};
+    ///
+    /// let mut tag = Userdata::default();
+    /// tag.set_title("title");
+    /// tag.set_artist("a");
+    ///
+    /// let mut other = Userdata::default();
+    /// other.set_title("other title");
+    /// other.set_artists(["a".to_owned(), "b".to_owned()]);
+    /// other.set_album("album");
+    ///
+    /// let mut merged = tag.clone();
+    /// merged.merge(&other, MergePolicy::KeepSelf);
+    /// assert_eq!(merged.strings_of(&ident::TITLE).collect::<Vec<_>>(), ["title"]);
+    /// assert_eq!(merged.artists().collect::<Vec<_>>(), ["a", "b"]);
+    /// assert_eq!(merged.album(), Some("album"));
+    ///
+    /// let mut merged = tag.clone();
+    /// merged.merge(&other, MergePolicy::PreferOther);
+    /// assert_eq!(merged.title(), Some("other title"));
+    /// assert_eq!(merged.artists().collect::<Vec<_>>(), ["a", "b"]);
+    ///
+    /// let mut merged = tag.clone();
+    /// merged.merge(&other, MergePolicy::OtherIfMissing);
+    /// assert_eq!(merged.title(), Some("title"));
+    /// assert_eq!(merged.artists().collect::<Vec<_>>(), ["a"]);
+    /// assert_eq!(merged.album(), Some("album"));
+    /// ```
+    pub fn merge(&mut self, other: &Userdata, policy: MergePolicy) {
+        self.merge_with(other, &MergeOptions::new(policy));
+    }
+
+    /// Merges the metadata items of the other tag into this one, applying the policy of the
+    /// options, or its override, to each metadata item. Identical values are never duplicated.
+    pub fn merge_with(&mut self, other: &Userdata, options: &MergeOptions) {
+        for (ident, data) in other.grouped_data() {
+            let policy = options.overrides.get(ident).copied();
+
+            #[cfg(feature = "image")]
+            if options.prefer_larger_artwork && policy.is_none() && crate::ident::ARTWORK == *ident
+            {
+                let max_area = |data: &[&Data]| data.iter().filter_map(|d| image_area(d)).max();
+                let existing = self.data_of(ident).collect::<Vec<_>>();
+                if existing.is_empty()
+                    || max_area(&data).unwrap_or(0) > max_area(&existing).unwrap_or(0)
+                {
+                    self.remove_data_of(ident);
+                    self.add_unique_data(ident, data);
+                }
+                continue;
+            }
+
+            let exists = self.data_of(ident).next().is_some();
+            match policy.unwrap_or(options.policy) {
+                MergePolicy::KeepSelf if exists && is_single_value_or_artwork(ident) => continue,
+                MergePolicy::OtherIfMissing if exists => continue,
+                MergePolicy::PreferOther => self.remove_data_of(ident),
+                MergePolicy::KeepSelf | MergePolicy::OtherIfMissing => (),
+            }
+            self.add_unique_data(ident, data);
+        }
+    }
+
+    /// Adds the data that isn't already present.
+    fn add_unique_data(&mut self, ident: &DataIdent, data: Vec<&Data>) {
+        for d in data {
+            if !self.data_of(ident).any(|a| a == d) {
+                self.add_data(ident.clone(), d.clone());
+            }
+        }
+    }
+}
+
+/// Returns whether the metadata item only holds a single value, or is the artwork, of which
+/// players only show the first one.
+fn is_single_value_or_artwork(ident: &DataIdent) -> bool {
+    is_single_value(ident) || crate::ident::ARTWORK == *ident
+}
+
+/// Returns the number of pixels of the image, if it can be decoded.
+#[cfg(feature = "image")]
+fn image_area(data: &Data) -> Option<u64> {
+    let img = data.image()?;
+    let format = match img.fmt {
+        crate::ImgFmt::Bmp => image::ImageFormat::Bmp,
+        crate::ImgFmt::Jpeg => image::ImageFormat::Jpeg,
+        crate::ImgFmt::Png => image::ImageFormat::Png,
+    };
+    let reader = image::ImageReader::with_format(std::io::Cursor::new(img.data), format);
+    let (width, height) = reader.into_dimensions().ok()?;
+    Some(width as u64 * height as u64)
+}
@@ -1,51 +1,232 @@
 use std::convert::TryFrom;
 use std::fmt;
-use std::fs::OpenOptions;
+use std::fs::{File, OpenOptions};
+use std::io::{self, BufReader, Read, Seek, Write};
+use std::ops::Range;
 use std::path::Path;
+use std::time::Duration;
 
 use crate::{
-    AdvisoryRating, Chapter, Data, DataIdent, Ident, Img, ImgBuf, ImgFmt, ImgMut, ImgRef,
-    MediaType, MetaItem, StorageFile, WriteConfig, atom, ident,
+    AdvisoryRating, ArtworkRef, Chapter, ChapterFormat, ChapterSource, Data, DataIdent, DataSource,
+    Fourcc, FreeformIdent, Ident, Img, ImgBuf, ImgFmt, ImgMut, ImgRef, MediaType, MetaItem,
+    StorageFile, Timestamp, WriteConfig, WriteOutcome, WritePlan, atom, ident,
 };
 
+pub use diff::{Change, DiffMode, TagDiff};
 pub use genre::*;
+pub use merge::{MergeOptions, MergePolicy};
+pub use normalize::NormalizeOptions;
 
 #[rustfmt::skip]
 mod generated;
+mod diff;
 mod genre;
+mod merge;
+mod normalize;
 mod tuple;
 
+/// The metadata items that only hold a single value, which accessors read and setters replace.
+const SINGLE_VALUE_IDENTS: &[Fourcc] = &[
+    ident::ADVISORY_RATING,
+    ident::ALBUM,
+    ident::ALBUM_SORT_ORDER,
+    ident::ARTIST_ID,
+    ident::BPM,
+    ident::CATALOG_ID,
+    ident::COMPILATION,
+    ident::COPYRIGHT,
+    ident::DISC_NUMBER,
+    ident::ENCODER,
+    ident::GAPLESS_PLAYBACK,
+    ident::KEYWORD,
+    ident::LYRICS,
+    ident::MEDIA_TYPE,
+    ident::MOVEMENT,
+    ident::MOVEMENT_COUNT,
+    ident::MOVEMENT_INDEX,
+    ident::PLAYLIST_ID,
+    ident::PODCAST,
+    ident::PUBLISHER,
+    ident::SHOW_MOVEMENT,
+    ident::STANDARD_GENRE,
+    ident::TITLE,
+    ident::TITLE_SORT_ORDER,
+    ident::TRACK_NUMBER,
+    ident::TV_EPISODE,
+    ident::TV_EPISODE_NAME,
+    ident::TV_NETWORK_NAME,
+    ident::TV_SEASON,
+    ident::TV_SHOW_NAME,
+    ident::TV_SHOW_NAME_SORT_ORDER,
+    ident::WORK,
+    ident::YEAR,
+];
+
+/// Returns whether the metadata item only holds a single value, see [`SINGLE_VALUE_IDENTS`].
+fn is_single_value(ident: &DataIdent) -> bool {
+    SINGLE_VALUE_IDENTS.iter().any(|f| *f == *ident)
+}
+
 /// User defined MPEG-4 audio metadata that can be modified.
 #[derive(Clone, Debug, Default, PartialEq, Eq)]
 pub struct Userdata {
     pub(crate) meta_items: Vec<MetaItem>,
     pub(crate) chapter_list: Vec<Chapter>,
     pub(crate) chapter_track: Vec<Chapter>,
+    /// The creation time that will overwrite the one of the movie header (`mvhd`).
+    pub(crate) created_at: Option<Timestamp>,
+    /// The modification time that will overwrite the one of the movie header (`mvhd`).
+    pub(crate) modified_at: Option<Timestamp>,
+    /// The language that will overwrite the one of the audio track's media header (`mdhd`).
+    pub(crate) audio_language: Option<String>,
+    /// The layout of the file this was read from by
+    /// [`Tag::read_for_update`](crate::Tag::read_for_update).
+    pub(crate) layout: atom::LayoutCache,
 }
 
 impl Userdata {
     /// Attempts to write the MPEG-4 audio tag to the writer.
-    pub fn write_with(&self, file: &mut impl StorageFile, cfg: &WriteConfig) -> crate::Result<()> {
+    ///
+    /// If the metadata fits into the existing space, the file is patched in place, otherwise
+    /// everything following the metadata is rewritten. The returned [`WriteOutcome`] reports
+    /// which of these happened.
+    pub fn write_with(
+        &self,
+        file: &mut impl StorageFile,
+        cfg: &WriteConfig,
+    ) -> crate::Result<WriteOutcome> {
         atom::write_tag(file, cfg, self)
     }
 
     /// Attempts to write the MPEG-4 audio tag to the writer. This will overwrite any metadata
     /// previously present on the file.
-    pub fn write_to(&self, file: &mut impl StorageFile) -> crate::Result<()> {
+    pub fn write_to(&self, file: &mut impl StorageFile) -> crate::Result<WriteOutcome> {
         self.write_with(file, &WriteConfig::DEFAULT)
     }
 
     /// Attempts to write the MPEG-4 audio tag to the path.
-    pub fn write_with_path(&self, path: impl AsRef<Path>, cfg: &WriteConfig) -> crate::Result<()> {
-        let mut file = OpenOptions::new().read(true).write(true).open(path)?;
-        self.write_with(&mut file, cfg)
+    ///
+    /// If the tag was read by [`Tag::read_for_update`](crate::Tag::read_for_update) from the
+    /// same path, and the file hasn't been modified since, the cached layout of the file is used
+    /// instead of parsing it again.
+    pub fn write_with_path(
+        &self,
+        path: impl AsRef<Path>,
+        cfg: &WriteConfig,
+    ) -> crate::Result<WriteOutcome> {
+        if let Some(backup) = &cfg.backup {
+            backup.backup(path.as_ref())?;
+        }
+
+        atom::write_tag_to_path(path.as_ref(), cfg, self)
     }
 
     /// Attempts to write the MPEG-4 audio tag to the path. This will overwrite any metadata
     /// previously present on the file.
-    pub fn write_to_path(&self, path: impl AsRef<Path>) -> crate::Result<()> {
+    pub fn write_to_path(&self, path: impl AsRef<Path>) -> crate::Result<WriteOutcome> {
         self.write_with_path(path, &WriteConfig::DEFAULT)
     }
+
+    /// Computes what writing the MPEG-4 audio tag to the reader would do, without modifying it.
+    pub fn plan_write_with(
+        &self,
+        reader: &mut (impl Read + Seek),
+        cfg: &WriteConfig,
+    ) -> crate::Result<WritePlan> {
+        atom::plan_write(reader, cfg, self)
+    }
+
+    /// Computes what writing the MPEG-4 audio tag to the path would do, without modifying the
+    /// file.
+    pub fn plan_write_with_path(
+        &self,
+        path: impl AsRef<Path>,
+        cfg: &WriteConfig,
+    ) -> crate::Result<WritePlan> {
+        let mut file = BufReader::new(File::open(path)?);
+        self.plan_write_with(&mut file, cfg)
+    }
+
+    /// Attempts to write a copy of the source with the MPEG-4 audio tag to the destination. The
+    /// source is never modified and is streamed, so the media data isn't buffered in memory.
+    ///
+    /// Since the destination can't be read back, [`WriteConfig::faststart`] is ignored.
+    pub fn write_copy_with(
+        &self,
+        src: &mut (impl Read + Seek),
+        dst: &mut impl Write,
+        cfg: &WriteConfig,
+    ) -> crate::Result<()> {
+        atom::write_tag_copy(src, dst, cfg, self)
+    }
+
+    /// Attempts to write a copy of the source with the MPEG-4 audio tag to the destination. This
+    /// will overwrite any metadata previously present in the copy.
+    pub fn write_copy(
+        &self,
+        src: &mut (impl Read + Seek),
+        dst: &mut impl Write,
+    ) -> crate::Result<()> {
+        self.write_copy_with(src, dst, &WriteConfig::DEFAULT)
+    }
+
+    /// Attempts to stream a copy of the source with the MPEG-4 audio tag to the destination, which
+    /// doesn't have to be seekable, for example stdout or a socket. Only the changed atoms are
+    /// held in memory, the rest of the source, including the media data, is copied in chunks.
+    /// This is the same as [`Userdata::write_copy`], taking the reader and writer by value.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use std::fs::File;
+    /// use std::io::BufReader;
+    ///
+    /// let mut tag = mp4ameta::Tag::read_from_path("audiobook.m4b").unwrap();
+    /// tag.set_title("title");
+    ///
+    /// let src = BufReader::new(File::open("audiobook.m4b").unwrap());
+    /// tag.stream_to(src, std::io::stdout().lock()).unwrap();
+    /// ```
+    pub fn stream_to(&self, mut src: impl Read + Seek, mut dst: impl Write) -> crate::Result<()> {
+        self.write_copy(&mut src, &mut dst)
+    }
+
+    /// Attempts to write a copy of the file at the source path with the MPEG-4 audio tag to the
+    /// destination path. The source file is only read, the destination file is created or
+    /// truncated.
+    pub fn write_copy_with_path(
+        &self,
+        src: impl AsRef<Path>,
+        dst: impl AsRef<Path>,
+        cfg: &WriteConfig,
+    ) -> crate::Result<()> {
+        let (src, dst) = (src.as_ref(), dst.as_ref());
+        if dst.exists() && src.canonicalize()? == dst.canonicalize()? {
+            return Err(crate::Error::new(
+                crate::ErrorKind::Io(io::ErrorKind::InvalidInput.into()),
+                "The source and destination of a copy are the same file",
+            ));
+        }
+
+        let mut src_file = File::open(src)?;
+        let mut dst_file =
+            OpenOptions::new().read(true).write(true).create(true).truncate(true).open(dst)?;
+        self.write_copy_with(&mut src_file, &mut dst_file, cfg)?;
+
+        if cfg.faststart {
+            atom::move_moov_to_front(&mut dst_file)?;
+        }
+        Ok(())
+    }
+
+    /// Attempts to write a copy of the file at the source path with the MPEG-4 audio tag to the
+    /// destination path. This will overwrite any metadata previously present in the copy.
+    pub fn write_copy_to_path(
+        &self,
+        src: impl AsRef<Path>,
+        dst: impl AsRef<Path>,
+    ) -> crate::Result<()> {
+        self.write_copy_with_path(src, dst, &WriteConfig::DEFAULT)
+    }
 }
 
 // ## Custom values
@@ -81,6 +262,58 @@ impl Userdata {
         self.set_all_data(ident::ARTWORK, images.into_iter().map(Img::into));
     }
 
+    /// The size in bytes above which [`Self::set_artwork_from_path`] streams an image file
+    /// instead of reading it into memory.
+    pub const STREAMED_ARTWORK_THRESHOLD: u64 = 1024 * 1024;
+
+    /// Reads the image file at the path and sets it as the artwork (`covr`). This will remove all
+    /// other artworks. The format is detected from the magic bytes of the data, and must match the
+    /// file extension, if it is a known image extension.
+    ///
+    /// Files larger than [`Self::STREAMED_ARTWORK_THRESHOLD`] aren't read into memory, but
+    /// streamed from the file when writing, like using
+    /// [`Userdata::set_artwork_streamed_from_path`].
+    ///
+    /// # Errors
+    /// Returns an [`ErrorKind::UnsupportedImageFormat`] error if the format can't be detected or
+    /// doesn't match the extension.
+    ///
+    /// [`ErrorKind::UnsupportedImageFormat`]: crate::ErrorKind::UnsupportedImageFormat
+    pub fn set_artwork_from_path(&mut self, path: impl AsRef<Path>) -> crate::Result<()> {
+        let path = path.as_ref();
+        let (mut file, fmt, mut data) = open_image(path)?;
+        if file.metadata()?.len() > Self::STREAMED_ARTWORK_THRESHOLD {
+            let source = DataSource::from_path(path)?;
+            self.set_data(ident::ARTWORK, Data::streamed_image(fmt, source));
+            return Ok(());
+        }
+        file.read_to_end(&mut data)?;
+        self.set_artwork(Img::new(fmt, data));
+        Ok(())
+    }
+
+    /// Sets the image file at the path as the artwork (`covr`), without reading it into memory.
+    /// Its data is streamed from the file when writing, see [`Data::Streamed`]. This will remove
+    /// all other artworks. The format is detected the same way as by
+    /// [`Userdata::set_artwork_from_path`].
+    ///
+    /// Until the tag is written, [`Userdata::artwork`] doesn't return the artwork. The file is
+    /// read as a whole before the tag is written, and has to remain unchanged until the write
+    /// completes.
+    ///
+    /// # Errors
+    /// Returns an [`ErrorKind::UnsupportedImageFormat`] error if the format can't be detected or
+    /// doesn't match the extension.
+    ///
+    /// [`ErrorKind::UnsupportedImageFormat`]: crate::ErrorKind::UnsupportedImageFormat
+    pub fn set_artwork_streamed_from_path(&mut self, path: impl AsRef<Path>) -> crate::Result<()> {
+        let path = path.as_ref();
+        let (_, fmt, _) = open_image(path)?;
+        let source = DataSource::from_path(path)?;
+        self.set_data(ident::ARTWORK, Data::streamed_image(fmt, source));
+        Ok(())
+    }
+
     /// Adds artwork image data (`covr`).
     pub fn add_artwork(&mut self, image: Img<impl Into<Vec<u8>>>) {
         self.add_data(ident::ARTWORK, image.into());
@@ -91,11 +324,50 @@ impl Userdata {
         self.add_all_data(ident::ARTWORK, images.into_iter().map(Img::into));
     }
 
+    /// Returns handles to all artwork images (`covr`) whose data was skipped while reading, see
+    /// [`ReadConfig::skip_artwork`].
+    ///
+    /// [`ReadConfig::skip_artwork`]: crate::ReadConfig::skip_artwork
+    pub fn artworks_lazy(&self) -> impl Iterator<Item = ArtworkRef> + '_ {
+        self.data_of(&ident::ARTWORK).filter_map(|d| match d {
+            Data::Skipped(d) => ArtworkRef::new(d),
+            _ => None,
+        })
+    }
+
+    /// Returns a handle to the first artwork image (`covr`) whose data was skipped while reading,
+    /// see [`ReadConfig::skip_artwork`].
+    ///
+    /// [`ReadConfig::skip_artwork`]: crate::ReadConfig::skip_artwork
+    pub fn artwork_lazy(&self) -> Option<ArtworkRef> {
+        self.artworks_lazy().next()
+    }
+
+    /// Returns the number of artwork images (`covr`), including ones whose data was skipped while
+    /// reading.
+    pub fn artwork_count(&self) -> usize {
+        self.data_of(&ident::ARTWORK).filter(|d| is_artwork(d)).count()
+    }
+
+    /// Returns the summed length of all artwork image data (`covr`) in bytes, including ones whose
+    /// data was skipped while reading.
+    pub fn artwork_len(&self) -> u64 {
+        self.data_of(&ident::ARTWORK).filter(|d| is_artwork(d)).map(Data::data_len).sum()
+    }
+
     /// Removes all artworks (`covr`).
     pub fn remove_artworks(&mut self) {
         self.remove_data_of(&ident::ARTWORK);
     }
 
+    /// Removes all artworks (`covr`), including the entire metadata item, and returns the number
+    /// of removed artworks.
+    pub fn remove_all_artwork(&mut self) -> usize {
+        let count = self.artwork_count();
+        self.remove_data_of(&ident::ARTWORK);
+        count
+    }
+
     /// Returns information about all artworks formatted in an easily readable way.
     pub(crate) fn format_artworks(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         fn format_artwork(f: &mut fmt::Formatter<'_>, i: ImgRef<'_>) -> fmt::Result {
@@ -146,13 +418,35 @@ impl Userdata {
         MediaType::try_from(vec[0]).ok()
     }
 
-    /// Sets the media type (`stik`).
+    /// Sets the media type (`stik`). The podcast flag (`pcst`) is set along with
+    /// [`MediaType::Podcast`], and removed along with any other media type, since iTunes expects
+    /// them to match.
+    ///
+    /// # Example
+    /// ```
+    /// use mp4ameta::{MediaType, Userdata};
+    ///
+    /// let mut tag = Userdata::default();
+    /// tag.set_media_type(MediaType::Podcast);
+    /// assert!(tag.podcast());
+    ///
+    /// tag.set_media_type(MediaType::Normal);
+    /// assert!(!tag.podcast());
+    /// ```
     pub fn set_media_type(&mut self, media_type: MediaType) {
         self.set_data(ident::MEDIA_TYPE, Data::Reserved(vec![media_type.code()]));
+        match media_type {
+            MediaType::Podcast => self.set_podcast(),
+            _ => self.remove_podcast(),
+        }
     }
 
-    /// Removes the media type (`stik`).
+    /// Removes the media type (`stik`). If it was [`MediaType::Podcast`], the podcast flag
+    /// (`pcst`) is removed as well.
     pub fn remove_media_type(&mut self) {
+        if self.media_type() == Some(MediaType::Podcast) {
+            self.remove_podcast();
+        }
         self.remove_data_of(&ident::MEDIA_TYPE);
     }
 
@@ -195,6 +489,335 @@ impl Userdata {
     }
 }
 
+/// ### Bpm
+impl Userdata {
+    /// Returns the bpm (`tmpo`). A bpm of 0 is treated as nonexistent.
+    pub fn bpm(&self) -> Option<u16> {
+        let vec = self.bytes_of(&ident::BPM).next()?;
+        be_int!(vec, 0, u16).filter(|bpm| *bpm > 0)
+    }
+
+    /// Sets the bpm (`tmpo`).
+    pub fn set_bpm(&mut self, bpm: u16) {
+        self.set_data(ident::BPM, Data::u16_be(bpm));
+    }
+
+    /// Removes the bpm (`tmpo`).
+    pub fn remove_bpm(&mut self) {
+        self.remove_data_of(&ident::BPM);
+    }
+
+    /// Returns the bpm formatted in an easily readable way.
+    pub(crate) fn format_bpm(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.bpm() {
+            Some(b) => writeln!(f, "bpm: {b}"),
+            None => Ok(()),
+        }
+    }
+}
+
+/// ### Keywords
+impl Userdata {
+    /// Returns the keywords (`keyw`), split on the common delimiters `,` and `\n`. Surrounding
+    /// whitespace is trimmed and empty keywords are skipped.
+    pub fn keywords(&self) -> Vec<String> {
+        self.strings_of(&ident::KEYWORD)
+            .flat_map(|s| s.split([',', '\n']))
+            .map(str::trim)
+            .filter(|k| !k.is_empty())
+            .map(str::to_owned)
+            .collect()
+    }
+
+    /// Sets the keywords (`keyw`), joined into a single comma separated string. This will remove
+    /// all other keywords.
+    pub fn set_keywords(&mut self, keywords: &[&str]) {
+        if keywords.is_empty() {
+            self.remove_keyword();
+        } else {
+            self.set_keyword(keywords.join(", "));
+        }
+    }
+
+    /// Removes and returns all raw keyword values (`keyw`), without splitting them.
+    pub fn take_keywords(&mut self) -> impl Iterator<Item = String> + '_ {
+        self.take_strings_of(&ident::KEYWORD)
+    }
+
+    /// Adds all keyword values (`keyw`), each stored as a separate value.
+    pub fn add_keywords(&mut self, keywords: impl IntoIterator<Item = String>) {
+        let data = keywords.into_iter().map(Data::Utf8);
+        self.add_all_data(ident::KEYWORD, data);
+    }
+
+    /// Adds a keyword value (`keyw`), stored as a separate value.
+    pub fn add_keyword(&mut self, keyword: impl Into<String>) {
+        self.add_data(ident::KEYWORD, Data::Utf8(keyword.into()));
+    }
+
+    /// Removes all keywords (`keyw`).
+    pub fn remove_keywords(&mut self) {
+        self.remove_data_of(&ident::KEYWORD);
+    }
+
+    /// Returns all raw keyword values formatted in an easily readable way.
+    pub(crate) fn format_keywords(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.strings_of(&ident::KEYWORD).count() > 1 {
+            writeln!(f, "keywords:")?;
+            for s in self.strings_of(&ident::KEYWORD) {
+                writeln!(f, "    {}", s)?;
+            }
+        } else if let Some(s) = self.keyword() {
+            writeln!(f, "keyword: {}", s)?;
+        }
+        Ok(())
+    }
+}
+
+/// ### Artist list
+impl Userdata {
+    /// The separators [`Self::artist_list`] splits artists on.
+    pub const DEFAULT_ARTIST_SEPARATORS: &[&str] = &[";", "/"];
+
+    /// Returns the artists (`©ART`), split on the [`Self::DEFAULT_ARTIST_SEPARATORS`]. Surrounding
+    /// whitespace is trimmed and empty artists are skipped. The raw values are still returned by
+    /// [`Self::artists`].
+    pub fn artist_list(&self) -> Vec<String> {
+        self.artist_list_with(Self::DEFAULT_ARTIST_SEPARATORS)
+    }
+
+    /// Returns the artists (`©ART`), split on all of the separators. Surrounding whitespace is
+    /// trimmed and empty artists are skipped.
+    ///
+    /// # Example
+    /// ```
+    /// use mp4ameta::Userdata;
+    ///
+    /// let mut tag = Userdata::default();
+    /// tag.set_artist("A feat. B; C");
+    ///
+    /// assert_eq!(tag.artist_list(), ["A feat. B", "C"]);
+    /// assert_eq!(tag.artist_list_with(&[";", "feat."]), ["A", "B", "C"]);
+    /// assert_eq!(tag.artist(), Some("A feat. B; C"));
+    /// ```
+    pub fn artist_list_with(&self, separators: &[&str]) -> Vec<String> {
+        let mut artists: Vec<&str> = self.artists().collect();
+        for sep in separators {
+            artists = artists.into_iter().flat_map(|a| a.split(sep)).collect();
+        }
+        artists.into_iter().map(str::trim).filter(|a| !a.is_empty()).map(str::to_owned).collect()
+    }
+}
+
+/// ### Freeform
+impl Userdata {
+    /// Returns the first string of the freeform (`----`) item with the mean and name.
+    ///
+    /// # Example
+    /// ```
+    /// use mp4ameta::Userdata;
+    ///
+    /// let mut tag = Userdata::default();
+    /// tag.set_freeform("com.apple.iTunes", "MusicBrainz Track Id", "track id");
+    ///
+    /// assert_eq!(tag.freeform("com.apple.iTunes", "MusicBrainz Track Id"), Some("track id"));
+    /// assert_eq!(tag.freeform("com.apple.iTunes", "MusicBrainz Album Id"), None);
+    /// ```
+    pub fn freeform(&self, mean: &str, name: &str) -> Option<&str> {
+        self.strings_of(&FreeformIdent::new_borrowed(mean, name)).next()
+    }
+
+    /// Sets the freeform (`----`) item with the mean and name to the string, replacing any
+    /// existing data.
+    pub fn set_freeform(&mut self, mean: &str, name: &str, value: impl Into<String>) {
+        self.set_data(FreeformIdent::new_borrowed(mean, name), Data::Utf8(value.into()));
+    }
+
+    /// Removes the freeform (`----`) item with the mean and name.
+    pub fn remove_freeform(&mut self, mean: &str, name: &str) {
+        self.remove_data_of(&FreeformIdent::new_borrowed(mean, name));
+    }
+}
+
+/// ### Purchase date
+///
+/// The purchase date (`purd`) is stored as text, iTunes uses the form `YYYY-MM-DD HH:MM:SS`.
+impl Userdata {
+    /// Returns the raw purchase date string (`purd`).
+    pub fn raw_purchase_date(&self) -> Option<&str> {
+        self.strings_of(&ident::PURCHASE_DATE).next()
+    }
+
+    /// Sets the raw purchase date string (`purd`).
+    pub fn set_raw_purchase_date(&mut self, purchase_date: impl Into<String>) {
+        self.set_data(ident::PURCHASE_DATE, Data::Utf8(purchase_date.into()));
+    }
+
+    /// Returns the purchase date (`purd`), parsed from either the `YYYY-MM-DD HH:MM:SS` or the
+    /// `YYYY-MM-DDTHH:MM:SS` form. A trailing `Z` is ignored.
+    ///
+    /// # Example
+    /// ```
+    /// use mp4ameta::Userdata;
+    ///
+    /// let mut tag = Userdata::default();
+    /// tag.set_raw_purchase_date("2012-03-04T05:06:07Z");
+    ///
+    /// let date = tag.purchase_date().unwrap();
+    /// assert_eq!(date.to_string(), "2012-03-04 05:06:07");
+    /// ```
+    #[cfg(feature = "chrono")]
+    pub fn purchase_date(&self) -> Option<chrono::NaiveDateTime> {
+        let raw = self.raw_purchase_date()?.trim();
+        let raw = raw.strip_suffix('Z').unwrap_or(raw);
+        ["%Y-%m-%d %H:%M:%S", "%Y-%m-%dT%H:%M:%S"]
+            .iter()
+            .find_map(|fmt| chrono::NaiveDateTime::parse_from_str(raw, fmt).ok())
+    }
+
+    /// Sets the purchase date (`purd`), formatted as `YYYY-MM-DD HH:MM:SS`.
+    #[cfg(feature = "chrono")]
+    pub fn set_purchase_date(&mut self, purchase_date: chrono::NaiveDateTime) {
+        self.set_raw_purchase_date(purchase_date.format("%Y-%m-%d %H:%M:%S").to_string());
+    }
+
+    /// Removes the purchase date (`purd`).
+    pub fn remove_purchase_date(&mut self) {
+        self.remove_data_of(&ident::PURCHASE_DATE);
+    }
+}
+
+/// ### Year
+///
+/// The year (`©day`) is stored as text, either as a bare year `YYYY` or as an ISO 8601 date like
+/// `YYYY-MM-DD` or `YYYY-MM-DDTHH:MM:SSZ`.
+impl Userdata {
+    /// Returns the year (`©day`).
+    pub fn year(&self) -> Option<&str> {
+        self.strings_of(&ident::YEAR).next()
+    }
+
+    /// Removes and returns the year (`©day`).
+    pub fn take_year(&mut self) -> Option<String> {
+        self.take_strings_of(&ident::YEAR).next()
+    }
+
+    /// Sets the year (`©day`), which is stored verbatim. A bare year has to consist of exactly 4
+    /// digits, otherwise it has to start with a `YYYY-MM-DD` date, optionally followed by a time
+    /// separated by `T`.
+    ///
+    /// # Example
+    /// ```
+    /// use mp4ameta::Userdata;
+    ///
+    /// let mut tag = Userdata::default();
+    /// tag.set_year("2013").unwrap();
+    /// assert_eq!(tag.year(), Some("2013"));
+    ///
+    /// tag.set_year("2013-05-21T07:00:00Z").unwrap();
+    /// assert_eq!(tag.year(), Some("2013-05-21T07:00:00Z"));
+    ///
+    /// assert!(tag.set_year("13").is_err());
+    /// ```
+    pub fn set_year(&mut self, year: impl Into<String>) -> crate::Result<()> {
+        let year = year.into();
+        if !is_valid_year(&year) {
+            return Err(crate::Error::new(
+                crate::ErrorKind::InvalidData,
+                format!("Invalid year '{year}', expected YYYY or an ISO 8601 date YYYY-MM-DD"),
+            ));
+        }
+        self.set_data(ident::YEAR, Data::Utf8(year));
+        Ok(())
+    }
+
+    /// Sets the year (`©day`) to the release date, formatted as `YYYY-MM-DD`.
+    #[cfg(feature = "chrono")]
+    pub fn set_release_date(&mut self, release_date: chrono::NaiveDate) {
+        let date = release_date.format("%Y-%m-%d").to_string();
+        self.set_data(ident::YEAR, Data::Utf8(date));
+    }
+
+    /// Removes the year (`©day`).
+    pub fn remove_year(&mut self) {
+        self.remove_data_of(&ident::YEAR);
+    }
+
+    /// Returns the year formatted in an easily readable way.
+    pub(crate) fn format_year(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.year() {
+            Some(s) => writeln!(f, "year: {s}"),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Checks for the form `YYYY`, or `YYYY-MM-DD` optionally followed by `T` and a time.
+fn is_valid_year(year: &str) -> bool {
+    let digits = |s: &[u8]| s.iter().all(u8::is_ascii_digit);
+    let b = year.as_bytes();
+    match b.len() {
+        4 => digits(b),
+        10.. => {
+            digits(&b[0..4])
+                && b[4] == b'-'
+                && digits(&b[5..7])
+                && b[7] == b'-'
+                && digits(&b[8..10])
+                && (b.len() == 10 || b[10] == b'T')
+        }
+        _ => false,
+    }
+}
+
+/// ### Timestamps
+///
+/// The creation and modification times of the movie header (`mvhd`) are overwritten in place
+/// during the next write, leaving the rest of the header untouched. This can be used to
+/// normalize them for reproducible output.
+impl Userdata {
+    /// Sets the creation time, that will be written to the movie header (`mvhd`).
+    pub fn set_created_at(&mut self, created_at: Timestamp) {
+        self.created_at = Some(created_at);
+    }
+
+    /// Sets the modification time, that will be written to the movie header (`mvhd`).
+    pub fn set_modified_at(&mut self, modified_at: Timestamp) {
+        self.modified_at = Some(modified_at);
+    }
+}
+
+/// ### Audio language
+impl Userdata {
+    /// Sets the ISO 639-2/T language code of the audio track, for example `deu`, or `und` if it is
+    /// undetermined. The media header (`mdhd`) of the first enabled audio track, or the first
+    /// audio track if none is enabled, is updated during the next write.
+    ///
+    /// Returns an error if the language doesn't consist of three lowercase ASCII letters.
+    ///
+    /// # Example
+    /// ```
+    /// use mp4ameta::Tag;
+    ///
+    /// let mut tag = Tag::default();
+    /// tag.set_audio_language("deu").unwrap();
+    /// assert_eq!(tag.audio_language(), Some("deu"));
+    ///
+    /// assert!(tag.set_audio_language("DE").is_err());
+    /// ```
+    pub fn set_audio_language(&mut self, language: impl Into<String>) -> crate::Result<()> {
+        let language = language.into();
+        if !atom::is_valid_language(&language) {
+            return Err(crate::Error::new(
+                crate::ErrorKind::InvalidData,
+                format!("Invalid language '{language}', expected three lowercase ASCII letters"),
+            ));
+        }
+        self.audio_language = Some(language);
+        Ok(())
+    }
+}
+
 /// ### Chapters
 ///
 /// These are convenience methods that operate on values of both, the chapter list and track.
@@ -206,6 +829,34 @@ impl Userdata {
         if !self.chapter_list.is_empty() { self.chapter_list() } else { self.chapter_track() }
     }
 
+    /// Returns where the chapters returned by [`Self::chapters`] are stored, or `None` if there
+    /// are no chapters.
+    ///
+    /// # Example
+    /// ```
+    /// use std::time::Duration;
+    /// use mp4ameta::{Userdata, Chapter, ChapterSource};
+    ///
+    /// let mut tag = Userdata::default();
+    /// assert_eq!(tag.chapters_source(), None);
+    ///
+    /// tag.chapter_track_mut().push(Chapter::new(Duration::ZERO, "track"));
+    /// assert_eq!(tag.chapters_source(), Some(ChapterSource::Track));
+    ///
+    /// tag.chapter_list_mut().push(Chapter::new(Duration::ZERO, "list"));
+    /// assert_eq!(tag.chapters_source(), Some(ChapterSource::List));
+    /// assert_eq!(tag.chapters()[0].title, "list");
+    /// ```
+    pub fn chapters_source(&self) -> Option<ChapterSource> {
+        if !self.chapter_list.is_empty() {
+            Some(ChapterSource::List)
+        } else if !self.chapter_track.is_empty() {
+            Some(ChapterSource::Track)
+        } else {
+            None
+        }
+    }
+
     /// Returns either the chapter list or the chapter track.
     /// The chapter list is preferred.
     pub fn chapters_mut(&mut self) -> &mut Vec<Chapter> {
@@ -225,6 +876,161 @@ impl Userdata {
             self.take_chapter_track()
         }
     }
+
+    /// Inserts a new chapter into [`Self::chapters_mut`], keeping the chapters sorted by their
+    /// start, and returns its index. If a chapter already starts at the same time an
+    /// [`ErrorKind::InvalidChapterList`] error is returned.
+    ///
+    /// # Example
+    /// ```
+    /// use std::time::Duration;
+    /// use mp4ameta::{Userdata, Chapter};
+    ///
+    /// let mut tag = Userdata::default();
+    /// tag.insert_chapter_at(Duration::from_secs(10), "second").unwrap();
+    /// tag.insert_chapter_at(Duration::ZERO, "first").unwrap();
+    /// assert!(tag.insert_chapter_at(Duration::ZERO, "duplicate").is_err());
+    ///
+    /// let titles: Vec<_> = tag.chapters().iter().map(|c| c.title.as_str()).collect();
+    /// assert_eq!(titles, ["first", "second"]);
+    /// ```
+    ///
+    /// [`ErrorKind::InvalidChapterList`]: crate::ErrorKind::InvalidChapterList
+    pub fn insert_chapter_at(
+        &mut self,
+        start: Duration,
+        title: impl Into<String>,
+    ) -> crate::Result<usize> {
+        let chapters = self.chapters_mut();
+        match chapters.binary_search_by_key(&start, |c| c.start) {
+            Ok(i) => Err(crate::Error::new(
+                crate::ErrorKind::InvalidChapterList,
+                format!("Chapter '{}' already starts at {start:?}", chapters[i].title),
+            )),
+            Err(i) => {
+                chapters.insert(i, Chapter::new(start, title));
+                Ok(i)
+            }
+        }
+    }
+
+    /// Splits the chapter at the index of [`Self::chapters_mut`] into two, the second one starting
+    /// at the given time and sharing the title, url and image of the first one. If the time
+    /// doesn't lie strictly between the start of the chapter and the start of the following one,
+    /// an [`ErrorKind::InvalidChapterList`] error is returned.
+    ///
+    /// # Panics
+    /// Panics if the index is out of bounds.
+    ///
+    /// # Example
+    /// ```
+    /// use std::time::Duration;
+    /// use mp4ameta::{Userdata, Chapter};
+    ///
+    /// let mut tag = Userdata::default();
+    /// tag.chapters_mut().push(Chapter::new(Duration::ZERO, "intro"));
+    /// tag.split_chapter(0, Duration::from_secs(5)).unwrap();
+    ///
+    /// assert_eq!(tag.chapters()[1], Chapter::new(Duration::from_secs(5), "intro"));
+    /// assert!(tag.split_chapter(1, Duration::from_secs(5)).is_err());
+    /// ```
+    ///
+    /// [`ErrorKind::InvalidChapterList`]: crate::ErrorKind::InvalidChapterList
+    pub fn split_chapter(&mut self, index: usize, at: Duration) -> crate::Result<()> {
+        let chapters = self.chapters_mut();
+        let chapter = &chapters[index];
+        let next_start = chapters.get(index + 1).map(|c| c.start);
+        if at <= chapter.start || next_start.is_some_and(|s| at >= s) {
+            return Err(crate::Error::new(
+                crate::ErrorKind::InvalidChapterList,
+                format!("Chapter '{}' can't be split at {at:?}", chapter.title),
+            ));
+        }
+
+        let second = Chapter { start: at, ..chapter.clone() };
+        chapters.insert(index + 1, second);
+        Ok(())
+    }
+
+    /// Merges the chapters in the range of [`Self::chapters_mut`] into the first one of the range,
+    /// which keeps its start and title.
+    ///
+    /// # Panics
+    /// Panics if the range is empty or out of bounds.
+    ///
+    /// # Example
+    /// ```
+    /// use std::time::Duration;
+    /// use mp4ameta::{Userdata, Chapter};
+    ///
+    /// let mut tag = Userdata::default();
+    /// tag.chapters_mut().extend([
+    ///     Chapter::new(Duration::ZERO, "first"),
+    ///     Chapter::new(Duration::from_secs(1), "second"),
+    ///     Chapter::new(Duration::from_secs(2), "third"),
+    /// ]);
+    /// tag.merge_chapters(0..2);
+    ///
+    /// let titles: Vec<_> = tag.chapters().iter().map(|c| c.title.as_str()).collect();
+    /// assert_eq!(titles, ["first", "third"]);
+    /// ```
+    pub fn merge_chapters(&mut self, range: Range<usize>) {
+        self.chapters_mut().drain(range.start + 1..range.end);
+    }
+
+    /// Stores the chapters consistently in the requested format, sorted by their start. If both
+    /// the chapter list and track are present, the one containing more chapters is used. On a tie
+    /// the chapter track is preferred, since its titles aren't truncated to 255 bytes.
+    ///
+    /// # Example
+    /// ```
+    /// use std::time::Duration;
+    /// use mp4ameta::{Userdata, Chapter, ChapterFormat};
+    ///
+    /// let mut tag = Userdata::default();
+    /// tag.chapter_list_mut().push(Chapter::new(Duration::ZERO, "first"));
+    ///
+    /// tag.normalize_chapters(ChapterFormat::Both);
+    /// assert_eq!(tag.chapter_list(), tag.chapter_track());
+    ///
+    /// tag.normalize_chapters(ChapterFormat::Track);
+    /// assert!(tag.chapter_list().is_empty());
+    /// assert_eq!(tag.chapter_track()[0].title, "first");
+    /// ```
+    pub fn normalize_chapters(&mut self, format: ChapterFormat) {
+        let list = self.take_chapter_list();
+        let track = self.take_chapter_track();
+        let mut chapters = if track.len() >= list.len() { track } else { list };
+        chapters.sort_by_key(|c| c.start);
+
+        match format {
+            ChapterFormat::List => self.chapter_list = chapters,
+            ChapterFormat::Track => self.chapter_track = chapters,
+            ChapterFormat::Both => {
+                self.chapter_list = chapters.clone();
+                self.chapter_track = chapters;
+            }
+        }
+    }
+
+    /// Checks that the starts of the chapter list and track are strictly increasing, otherwise an
+    /// [`ErrorKind::InvalidChapterList`] error is returned.
+    ///
+    /// [`ErrorKind::InvalidChapterList`]: crate::ErrorKind::InvalidChapterList
+    pub fn validate_chapters(&self) -> crate::Result<()> {
+        for (name, chapters) in [("list", &self.chapter_list), ("track", &self.chapter_track)] {
+            if let Some(w) = chapters.windows(2).find(|w| w[0].start >= w[1].start) {
+                return Err(crate::Error::new(
+                    crate::ErrorKind::InvalidChapterList,
+                    format!(
+                        "Chapter '{}' of the chapter {name} doesn't start after '{}'",
+                        w[1].title, w[0].title
+                    ),
+                ));
+            }
+        }
+        Ok(())
+    }
 }
 
 /// ### Chapter list
@@ -239,6 +1045,14 @@ impl Userdata {
         &mut self.chapter_list
     }
 
+    /// Sets the chapter list. The chapters are sorted by their start when written, multiple
+    /// chapters starting at the same time result in an [`ErrorKind::InvalidChapterList`] error.
+    ///
+    /// [`ErrorKind::InvalidChapterList`]: crate::ErrorKind::InvalidChapterList
+    pub fn set_chapter_list(&mut self, chapters: impl Into<Vec<Chapter>>) {
+        self.chapter_list = chapters.into();
+    }
+
     /// Removes and returns the chapter list.
     pub fn take_chapter_list(&mut self) -> Vec<Chapter> {
         std::mem::take(&mut self.chapter_list)
@@ -633,6 +1447,27 @@ impl Userdata {
         self.into_data().filter_map(|(i, d)| Some((i, d.into_string()?)))
     }
 
+    /// Returns an iterator over all human-readable text fields, for example for full-text
+    /// indexing. Only decoded UTF-8 and UTF-16 strings, including freeform ones, are yielded,
+    /// integers, flags, images and other binary data are skipped, as well as empty strings.
+    ///
+    /// # Example
+    /// ```
+    /// use mp4ameta::{Userdata, DataIdent, ident};
+    ///
+    /// let mut tag = Userdata::default();
+    /// tag.set_title("title");
+    /// tag.set_track_number(3);
+    /// tag.set_bpm(120);
+    ///
+    /// let mut fields = tag.text_fields();
+    /// assert_eq!(fields.next(), Some((DataIdent::from(ident::TITLE), "title")));
+    /// assert_eq!(fields.next(), None);
+    /// ```
+    pub fn text_fields(&self) -> impl Iterator<Item = (DataIdent, &str)> {
+        self.strings().filter(|(_, s)| !s.is_empty()).map(|(i, s)| (i.clone(), s))
+    }
+
     /// Returns an iterator over references to all images.
     ///
     /// # Example
@@ -771,11 +1606,34 @@ impl Userdata {
     /// assert_eq!(image, Data::Jpeg(b"data".to_vec()));
     /// ```
     pub fn into_data(self) -> impl Iterator<Item = (DataIdent, Data)> {
-        self.meta_items.into_iter().flat_map(move |a| {
-            let ident = a.ident;
-            let data = a.data;
-            data.into_iter().map(move |d| (ident.clone(), d))
-        })
+        self.into_iter()
+    }
+
+    /// Returns an iterator over references to all metadata items, yielding each data value
+    /// separately, in item order. This is the same as [`Userdata::data`].
+    ///
+    /// # Example
+    /// ```
+    /// use mp4ameta::Userdata;
+    ///
+    /// let mut src = Userdata::default();
+    /// src.set_title("title");
+    /// src.set_artists(["a".to_owned(), "b".to_owned()]);
+    ///
+    /// let mut dst = Userdata::default();
+    /// for (ident, data) in src.items() {
+    ///     dst.add_data(ident.clone(), data.clone());
+    /// }
+    /// assert_eq!(dst, src);
+    /// ```
+    pub fn items(&self) -> impl Iterator<Item = (&DataIdent, &Data)> {
+        self.data()
+    }
+
+    /// Returns an iterator over mutable references to all metadata items, yielding each data
+    /// value separately, in item order. This is the same as [`Userdata::data_mut`].
+    pub fn items_mut(&mut self) -> impl Iterator<Item = (&DataIdent, &mut Data)> {
+        self.data_mut()
     }
 
     /// Removes all byte data corresponding to the identifier. Other data will remain unaffected.\
@@ -1106,7 +1964,8 @@ impl Userdata {
         self.retain_data(|i, d| d.image().is_none_or(|s| predicate(i, s)));
     }
 
-    /// Retains only the data matching the predicate.
+    /// Retains only the data matching the predicate. This is the same as [`Userdata::retain`],
+    /// without returning the number of removed data values.
     ///
     /// # Example
     /// ```
@@ -1132,24 +1991,89 @@ impl Userdata {
     /// assert_eq!(data.next(), None);
     /// ```
     pub fn retain_data(&mut self, predicate: impl Fn(&DataIdent, &Data) -> bool) {
-        let mut i = 0;
-        while i < self.meta_items.len() {
-            let a = &mut self.meta_items[i];
+        self.retain(predicate);
+    }
+
+    /// Retains only the data values matching the predicate, including those of unknown metadata
+    /// items, and returns the number of removed data values. Metadata items without any
+    /// remaining data are removed. See [`Userdata::remove_if`] for the inverse.
+    ///
+    /// # Example
+    /// ```
+    /// use mp4ameta::{Userdata, ident};
+    ///
+    /// let mut tag = Userdata::default();
+    /// tag.set_title("title");
+    /// tag.set_artists(["a".to_owned(), "b".to_owned()]);
+    ///
+    /// assert_eq!(tag.retain(|i, d| ident::TITLE == *i || d.string() == Some("b")), 1);
+    /// assert_eq!(tag.title(), Some("title"));
+    /// assert_eq!(tag.artists().collect::<Vec<_>>(), ["b"]);
+    /// ```
+    pub fn retain(&mut self, mut predicate: impl FnMut(&DataIdent, &Data) -> bool) -> usize {
+        self.remove_if(|i, d| !predicate(i, d))
+    }
+
+    /// Removes the data values matching the predicate, including those of unknown metadata items,
+    /// and returns the number of removed data values. Metadata items without any remaining data
+    /// are removed.
+    ///
+    /// # Example
+    /// ```
+    /// use mp4ameta::Userdata;
+    ///
+    /// let mut tag = Userdata::default();
+    /// tag.set_title("");
+    /// tag.set_album("album");
+    ///
+    /// assert_eq!(tag.remove_if(|_, d| d.is_empty()), 1);
+    /// assert_eq!(tag.title(), None);
+    /// assert_eq!(tag.album(), Some("album"));
+    /// ```
+    pub fn remove_if(&mut self, mut predicate: impl FnMut(&DataIdent, &Data) -> bool) -> usize {
+        let mut removed = 0;
+        self.meta_items.retain_mut(|a| {
+            let len = a.data.len();
+            a.data.retain(|d| !predicate(&a.ident, d));
+            removed += len - a.data.len();
+            !a.data.is_empty()
+        });
+        removed
+    }
+
+    /// Removes the data values matching the predicate, including those of unknown metadata items,
+    /// and returns them in item order. Metadata items without any remaining data are removed.
+    ///
+    /// # Example
+    /// ```
+    /// use mp4ameta::{Data, Userdata, ident};
+    ///
+    /// let mut tag = Userdata::default();
+    /// tag.set_title("title");
+    /// tag.set_artists(["a".to_owned(), "b".to_owned()]);
+    ///
+    /// let drained = tag.drain_if(|i, _| ident::ARTIST == *i);
+    /// assert_eq!(drained.len(), 2);
+    /// assert_eq!(drained[1], (ident::ARTIST.into(), Data::Utf8("b".into())));
+    /// assert_eq!(tag.artist(), None);
+    /// ```
+    pub fn drain_if(
+        &mut self,
+        mut predicate: impl FnMut(&DataIdent, &Data) -> bool,
+    ) -> Vec<(DataIdent, Data)> {
+        let mut drained = Vec::new();
+        self.meta_items.retain_mut(|a| {
             let mut j = 0;
             while j < a.data.len() {
                 if predicate(&a.ident, &a.data[j]) {
-                    j += 1;
+                    drained.push((a.ident.clone(), a.data.remove(j)));
                 } else {
-                    a.data.remove(j);
+                    j += 1;
                 }
             }
-
-            if a.data.is_empty() {
-                self.meta_items.remove(i);
-            } else {
-                i += 1;
-            }
-        }
+            !a.data.is_empty()
+        });
+        drained
     }
 
     /// Removes user data.
@@ -1197,6 +2121,126 @@ impl Userdata {
         self.meta_items.clear();
     }
 
+    /// Removes all iTunes store data, such as the catalog, artist and playlist ids, the account
+    /// information and the purchase date.
+    ///
+    /// # Example
+    /// ```
+    /// use mp4ameta::{Userdata, Data, ident};
+    ///
+    /// let mut tag = Userdata::default();
+    /// tag.set_title("title");
+    /// tag.set_data(ident::CATALOG_ID, Data::u32_be(1234));
+    /// tag.set_data(ident::OWNER, Data::Utf8("owner".into()));
+    ///
+    /// tag.clear_itunes_store_data();
+    /// assert_eq!(tag.data().count(), 1);
+    /// assert_eq!(tag.title(), Some("title"));
+    /// ```
+    pub fn clear_itunes_store_data(&mut self) {
+        const STORE_DATA: [Fourcc; 8] = [
+            ident::CATALOG_ID,
+            ident::ARTIST_ID,
+            ident::PLAYLIST_ID,
+            ident::STOREFRONT_ID,
+            ident::ACCOUNT_ID,
+            ident::OWNER,
+            ident::PURCHASE_DATE,
+            ident::XID,
+        ];
+        self.meta_items.retain(|a| !STORE_DATA.iter().any(|f| *f == a.ident));
+    }
+
+    /// Removes all sort orders, such as the album, artist and title sort order.
+    ///
+    /// # Example
+    /// ```
+    /// use mp4ameta::Userdata;
+    ///
+    /// let mut tag = Userdata::default();
+    /// tag.set_title("title");
+    /// tag.set_title_sort_order("title");
+    /// tag.set_artist_sort_order("artist");
+    ///
+    /// tag.clear_sort_tags();
+    /// assert_eq!(tag.data().count(), 1);
+    /// assert_eq!(tag.title(), Some("title"));
+    /// ```
+    pub fn clear_sort_tags(&mut self) {
+        const SORT_ORDERS: [Fourcc; 6] = [
+            ident::ALBUM_ARTIST_SORT_ORDER,
+            ident::ALBUM_SORT_ORDER,
+            ident::ARTIST_SORT_ORDER,
+            ident::COMPOSER_SORT_ORDER,
+            ident::TITLE_SORT_ORDER,
+            ident::TV_SHOW_NAME_SORT_ORDER,
+        ];
+        self.meta_items.retain(|a| !SORT_ORDERS.iter().any(|f| *f == a.ident));
+    }
+
+    /// Removes all freeform (`----`) items.
+    ///
+    /// # Example
+    /// ```
+    /// use mp4ameta::Userdata;
+    ///
+    /// let mut tag = Userdata::default();
+    /// tag.set_title("title");
+    /// tag.set_isrc("isrc");
+    /// tag.set_label("label");
+    ///
+    /// tag.clear_all_freeform();
+    /// assert_eq!(tag.data().count(), 1);
+    /// assert_eq!(tag.title(), Some("title"));
+    /// ```
+    pub fn clear_all_freeform(&mut self) {
+        self.meta_items.retain(|a| !matches!(a.ident, DataIdent::Freeform { .. }));
+    }
+
+    /// Applies a batch of edits. Identifiers with `Some` data are set as in [`Self::set_data`],
+    /// identifiers with `None` are removed as in [`Self::remove_data_of`]. The edits are applied
+    /// in order.
+    ///
+    /// All edits are validated before any of them is applied, so if an error is returned the
+    /// userdata is left unchanged. Big-endian signed integers must be 1, 2, 3, 4 or 8 bytes wide,
+    /// and freeform identifiers need a non-empty mean and name.
+    ///
+    /// # Example
+    /// ```
+    /// use mp4ameta::{Userdata, Data, DataIdent, ident};
+    ///
+    /// let mut tag = Userdata::default();
+    /// tag.set_title("title");
+    ///
+    /// tag.apply(&[
+    ///     (DataIdent::from(ident::ARTIST), Some(Data::Utf8("artist".into()))),
+    ///     (DataIdent::from(ident::TITLE), None),
+    /// ])
+    /// .unwrap();
+    /// assert_eq!(tag.artist(), Some("artist"));
+    /// assert_eq!(tag.title(), None);
+    ///
+    /// // a 5 byte integer is rejected and nothing is applied
+    /// let res = tag.apply(&[
+    ///     (DataIdent::from(ident::ARTIST), None),
+    ///     (DataIdent::from(ident::BPM), Some(Data::BeSigned(vec![0; 5]))),
+    /// ]);
+    /// assert!(res.is_err());
+    /// assert_eq!(tag.artist(), Some("artist"));
+    /// ```
+    pub fn apply(&mut self, changes: &[(DataIdent, Option<Data>)]) -> crate::Result<()> {
+        for (ident, data) in changes {
+            validate_change(ident, data.as_ref())?;
+        }
+        for (ident, data) in changes {
+            match data {
+                Some(d) => self.set_data(ident.clone(), d.clone()),
+                None => self.remove_data_of(ident),
+            }
+        }
+        Ok(())
+    }
+
     /// If an atom corresponding to the identifier exists, it's data will be replaced by the new
     /// data, otherwise a new metadata item atom containing the data will be created.
     ///
@@ -1356,4 +2400,162 @@ impl Userdata {
     pub fn meta_items_is_empty(&self) -> bool {
         self.meta_items.is_empty()
     }
+
+    /// Creates empty user data with space for at least `capacity` metadata items, so bulk
+    /// construction doesn't have to reallocate.
+    ///
+    /// # Example
+    /// ```
+    /// use mp4ameta::{Userdata, Data, Fourcc};
+    ///
+    /// let mut tag = Userdata::with_capacity(16);
+    /// let capacity = tag.meta_items_capacity();
+    /// assert!(capacity >= 16);
+    ///
+    /// for i in 0..16u8 {
+    ///     tag.set_data(Fourcc([b't', b'e', b's', i]), Data::Utf8("data".into()));
+    /// }
+    /// assert_eq!(tag.meta_items_capacity(), capacity);
+    /// ```
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            meta_items: Vec::with_capacity(capacity),
+            ..Default::default()
+        }
+    }
+
+    /// Returns the number of metadata items that can be stored without reallocating.
+    pub fn meta_items_capacity(&self) -> usize {
+        self.meta_items.capacity()
+    }
+
+    /// Reserves space for at least `additional` more metadata items.
+    ///
+    /// # Example
+    /// ```
+    /// use mp4ameta::{Userdata, Data, Fourcc};
+    ///
+    /// let mut tag = Userdata::default();
+    /// tag.set_data(Fourcc(*b"test"), Data::Utf8("data".into()));
+    /// tag.reserve(8);
+    /// assert!(tag.meta_items_capacity() >= 9);
+    /// ```
+    pub fn reserve(&mut self, additional: usize) {
+        self.meta_items.reserve(additional);
+    }
+
+    /// Returns the total length of all metadata items in bytes, as they would be written.
+    pub fn total_metadata_bytes(&self) -> u64 {
+        self.meta_items.iter().map(MetaItem::len).sum()
+    }
+
+    /// Returns the identifier and length in bytes of each metadata item, as it would be written,
+    /// sorted by length in descending order.
+    ///
+    /// # Example
+    /// ```
+    /// use mp4ameta::{Userdata, Img, ident};
+    ///
+    /// let mut tag = Userdata::default();
+    /// tag.set_title("title");
+    /// tag.set_artwork(Img::png(vec![0; 1024]));
+    ///
+    /// let report = tag.size_report();
+    /// assert_eq!(ident::ARTWORK, report[0].0);
+    /// assert_eq!(report.iter().map(|(_, len)| len).sum::<u64>(), tag.total_metadata_bytes());
+    /// ```
+    pub fn size_report(&self) -> Vec<(DataIdent, u64)> {
+        let mut report: Vec<_> =
+            self.meta_items.iter().map(|a| (a.ident.clone(), a.len())).collect();
+        report.sort_by(|(_, a), (_, b)| b.cmp(a));
+        report
+    }
+}
+
+fn is_artwork(data: &Data) -> bool {
+    data.has_image_type()
+}
+
+/// Opens the image file at the path, detecting its format from the magic bytes, which are
+/// returned as well.
+fn open_image(path: &Path) -> crate::Result<(File, ImgFmt, Vec<u8>)> {
+    let mut file = File::open(path)?;
+    let mut magic = Vec::with_capacity(8);
+    (&mut file).take(8).read_to_end(&mut magic)?;
+    let Some(fmt) = ImgFmt::from_magic(&magic) else {
+        return Err(crate::Error::new(
+            crate::ErrorKind::UnsupportedImageFormat,
+            format!("Unsupported image format of {}", path.display()),
+        ));
+    };
+    let extension = path.extension().and_then(|e| e.to_str());
+    if let Some(ext_fmt) = extension.and_then(ImgFmt::from_extension)
+        && ext_fmt != fmt
+    {
+        return Err(crate::Error::new(
+            crate::ErrorKind::UnsupportedImageFormat,
+            format!("The {fmt:?} image data of {} doesn't match its extension", path.display()),
+        ));
+    }
+    Ok((file, fmt, magic))
+}
+
+fn validate_change(ident: &DataIdent, data: Option<&Data>) -> crate::Result<()> {
+    if let DataIdent::Freeform { mean, name } = ident
+        && (mean.is_empty() || name.is_empty())
+    {
+        return Err(crate::Error::new(
+            crate::ErrorKind::InvalidData,
+            format!("Freeform identifier {ident} has an empty mean or name"),
+        ));
+    }
+    if let Some(Data::BeSigned(v)) = data
+        && !matches!(v.len(), 1 | 2 | 3 | 4 | 8)
+    {
+        return Err(crate::Error::new(
+            crate::ErrorKind::InvalidData,
+            format!(
+                "Big-endian signed integer of {ident} has an invalid width of {} bytes",
+                v.len()
+            ),
+        ));
+    }
+    Ok(())
+}
+
+impl IntoIterator for Userdata {
+    type Item = (DataIdent, Data);
+    type IntoIter = IntoItems;
+
+    /// Consumes `self` and returns an iterator over all metadata items, yielding each data value
+    /// separately, in item order.
+    fn into_iter(self) -> Self::IntoIter {
+        IntoItems { items: self.meta_items.into_iter(), current: None }
+    }
+}
+
+/// An iterator over the owned metadata items of a tag, yielding each data value separately.
+///
+/// This is returned by the [`IntoIterator`] implementations of [`Userdata`] and
+/// [`Tag`](crate::Tag).
+#[derive(Debug)]
+pub struct IntoItems {
+    items: std::vec::IntoIter<MetaItem>,
+    current: Option<(DataIdent, std::vec::IntoIter<Data>)>,
+}
+
+impl Iterator for IntoItems {
+    type Item = (DataIdent, Data);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some((ident, data)) = &mut self.current
+                && let Some(d) = data.next()
+            {
+                return Some((ident.clone(), d));
+            }
+            let item = self.items.next()?;
+            self.current = Some((item.ident, item.data.into_iter()));
+        }
+    }
 }
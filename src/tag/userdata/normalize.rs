@@ -0,0 +1,110 @@
+use super::is_single_value;
+use crate::{Userdata, ident};
+
+/// Configure which fixups are applied when normalizing metadata.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct NormalizeOptions {
+    /// Whether to remove custom genres (`©gen`) if a standard genre (`gnre`) is present.
+    pub prefer_standard_genre: bool,
+    /// Whether to trim trailing whitespace and null characters from strings.
+    pub trim_strings: bool,
+    /// Whether to remove data of length 0 and metadata items without any data.
+    pub remove_empty: bool,
+    /// Whether to coalesce duplicate data of a metadata item, keeping the last occurrence. Of
+    /// metadata items that only hold a single value, such as the title or track number, only the
+    /// last value is kept.
+    pub coalesce_duplicates: bool,
+}
+
+impl NormalizeOptions {
+    /// The default configuration applying all fixups.
+    pub const DEFAULT: Self = Self {
+        prefer_standard_genre: true,
+        trim_strings: true,
+        remove_empty: true,
+        coalesce_duplicates: true,
+    };
+
+    /// A configuration that wouldn't apply any fixups.
+    pub const NONE: Self = Self {
+        prefer_standard_genre: false,
+        trim_strings: false,
+        remove_empty: false,
+        coalesce_duplicates: false,
+    };
+}
+
+impl Default for NormalizeOptions {
+    fn default() -> Self {
+        Self::DEFAULT
+    }
+}
+
+/// ### Normalization
+impl Userdata {
+    /// Fixes common metadata inconsistencies, by applying all fixups of
+    /// [`NormalizeOptions::DEFAULT`].
+    pub fn normalize(&mut self) {
+        self.normalize_with(&NormalizeOptions::DEFAULT);
+    }
+
+    /// Fixes common metadata inconsistencies, by applying the fixups enabled in the options.
+    ///
+    /// # Example
+    /// ```
+    /// use mp4ameta::{Userdata, NormalizeOptions};
+    ///
+    /// let mut tag = Userdata::default();
+    /// tag.set_title("title \0");
+    /// tag.set_artists(["artist".to_owned(), "artist".to_owned()]);
+    ///
+    /// tag.normalize_with(&NormalizeOptions { trim_strings: true, ..NormalizeOptions::NONE });
+    /// assert_eq!(tag.title(), Some("title"));
+    /// assert_eq!(tag.artists().count(), 2);
+    /// ```
+    pub fn normalize_with(&mut self, options: &NormalizeOptions) {
+        if options.trim_strings {
+            let data = self.meta_items.iter_mut().flat_map(|a| a.data.iter_mut());
+            for s in data.filter_map(|d| d.string_mut()) {
+                let len = s.trim_end_matches(|c: char| c.is_whitespace() || c == '\0').len();
+                s.truncate(len);
+            }
+        }
+
+        if options.remove_empty {
+            for a in self.meta_items.iter_mut() {
+                a.data.retain(|d| !d.is_empty());
+            }
+            self.meta_items.retain(|a| !a.data.is_empty());
+        }
+
+        if options.coalesce_duplicates {
+            let mut single_values = Vec::new();
+            for i in (0..self.meta_items.len()).rev() {
+                let a = &mut self.meta_items[i];
+                if is_single_value(&a.ident) && !a.data.is_empty() {
+                    if single_values.contains(&a.ident) {
+                        self.meta_items.remove(i);
+                    } else {
+                        single_values.push(a.ident.clone());
+                        a.data.drain(..a.data.len() - 1);
+                    }
+                    continue;
+                }
+
+                let mut j = 0;
+                while j < a.data.len() {
+                    if a.data[j + 1..].contains(&a.data[j]) {
+                        a.data.remove(j);
+                    } else {
+                        j += 1;
+                    }
+                }
+            }
+        }
+
+        if options.prefer_standard_genre && self.standard_genre().is_some() {
+            self.remove_data_of(&ident::CUSTOM_GENRE);
+        }
+    }
+}
@@ -30,8 +30,7 @@ impl Userdata {
     }
 
     fn set_new_track(&mut self, track_number: u16, total_tracks: u16) {
-        let vec = new(track_number, total_tracks);
-        self.set_data(ident::TRACK_NUMBER, Data::Reserved(vec));
+        self.set_data(ident::TRACK_NUMBER, Data::number_and_total(track_number, total_tracks));
     }
 
     /// Sets the track number and the total number of tracks (`trkn`).
@@ -126,8 +125,7 @@ impl Userdata {
     }
 
     fn set_new_disc(&mut self, disc_number: u16, total_discs: u16) {
-        let vec = new(disc_number, total_discs);
-        self.set_data(ident::DISC_NUMBER, Data::Reserved(vec));
+        self.set_data(ident::DISC_NUMBER, Data::number_and_total(disc_number, total_discs));
     }
 
     /// Sets the disc number and the total number of discs (`disk`).
@@ -142,6 +140,12 @@ impl Userdata {
         }
     }
 
+    /// Sets the disc number and optionally the total number of discs (`disk`). A missing total is
+    /// stored as 0, which is treated as nonexistent.
+    pub fn set_disc_and_total(&mut self, disc_number: u16, total_discs: Option<u16>) {
+        self.set_disc(disc_number, total_discs.unwrap_or(0));
+    }
+
     /// Sets the disc number (`disk`).
     pub fn set_disc_number(&mut self, disc_number: u16) {
         let vec = self.bytes_mut_of(&ident::DISC_NUMBER).next();
@@ -219,9 +223,3 @@ fn check_correct_size(vec: &mut Vec<u8>) {
         vec.resize(8, 0);
     }
 }
-
-fn new(number: u16, total: u16) -> Vec<u8> {
-    let [n0, n1] = number.to_be_bytes();
-    let [t0, t1] = total.to_be_bytes();
-    vec![0, 0, n0, n1, t0, t1, 0, 0]
-}
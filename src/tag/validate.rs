@@ -0,0 +1,204 @@
+use std::fmt;
+
+use crate::{Data, DataIdent, Fourcc, ImgFmt, Tag, ident};
+
+/// A spec-compliance problem found by [`Tag::validate`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ValidationIssue {
+    /// The identifier of the affected metadata item, or `None` if the problem concerns the
+    /// structure of the file, for example a missing atom.
+    pub ident: Option<DataIdent>,
+    /// A description of the problem.
+    pub message: String,
+}
+
+impl ValidationIssue {
+    fn new(ident: Option<&DataIdent>, message: impl Into<String>) -> Self {
+        Self { ident: ident.cloned(), message: message.into() }
+    }
+}
+
+impl fmt::Display for ValidationIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.ident {
+            Some(ident) => write!(f, "{ident}: {}", self.message),
+            None => f.write_str(&self.message),
+        }
+    }
+}
+
+/// The widths in bytes allowed for integer metadata items.
+const INTEGER_WIDTHS: &[(Fourcc, &[usize])] = &[
+    (ident::TRACK_NUMBER, &[6, 8]),
+    (ident::DISC_NUMBER, &[6, 8]),
+    (ident::STANDARD_GENRE, &[2]),
+    (ident::BPM, &[2]),
+    (ident::MOVEMENT_COUNT, &[2]),
+    (ident::MOVEMENT_INDEX, &[2]),
+    (ident::COMPILATION, &[1]),
+    (ident::GAPLESS_PLAYBACK, &[1]),
+    (ident::SHOW_MOVEMENT, &[1]),
+    (ident::PODCAST, &[1]),
+    (ident::MEDIA_TYPE, &[1]),
+    (ident::ADVISORY_RATING, &[1]),
+    (ident::TV_EPISODE, &[4]),
+    (ident::TV_SEASON, &[4]),
+    (ident::ARTIST_ID, &[4]),
+    (ident::CATALOG_ID, &[4]),
+    (ident::PLAYLIST_ID, &[8]),
+];
+
+/// The metadata items that are expected to contain text.
+const TEXT_IDENTS: &[Fourcc] = &[
+    ident::ALBUM,
+    ident::ALBUM_ARTIST,
+    ident::ARTIST,
+    ident::AUTHOR,
+    ident::COMMENT,
+    ident::COMPOSER,
+    ident::COPYRIGHT,
+    ident::CUSTOM_GENRE,
+    ident::DIRECTOR,
+    ident::ENCODER,
+    ident::PRODUCER,
+    ident::PUBLISHER,
+    ident::TITLE,
+    ident::YEAR,
+    ident::GROUPING,
+    ident::CATEGORY,
+    ident::KEYWORD,
+    ident::PODCAST_EPISODE_GLOBAL_UNIQUE_ID,
+    ident::PODCAST_URL,
+    ident::DESCRIPTION,
+    ident::LYRICS,
+    ident::TV_EPISODE_NAME,
+    ident::TV_NETWORK_NAME,
+    ident::TV_SHOW_NAME,
+    ident::PURCHASE_DATE,
+    ident::ACCOUNT_ID,
+    ident::OWNER,
+    ident::XID,
+    ident::MOVEMENT,
+    ident::WORK,
+    ident::ALBUM_ARTIST_SORT_ORDER,
+    ident::ALBUM_SORT_ORDER,
+    ident::ARTIST_SORT_ORDER,
+    ident::COMPOSER_SORT_ORDER,
+    ident::TITLE_SORT_ORDER,
+    ident::TV_SHOW_NAME_SORT_ORDER,
+];
+
+/// ### Validation
+impl Tag {
+    /// Checks the metadata for spec-compliance problems, without modifying anything. The
+    /// following problems are reported:
+    /// - integer items (e.g. `tmpo`, `trkn`, `cpil`) with a wrong width
+    /// - text items (e.g. `©nam`) that aren't stored as text, or contain invalid utf-8
+    /// - artwork (`covr`) whose type code doesn't match the format of the image data
+    /// - both a standard genre (`gnre`) and a custom genre (`©gen`)
+    /// - a metadata (`meta`) atom without the required handler reference (`hdlr`) atom
+    ///
+    /// # Example
+    /// ```no_run
+    /// let tag = mp4ameta::Tag::read_from_path("music.m4a").unwrap();
+    ///
+    /// for issue in tag.validate() {
+    ///     println!("{issue}");
+    /// }
+    /// ```
+    pub fn validate(&self) -> Vec<ValidationIssue> {
+        let mut issues = Vec::new();
+
+        if self.meta_hdlr_missing {
+            issues.push(ValidationIssue::new(
+                None,
+                "The metadata atom (meta) is missing the handler reference atom (hdlr)",
+            ));
+        }
+
+        for item in self.userdata.meta_items.iter() {
+            let ident = &item.ident;
+            // the payload of skipped and streamed data isn't available
+            let in_memory = |d: &&Data| !matches!(d, Data::Skipped(_) | Data::Streamed { .. });
+            for data in item.data.iter().filter(in_memory) {
+                if let Some(issue) = validate_integer(ident, data)
+                    .or_else(|| validate_text(ident, data))
+                    .or_else(|| validate_image(ident, data))
+                {
+                    issues.push(issue);
+                }
+            }
+        }
+
+        let standard_genre =
+            self.userdata.meta_items.iter().find(|a| ident::STANDARD_GENRE == a.ident);
+        let custom_genre = self.userdata.meta_items.iter().any(|a| ident::CUSTOM_GENRE == a.ident);
+        if let Some(item) = standard_genre
+            && custom_genre
+        {
+            issues.push(ValidationIssue::new(
+                Some(&item.ident),
+                "Both a standard genre (gnre) and a custom genre (©gen) are present",
+            ));
+        }
+
+        issues
+    }
+}
+
+fn validate_integer(ident: &DataIdent, data: &Data) -> Option<ValidationIssue> {
+    let (_, widths) = INTEGER_WIDTHS.iter().find(|(f, _)| *f == *ident)?;
+    let bytes = match data {
+        Data::Reserved(v) | Data::BeSigned(v) => v,
+        _ => {
+            let msg = format!("Expected an integer, found data type code {}", data.data_type());
+            return Some(ValidationIssue::new(Some(ident), msg));
+        }
+    };
+    if widths.contains(&bytes.len()) {
+        return None;
+    }
+    let msg = format!("Integer has a width of {} bytes, expected {widths:?}", bytes.len());
+    Some(ValidationIssue::new(Some(ident), msg))
+}
+
+fn validate_text(ident: &DataIdent, data: &Data) -> Option<ValidationIssue> {
+    let is_text = TEXT_IDENTS.iter().any(|f| *f == *ident)
+        || ident::ISRC == *ident
+        || ident::LABEL == *ident
+        || ident::LYRICIST == *ident
+        || ident::MUSICBRAINZ_TRACK_ID == *ident
+        || ident::MUSICBRAINZ_ALBUM_ID == *ident
+        || ident::MUSICBRAINZ_ARTIST_ID == *ident
+        || ident::MUSICBRAINZ_RELEASE_GROUP_ID == *ident;
+    if !is_text || data.is_string() {
+        return None;
+    }
+    let msg = match data.bytes().map(std::str::from_utf8) {
+        Some(Err(_)) => "Text contains invalid utf-8".to_owned(),
+        _ if data.is_invalid_utf8() => "Text contains invalid utf-8".to_owned(),
+        _ => format!("Expected utf-8 text, found data type code {}", data.data_type()),
+    };
+    Some(ValidationIssue::new(Some(ident), msg))
+}
+
+fn validate_image(ident: &DataIdent, data: &Data) -> Option<ValidationIssue> {
+    if ident::ARTWORK != *ident {
+        return None;
+    }
+    let (declared, bytes) = match data {
+        Data::Jpeg(v) => (ImgFmt::Jpeg, v),
+        Data::Png(v) => (ImgFmt::Png, v),
+        Data::Bmp(v) => (ImgFmt::Bmp, v),
+        _ => {
+            let msg = format!("Expected an image, found data type code {}", data.data_type());
+            return Some(ValidationIssue::new(Some(ident), msg));
+        }
+    };
+    let actual = ImgFmt::from_magic(bytes)?;
+    if declared == actual {
+        return None;
+    }
+    let msg = format!("Image is declared as {declared:?}, but the data is {actual:?}");
+    Some(ValidationIssue::new(Some(ident), msg))
+}
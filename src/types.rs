@@ -1,11 +1,16 @@
 use std::convert::TryFrom;
 use std::fmt;
-use std::time::Duration;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
-use crate::ErrorKind;
+use crate::atom::{scale_duration, unscale_duration};
+use crate::util::BytesPreview;
+use crate::{ErrorKind, Fourcc};
 
 /// The iTunes media type of a file. This is stored in the `stik` atom.
+///
+/// More media types may be added in the future, so matches need a wildcard arm.
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[non_exhaustive]
 pub enum MediaType {
     /// A media type stored as 0 in the `stik` atom.
     Movie = 0,
@@ -23,6 +28,8 @@ pub enum MediaType {
     TvShow = 10,
     /// A media type stored as 11 in the `stik` atom.
     Booklet = 11,
+    /// A media type stored as 21 in the `stik` atom.
+    Podcast = 21,
 }
 
 impl MediaType {
@@ -34,6 +41,7 @@ impl MediaType {
     const SHORT_FILM: u8 = Self::ShortFilm as u8;
     const TV_SHOW: u8 = Self::TvShow as u8;
     const BOOKLET: u8 = Self::Booklet as u8;
+    const PODCAST: u8 = Self::Podcast as u8;
 
     pub fn code(&self) -> u8 {
         *self as u8
@@ -53,6 +61,7 @@ impl TryFrom<u8> for MediaType {
             Self::SHORT_FILM => Ok(Self::ShortFilm),
             Self::TV_SHOW => Ok(Self::TvShow),
             Self::BOOKLET => Ok(Self::Booklet),
+            Self::PODCAST => Ok(Self::Podcast),
             _ => Err(Self::Error::new(ErrorKind::UnknownMediaType(value), "Unknown media type")),
         }
     }
@@ -69,6 +78,7 @@ impl fmt::Display for MediaType {
             Self::ShortFilm => write!(f, "Short Film"),
             Self::TvShow => write!(f, "TV-Show"),
             Self::Booklet => write!(f, "Booklet"),
+            Self::Podcast => write!(f, "Podcast"),
         }
     }
 }
@@ -153,6 +163,20 @@ impl ChannelConfig {
             Self::SevenOne => 8,
         }
     }
+
+    /// Returns the channel config with the number of channels, see [`Self::channel_count`].
+    pub const fn from_channel_count(count: u8) -> Option<Self> {
+        match count {
+            1 => Some(Self::Mono),
+            2 => Some(Self::Stereo),
+            3 => Some(Self::Three),
+            4 => Some(Self::Four),
+            5 => Some(Self::Five),
+            6 => Some(Self::FiveOne),
+            8 => Some(Self::SevenOne),
+            _ => None,
+        }
+    }
 }
 
 impl TryFrom<u8> for ChannelConfig {
@@ -189,6 +213,25 @@ impl fmt::Display for ChannelConfig {
     }
 }
 
+/// Serialized as the number of channels.
+#[cfg(feature = "serde")]
+impl serde::Serialize for ChannelConfig {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_u8(self.channel_count())
+    }
+}
+
+/// Deserialized from the number of channels.
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for ChannelConfig {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let count = u8::deserialize(deserializer)?;
+        Self::from_channel_count(count).ok_or_else(|| {
+            serde::de::Error::custom(format_args!("unsupported channel count {count}"))
+        })
+    }
+}
+
 /// An enum representing the sample rate of an MPEG-4 audio track.
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum SampleRate {
@@ -253,6 +296,26 @@ impl SampleRate {
             Self::Hz7350 => 7350,
         }
     }
+
+    /// Returns the sample rate corresponding to the frequency in Hz, see [`Self::hz`].
+    pub const fn from_hz(hz: u32) -> Option<Self> {
+        match hz {
+            96000 => Some(Self::Hz96000),
+            88200 => Some(Self::Hz88200),
+            64000 => Some(Self::Hz64000),
+            48000 => Some(Self::Hz48000),
+            44100 => Some(Self::Hz44100),
+            32000 => Some(Self::Hz32000),
+            24000 => Some(Self::Hz24000),
+            22050 => Some(Self::Hz22050),
+            16000 => Some(Self::Hz16000),
+            12000 => Some(Self::Hz12000),
+            11025 => Some(Self::Hz11025),
+            8000 => Some(Self::Hz8000),
+            7350 => Some(Self::Hz7350),
+            _ => None,
+        }
+    }
 }
 
 impl TryFrom<u8> for SampleRate {
@@ -287,19 +350,436 @@ impl fmt::Display for SampleRate {
     }
 }
 
+/// Serialized as the frequency in Hz.
+#[cfg(feature = "serde")]
+impl serde::Serialize for SampleRate {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_u32(self.hz())
+    }
+}
+
+/// Deserialized from the frequency in Hz.
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for SampleRate {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let hz = u32::deserialize(deserializer)?;
+        Self::from_hz(hz)
+            .ok_or_else(|| serde::de::Error::custom(format_args!("unsupported sample rate {hz}Hz")))
+    }
+}
+
+/// The codec of an audio track, identified by the sample entry (`stsd`).
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Codec {
+    /// Advanced audio coding low complexity (AAC-LC).
+    AacLc,
+    /// High efficiency AAC (HE-AAC), AAC-LC with spectral band replication (SBR).
+    HeAac,
+    /// High efficiency AAC version 2 (HE-AACv2), HE-AAC with parametric stereo (PS).
+    HeAacV2,
+    /// Apple lossless audio codec (ALAC).
+    Alac,
+    /// Dolby digital (AC-3).
+    Ac3,
+    /// Dolby digital plus (E-AC-3).
+    Eac3,
+    /// Any other codec, identified by the fourcc of the sample entry. This includes MPEG-4 audio
+    /// (`mp4a`) of other audio object types.
+    Other(Fourcc),
+}
+
+impl fmt::Display for Codec {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::AacLc => write!(f, "AAC-LC"),
+            Self::HeAac => write!(f, "HE-AAC"),
+            Self::HeAacV2 => write!(f, "HE-AACv2"),
+            Self::Alac => write!(f, "ALAC"),
+            Self::Ac3 => write!(f, "AC-3"),
+            Self::Eac3 => write!(f, "E-AC-3"),
+            Self::Other(fourcc) => write!(f, "{fourcc}"),
+        }
+    }
+}
+
+/// Whether the bitrate of a track is constant or variable.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum BitrateKind {
+    /// Constant bitrate (CBR), the maximum bitrate equals the average bitrate.
+    Constant,
+    /// Variable bitrate (VBR), the maximum bitrate exceeds the average bitrate.
+    Variable,
+    /// The bitrates aren't declared, or are inconsistent.
+    #[default]
+    Unknown,
+}
+
+impl BitrateKind {
+    /// Infers the kind from the declared maximum and average bitrate. A value of zero means the
+    /// bitrate isn't known.
+    pub(crate) fn from_bitrates(max: u32, avg: u32) -> Self {
+        match (max, avg) {
+            (0, _) | (_, 0) => Self::Unknown,
+            (max, avg) if max == avg => Self::Constant,
+            (max, avg) if max > avg => Self::Variable,
+            _ => Self::Unknown,
+        }
+    }
+}
+
+impl fmt::Display for BitrateKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Constant => write!(f, "CBR"),
+            Self::Variable => write!(f, "VBR"),
+            Self::Unknown => write!(f, "unknown"),
+        }
+    }
+}
+
 /// Audio information of an mp4 track.
 #[derive(Clone, Debug, Default, PartialEq, Eq)]
 pub struct AudioInfo {
-    /// The duration of the track.
+    /// The duration of the track. This is the media duration of the audio track (`mdhd`), or the
+    /// duration of the whole movie (`mvhd`) if that isn't available.
     pub duration: Duration,
+    /// The exact duration in units of the [`AudioInfo::timescale`], for example the number of
+    /// samples if the timescale is the sample rate.
+    pub duration_ticks: u64,
+    /// The timescale of [`AudioInfo::duration_ticks`] in units per second.
+    pub timescale: u32,
     /// The channel configuration of the track.
     pub channel_config: Option<ChannelConfig>,
-    /// The sample rate of the track.
+    /// The sample rate of the track. For HE-AAC this is the sample rate of the AAC core, see
+    /// [`AudioInfo::effective_sample_rate`].
     pub sample_rate: Option<SampleRate>,
+    /// Whether spectral band replication (SBR) is present, as used by HE-AAC. `None` if it isn't
+    /// signaled in the audio specific config, in which case it may be signaled implicitly in the
+    /// bitstream.
+    pub spectral_band_replication: Option<bool>,
+    /// Whether parametric stereo (PS) is present, as used by HE-AACv2. `None` if it isn't
+    /// signaled in the audio specific config.
+    pub parametric_stereo: Option<bool>,
+    /// The output sample rate of the SBR extension in Hz, if signaled.
+    pub extension_sample_rate: Option<u32>,
     /// The maximum bitrate of the track.
     pub max_bitrate: Option<u32>,
-    /// The average bitrate of the track.
+    /// The average bitrate of the track. If the sample entry doesn't specify it, it is
+    /// estimated from the length of the media data (`mdat`), see
+    /// [`AudioInfo::bitrate_estimated`].
     pub avg_bitrate: Option<u32>,
+    /// Whether the [`AudioInfo::avg_bitrate`] is estimated from the length of the media data
+    /// (`mdat`) and duration, instead of being declared in the sample entry. Files containing
+    /// video aren't estimated.
+    pub bitrate_estimated: bool,
+    /// Whether the bitrate is constant or variable, inferred from the maximum and average
+    /// bitrate declared in the elementary stream descriptor (`esds`), or in the bitrate atom
+    /// (`btrt`) if the former doesn't declare them.
+    pub bitrate_kind: BitrateKind,
+    /// The bit depth of the track. For ALAC this is read from the magic cookie, otherwise from the
+    /// sample size of the sample entry. Note that AAC commonly reports 16, regardless of the bit
+    /// depth of the source.
+    pub bit_depth: Option<u8>,
+    /// The codec of the track.
+    pub codec: Option<Codec>,
+    /// The number of frames per packet, only present for ALAC.
+    pub frames_per_packet: Option<u32>,
+    /// The 24 byte ALAC specific config of the magic cookie (`alac`), as required to initialize
+    /// a decoder. Only present for ALAC.
+    pub alac_cookie: Option<Vec<u8>>,
+    /// The sum of all sample durations (`stts`) in units of the media timescale. For audio the
+    /// timescale usually is the sample rate, so this is the number of PCM samples per channel,
+    /// including encoder delay and padding.
+    pub total_samples: Option<u64>,
+    /// The number of samples (`stsz`), which are frames of the codec, for example AAC frames of
+    /// 1024 PCM samples each.
+    pub frame_count: Option<u64>,
+    /// The number of priming samples added by the encoder, read from the gapless playback
+    /// information (`iTunSMPB`).
+    pub encoder_delay: Option<u32>,
+    /// The number of padding samples added by the encoder, read from the gapless playback
+    /// information (`iTunSMPB`).
+    pub encoder_padding: Option<u32>,
+    /// The number of PCM samples per channel of the source, without the encoder delay and
+    /// padding, read from the gapless playback information (`iTunSMPB`).
+    pub exact_sample_count: Option<u64>,
+}
+
+impl AudioInfo {
+    /// Returns the number of PCM samples per channel after decoding. This is the
+    /// [`total_samples`] without the [`encoder_delay`] and [`encoder_padding`], if these are
+    /// known.
+    ///
+    /// [`total_samples`]: Self::total_samples
+    /// [`encoder_delay`]: Self::encoder_delay
+    /// [`encoder_padding`]: Self::encoder_padding
+    pub fn pcm_length(&self) -> Option<u64> {
+        let total = self.total_samples?;
+        let delay = self.encoder_delay.unwrap_or(0) as u64;
+        let padding = self.encoder_padding.unwrap_or(0) as u64;
+        Some(total.saturating_sub(delay + padding))
+    }
+
+    /// Returns the sample rate in Hz at which the audio is played back. If spectral band
+    /// replication is present this is the signaled extension sample rate, or otherwise twice the
+    /// [`sample_rate`] of the AAC core.
+    ///
+    /// [`sample_rate`]: Self::sample_rate
+    pub fn effective_sample_rate(&self) -> Option<u32> {
+        let core = self.sample_rate?.hz();
+        match self.spectral_band_replication {
+            Some(true) => Some(self.extension_sample_rate.unwrap_or(2 * core)),
+            _ => Some(core),
+        }
+    }
+
+    /// Returns the playback duration without the encoder delay and padding, or `None` if there
+    /// is no gapless playback information (`iTunSMPB`).
+    ///
+    /// The gapless playback information counts samples at the output sample rate of the decoder,
+    /// the [`effective_sample_rate`]. For HE-AAC this usually is twice the sample rate of the AAC
+    /// core.
+    ///
+    /// [`effective_sample_rate`]: Self::effective_sample_rate
+    pub fn gapless_duration(&self) -> Option<Duration> {
+        let delay = self.encoder_delay? as u64;
+        let padding = self.encoder_padding.unwrap_or(0) as u64;
+        let rate = self.effective_sample_rate()?;
+        let samples = match self.exact_sample_count {
+            Some(count) => count,
+            None => {
+                let total = self.total_samples? as u128 * rate as u128;
+                let total = total.checked_div(self.timescale as u128)? as u64;
+                total.saturating_sub(delay + padding)
+            }
+        };
+        Some(scale_duration(rate, samples))
+    }
+}
+
+impl AudioInfo {
+    /// Returns whether the codec is lossless, for example ALAC, FLAC or PCM. An unknown codec is
+    /// considered lossy.
+    pub fn is_lossless(&self) -> bool {
+        match self.codec {
+            Some(Codec::Alac) => true,
+            Some(Codec::Other(fourcc)) => LOSSLESS_CODECS.contains(&fourcc.0),
+            _ => false,
+        }
+    }
+
+    /// Returns a one-line summary of the codec, sample rate, bit depth, channel config and
+    /// bitrate, omitting unknown parts. The bit depth is only included for lossless codecs, since
+    /// lossy codecs don't have a meaningful one. An estimated bitrate is prefixed with `~`, and a
+    /// maximum bitrate above the average one is indicated as `VBR`.
+    ///
+    /// # Example
+    /// ```
+    /// use mp4ameta::{AudioInfo, ChannelConfig, Codec, SampleRate};
+    ///
+    /// let info = AudioInfo {
+    ///     codec: Some(Codec::Alac),
+    ///     sample_rate: Some(SampleRate::Hz44100),
+    ///     bit_depth: Some(16),
+    ///     channel_config: Some(ChannelConfig::Stereo),
+    ///     ..Default::default()
+    /// };
+    /// assert_eq!(info.summary(), "ALAC 44.1 kHz / 16-bit / Stereo");
+    ///
+    /// let info = AudioInfo {
+    ///     codec: Some(Codec::AacLc),
+    ///     avg_bitrate: Some(256_000),
+    ///     max_bitrate: Some(320_000),
+    ///     ..Default::default()
+    /// };
+    /// assert_eq!(info.summary(), "AAC-LC 256 kbps VBR");
+    /// ```
+    pub fn summary(&self) -> String {
+        let mut parts = Vec::new();
+        if let Some(rate) = self.effective_sample_rate() {
+            let khz = format!("{:.3}", rate as f64 / 1000.0);
+            parts.push(format!("{} kHz", khz.trim_end_matches('0').trim_end_matches('.')));
+        }
+        if let Some(depth) = self.bit_depth.filter(|_| self.is_lossless()) {
+            parts.push(format!("{depth}-bit"));
+        }
+        if let Some(config) = self.channel_config {
+            parts.push(config.to_string());
+        }
+        if let Some(avg) = self.avg_bitrate.filter(|b| *b != 0) {
+            let estimated = if self.bitrate_estimated { "~" } else { "" };
+            let vbr = if self.max_bitrate.is_some_and(|m| m > avg) { " VBR" } else { "" };
+            parts.push(format!("{estimated}{} kbps{vbr}", (avg + 500) / 1000));
+        }
+
+        let parts = parts.join(" / ");
+        match (self.codec, parts.is_empty()) {
+            (Some(codec), true) => codec.to_string(),
+            (Some(codec), false) => format!("{codec} {parts}"),
+            (None, _) => parts,
+        }
+    }
+}
+
+/// The fourccs of lossless sample entries, besides ALAC.
+const LOSSLESS_CODECS: [[u8; 4]; 9] =
+    [*b"fLaC", *b"ipcm", *b"lpcm", *b"sowt", *b"twos", *b"in24", *b"in32", *b"fl32", *b"fl64"];
+
+impl fmt::Display for AudioInfo {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.summary())
+    }
+}
+
+/// The file type information stored in the filetype (`ftyp`) atom.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct FileType {
+    /// The major brand, for example `M4A ` or `isom`.
+    pub major_brand: Fourcc,
+    /// The minor version of the major brand.
+    pub minor_version: u32,
+    /// The brands the file is compatible with.
+    pub compatible_brands: Vec<Fourcc>,
+}
+
+impl FileType {
+    /// Returns whether the brand is either the major brand or one of the compatible brands.
+    pub fn has_brand(&self, brand: Fourcc) -> bool {
+        self.major_brand == brand || self.compatible_brands.contains(&brand)
+    }
+
+    /// Returns whether the file is an Apple audio file (`M4A ` brand).
+    pub fn is_audio(&self) -> bool {
+        self.has_brand(Fourcc(*b"M4A "))
+    }
+
+    /// Returns whether the file is an Apple audiobook (`M4B ` brand).
+    pub fn is_audiobook(&self) -> bool {
+        self.has_brand(Fourcc(*b"M4B "))
+    }
+
+    /// Returns whether the file is an Apple video file (`M4V ` brand).
+    pub fn is_video(&self) -> bool {
+        self.has_brand(Fourcc(*b"M4V "))
+    }
+
+    /// Returns whether the file conforms to the ISO base media file format (`isom` brand).
+    pub fn is_iso_base_media(&self) -> bool {
+        self.has_brand(Fourcc(*b"isom"))
+    }
+}
+
+/// Video information of an mp4 track.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct VideoInfo {
+    /// The width of the encoded frames in pixels.
+    pub width: u16,
+    /// The height of the encoded frames in pixels.
+    pub height: u16,
+    /// The width in pixels, scaled by the pixel aspect ratio (`pasp`) if present.
+    pub display_width: u16,
+    /// The fourcc of the sample entry, for example `avc1` or `hvc1`.
+    pub codec: Fourcc,
+    /// The frame rate in frames per second, if all samples have the same duration.
+    pub frame_rate: Option<f64>,
+    /// The duration of the track.
+    pub duration: Option<Duration>,
+}
+
+// The frame rate is never NaN.
+impl Eq for VideoInfo {}
+
+/// Information about a single track of an mp4 file.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct TrackInfo {
+    /// The track id (`tkhd`).
+    pub id: u32,
+    /// The handler type (`hdlr`), for example `soun`, `vide` or `text`.
+    pub handler_type: Option<Fourcc>,
+    /// Whether the track is enabled (`tkhd`).
+    pub enabled: bool,
+    /// The ISO 639-2/T language code (`mdhd`), for example `eng`.
+    pub language: Option<String>,
+    /// The BCP 47 extended language tag (`elng`), for example `en-US`.
+    pub extended_language: Option<String>,
+    /// The duration of the track (`tkhd`).
+    pub duration: Duration,
+    /// The audio information, if this is an audio track.
+    pub audio: Option<AudioInfo>,
+    /// The video information, if this is a video track.
+    pub video: Option<VideoInfo>,
+}
+
+/// A timestamp of the movie header (`mvhd`), stored as seconds since midnight, January 1, 1904
+/// (UTC).
+///
+/// # Example
+/// ```
+/// use std::time::{Duration, UNIX_EPOCH};
+///
+/// use mp4ameta::Timestamp;
+///
+/// // 2024-01-01 00:00:00 UTC
+/// let time = Timestamp::from_secs(3_786_912_000);
+/// assert_eq!(time.to_system_time(), Some(UNIX_EPOCH + Duration::from_secs(1_704_067_200)));
+///
+/// // dates before 1970 are supported as well
+/// let time = Timestamp::from_secs(0);
+/// assert_eq!(time.to_system_time(), UNIX_EPOCH.checked_sub(Duration::from_secs(2_082_844_800)));
+/// ```
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Timestamp(u64);
+
+impl Timestamp {
+    /// The seconds between 1904-01-01 and 1970-01-01.
+    const UNIX_EPOCH_SECS: u64 = 2_082_844_800;
+
+    /// Creates a timestamp from seconds since 1904-01-01 00:00:00 UTC.
+    pub const fn from_secs(secs: u64) -> Self {
+        Self(secs)
+    }
+
+    /// Returns the seconds since 1904-01-01 00:00:00 UTC.
+    pub const fn secs(self) -> u64 {
+        self.0
+    }
+
+    /// Converts the timestamp into a [`SystemTime`], returns `None` if the platform can't
+    /// represent it.
+    pub fn to_system_time(self) -> Option<SystemTime> {
+        match self.0.checked_sub(Self::UNIX_EPOCH_SECS) {
+            Some(secs) => UNIX_EPOCH.checked_add(Duration::from_secs(secs)),
+            None => UNIX_EPOCH.checked_sub(Duration::from_secs(Self::UNIX_EPOCH_SECS - self.0)),
+        }
+    }
+
+    /// Creates a timestamp from a [`SystemTime`], returns `None` if it lies before 1904.
+    /// Fractions of a second are truncated.
+    pub fn from_system_time(time: SystemTime) -> Option<Self> {
+        match time.duration_since(UNIX_EPOCH) {
+            Ok(d) => Self::UNIX_EPOCH_SECS.checked_add(d.as_secs()).map(Self),
+            Err(e) => {
+                let d = e.duration();
+                let secs = d.as_secs() + u64::from(d.subsec_nanos() > 0);
+                Self::UNIX_EPOCH_SECS.checked_sub(secs).map(Self)
+            }
+        }
+    }
+
+    /// Converts the timestamp into a [`chrono::DateTime`], returns `None` if it is out of range.
+    #[cfg(feature = "chrono")]
+    pub fn to_date_time(self) -> Option<chrono::DateTime<chrono::Utc>> {
+        let secs = i64::try_from(self.0).ok()? - Self::UNIX_EPOCH_SECS as i64;
+        chrono::DateTime::from_timestamp(secs, 0)
+    }
+
+    /// Creates a timestamp from a [`chrono::DateTime`], returns `None` if it lies before 1904.
+    /// Fractions of a second are truncated.
+    #[cfg(feature = "chrono")]
+    pub fn from_date_time(time: chrono::DateTime<chrono::Utc>) -> Option<Self> {
+        let secs = time.timestamp().checked_add(Self::UNIX_EPOCH_SECS as i64)?;
+        u64::try_from(secs).ok().map(Self)
+    }
 }
 
 /// Type alias for an image reference.
@@ -310,7 +790,7 @@ pub type ImgMut<'a> = Img<&'a mut Vec<u8>>;
 pub type ImgBuf = Img<Vec<u8>>;
 
 /// Image data with an associated format.
-#[derive(Clone, Debug, PartialEq, Eq)]
+#[derive(Clone, PartialEq, Eq)]
 pub struct Img<T> {
     /// The image format.
     pub fmt: ImgFmt,
@@ -318,6 +798,15 @@ pub struct Img<T> {
     pub data: T,
 }
 
+impl<T: AsRef<[u8]>> fmt::Debug for Img<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Img")
+            .field("fmt", &self.fmt)
+            .field("data", &BytesPreview(self.data.as_ref()))
+            .finish()
+    }
+}
+
 impl<T> Img<T> {
     pub const fn new(fmt: ImgFmt, data: T) -> Self {
         Self { fmt, data }
@@ -336,6 +825,60 @@ impl<T> Img<T> {
     }
 }
 
+#[cfg(feature = "image")]
+impl ImgBuf {
+    /// Decodes the image and encodes it in the target format, for example to convert BMP
+    /// artwork, which many players don't render, into PNG. Transparency is dropped when encoding
+    /// JPEG. If the image already has the target format it is returned unchanged.
+    ///
+    /// # Example
+    /// ```
+    /// use mp4ameta::{ImgBuf, ImgFmt};
+    ///
+    /// # let bmp = {
+    /// #     let mut bmp = Vec::new();
+    /// #     let img = image::RgbImage::new(1, 1);
+    /// #     img.write_to(&mut std::io::Cursor::new(&mut bmp), image::ImageFormat::Bmp).unwrap();
+    /// #     bmp
+    /// # };
+    /// let img = ImgBuf::bmp(bmp);
+    /// let png = img.transcode(ImgFmt::Png).unwrap();
+    /// assert_eq!(png.fmt, ImgFmt::Png);
+    /// assert!(png.data.starts_with(b"\x89PNG"));
+    /// ```
+    pub fn transcode(self, target: ImgFmt) -> crate::Result<ImgBuf> {
+        if self.fmt == target {
+            return Ok(self);
+        }
+
+        let format = |fmt: &ImgFmt| match fmt {
+            ImgFmt::Bmp => image::ImageFormat::Bmp,
+            ImgFmt::Jpeg => image::ImageFormat::Jpeg,
+            ImgFmt::Png => image::ImageFormat::Png,
+        };
+        let img =
+            image::load_from_memory_with_format(&self.data, format(&self.fmt)).map_err(|e| {
+                crate::Error::new(
+                    ErrorKind::ImageTranscoding,
+                    format!("Error decoding {:?} image: {e}", self.fmt),
+                )
+            })?;
+        let img = match target {
+            ImgFmt::Jpeg => image::DynamicImage::ImageRgb8(img.to_rgb8()),
+            _ => img,
+        };
+
+        let mut data = Vec::new();
+        img.write_to(&mut std::io::Cursor::new(&mut data), format(&target)).map_err(|e| {
+            crate::Error::new(
+                ErrorKind::ImageTranscoding,
+                format!("Error encoding {target:?} image: {e}"),
+            )
+        })?;
+        Ok(Img::new(target, data))
+    }
+}
+
 /// The image format used to store images inside the userdata of an MPEG-4 file.
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub enum ImgFmt {
@@ -368,6 +911,29 @@ impl ImgFmt {
     pub fn is_png(&self) -> bool {
         matches!(self, Self::Png)
     }
+
+    /// Detects the image format from the magic bytes at the start of the data.
+    pub fn from_magic(data: &[u8]) -> Option<Self> {
+        if data.starts_with(&[0xff, 0xd8, 0xff]) {
+            Some(Self::Jpeg)
+        } else if data.starts_with(b"\x89PNG\r\n\x1a\n") {
+            Some(Self::Png)
+        } else if data.starts_with(b"BM") {
+            Some(Self::Bmp)
+        } else {
+            None
+        }
+    }
+
+    /// Returns the image format corresponding to the file extension, ignoring case.
+    pub fn from_extension(extension: &str) -> Option<Self> {
+        match extension.to_ascii_lowercase().as_str() {
+            "jpg" | "jpeg" | "jpe" | "jfif" => Some(Self::Jpeg),
+            "png" => Some(Self::Png),
+            "bmp" | "dib" => Some(Self::Bmp),
+            _ => None,
+        }
+    }
 }
 
 /// A chapter.
@@ -376,16 +942,114 @@ impl ImgFmt {
 /// For chapter lists this limit is 255 ([`u8::MAX`]);
 /// For chapter tracks this limit is 65535 ([`u16::MAX`]);
 /// If this limit is exceeded the title is truncated.
-#[derive(Clone, Debug, Default, PartialEq, Eq)]
+///
+/// The url and image of a chapter can only be stored in a chapter track. The url is stored as a
+/// hypertext link (`href`) of the text sample, which is limited to 255 ([`u8::MAX`]) bytes. The
+/// image is read from an image track referenced alongside the text track, and is preserved, but
+/// not written.
+///
+/// Chapters read from a file retain their start in the ticks of the original timescale. When
+/// written with the same timescale, these ticks are used verbatim as long as they still
+/// correspond to the start, which avoids rounding errors accumulating over repeated edits. The
+/// ticks aren't considered when comparing chapters.
+#[derive(Clone, Debug, Default)]
 pub struct Chapter {
     /// The start of the chapter.
     pub start: Duration,
     /// The title of the chapter.
     pub title: String,
+    /// The url of the chapter.
+    pub url: Option<String>,
+    /// The image of the chapter.
+    pub image: Option<ImgBuf>,
+    /// The start of the chapter in ticks of the [`Self::timescale`] it was read with.
+    pub start_ticks: u64,
+    /// The timescale of the [`Self::start_ticks`], or 0 if unknown.
+    pub timescale: u32,
+}
+
+impl PartialEq for Chapter {
+    fn eq(&self, other: &Self) -> bool {
+        self.start == other.start
+            && self.title == other.title
+            && self.url == other.url
+            && self.image == other.image
+    }
 }
 
+impl Eq for Chapter {}
+
 impl Chapter {
     pub fn new(start: Duration, title: impl Into<String>) -> Self {
-        Self { start, title: title.into() }
+        Self { start, title: title.into(), ..Default::default() }
+    }
+
+    /// Returns the start in ticks of the timescale. The original [`Self::start_ticks`] are
+    /// returned if they have the same timescale and still correspond to the start.
+    ///
+    /// # Example
+    /// ```
+    /// use std::time::Duration;
+    /// use mp4ameta::Chapter;
+    ///
+    /// let mut chapter = Chapter::new(Duration::from_nanos(333_333_333), "first");
+    /// assert_eq!(chapter.start_ticks_in(3), 0);
+    ///
+    /// chapter.start_ticks = 1;
+    /// chapter.timescale = 3;
+    /// assert_eq!(chapter.start_ticks_in(3), 1);
+    /// ```
+    pub fn start_ticks_in(&self, timescale: u32) -> u64 {
+        let unchanged = self.timescale != 0
+            && self.timescale == timescale
+            && scale_duration(timescale, self.start_ticks) == self.start;
+        match unchanged {
+            true => self.start_ticks,
+            false => unscale_duration(timescale, self.start),
+        }
+    }
+
+    /// Returns the end of each chapter, which is the start of the following chapter, or the
+    /// `duration` of the file for the last chapter. The chapters are expected to be sorted.
+    ///
+    /// # Example
+    /// ```
+    /// use std::time::Duration;
+    /// use mp4ameta::Chapter;
+    ///
+    /// let chapters = [
+    ///     Chapter::new(Duration::ZERO, "first"),
+    ///     Chapter::new(Duration::from_secs(20), "second"),
+    /// ];
+    /// let ends = Chapter::ends(&chapters, Duration::from_secs(60));
+    /// assert_eq!(ends, [Duration::from_secs(20), Duration::from_secs(60)]);
+    /// ```
+    pub fn ends(chapters: &[Chapter], duration: Duration) -> Vec<Duration> {
+        let next_starts = chapters.iter().skip(1).map(|c| c.start);
+        next_starts.chain(chapters.last().map(|c| duration.max(c.start))).collect()
     }
 }
+
+/// The forms chapters are stored in, see [`Userdata::normalize_chapters`].
+///
+/// [`Userdata::normalize_chapters`]: crate::Userdata::normalize_chapters
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ChapterFormat {
+    /// Only a chapter list (`chpl`).
+    List,
+    /// Only a chapter track.
+    Track,
+    /// Both a chapter list (`chpl`) and a chapter track.
+    Both,
+}
+
+/// The way chapters are stored, see [`Userdata::chapters_source`].
+///
+/// [`Userdata::chapters_source`]: crate::Userdata::chapters_source
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ChapterSource {
+    /// A chapter list (`chpl`) inside the user data, as written by Nero and mp4chaps.
+    List,
+    /// A text track referenced by other tracks (`tref/chap`), as written by iTunes.
+    Track,
+}
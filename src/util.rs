@@ -22,6 +22,23 @@ pub(crate) fn format_duration(f: &mut fmt::Formatter<'_>, duration: Duration) ->
     }
 }
 
+/// The number of bytes shown by [`BytesPreview`].
+const PREVIEW_LEN: usize = 16;
+
+/// Debug formats only the first few bytes of a possibly large payload, like image data.
+pub(crate) struct BytesPreview<'a>(pub &'a [u8]);
+
+impl fmt::Debug for BytesPreview<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.0.get(..PREVIEW_LEN) {
+            Some(preview) if self.0.len() > PREVIEW_LEN => {
+                write!(f, "{preview:?}... ({} bytes)", self.0.len())
+            }
+            _ => write!(f, "{:?}", self.0),
+        }
+    }
+}
+
 pub(crate) fn format_chapters(
     f: &mut fmt::Formatter<'_>,
     chapters: &[Chapter],
@@ -0,0 +1,137 @@
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::cell::Cell;
+use std::fs;
+use std::io::Cursor;
+
+use mp4ameta::{ReadConfig, Tag, Userdata};
+
+/// An allocator counting the allocations and the allocated bytes of the current thread.
+struct CountingAllocator;
+
+thread_local! {
+    static ALLOCATIONS: Cell<usize> = const { Cell::new(0) };
+    static ALLOCATED: Cell<isize> = const { Cell::new(0) };
+    static PEAK_ALLOCATED: Cell<isize> = const { Cell::new(0) };
+}
+
+/// Adds the difference to the allocated bytes, updating the peak.
+fn track_allocated(diff: isize) {
+    let allocated = ALLOCATED.with(|a| {
+        a.set(a.get() + diff);
+        a.get()
+    });
+    PEAK_ALLOCATED.with(|p| p.set(p.get().max(allocated)));
+}
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.with(|a| a.set(a.get() + 1));
+        track_allocated(layout.size() as isize);
+        unsafe { System.alloc(layout) }
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        track_allocated(-(layout.size() as isize));
+        unsafe { System.dealloc(ptr, layout) }
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        ALLOCATIONS.with(|a| a.set(a.get() + 1));
+        track_allocated(new_size as isize - layout.size() as isize);
+        unsafe { System.realloc(ptr, layout, new_size) }
+    }
+}
+
+#[global_allocator]
+static GLOBAL: CountingAllocator = CountingAllocator;
+
+/// Reads the tag and returns the number of allocations.
+fn count_allocations(data: &[u8], cfg: &ReadConfig) -> usize {
+    let mut reader = Cursor::new(data);
+    let before = ALLOCATIONS.with(Cell::get);
+    let tag = Tag::read_with(&mut reader, cfg).unwrap();
+    let allocations = ALLOCATIONS.with(Cell::get) - before;
+    drop(tag);
+    allocations
+}
+
+#[test]
+fn allocations_proportional_to_kept_data() {
+    let files = [
+        "files/sample.m4a",
+        "files/sample-alac.m4a",
+        "files/sample-chaptered.m4a",
+        "files/sample.m4v",
+        "files/sample-multi-track.3gp",
+    ];
+    for path in files {
+        let data = fs::read(path).unwrap();
+
+        // only the file type is kept
+        let allocations = count_allocations(&data, &ReadConfig::NONE);
+        assert!(allocations <= 2, "{path}: {allocations} allocations");
+
+        // skipped atoms don't allocate
+        let mut padded = data.clone();
+        for _ in 0..1000 {
+            padded.extend(16u32.to_be_bytes());
+            padded.extend(b"free");
+            padded.extend([0; 8]);
+        }
+        for cfg in [ReadConfig::NONE, ReadConfig::DEFAULT] {
+            let allocations = count_allocations(&data, &cfg);
+            let padded_allocations = count_allocations(&padded, &cfg);
+            assert_eq!(allocations, padded_allocations, "{path}");
+        }
+    }
+}
+
+#[test]
+fn streamed_artwork_peak_memory() {
+    // 20 MiB of artwork is copied in chunks instead of being held in memory
+    let mut png = fs::read("files/artwork.png").unwrap();
+    png.resize(20 * 1024 * 1024, 0xa5);
+    fs::write("target/streamed_artwork_peak_memory.png", &png).unwrap();
+    drop(png);
+    fs::copy("files/sample.m4a", "target/streamed_artwork_peak_memory.m4a").unwrap();
+
+    let before = ALLOCATED.with(Cell::get);
+    PEAK_ALLOCATED.with(|p| p.set(before));
+    let mut tag = Tag::read_from_path("target/streamed_artwork_peak_memory.m4a").unwrap();
+    tag.set_artwork_streamed_from_path("target/streamed_artwork_peak_memory.png").unwrap();
+    tag.write_to_path("target/streamed_artwork_peak_memory.m4a").unwrap();
+    drop(tag);
+    let peak = PEAK_ALLOCATED.with(Cell::get) - before;
+    assert!(peak < 1024 * 1024, "{peak} bytes allocated at the peak");
+
+    let tag = Tag::read_from_path("target/streamed_artwork_peak_memory.m4a").unwrap();
+    assert_eq!(tag.artwork().unwrap().data.len(), 20 * 1024 * 1024);
+}
+
+#[test]
+fn large_artwork_from_path_peak_memory() {
+    // artwork above the threshold is streamed as well
+    let len = 2 * Userdata::STREAMED_ARTWORK_THRESHOLD as usize;
+    let mut png = fs::read("files/artwork.png").unwrap();
+    png.resize(len, 0x5a);
+    fs::write("target/large_artwork_from_path_peak_memory.png", &png).unwrap();
+    fs::copy("files/sample.m4a", "target/large_artwork_from_path_peak_memory.m4a").unwrap();
+
+    let before = ALLOCATED.with(Cell::get);
+    PEAK_ALLOCATED.with(|p| p.set(before));
+    let mut tag = Tag::read_from_path("target/large_artwork_from_path_peak_memory.m4a").unwrap();
+    tag.set_artwork_from_path("target/large_artwork_from_path_peak_memory.png").unwrap();
+    assert!(tag.artwork().is_none());
+    tag.write_to_path("target/large_artwork_from_path_peak_memory.m4a").unwrap();
+    drop(tag);
+    let peak = PEAK_ALLOCATED.with(Cell::get) - before;
+    assert!(peak < len as isize / 2, "{peak} bytes allocated at the peak");
+
+    let tag = Tag::read_from_path("target/large_artwork_from_path_peak_memory.m4a").unwrap();
+    assert_eq!(tag.artwork().unwrap().data, png);
+
+    // smaller artwork is read into memory
+    let mut tag = Tag::default();
+    tag.set_artwork_from_path("files/artwork.png").unwrap();
+    assert_eq!(tag.artwork().unwrap().data, fs::read("files/artwork.png").unwrap());
+}
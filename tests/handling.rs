@@ -1,4 +1,10 @@
-use mp4ameta::{Data, Img, STANDARD_GENRES, Tag, ident};
+use std::time::Duration;
+
+use mp4ameta::{
+    AudioInfo, Change, ChannelConfig, Chapter, Codec, Data, DataIdent, DiffMode, ErrorKind, Fourcc,
+    FreeformIdent, IdentMap, Img, MediaType, MergeOptions, MergePolicy, NormalizeOptions,
+    STANDARD_GENRES, SampleRate, Tag, ident,
+};
 
 #[test]
 fn multiple_value_handling() {
@@ -158,6 +164,25 @@ fn track_disc_handling() {
         tag.data_of(&ident::TRACK_NUMBER).next(),
         Some(&Data::Reserved(vec![0, 0, 0, 1, 0, 2, 0, 0]))
     );
+
+    // with and without a total number of discs
+    tag.set_disc_and_total(1, Some(2));
+    assert_eq!(tag.disc(), (Some(1), Some(2)));
+    assert_eq!(
+        tag.data_of(&ident::DISC_NUMBER).next(),
+        Some(&Data::Reserved(vec![0, 0, 0, 1, 0, 2, 0, 0]))
+    );
+
+    tag.set_disc_and_total(3, None);
+    assert_eq!(tag.disc(), (Some(3), None));
+    assert_eq!(
+        tag.data_of(&ident::DISC_NUMBER).next(),
+        Some(&Data::Reserved(vec![0, 0, 0, 3, 0, 0, 0, 0]))
+    );
+
+    // a short disc number atom without a total
+    tag.set_data(ident::DISC_NUMBER, Data::Reserved(vec![0, 0, 0, 1]));
+    assert_eq!(tag.disc(), (Some(1), None));
 }
 
 #[test]
@@ -171,7 +196,7 @@ fn work_movement_handling() {
     assert_eq!(tag.movement(), None);
     assert_eq!(tag.movement_count(), None);
     assert_eq!(tag.movement_index(), None);
-    assert_eq!(tag.show_movement(), false);
+    assert!(!tag.show_movement());
     assert_eq!(tag.work(), None);
 
     tag.set_movement(movement);
@@ -183,10 +208,411 @@ fn work_movement_handling() {
     assert_eq!(tag.movement(), Some(movement));
     assert_eq!(tag.movement_count(), Some(count));
     assert_eq!(tag.movement_index(), Some(index));
-    assert_eq!(tag.show_movement(), true);
+    assert!(tag.show_movement());
     assert_eq!(tag.work(), Some(work));
 }
 
+#[test]
+fn keywords_handling() {
+    let mut tag = Tag::default();
+    assert!(tag.keywords().is_empty());
+
+    tag.set_keyword("rock, live,  90s,");
+    assert_eq!(tag.keywords(), ["rock", "live", "90s"]);
+
+    tag.set_keyword("rock\nlive\r\n\n90s");
+    assert_eq!(tag.keywords(), ["rock", "live", "90s"]);
+
+    tag.set_keyword("rock, live\n90s");
+    assert_eq!(tag.keywords(), ["rock", "live", "90s"]);
+
+    tag.set_keywords(&["jazz", "studio"]);
+    assert_eq!(tag.keyword(), Some("jazz, studio"));
+    assert_eq!(tag.keywords(), ["jazz", "studio"]);
+
+    tag.set_keywords(&[]);
+    assert_eq!(tag.keyword(), None);
+    assert!(tag.keywords().is_empty());
+
+    // separate values are split and joined as well
+    tag.add_keyword("rock, live");
+    tag.add_keywords(["90s".to_owned(), "jazz".to_owned()]);
+    assert_eq!(tag.keyword(), Some("rock, live"));
+    assert_eq!(tag.keywords(), ["rock", "live", "90s", "jazz"]);
+    assert_eq!(tag.take_keywords().collect::<Vec<_>>(), ["rock, live", "90s", "jazz"]);
+    assert!(tag.keywords().is_empty());
+
+    tag.add_keyword("rock");
+    tag.remove_keywords();
+    assert_eq!(tag.keyword(), None);
+}
+
+#[test]
+fn bpm_handling() {
+    let mut tag = Tag::default();
+    assert_eq!(tag.bpm(), None);
+
+    tag.set_bpm(128);
+    assert_eq!(tag.bpm(), Some(128));
+    assert_eq!(tag.data_of(&ident::BPM).next(), Some(&Data::BeSigned(vec![0, 128])));
+
+    tag.set_bpm(0);
+    assert_eq!(tag.bpm(), None);
+
+    tag.remove_bpm();
+    assert_eq!(tag.data_of(&ident::BPM).next(), None);
+}
+
+#[test]
+fn artist_list_handling() {
+    let mut tag = Tag::default();
+    assert!(tag.artist_list().is_empty());
+
+    tag.set_artist("A");
+    assert_eq!(tag.artist_list(), ["A"]);
+
+    tag.set_artist("A; B");
+    assert_eq!(tag.artist_list(), ["A", "B"]);
+
+    tag.add_artist("C / D feat. E");
+    assert_eq!(tag.artist_list(), ["A", "B", "C", "D feat. E"]);
+    assert_eq!(tag.artist_list_with(&["feat."]), ["A; B", "C / D", "E"]);
+    assert_eq!(tag.artist(), Some("A; B"));
+}
+
+#[test]
+fn raw_purchase_date_handling() {
+    let mut tag = Tag::default();
+    assert_eq!(tag.raw_purchase_date(), None);
+
+    tag.set_raw_purchase_date("2012-03-04 05:06:07");
+    assert_eq!(tag.raw_purchase_date(), Some("2012-03-04 05:06:07"));
+
+    tag.remove_purchase_date();
+    assert_eq!(tag.raw_purchase_date(), None);
+}
+
+#[cfg(feature = "chrono")]
+#[test]
+fn purchase_date_handling() {
+    use chrono::{NaiveDate, NaiveDateTime};
+
+    let date: NaiveDateTime =
+        NaiveDate::from_ymd_opt(2012, 3, 4).unwrap().and_hms_opt(5, 6, 7).unwrap();
+    let mut tag = Tag::default();
+    assert_eq!(tag.purchase_date(), None);
+
+    // space separated
+    tag.set_raw_purchase_date("2012-03-04 05:06:07");
+    assert_eq!(tag.purchase_date(), Some(date));
+
+    // `T` separated
+    tag.set_raw_purchase_date("2012-03-04T05:06:07");
+    assert_eq!(tag.purchase_date(), Some(date));
+
+    // `T` separated with a trailing `Z`
+    tag.set_raw_purchase_date("2012-03-04T05:06:07Z");
+    assert_eq!(tag.purchase_date(), Some(date));
+
+    tag.set_raw_purchase_date("March 4th 2012");
+    assert_eq!(tag.purchase_date(), None);
+
+    tag.set_purchase_date(date);
+    assert_eq!(tag.raw_purchase_date(), Some("2012-03-04 05:06:07"));
+}
+
+#[test]
+fn year_handling() {
+    let mut tag = Tag::default();
+
+    for year in ["2013", "2013-05-21", "2013-05-21T07:00:00Z"] {
+        tag.set_year(year).unwrap();
+        assert_eq!(tag.year(), Some(year));
+    }
+
+    for year in ["13", "20134", "2013-5-21", "21.05.2013", "2013-05-21 07:00:00", "year"] {
+        let err = tag.set_year(year).unwrap_err();
+        assert!(matches!(err.kind, ErrorKind::InvalidData));
+        assert_eq!(tag.year(), Some("2013-05-21T07:00:00Z"));
+    }
+}
+
+#[cfg(feature = "chrono")]
+#[test]
+fn release_date_handling() {
+    use chrono::NaiveDate;
+
+    let mut tag = Tag::default();
+    tag.set_release_date(NaiveDate::from_ymd_opt(987, 6, 5).unwrap());
+    assert_eq!(tag.year(), Some("0987-06-05"));
+
+    tag.set_release_date(NaiveDate::from_ymd_opt(2013, 5, 21).unwrap());
+    assert_eq!(tag.year(), Some("2013-05-21"));
+}
+
+#[test]
+fn validate_chapters() {
+    let mut tag = Tag::default();
+    tag.validate_chapters().unwrap();
+
+    tag.chapter_track_mut().extend([
+        Chapter::new(Duration::ZERO, "first"),
+        Chapter::new(Duration::from_secs(1), "second"),
+    ]);
+    tag.validate_chapters().unwrap();
+
+    tag.chapter_track_mut().push(Chapter::new(Duration::from_secs(1), "third"));
+    let err = tag.validate_chapters().unwrap_err();
+    assert!(matches!(err.kind, ErrorKind::InvalidChapterList));
+
+    tag.chapter_track_mut().pop();
+    tag.chapter_list_mut().extend([
+        Chapter::new(Duration::from_secs(1), "second"),
+        Chapter::new(Duration::ZERO, "first"),
+    ]);
+    let err = tag.validate_chapters().unwrap_err();
+    assert!(matches!(err.kind, ErrorKind::InvalidChapterList));
+
+    // chapters have to start within the audio duration, if it is known
+    tag.chapter_list_mut().clear();
+    tag.info.duration = Duration::from_secs(2);
+    tag.validate_chapters().unwrap();
+    tag.info.duration = Duration::from_secs(1);
+    let err = tag.validate_chapters().unwrap_err();
+    assert!(matches!(err.kind, ErrorKind::InvalidChapterList));
+    assert!(tag.userdata.validate_chapters().is_ok());
+}
+
+#[test]
+fn chapter_editing() {
+    // a simple deterministic pseudo random number generator (xorshift)
+    let mut state = 0x2545_f491_4f6c_dd1d_u64;
+    let mut rand = |max: u64| {
+        state ^= state << 13;
+        state ^= state >> 7;
+        state ^= state << 17;
+        state % max
+    };
+
+    for _ in 0..200 {
+        let mut tag = Tag::default();
+        tag.info.duration = Duration::from_millis(1000);
+
+        for i in 0..rand(16) {
+            // starts with a precision of 100ns, the chapter list timescale
+            let start = Duration::from_nanos(rand(10_000_000) * 100);
+            let exists = tag.chapters().iter().any(|c| c.start == start);
+            let len = tag.chapters().len();
+            let res = tag.insert_chapter_at(start, format!("chapter {i}"));
+            assert_eq!(res.is_err(), exists);
+            assert_eq!(tag.chapters().len(), len + !exists as usize);
+            if let Ok(idx) = res {
+                assert_eq!(tag.chapters()[idx].start, start);
+            }
+            tag.validate_chapters().unwrap();
+        }
+
+        for _ in 0..rand(8) {
+            let len = tag.chapters().len();
+            if len == 0 {
+                break;
+            }
+            let idx = rand(len as u64) as usize;
+            let at = Duration::from_nanos(rand(10_000_000) * 100);
+            let start = tag.chapters()[idx].start;
+            let next_start = tag.chapters().get(idx + 1).map_or(Duration::MAX, |c| c.start);
+            let splittable = start < at && at < next_start;
+
+            let res = tag.split_chapter(idx, at);
+            assert_eq!(res.is_ok(), splittable);
+            if splittable {
+                assert_eq!(tag.chapters().len(), len + 1);
+                assert_eq!(tag.chapters()[idx + 1].start, at);
+                assert_eq!(tag.chapters()[idx + 1].title, tag.chapters()[idx].title);
+            }
+            tag.validate_chapters().unwrap();
+        }
+
+        let len = tag.chapters().len();
+        if len > 0 {
+            let start = rand(len as u64) as usize;
+            let end = start + 1 + rand((len - start) as u64) as usize;
+            let first = tag.chapters()[start].clone();
+            let following = tag.chapters()[end..].to_vec();
+
+            tag.merge_chapters(start..end);
+            assert_eq!(tag.chapters().len(), len - (end - start - 1));
+            assert_eq!(tag.chapters()[start], first);
+            assert_eq!(tag.chapters()[start + 1..], following);
+            tag.validate_chapters().unwrap();
+        }
+    }
+}
+
+#[test]
+fn artwork_handling() {
+    let mut tag = Tag::default();
+    assert_eq!(tag.artwork(), None);
+    assert_eq!(tag.take_artwork(), None);
+
+    tag.add_artwork(Img::png(b"FIRST".to_vec()));
+    tag.add_artwork(Img::jpeg(b"SECOND".to_vec()));
+
+    let artwork = tag.artwork().unwrap();
+    assert_eq!(artwork, Img::png(&b"FIRST"[..]));
+    let data_ptr = artwork.data.as_ptr();
+
+    // the image data is moved out of the tag, not copied
+    let artwork = tag.take_artwork().unwrap();
+    assert_eq!(artwork, Img::png(b"FIRST".to_vec()));
+    assert_eq!(artwork.data.as_ptr(), data_ptr);
+    assert_eq!(tag.artwork(), None);
+
+    tag.add_artwork(Img::png(b"FIRST".to_vec()));
+    tag.set_title("TITLE");
+    let clone = tag.clone_without_artwork();
+    assert_eq!(clone.artwork(), None);
+    assert_eq!(clone.title(), Some("TITLE"));
+    assert_eq!(tag.artwork(), Some(Img::png(&b"FIRST"[..])));
+
+    tag.add_artwork(Img::jpeg(b"SECOND".to_vec()));
+    assert_eq!(tag.remove_all_artwork(), 2);
+    assert_eq!(tag.artwork(), None);
+    assert_eq!(tag.data_of(&ident::ARTWORK).count(), 0);
+    assert_eq!(tag.remove_all_artwork(), 0);
+
+    // the whole metadata item is removed
+    tag.remove_title();
+    assert!(tag.meta_items_is_empty());
+}
+
+#[cfg(feature = "image")]
+#[test]
+fn artwork_transcoding() {
+    use mp4ameta::{ImgBuf, ImgFmt};
+
+    // a 2x1 pixel 24 bit BMP with a red and a blue pixel
+    #[rustfmt::skip]
+    let bmp = [
+        b'B', b'M', 62, 0, 0, 0, 0, 0, 0, 0, 54, 0, 0, 0,
+        40, 0, 0, 0, 2, 0, 0, 0, 1, 0, 0, 0, 1, 0, 24, 0, 0, 0, 0, 0, 8, 0, 0, 0,
+        0x13, 0x0b, 0, 0, 0x13, 0x0b, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+        0, 0, 255, 255, 0, 0, 0, 0,
+    ];
+
+    let png = ImgBuf::bmp(bmp.to_vec()).transcode(ImgFmt::Png).unwrap();
+    assert_eq!(png.fmt, ImgFmt::Png);
+    assert_eq!(png.data[..8], *b"\x89PNG\r\n\x1a\n");
+
+    let jpeg = png.clone().transcode(ImgFmt::Jpeg).unwrap();
+    assert_eq!(jpeg.fmt, ImgFmt::Jpeg);
+    assert_eq!(jpeg.data[..3], [0xff, 0xd8, 0xff]);
+
+    // the same format is returned unchanged
+    assert_eq!(png.clone().transcode(ImgFmt::Png).unwrap(), png);
+
+    let err = ImgBuf::bmp(b"no bmp".to_vec()).transcode(ImgFmt::Png).unwrap_err();
+    assert!(matches!(err.kind, ErrorKind::ImageTranscoding));
+}
+
+#[test]
+fn debug_output_is_bounded() {
+    let image = vec![0xab; 4 * 1024 * 1024];
+    let mut tag = Tag::default();
+    tag.add_artwork(Img::jpeg(image.clone()));
+    tag.set_data(Fourcc(*b"xxxx"), Data::Reserved(image.clone()));
+    tag.chapter_track_mut().push(Chapter {
+        image: Some(Img::png(image)),
+        ..Chapter::new(Duration::ZERO, "CHAPTER")
+    });
+
+    let debug = format!("{tag:#?}");
+    assert!(debug.len() < 8 * 1024, "debug output has {} bytes", debug.len());
+    assert!(debug.contains("(4194304 bytes)"));
+
+    assert_eq!(format!("{:?}", Data::Reserved(vec![1, 2])), "Data::Reserved([1, 2])");
+    assert_eq!(
+        format!("{:?}", Data::Png(vec![0; 17])),
+        "Data::Png([0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0]... (17 bytes))"
+    );
+    // the data is still fully accessible
+    assert_eq!(tag.artwork().unwrap().data.len(), 4 * 1024 * 1024);
+}
+
+#[test]
+fn text_fields() {
+    let mut tag = Tag::default();
+    tag.set_title("TITLE");
+    tag.set_artist("ARTIST");
+    tag.set_track(3, 12);
+    tag.set_artwork(Img::png(b"ARTWORK".to_vec()));
+    tag.set_compilation();
+    tag.set_label("LABEL");
+    tag.set_comment("");
+    tag.add_data(Fourcc(*b"test"), Data::Reserved(b"binary".to_vec()));
+
+    let fields: Vec<_> = tag.text_fields().collect();
+    assert_eq!(
+        fields,
+        [
+            (DataIdent::from(ident::TITLE), "TITLE"),
+            (DataIdent::from(ident::ARTIST), "ARTIST"),
+            (DataIdent::from(ident::LABEL), "LABEL"),
+        ]
+    );
+    assert!(!fields.iter().any(|(i, _)| ident::TRACK_NUMBER == *i || ident::ARTWORK == *i));
+}
+
+#[test]
+fn apply_handling() {
+    let mut tag = Tag::default();
+    tag.set_title("TITLE");
+    tag.set_album("ALBUM");
+    tag.set_isrc("ISRC");
+
+    tag.apply(&[
+        (ident::ARTIST.into(), Some(Data::Utf8("ARTIST".into()))),
+        (ident::TITLE.into(), None),
+        (ident::BPM.into(), Some(Data::BeSigned(vec![0, 120]))),
+        (ident::ISRC.into(), None),
+        (ident::LYRICIST.into(), Some(Data::Utf8("LYRICIST".into()))),
+        (ident::ALBUM.into(), Some(Data::Utf8("OTHER ALBUM".into()))),
+    ])
+    .unwrap();
+    assert_eq!(tag.artist(), Some("ARTIST"));
+    assert_eq!(tag.title(), None);
+    assert_eq!(tag.bpm(), Some(120));
+    assert_eq!(tag.isrc(), None);
+    assert_eq!(tag.lyricist(), Some("LYRICIST"));
+    assert_eq!(tag.album(), Some("OTHER ALBUM"));
+
+    // edits are applied in order
+    tag.apply(&[
+        (ident::TITLE.into(), Some(Data::Utf8("TITLE".into()))),
+        (ident::TITLE.into(), None),
+    ])
+    .unwrap();
+    assert_eq!(tag.title(), None);
+
+    // nothing is applied if a single edit is invalid
+    let before = tag.clone();
+    let err = tag
+        .apply(&[
+            (ident::ARTIST.into(), None),
+            (ident::BPM.into(), Some(Data::BeSigned(vec![0; 5]))),
+        ])
+        .unwrap_err();
+    assert!(matches!(err.kind, ErrorKind::InvalidData));
+    let err = tag
+        .apply(&[
+            (ident::ALBUM.into(), None),
+            (DataIdent::freeform("", "NAME"), Some(Data::Utf8("VALUE".into()))),
+        ])
+        .unwrap_err();
+    assert!(matches!(err.kind, ErrorKind::InvalidData));
+    assert_eq!(tag, before);
+}
+
 #[test]
 fn tag_destructuring() {
     let mut tag = Tag::default();
@@ -205,7 +631,7 @@ fn tag_destructuring() {
     tag.set_keyword("TEST KEYWORD");
     tag.set_lyrics("TEST LYRICS");
     tag.set_title("TEST TITLE");
-    tag.set_year("2013");
+    tag.set_year("2013").unwrap();
     tag.set_artwork(Img::png(b"TEST ARTWORK".to_vec()));
 
     assert_eq!(tag.take_album(), Some("TEST ALBUM".to_string()));
@@ -242,3 +668,675 @@ fn tag_destructuring() {
     assert_eq!(tag.year(), None);
     assert_eq!(tag.artwork(), None);
 }
+
+#[test]
+fn error_conversion() {
+    fn read_missing_file() -> mp4ameta::Result<Vec<u8>> {
+        Ok(std::fs::read("files/does-not-exist.m4a")?)
+    }
+    fn parse_fourcc(s: &str) -> mp4ameta::Result<Fourcc> {
+        Ok(s.parse()?)
+    }
+
+    let err = read_missing_file().unwrap_err();
+    assert!(matches!(err.kind, ErrorKind::Io(ref e) if e.kind() == std::io::ErrorKind::NotFound));
+
+    assert_eq!(parse_fourcc("catg").unwrap(), Fourcc(*b"catg"));
+    let err = parse_fourcc("cat").unwrap_err();
+    assert!(matches!(err.kind, ErrorKind::InvalidSliceLength));
+}
+
+#[test]
+fn normalize_handling() {
+    fn messy_tag() -> Tag {
+        let mut tag = Tag::default();
+        tag.set_standard_genre(4);
+        tag.set_custom_genre("genre");
+        tag.set_title("title \0\0");
+        tag.set_artists(["a".to_owned(), "b".to_owned(), "a".to_owned()]);
+        tag.set_album("");
+        tag.add_data(Fourcc(*b"test"), Data::Reserved(Vec::new()));
+        tag
+    }
+
+    // prefer standard genre
+    let mut tag = messy_tag();
+    tag.normalize_with(&NormalizeOptions {
+        prefer_standard_genre: true,
+        ..NormalizeOptions::NONE
+    });
+    assert_eq!(tag.standard_genre(), Some(4));
+    assert_eq!(tag.custom_genre(), None);
+    assert_eq!(tag.title(), Some("title \0\0"));
+
+    let mut tag = messy_tag();
+    tag.remove_standard_genres();
+    tag.normalize();
+    assert_eq!(tag.custom_genre(), Some("genre"));
+
+    // trim strings
+    let mut tag = messy_tag();
+    tag.normalize_with(&NormalizeOptions { trim_strings: true, ..NormalizeOptions::NONE });
+    assert_eq!(tag.title(), Some("title"));
+    assert_eq!(tag.custom_genre(), Some("genre"));
+
+    // remove empty
+    let mut tag = messy_tag();
+    tag.normalize_with(&NormalizeOptions { remove_empty: true, ..NormalizeOptions::NONE });
+    assert_eq!(tag.album(), None);
+    assert_eq!(tag.data_of(&Fourcc(*b"test")).next(), None);
+    assert_eq!(tag.artists().collect::<Vec<_>>(), ["a", "b", "a"]);
+
+    let mut tag = messy_tag();
+    tag.set_title(" \0");
+    tag.normalize();
+    assert_eq!(tag.title(), None);
+
+    // coalesce duplicates
+    let mut tag = messy_tag();
+    tag.normalize_with(&NormalizeOptions {
+        coalesce_duplicates: true,
+        ..NormalizeOptions::NONE
+    });
+    assert_eq!(tag.artists().collect::<Vec<_>>(), ["b", "a"]);
+    assert_eq!(tag.album(), Some(""));
+
+    // only the last value of single value items is kept
+    let mut tag = messy_tag();
+    tag.add_data(ident::TITLE, Data::Utf8("other title".to_owned()));
+    tag.add_data(ident::TRACK_NUMBER, Data::Reserved(vec![0, 0, 0, 1, 0, 2, 0, 0]));
+    tag.add_data(ident::TRACK_NUMBER, Data::Reserved(vec![0, 0, 0, 3, 0, 4, 0, 0]));
+    tag.normalize_with(&NormalizeOptions {
+        coalesce_duplicates: true,
+        ..NormalizeOptions::NONE
+    });
+    assert_eq!(tag.strings_of(&ident::TITLE).collect::<Vec<_>>(), ["other title"]);
+    assert_eq!(tag.data_of(&ident::TRACK_NUMBER).count(), 1);
+    assert_eq!(tag.track(), (Some(3), Some(4)));
+
+    // nothing
+    let mut tag = messy_tag();
+    tag.normalize_with(&NormalizeOptions::NONE);
+    assert_eq!(tag, messy_tag());
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn audio_info_serde() {
+    use mp4ameta::{ChannelConfig, SampleRate};
+
+    for config in [
+        ChannelConfig::Mono,
+        ChannelConfig::Stereo,
+        ChannelConfig::Three,
+        ChannelConfig::Four,
+        ChannelConfig::Five,
+        ChannelConfig::FiveOne,
+        ChannelConfig::SevenOne,
+    ] {
+        let json = serde_json::to_string(&config).unwrap();
+        assert_eq!(json, config.channel_count().to_string());
+        assert_eq!(serde_json::from_str::<ChannelConfig>(&json).unwrap(), config);
+    }
+    assert!(serde_json::from_str::<ChannelConfig>("7").is_err());
+
+    for rate in [
+        SampleRate::Hz96000,
+        SampleRate::Hz88200,
+        SampleRate::Hz64000,
+        SampleRate::Hz48000,
+        SampleRate::Hz44100,
+        SampleRate::Hz32000,
+        SampleRate::Hz24000,
+        SampleRate::Hz22050,
+        SampleRate::Hz16000,
+        SampleRate::Hz12000,
+        SampleRate::Hz11025,
+        SampleRate::Hz8000,
+        SampleRate::Hz7350,
+    ] {
+        let json = serde_json::to_string(&rate).unwrap();
+        assert_eq!(json, rate.hz().to_string());
+        assert_eq!(serde_json::from_str::<SampleRate>(&json).unwrap(), rate);
+    }
+    assert_eq!(serde_json::to_string(&SampleRate::Hz44100).unwrap(), "44100");
+    assert!(serde_json::from_str::<SampleRate>("44000").is_err());
+}
+
+#[test]
+fn audio_info_summary() {
+    let info = AudioInfo::default();
+    assert!(!info.is_lossless());
+    assert_eq!(info.summary(), "");
+
+    let mut info = AudioInfo {
+        codec: Some(Codec::Alac),
+        sample_rate: Some(SampleRate::Hz44100),
+        bit_depth: Some(16),
+        channel_config: Some(ChannelConfig::Stereo),
+        ..Default::default()
+    };
+    assert!(info.is_lossless());
+    assert_eq!(info.summary(), "ALAC 44.1 kHz / 16-bit / Stereo");
+    assert_eq!(info.to_string(), info.summary());
+
+    info.bit_depth = None;
+    info.sample_rate = Some(SampleRate::Hz48000);
+    assert_eq!(info.summary(), "ALAC 48 kHz / Stereo");
+
+    info.codec = None;
+    assert_eq!(info.summary(), "48 kHz / Stereo");
+
+    let mut info = AudioInfo {
+        codec: Some(Codec::AacLc),
+        avg_bitrate: Some(256_000),
+        max_bitrate: Some(320_000),
+        bit_depth: Some(16),
+        ..Default::default()
+    };
+    assert!(!info.is_lossless());
+    assert_eq!(info.summary(), "AAC-LC 256 kbps VBR");
+
+    info.max_bitrate = Some(256_000);
+    assert_eq!(info.summary(), "AAC-LC 256 kbps");
+
+    info.bitrate_estimated = true;
+    info.max_bitrate = None;
+    assert_eq!(info.summary(), "AAC-LC ~256 kbps");
+
+    info.avg_bitrate = Some(0);
+    assert_eq!(info.summary(), "AAC-LC");
+
+    let info = AudioInfo {
+        codec: Some(Codec::HeAac),
+        sample_rate: Some(SampleRate::Hz22050),
+        extension_sample_rate: Some(44100),
+        spectral_band_replication: Some(true),
+        channel_config: Some(ChannelConfig::Mono),
+        ..Default::default()
+    };
+    assert_eq!(info.summary(), "HE-AAC 44.1 kHz / Mono");
+
+    let info = AudioInfo {
+        codec: Some(Codec::Other(Fourcc(*b"fLaC"))),
+        sample_rate: Some(SampleRate::Hz22050),
+        bit_depth: Some(24),
+        ..Default::default()
+    };
+    assert!(info.is_lossless());
+    assert_eq!(info.summary(), "fLaC 22.05 kHz / 24-bit");
+}
+
+#[test]
+fn mutable_item_access() {
+    let mut tag = Tag::default();
+    tag.set_title("title");
+    tag.add_artist("artist 1");
+    tag.add_artist("artist 2");
+    tag.set_bpm(120);
+
+    for artist in tag.strings_mut_of(&ident::ARTIST) {
+        artist.push('!');
+    }
+    assert_eq!(tag.artists().collect::<Vec<_>>(), ["artist 1!", "artist 2!"]);
+
+    let data = tag.data_mut_of(&ident::BPM).next().unwrap();
+    *data = Data::u16_be(90);
+    assert_eq!(tag.bpm(), Some(90));
+
+    // only existing items are matched and nothing else is modified
+    assert_eq!(tag.data_mut_of(&ident::ALBUM).count(), 0);
+    assert_eq!(tag.title(), Some("title"));
+    assert_eq!(tag.album(), None);
+}
+
+#[test]
+fn ident_map_lookup() {
+    assert_eq!(DataIdent::from(ident::TITLE).fourcc_ref(), Some(b"\xa9nam"));
+    assert_eq!(DataIdent::from(ident::ISRC).fourcc_ref(), None);
+
+    let idents = [ident::TITLE, ident::ARTIST, ident::ALBUM, ident::ARTWORK, ident::BPM];
+    let mut map = IdentMap::new();
+    for (i, ident) in idents.iter().enumerate() {
+        assert_eq!(map.insert(*ident, i), None);
+    }
+    assert_eq!(map.insert(ident::LYRICIST, 10), None);
+    assert_eq!(map.insert(ident::LYRICIST, 11), Some(10));
+    assert_eq!(map.len(), 6);
+
+    // raw heads, as they would be read from a file
+    let heads: Vec<[u8; 4]> = (0..100_000).map(|i| idents[i % idents.len()].0).collect();
+    let start = std::time::Instant::now();
+    let sum: usize = heads.iter().filter_map(|h| map.get_fourcc(h)).sum();
+    println!("100000 lookups took {:?}", start.elapsed());
+    assert_eq!(sum, 20_000 * (1 + 2 + 3 + 4));
+    assert_eq!(map.get_fourcc(b"xxxx"), None);
+
+    assert_eq!(map.get(&ident::ALBUM), Some(&2));
+    assert_eq!(map.get(&ident::LYRICIST), Some(&11));
+    assert_eq!(map.get(&DataIdent::freeform("com.apple.iTunes", "LYRICIST")), Some(&11));
+    *map.get_mut(&ident::BPM).unwrap() = 40;
+    assert_eq!(map.get_fourcc(b"tmpo"), Some(&40));
+    assert_eq!(map.remove(&ident::LYRICIST), Some(11));
+    assert_eq!(map.remove(&ident::LYRICIST), None);
+    assert_eq!(map.len(), 5);
+}
+
+/// Assembles an atom from its fourcc and content.
+fn atom(fourcc: &[u8; 4], content: &[&[u8]]) -> Vec<u8> {
+    let content = content.concat();
+    let mut buf = (8 + content.len() as u32).to_be_bytes().to_vec();
+    buf.extend(fourcc);
+    buf.extend(content);
+    buf
+}
+
+#[test]
+fn tag_from_assembled_atoms() {
+    let ftyp = atom(b"ftyp", &[b"M4A ", &[0; 4], b"isom"]);
+    let mvhd = atom(b"mvhd", &[&[0; 12], &1000u32.to_be_bytes(), &2500u32.to_be_bytes(), &[0; 80]]);
+    let hdlr = atom(b"hdlr", &[&[0; 8], b"mdir", b"appl", &[0; 9]]);
+    let title = atom(b"data", &[&[0, 0, 0, 1, 0, 0, 0, 0], b"TITLE"]);
+    let ilst = atom(b"ilst", &[&atom(b"\xa9nam", &[&title])]);
+    let meta = atom(b"meta", &[&[0; 4], &hdlr, &ilst]);
+    let moov = atom(b"moov", &[&mvhd, &atom(b"udta", &[&meta])]);
+
+    let buf = [ftyp, moov].concat();
+    let tag = Tag::read_from(&mut std::io::Cursor::new(buf)).unwrap();
+    assert!(tag.file_type().is_audio());
+    assert_eq!(tag.duration(), Duration::from_millis(2500));
+    assert_eq!(tag.title(), Some("TITLE"));
+    assert_eq!(tag.validate(), []);
+
+    // the hierarchy is validated
+    let moov = atom(b"moov", &[&atom(b"udta", &[])]);
+    let buf = [atom(b"ftyp", &[b"M4A ", &[0; 4]]), moov].concat();
+    let err = Tag::read_from(&mut std::io::Cursor::new(buf)).unwrap_err();
+    assert!(matches!(err.kind, ErrorKind::AtomNotFound(f) if f == Fourcc(*b"mvhd")));
+}
+
+#[test]
+fn freeform_accessors() {
+    const MEAN: &str = "com.apple.iTunes";
+    const TRACK_ID: &str = "MusicBrainz Track Id";
+    let mut tag = Tag::default();
+    assert_eq!(tag.freeform(MEAN, TRACK_ID), None);
+
+    tag.set_freeform(MEAN, TRACK_ID, "f0c2c3e4-1111-4a2b-9c3d-0123456789ab");
+    assert_eq!(tag.freeform(MEAN, TRACK_ID), Some("f0c2c3e4-1111-4a2b-9c3d-0123456789ab"));
+    assert_eq!(tag.freeform(MEAN, "MusicBrainz Album Id"), None);
+    assert_eq!(tag.freeform("com.example", TRACK_ID), None);
+
+    // the value is replaced
+    tag.set_freeform(MEAN, TRACK_ID, "5a1b2c3d-2222-4e5f-8a9b-abcdef012345");
+    let ident = DataIdent::freeform(MEAN, TRACK_ID);
+    assert_eq!(
+        tag.strings_of(&ident).collect::<Vec<_>>(),
+        ["5a1b2c3d-2222-4e5f-8a9b-abcdef012345"]
+    );
+
+    tag.remove_freeform(MEAN, TRACK_ID);
+    assert_eq!(tag.freeform(MEAN, TRACK_ID), None);
+    assert!(tag.is_empty());
+}
+
+#[test]
+fn diff_handling() {
+    let mut old = Tag::default();
+    old.set_title("title");
+    old.set_album("album");
+    old.set_artists(["a".to_owned(), "b".to_owned()]);
+    old.set_artwork(Img::png(vec![1, 2, 3]));
+    old.set_data(FreeformIdent::new_static("com.apple.iTunes", "ISRC"), Data::Utf8("isrc".into()));
+    assert!(old.diff(&old).is_empty());
+    assert!(old.diff(&old).to_string().is_empty());
+
+    let mut new = old.clone();
+    new.set_title("new title");
+    new.remove_album();
+    new.add_artist("c");
+    new.set_artwork(Img::png(vec![1, 2, 4]));
+    new.set_comment("comment");
+    new.remove_data_of(&FreeformIdent::new_static("com.apple.iTunes", "ISRC"));
+    new.set_data(
+        FreeformIdent::new_static("com.apple.iTunes", "LABEL"),
+        Data::Utf8("label".into()),
+    );
+
+    let diff = old.diff(&new);
+    assert_eq!(diff.added().count(), 2);
+    assert_eq!(diff.removed().count(), 2);
+    assert_eq!(diff.modified().count(), 3);
+    let diff = diff.into_iter().collect::<Vec<_>>();
+    let utf8 = |s: &str| Data::Utf8(s.to_owned());
+    assert_eq!(
+        diff,
+        [
+            (
+                ident::TITLE.into(),
+                Change::Modified {
+                    old: vec![&utf8("title")],
+                    new: vec![&utf8("new title")]
+                },
+            ),
+            (ident::ALBUM.into(), Change::Removed(vec![&utf8("album")])),
+            (
+                ident::ARTIST.into(),
+                Change::Modified {
+                    old: vec![&utf8("a"), &utf8("b")],
+                    new: vec![&utf8("a"), &utf8("b"), &utf8("c")],
+                },
+            ),
+            (
+                ident::ARTWORK.into(),
+                Change::Modified {
+                    old: vec![&Data::Png(vec![1, 2, 3])],
+                    new: vec![&Data::Png(vec![1, 2, 4])],
+                },
+            ),
+            (
+                FreeformIdent::new_static("com.apple.iTunes", "ISRC").into(),
+                Change::Removed(vec![&utf8("isrc")]),
+            ),
+            (ident::COMMENT.into(), Change::Added(vec![&utf8("comment")])),
+            (
+                FreeformIdent::new_static("com.apple.iTunes", "LABEL").into(),
+                Change::Added(vec![&utf8("label")]),
+            ),
+        ]
+    );
+
+    // the reverse diff swaps additions and removals
+    let reverse = new.diff(&old);
+    assert_eq!(reverse.len(), diff.len());
+    let reverse = reverse.into_iter().collect::<Vec<_>>();
+    assert!(reverse.contains(&(ident::ALBUM.into(), Change::Added(vec![&utf8("album")]))));
+    assert!(reverse.contains(&(ident::COMMENT.into(), Change::Removed(vec![&utf8("comment")]))));
+}
+
+#[test]
+fn diff_modes() {
+    let mut old = Tag::default();
+    old.set_title("title");
+    old.set_artists(["a".to_owned(), "b".to_owned()]);
+    old.set_artwork(Img::png(vec![0; 1024]));
+
+    // artwork replacement is summarized by the length
+    let mut new = old.clone();
+    new.set_artwork(Img::jpeg(vec![1; 2048]));
+    let diff = new.diff(&old);
+    assert_eq!(diff.modified().collect::<Vec<_>>(), [&ident::ARTWORK.into()]);
+    assert_eq!(diff.to_string(), "~ covr: [<2048 bytes>] -> [<1024 bytes>]\n");
+    let diff = old.diff(&new);
+    assert_eq!(diff.to_string(), "~ covr: [<1024 bytes>] -> [<2048 bytes>]\n");
+    let (_, change) = diff.iter().next().unwrap();
+    assert_eq!(format!("{change:?}"), "Modified { old: [<1024 bytes>], new: [<2048 bytes>] }");
+
+    // value edits
+    let mut new = old.clone();
+    new.set_title("new title");
+    let diff = old.diff(&new);
+    assert_eq!(diff.to_string(), "~ ©nam: [\"title\"] -> [\"new title\"]\n");
+
+    // pure reordering of items and values
+    let mut new = Tag::default();
+    new.set_artwork(Img::png(vec![0; 1024]));
+    new.set_artists(["b".to_owned(), "a".to_owned()]);
+    new.set_title("title");
+    assert!(old.diff(&new).is_empty());
+    assert!(old.diff_with(&new, DiffMode::Unordered).is_empty());
+
+    let diff = old.diff_with(&new, DiffMode::Positional);
+    assert_eq!(diff.modified().collect::<Vec<_>>(), [&ident::ARTIST.into()]);
+    assert_eq!(diff.to_string(), "~ ©ART: [\"a\", \"b\"] -> [\"b\", \"a\"]\n");
+
+    // duplicate values are compared as a multiset
+    let mut new = old.clone();
+    new.set_artists(["a".to_owned(), "a".to_owned()]);
+    assert_eq!(old.diff(&new).len(), 1);
+    let mut dup = old.clone();
+    dup.set_artists(["a".to_owned(), "a".to_owned(), "b".to_owned()]);
+    assert_eq!(old.diff(&dup).len(), 1);
+}
+
+#[test]
+fn item_iteration() {
+    let mut src = Tag::default();
+    src.set_title("title");
+    src.set_artists(["a".to_owned(), "b".to_owned()]);
+    src.set_artwork(Img::png(vec![1, 2, 3]));
+    src.set_data(FreeformIdent::new_static("com.apple.iTunes", "ISRC"), Data::Utf8("isrc".into()));
+
+    let items = src.items().map(|(i, d)| (i.clone(), d.clone())).collect::<Vec<_>>();
+    assert_eq!(items.len(), 5);
+    assert_eq!(items[1], (ident::ARTIST.into(), Data::Utf8("a".into())));
+    assert_eq!(items[2], (ident::ARTIST.into(), Data::Utf8("b".into())));
+
+    for (_, data) in src.items_mut() {
+        if let Some(s) = data.string_mut() {
+            s.push('!');
+        }
+    }
+    assert_eq!(src.title(), Some("title!"));
+    assert_eq!(src.artists().collect::<Vec<_>>(), ["a!", "b!"]);
+
+    // copy all items to another tag
+    let mut dst = Tag::default();
+    for (ident, data) in src.clone() {
+        dst.add_data(ident, data);
+    }
+    assert_eq!(dst.userdata, src.userdata);
+    assert_eq!(src.into_iter().count(), 5);
+}
+
+#[test]
+fn predicate_removal() {
+    fn cluttered_tag() -> Tag {
+        let mut tag = Tag::default();
+        tag.set_title("title");
+        tag.set_album("");
+        tag.set_artists(["a".to_owned(), String::new(), "b".to_owned()]);
+        tag.set_data(
+            FreeformIdent::new_static("com.apple.iTunes", "ISRC"),
+            Data::Utf8("isrc".into()),
+        );
+        tag.set_data(FreeformIdent::new_static("com.example", "keep"), Data::Utf8("keep".into()));
+        tag.set_data(Fourcc(*b"test"), Data::Reserved(vec![1, 2]));
+        tag
+    }
+
+    // freeform items outside of an allowlist
+    let mut tag = cluttered_tag();
+    let removed = tag
+        .remove_if(|i, _| matches!(i, DataIdent::Freeform { mean, .. } if mean != "com.example"));
+    assert_eq!(removed, 1);
+    assert_eq!(tag.isrc(), None);
+    assert_eq!(tag.strings_of(&FreeformIdent::new_static("com.example", "keep")).count(), 1);
+    assert_eq!(tag.data().count(), 7);
+
+    // empty strings, with per-value removal of multi-value items
+    let mut tag = cluttered_tag();
+    let drained = tag.drain_if(|_, d| d.string() == Some(""));
+    assert_eq!(
+        drained,
+        [
+            (ident::ALBUM.into(), Data::Utf8(String::new())),
+            (ident::ARTIST.into(), Data::Utf8(String::new())),
+        ]
+    );
+    assert_eq!(tag.album(), None);
+    assert_eq!(tag.artists().collect::<Vec<_>>(), ["a", "b"]);
+    assert_eq!(tag.bytes_of(&Fourcc(*b"test")).next(), Some(&[1, 2][..]));
+
+    // everything but the title, including unknown items
+    let mut tag = cluttered_tag();
+    assert_eq!(tag.retain(|i, _| ident::TITLE == *i), 7);
+    assert_eq!(tag.title(), Some("title"));
+    assert_eq!(tag.data().count(), 1);
+
+    assert_eq!(tag.retain(|_, _| true), 0);
+    assert_eq!(tag.remove_if(|_, _| true), 1);
+    assert!(tag.is_empty());
+}
+
+#[test]
+fn podcast_media_type() {
+    let mut tag = Tag::default();
+    assert!(!tag.podcast());
+
+    tag.set_media_type(MediaType::Podcast);
+    assert_eq!(tag.media_type(), Some(MediaType::Podcast));
+    assert!(tag.podcast());
+    assert_eq!(tag.bytes_of(&ident::PODCAST).next(), Some(&[1][..]));
+    assert!(tag.validate().is_empty());
+
+    tag.set_media_type(MediaType::AudioBook);
+    assert_eq!(tag.media_type(), Some(MediaType::AudioBook));
+    assert!(!tag.podcast());
+
+    // the flag is only removed along with a podcast media type
+    tag.set_podcast();
+    tag.remove_media_type();
+    assert!(tag.podcast());
+
+    tag.set_media_type(MediaType::Podcast);
+    tag.remove_media_type();
+    assert_eq!(tag.media_type(), None);
+    assert!(!tag.podcast());
+}
+
+#[test]
+fn merge_handling() {
+    fn base() -> Tag {
+        let mut tag = Tag::default();
+        tag.set_title("title");
+        tag.set_artists(["a".to_owned(), "b".to_owned()]);
+        tag.set_isrc("isrc");
+        tag
+    }
+    fn overlapping() -> Tag {
+        let mut tag = Tag::default();
+        tag.set_title("other title");
+        tag.set_artists(["b".to_owned(), "c".to_owned(), "c".to_owned()]);
+        tag.set_album("album");
+        tag
+    }
+    fn disjoint() -> Tag {
+        let mut tag = Tag::default();
+        tag.set_album("album");
+        tag.set_label("label");
+        tag
+    }
+
+    // keep self, adding values to items holding multiple values
+    let mut tag = base();
+    tag.set_track(1, 10);
+    let mut other = overlapping();
+    other.set_track(2, 12);
+    tag.merge(&other, MergePolicy::KeepSelf);
+    assert_eq!(tag.strings_of(&ident::TITLE).collect::<Vec<_>>(), ["title"]);
+    assert_eq!(tag.artists().collect::<Vec<_>>(), ["a", "b", "c"]);
+    assert_eq!(tag.data_of(&ident::TRACK_NUMBER).count(), 1);
+    assert_eq!(tag.track(), (Some(1), Some(10)));
+    assert_eq!(tag.album(), Some("album"));
+    assert_eq!(tag.isrc(), Some("isrc"));
+    assert!(tag.validate().is_empty());
+
+    // prefer other
+    let mut tag = base();
+    tag.merge(&overlapping(), MergePolicy::PreferOther);
+    assert_eq!(tag.strings_of(&ident::TITLE).collect::<Vec<_>>(), ["other title"]);
+    assert_eq!(tag.artists().collect::<Vec<_>>(), ["b", "c"]);
+    assert_eq!(tag.album(), Some("album"));
+    assert_eq!(tag.isrc(), Some("isrc"));
+
+    // other if missing, leaving existing items untouched
+    let mut tag = base();
+    tag.merge(&overlapping(), MergePolicy::OtherIfMissing);
+    assert_eq!(tag.strings_of(&ident::TITLE).collect::<Vec<_>>(), ["title"]);
+    assert_eq!(tag.artists().collect::<Vec<_>>(), ["a", "b"]);
+    assert_eq!(tag.album(), Some("album"));
+    assert_eq!(tag.isrc(), Some("isrc"));
+
+    // disjoint tags result in the same union with every policy
+    for policy in [MergePolicy::KeepSelf, MergePolicy::PreferOther, MergePolicy::OtherIfMissing] {
+        let mut tag = base();
+        tag.merge(&disjoint(), policy);
+        let mut expected = base();
+        expected.set_album("album");
+        expected.set_label("label");
+        assert_eq!(tag, expected, "{policy:?}");
+    }
+
+    // merging a tag into itself doesn't change anything
+    for policy in [MergePolicy::KeepSelf, MergePolicy::PreferOther, MergePolicy::OtherIfMissing] {
+        let mut tag = base();
+        tag.merge(&base(), policy);
+        assert_eq!(tag, base(), "{policy:?}");
+    }
+
+    // per identifier overrides
+    let mut options = MergeOptions::new(MergePolicy::OtherIfMissing);
+    options.overrides.insert(ident::ARTIST, MergePolicy::KeepSelf);
+    options.overrides.insert(ident::TITLE, MergePolicy::PreferOther);
+    let mut tag = base();
+    tag.merge_with(&overlapping(), &options);
+    assert_eq!(tag.strings_of(&ident::TITLE).collect::<Vec<_>>(), ["other title"]);
+    assert_eq!(tag.artists().collect::<Vec<_>>(), ["a", "b", "c"]);
+    assert_eq!(tag.album(), Some("album"));
+}
+
+#[cfg(feature = "image")]
+#[test]
+fn merge_larger_artwork() {
+    fn png(size: u32) -> Img<Vec<u8>> {
+        let mut data = Vec::new();
+        let img = image::RgbImage::new(size, size);
+        img.write_to(&mut std::io::Cursor::new(&mut data), image::ImageFormat::Png).unwrap();
+        Img::png(data)
+    }
+
+    let options = MergeOptions {
+        prefer_larger_artwork: true,
+        ..MergeOptions::new(MergePolicy::PreferOther)
+    };
+
+    let mut tag = Tag::default();
+    tag.set_artwork(png(4));
+    let mut smaller = Tag::default();
+    smaller.set_artwork(png(2));
+    tag.merge_with(&smaller, &options);
+    assert_eq!(tag.artworks().map(|a| a.data.to_vec()).collect::<Vec<_>>(), [png(4).data]);
+
+    let mut larger = Tag::default();
+    larger.set_artwork(png(8));
+    tag.merge_with(&larger, &options);
+    assert_eq!(tag.artworks().map(|a| a.data.to_vec()).collect::<Vec<_>>(), [png(8).data]);
+
+    // artwork that can't be decoded is still added if there is none
+    let mut undecodable = Tag::default();
+    undecodable.set_artwork(Img::jpeg(b"not an image".to_vec()));
+    let mut tag = Tag::default();
+    tag.merge_with(&undecodable, &options);
+    assert_eq!(tag.artworks().map(|a| a.data.to_vec()).collect::<Vec<_>>(), [b"not an image"]);
+
+    // but doesn't replace existing artwork
+    let mut tag = Tag::default();
+    tag.set_artwork(png(4));
+    tag.merge_with(&undecodable, &options);
+    assert_eq!(tag.artworks().map(|a| a.data.to_vec()).collect::<Vec<_>>(), [png(4).data]);
+
+    // without the heuristic, the policy applies
+    let mut tag = Tag::default();
+    tag.set_artwork(png(4));
+    tag.merge(&smaller, MergePolicy::PreferOther);
+    assert_eq!(tag.artworks().map(|a| a.data.to_vec()).collect::<Vec<_>>(), [png(2).data]);
+
+    // an override for the artwork takes precedence over the heuristic
+    let mut options = options.clone();
+    options.overrides.insert(ident::ARTWORK, MergePolicy::PreferOther);
+    let mut tag = Tag::default();
+    tag.set_artwork(png(4));
+    tag.merge_with(&smaller, &options);
+    assert_eq!(tag.artworks().map(|a| a.data.to_vec()).collect::<Vec<_>>(), [png(2).data]);
+}
@@ -1,9 +1,15 @@
-use std::fs;
+use std::fs::{self, File};
+use std::io::{BufReader, Read, Seek, SeekFrom, Write};
+use std::num::NonZeroU32;
+use std::ops::Range;
 use std::path::{Path, PathBuf};
-use std::time::Duration;
+use std::time::{Duration, UNIX_EPOCH};
 
 use mp4ameta::{
-    AdvisoryRating, ChannelConfig, Chapter, Data, Fourcc, Img, MediaType, SampleRate, Tag, Userdata,
+    AdvisoryRating, BackupMode, BitrateKind, ChannelConfig, Chapter, ChapterFormat, ChapterSource,
+    ChplTimescale, Codec, Data, DataIdent, DataSource, ErrorKind, Fourcc, FreeformIdent, Img,
+    ImgFmt, ItemOrder, MediaType, PaddingPosition, ReadConfig, SampleRate, Tag, Timestamp,
+    Userdata, ValidationIssue, WriteConfig, WriteOutcome, ident,
 };
 use walkdir::WalkDir;
 
@@ -69,9 +75,61 @@ fn use_sample_file<'a>(in_file: &str, target_file: &'a str) -> &'a str {
 }
 
 #[track_caller]
-fn write_tag(tag: &Userdata, target_file: &str) {
+fn write_tag(tag: &Userdata, target_file: &str) -> WriteOutcome {
     println!("writing to `{target_file}`...");
-    tag.write_to_path(target_file).unwrap();
+    tag.write_to_path(target_file).unwrap()
+}
+
+/// Returns the fourcc and range of all atoms inside `buf[start..end]`.
+fn child_atoms(buf: &[u8], start: usize, end: usize) -> Vec<([u8; 4], Range<usize>)> {
+    let mut atoms = Vec::new();
+    let mut pos = start;
+    while pos + 8 <= end {
+        let mut len = u32::from_be_bytes(buf[pos..pos + 4].try_into().unwrap()) as usize;
+        if len == 1 {
+            len = u64::from_be_bytes(buf[pos + 8..pos + 16].try_into().unwrap()) as usize;
+        }
+        let fourcc = buf[pos + 4..pos + 8].try_into().unwrap();
+        atoms.push((fourcc, pos..pos + len));
+        pos += len;
+    }
+    atoms
+}
+
+/// Returns the range of the atom at the path, including its head.
+#[track_caller]
+fn find_atom(buf: &[u8], path: &[&[u8; 4]]) -> Option<Range<usize>> {
+    let mut range = 0..buf.len();
+    let mut content_start = 0;
+    for fourcc in path {
+        let atoms = child_atoms(buf, content_start, range.end);
+        range = atoms.into_iter().find(|(f, _)| f == *fourcc)?.1;
+        // the meta atom is a full atom with an additional 4 bytes of version and flags
+        content_start = range.start + if *fourcc == b"meta" { 12 } else { 8 };
+    }
+    Some(range)
+}
+
+/// Returns the first chunk offset (`stco` or `co64`) of the first track.
+#[track_caller]
+fn first_chunk_offset(buf: &[u8]) -> usize {
+    let stbl: &[&[u8; 4]] = &[b"moov", b"trak", b"mdia", b"minf", b"stbl"];
+    let stbl = find_atom(buf, stbl).unwrap();
+    let atoms = child_atoms(buf, stbl.start + 8, stbl.end);
+    let (fourcc, range) = atoms.iter().find(|(f, _)| f == b"stco" || f == b"co64").unwrap();
+    let pos = range.start + 16;
+    match fourcc {
+        b"stco" => u32::from_be_bytes(buf[pos..pos + 4].try_into().unwrap()) as usize,
+        _ => u64::from_be_bytes(buf[pos..pos + 8].try_into().unwrap()) as usize,
+    }
+}
+
+/// Returns the media data (`mdat`) atom, including its head, of the file.
+#[track_caller]
+fn read_mdat(file: &str) -> (usize, Vec<u8>) {
+    let buf = fs::read(file).unwrap();
+    let range = find_atom(&buf, &[b"mdat"]).unwrap();
+    (range.start, buf[range].to_vec())
 }
 
 #[track_caller]
@@ -103,7 +161,7 @@ fn get_tag_1() -> Tag {
     tag.set_media_type(MediaType::Normal);
     tag.set_title("TEST TITLE");
     tag.set_track(7, 13);
-    tag.set_year("2013");
+    tag.set_year("2013").unwrap();
     tag.set_artwork(Img::png(fs::read("files/artwork.png").unwrap()));
     tag.set_isrc("TEST ISRC");
     tag.set_lyricist("TEST LYRICIST");
@@ -133,7 +191,7 @@ fn get_tag_2() -> Tag {
     tag.set_media_type(MediaType::AudioBook);
     tag.set_title("NEW TITLE");
     tag.set_track(3, 7);
-    tag.set_year("1998");
+    tag.set_year("1998").unwrap();
     tag.set_artwork(Img::jpeg(b"NEW ARTWORK".to_vec()));
     tag.set_isrc("NEW ISRC");
     tag.set_lyricist("NEW LYRICIST");
@@ -161,7 +219,7 @@ fn assert_tag_1(tag: &Tag) {
     assert_eq!(tag.bpm(), Some(132));
     assert_eq!(tag.category(), Some("TEST CATEGORY"));
     assert_eq!(tag.comment(), Some("TEST COMMENT"));
-    assert_eq!(tag.compilation(), true);
+    assert!(tag.compilation());
     assert_eq!(tag.composer(), Some("TEST COMPOSER"));
     assert_eq!(tag.copyright(), Some("TEST COPYRIGHT"));
     assert_eq!(tag.description(), Some("TEST DESCRIPTION"));
@@ -169,7 +227,7 @@ fn assert_tag_1(tag: &Tag) {
     assert_eq!(tag.disc_number(), Some(1));
     assert_eq!(tag.total_discs(), Some(2));
     assert_eq!(tag.encoder(), Some("Lavf58.29.100"));
-    assert_eq!(tag.gapless_playback(), true);
+    assert!(tag.gapless_playback());
     assert_eq!(tag.genre(), Some("Hard Rock"));
     assert_eq!(tag.grouping(), Some("TEST GROUPING"));
     assert_eq!(tag.keyword(), Some("TEST KEYWORD"));
@@ -194,7 +252,7 @@ fn assert_tag_2(tag: &Tag) {
     assert_eq!(tag.bpm(), Some(98));
     assert_eq!(tag.category(), Some("NEW CATEGORY"));
     assert_eq!(tag.comment(), Some("NEW COMMENT"));
-    assert_eq!(tag.compilation(), true);
+    assert!(tag.compilation());
     assert_eq!(tag.composer(), Some("NEW COMPOSER"));
     assert_eq!(tag.copyright(), Some("NEW COPYRIGHT"));
     assert_eq!(tag.description(), Some("NEW DESCRIPTION"));
@@ -202,7 +260,7 @@ fn assert_tag_2(tag: &Tag) {
     assert_eq!(tag.disc_number(), Some(2));
     assert_eq!(tag.total_discs(), None);
     assert_eq!(tag.encoder(), Some("Lavf58.12.100"));
-    assert_eq!(tag.gapless_playback(), true);
+    assert!(tag.gapless_playback());
     assert_eq!(tag.genre(), Some("Hard Rock"));
     assert_eq!(tag.grouping(), Some("NEW GROUPING"));
     assert_eq!(tag.keyword(), Some("NEW KEYWORD"));
@@ -250,7 +308,7 @@ fn assert_tag_3(tag: &Tag) {
     assert_eq!(tag.bpm(), Some(132));
     assert_eq!(tag.category(), Some("TEST CATEGORY"));
     assert_eq!(tag.comment(), Some("TEST COMMENT"));
-    assert_eq!(tag.compilation(), true);
+    assert!(tag.compilation());
     assert_eq!(tag.composer(), Some("TEST COMPOSER"));
     assert_eq!(tag.copyright(), Some("TEST COPYRIGHT"));
     assert_eq!(tag.description(), Some("TEST DESCRIPTION"));
@@ -258,7 +316,7 @@ fn assert_tag_3(tag: &Tag) {
     assert_eq!(tag.disc_number(), Some(1));
     assert_eq!(tag.total_discs(), Some(2));
     assert_eq!(tag.encoder(), Some("Lavf58.29.100"));
-    assert_eq!(tag.gapless_playback(), true);
+    assert!(tag.gapless_playback());
 
     let mut genres = tag.genres();
     assert_eq!(genres.next(), Some("GENRE 1"));
@@ -290,238 +348,3076 @@ fn assert_tag_3(tag: &Tag) {
 
 #[track_caller]
 fn assert_readonly(tag: &Tag) {
-    assert_eq!(tag.duration(), Duration::from_millis(486));
+    // 21399 samples at 44.1kHz, the media duration of the audio track (`mdhd`)
+    assert_eq!(tag.duration(), Duration::from_nanos(485_238_095));
+    assert_eq!(tag.audio_info().duration_ticks, 21399);
+    assert_eq!(tag.audio_info().timescale, 44100);
     assert_eq!(tag.filetype(), "M4A \u{0}\u{0}\u{2}\u{0}isomiso2");
     assert_eq!(tag.channel_config(), Some(ChannelConfig::Mono));
     assert_eq!(tag.sample_rate(), Some(SampleRate::Hz44100));
     assert_eq!(tag.avg_bitrate(), Some(64776));
     assert_eq!(tag.max_bitrate(), Some(69000));
+    assert_eq!(tag.bit_depth(), Some(16));
+    assert_eq!(tag.codec(), Some(Codec::AacLc));
+    assert_eq!(tag.frames_per_packet(), None);
+    assert_eq!(tag.alac_cookie(), None);
+    assert_eq!(tag.video_info(), None);
 }
 
 #[test]
-fn chaptered() {
-    let tag = read_tag("files/sample-chaptered.m4a");
-    let chapters = [
-        Chapter::new(Duration::ZERO, "The Pledge"),
-        Chapter::new(Duration::new(7 * 60 + 28, 1_000_000), "The Turn"),
-        Chapter::new(Duration::new(64 * 60 + 44, 0), "The Prestige"),
-    ];
-    assert_eq!(tag.chapter_list(), chapters);
-    assert_eq!(tag.chapter_track(), chapters);
-}
+fn file_type() {
+    let tag = read_tag("files/sample.m4a");
+    let file_type = tag.file_type();
+    assert_eq!(file_type.major_brand, Fourcc(*b"M4A "));
+    assert_eq!(file_type.minor_version, 512);
+    assert_eq!(file_type.compatible_brands, [Fourcc(*b"isom"), Fourcc(*b"iso2")]);
+    assert!(file_type.is_audio());
+    assert!(file_type.is_iso_base_media());
+    assert!(!file_type.is_audiobook());
+    assert!(!file_type.is_video());
+
+    let tag = read_tag("files/sample.m4b");
+    let file_type = tag.file_type();
+    assert_eq!(file_type.major_brand, Fourcc(*b"M4B "));
+    assert_eq!(file_type.compatible_brands, [Fourcc(*b"M4B "), Fourcc(*b"isom")]);
+    assert!(file_type.is_audiobook());
+    assert!(file_type.is_iso_base_media());
+    assert!(!file_type.is_audio());
+
+    let tag = read_tag("files/sample.m4v");
+    let file_type = tag.file_type();
+    assert_eq!(file_type.major_brand, Fourcc(*b"M4V "));
+    assert_eq!(file_type.minor_version, 0);
+    assert!(file_type.is_video());
+    assert!(file_type.has_brand(Fourcc(*b"mp42")));
+
+    let tag = read_tag("files/sample-64.mp4");
+    let file_type = tag.file_type();
+    assert_eq!(file_type.major_brand, Fourcc(*b"mp42"));
+    assert!(file_type.is_iso_base_media());
+    assert!(!file_type.is_audio());
+
+    // the filetype atom is preserved exactly
+    let target_file = use_sample_file("files/sample.m4b", "target/file_type.m4b");
+    let buf = fs::read(target_file).unwrap();
+    let ftyp = find_atom(&buf, &[b"ftyp"]).unwrap();
+    let mut tag = read_tag(target_file);
+    tag.set_title("a considerably longer title that forces the metadata to be rewritten");
+    write_tag(&tag, target_file);
 
-#[test]
-fn collection() {
-    if let Some(path) = std::env::args().skip_while(|a| a != "collection").nth(1) {
-        println!("Testing collection at {}", &path);
-        read_dir(&path, |_, _| {});
-    } else {
-        println!("Skipping collection test since no path was provided.");
-    }
+    let new_buf = fs::read(target_file).unwrap();
+    assert_eq!(new_buf[ftyp.clone()], buf[ftyp]);
+    assert_eq!(read_tag(target_file).file_type(), tag.file_type());
 }
 
 #[test]
-fn sample_files() {
-    let _ = fs::remove_dir_all("target/files");
-    let _ = fs::create_dir("target/files");
-
-    read_dir("files", |p, t| {
-        let file_name = p.file_name().unwrap().to_owned();
-        let mut path = PathBuf::from("target/files");
-        path.push(file_name);
-
-        println!("copying {} to {}...", p.display(), path.display());
-        fs::copy(p, &path).unwrap();
-
-        println!("writing empty tag");
-        Tag::default().write_to_path(&path).unwrap();
-        println!("reading empty tag");
-        let tag = Tag::read_from_path(&path).unwrap();
-        assert!(tag.is_empty());
-        assert_eq!(tag.audio_info(), t.audio_info());
-
-        println!("writing sample tag 1");
-        get_tag_1().write_to_path(&path).unwrap();
-        println!("reading sample tag 1");
-        let tag = Tag::read_from_path(&path).unwrap();
-        assert_tag_1(&tag);
-        assert_eq!(tag.audio_info(), t.audio_info());
+fn artwork_from_path() {
+    let png = fs::read("files/artwork.png").unwrap();
+    let mut tag = Userdata::default();
+    tag.add_artwork(Img::jpeg(vec![0xff, 0xd8, 0xff]));
+    tag.set_artwork_from_path("files/artwork.png").unwrap();
+    assert_eq!(tag.artworks().collect::<Vec<_>>(), [Img::png(png.as_slice())]);
+
+    // the extension has to match the data
+    fs::write("target/artwork_from_path.jpg", &png).unwrap();
+    let err = tag.set_artwork_from_path("target/artwork_from_path.jpg").unwrap_err();
+    assert!(matches!(err.kind, ErrorKind::UnsupportedImageFormat));
+
+    // an unknown extension is ignored
+    fs::write("target/artwork_from_path.cover", &png).unwrap();
+    tag.set_artwork_from_path("target/artwork_from_path.cover").unwrap();
+    assert_eq!(tag.artwork(), Some(Img::png(png.as_slice())));
+
+    // unsupported formats are rejected
+    fs::write("target/artwork_from_path.gif", b"GIF89a").unwrap();
+    let err = tag.set_artwork_from_path("target/artwork_from_path.gif").unwrap_err();
+    assert!(matches!(err.kind, ErrorKind::UnsupportedImageFormat));
+    assert_eq!(tag.artwork(), Some(Img::png(png.as_slice())));
+
+    let err = tag.set_artwork_from_path("target/artwork_from_path.missing").unwrap_err();
+    assert!(matches!(err.kind, ErrorKind::Io(_)));
+}
 
-        println!("writing sample tag 2");
-        get_tag_2().write_to_path(&path).unwrap();
-        println!("reading sample tag 2");
-        let tag = Tag::read_from_path(&path).unwrap();
-        assert_tag_2(&tag);
-        assert_eq!(tag.audio_info(), t.audio_info());
-        println!();
-    });
+/// Returns the hash of the bytes.
+fn hash(bytes: &[u8]) -> u64 {
+    use std::hash::{DefaultHasher, Hasher};
+    let mut hasher = DefaultHasher::new();
+    hasher.write(bytes);
+    hasher.finish()
 }
 
 #[test]
-fn chapter_list_title_truncation() {
-    let target_file = use_sample_file("files/sample.m4a", "target/chapter_list_title.m4a");
-
-    let mut tag = Userdata::default();
-    tag.chapter_list_mut().extend([
-        Chapter::new(Duration::ZERO, "a".repeat(255) + "truncated"),
-        Chapter::new(Duration::from_millis(20), "after"),
-    ]);
+fn streamed_artwork() {
+    // a large image is streamed from its file when writing
+    let mut png = fs::read("files/artwork.png").unwrap();
+    png.extend((0..3 * 1024 * 1024u32).map(|i| (i.wrapping_mul(2654435761) >> 24) as u8));
+    fs::write("target/streamed_artwork.png", &png).unwrap();
+
+    let target_file = use_sample_file("files/sample.m4a", "target/streamed_artwork.m4a");
+    let mut tag = read_tag(target_file);
+    tag.set_artwork_streamed_from_path("target/streamed_artwork.png").unwrap();
+    let data = tag.data_of(&ident::ARTWORK).next().unwrap();
+    assert!(matches!(data, Data::Streamed { .. }));
+    assert_eq!(data.data_len(), png.len() as u64);
+    assert_eq!(tag.artwork_count(), 1);
     write_tag(&tag, target_file);
 
     let tag = read_tag(target_file);
-    assert_eq!(
-        tag.chapter_list(),
-        [
-            Chapter::new(Duration::ZERO, "a".repeat(255)),
-            Chapter::new(Duration::from_millis(20), "after"),
-        ],
+    let artwork = tag.artwork().unwrap();
+    assert_eq!(artwork.fmt, ImgFmt::Png);
+    assert_eq!(hash(artwork.data), hash(&png));
+
+    // images above the threshold are streamed, smaller ones are read into memory
+    let mut tag = read_tag(target_file);
+    tag.set_artwork_from_path("target/streamed_artwork.png").unwrap();
+    assert!(matches!(tag.data_of(&ident::ARTWORK).next(), Some(Data::Streamed { .. })));
+    tag.set_artwork_from_path("files/artwork.png").unwrap();
+    assert!(matches!(tag.data_of(&ident::ARTWORK).next(), Some(Data::Png(_))));
+
+    // the source has to keep its length, otherwise the file is left untouched
+    let before = fs::read(target_file).unwrap();
+    let mut tag = read_tag(target_file);
+    tag.set_data(
+        ident::ARTWORK,
+        Data::streamed_image(ImgFmt::Png, DataSource::from_reader(16, || Ok(&[0u8; 8][..]))),
     );
+    let err = tag.write_to_path(target_file).unwrap_err();
+    assert!(matches!(err.kind, ErrorKind::InvalidData));
+    assert!(fs::read(target_file).unwrap() == before);
+
+    // the same applies to a source file that's deleted before writing
+    fs::copy("files/artwork.png", "target/streamed_artwork_deleted.png").unwrap();
+    let mut tag = read_tag(target_file);
+    tag.set_artwork_streamed_from_path("target/streamed_artwork_deleted.png").unwrap();
+    fs::remove_file("target/streamed_artwork_deleted.png").unwrap();
+    let err = tag.write_to_path(target_file).unwrap_err();
+    assert!(matches!(err.kind, ErrorKind::Io(_)));
+    assert!(fs::read(target_file).unwrap() == before);
 }
 
 #[test]
-fn chapter_track_title_truncation() {
-    let target_file = use_sample_file("files/sample.m4a", "target/chapter_track_title.m4a");
+fn lazy_artwork() {
+    let png = fs::read("files/artwork.png").unwrap();
+    let target_file = use_sample_file("files/sample.m4a", "target/lazy_artwork.m4a");
+    let mut tag = get_tag_1();
+    tag.add_artwork(Img::jpeg(vec![0xff, 0xd8, 0xff, 0xe0]));
+    write_tag(&tag, target_file);
 
-    let mut tag = Userdata::default();
-    tag.chapter_track_mut().extend([
-        Chapter::new(Duration::ZERO, "a".repeat(65535) + "truncated"),
-        Chapter::new(Duration::from_millis(20), "after"),
-    ]);
+    // fully read tags don't contain lazy handles
+    assert_eq!(read_tag(target_file).artwork_lazy(), None);
+
+    let cfg = ReadConfig { skip_artwork: true, ..Default::default() };
+    let tag = Tag::read_with_path(target_file, &cfg).unwrap();
+    let artworks: Vec<_> = tag.artworks_lazy().collect();
+    assert_eq!(artworks.len(), 2);
+    assert_eq!(artworks[0].fmt(), &ImgFmt::Png);
+    assert_eq!(artworks[0].len(), png.len() as u64);
+    assert_eq!(artworks[1].fmt(), &ImgFmt::Jpeg);
+    assert_eq!(tag.artwork_lazy().as_ref(), Some(&artworks[0]));
+
+    let buf = fs::read(target_file).unwrap();
+    let offset = artworks[0].offset() as usize;
+    assert_eq!(buf[offset..offset + png.len()], png);
+
+    let mut file = File::open(target_file).unwrap();
+    assert_eq!(artworks[0].load(&mut file).unwrap(), Img::png(png.clone()));
+    assert_eq!(artworks[1].load(&mut file).unwrap(), Img::jpeg(vec![0xff, 0xd8, 0xff, 0xe0]));
+
+    // unloaded artwork is copied from the source when writing a copy
+    let copy_file = "target/lazy_artwork_copy.m4a";
+    let mut copy = File::create(copy_file).unwrap();
+    tag.write_copy(&mut File::open(target_file).unwrap(), &mut copy).unwrap();
+    drop(copy);
+    let copy_tag = read_tag(copy_file);
+    let images: Vec<_> = copy_tag.artworks().collect();
+    assert_eq!(images, [Img::png(png.as_slice()), Img::jpeg(&[0xff, 0xd8, 0xff, 0xe0][..])]);
+
+    // the source file is unchanged
+    assert_eq!(fs::read(target_file).unwrap(), buf);
+}
+
+#[test]
+fn trailing_garbage() {
+    // an atom head with a size smaller than the head itself, followed by a few bytes
+    let tag = read_tag("files/sample-trailing-garbage.m4a");
+    let sample = read_tag("files/sample.m4a");
+    assert_tag_1(&tag);
+    assert_eq!(tag.audio_info(), sample.audio_info());
+    assert_eq!(tag.mdat_len(), sample.mdat_len());
+
+    // a partial atom head
+    let target_file = use_sample_file("files/sample.m4a", "target/trailing_garbage.m4a");
+    let mut file = fs::OpenOptions::new().append(true).open(target_file).unwrap();
+    file.write_all(&[0, 0, 1]).unwrap();
+    drop(file);
+    assert_tag_1(&read_tag(target_file));
+
+    // an atom head with a size exceeding the file
+    let target_file = use_sample_file("files/sample.m4a", "target/trailing_garbage.m4a");
+    let mut file = fs::OpenOptions::new().append(true).open(target_file).unwrap();
+    file.write_all(b"\0\0\x01\0free\xde\xad\xbe").unwrap();
+    drop(file);
+    assert_tag_1(&read_tag(target_file));
+
+    // the garbage is preserved when writing
+    let target_file =
+        use_sample_file("files/sample-trailing-garbage.m4a", "target/trailing_garbage_write.m4a");
+    let mut tag = read_tag(target_file);
+    tag.set_title("a considerably longer title that forces the metadata to be rewritten");
     write_tag(&tag, target_file);
+    let buf = fs::read(target_file).unwrap();
+    assert!(buf.ends_with(b"\0\0\0\x04free\xde\xad\xbe"));
+    assert_eq!(read_tag(target_file).title(), tag.title());
+}
 
+#[test]
+fn zero_size_mdat() {
+    // the media data (mdat) atom extends to the end of the file
+    let tag = read_tag("files/sample-zero-size-mdat.m4a");
+    let sample = read_tag("files/sample.m4a");
+    assert_eq!(tag, sample);
+    assert_eq!(tag.audio_info(), sample.audio_info());
+    assert_eq!(tag.mdat_len(), sample.mdat_len());
+
+    let target_file =
+        use_sample_file("files/sample-zero-size-mdat.m4a", "target/zero_size_mdat.m4a");
+    let mut tag = read_tag(target_file);
+    tag.set_title("a considerably longer title that forces the metadata to be rewritten");
+    write_tag(&tag, target_file);
     let tag = read_tag(target_file);
     assert_eq!(
-        tag.chapter_track(),
-        [
-            Chapter::new(Duration::ZERO, "a".repeat(65535)),
-            Chapter::new(Duration::from_millis(20), "after"),
-        ],
+        tag.title(),
+        Some("a considerably longer title that forces the metadata to be rewritten")
     );
+    assert_eq!(tag.mdat_len(), sample.mdat_len());
 }
 
 #[test]
-fn previous_chapter_track_media_data_is_removed() {
-    let target_file = use_sample_file("files/sample.m4a", "target/chapter_track_doesnt_grow.m4a");
-
-    let chapters = [
-        Chapter::new(Duration::ZERO, "The Pledge"),
-        Chapter::new(Duration::from_millis(135), "The Turn"),
-        Chapter::new(Duration::from_millis(324), "The Prestige"),
-    ];
-
-    let mut tag = Userdata::default();
-    tag.chapter_track_mut().extend(chapters.clone());
+fn partial_reads() {
+    let target_file = use_sample_file("files/sample.m4a", "target/partial_reads.m4a");
+    let mut tag = get_tag_1();
+    let mut png = fs::read("files/artwork.png").unwrap();
+    png.resize(1 << 16, 0);
+    tag.add_artwork(Img::png(png));
     write_tag(&tag, target_file);
 
-    let file = std::fs::File::open(target_file).unwrap();
-    let prev_size = file.metadata().unwrap().len();
+    let data = fs::read(target_file).unwrap();
+    let len = data.len() as u64;
+    let reader = || CountingReader { data: data.clone(), len, pos: 0, read: 0 };
+
+    let mut full_reader = reader();
+    let full = Tag::read_from(&mut full_reader).unwrap();
+
+    // the user data is skipped
+    let mut audio_reader = reader();
+    let (info, file_type) = Tag::read_audio_info_from(&mut audio_reader).unwrap();
+    assert_eq!(&info, full.audio_info());
+    assert_eq!(&file_type, full.file_type());
+    assert!(audio_reader.read < full_reader.read / 10, "read {} bytes", audio_reader.read);
+
+    // the sample tables are skipped
+    let mut meta_reader = reader();
+    let meta = Tag::read_metadata_only_from(&mut meta_reader).unwrap();
+    assert_tag_1(&meta);
+    assert_eq!(meta.artworks().collect::<Vec<_>>(), full.artworks().collect::<Vec<_>>());
+    // the duration of the movie header is less precise
+    assert!(meta.duration().abs_diff(full.duration()) < Duration::from_millis(1));
+    assert_eq!(meta.codec(), None);
+    assert!(meta_reader.read < full_reader.read, "read {} bytes", meta_reader.read);
+
+    // malformed metadata items don't affect the audio information
+    let mut buf = data.clone();
+    let ilst = find_atom(&buf, &[b"moov", b"udta", b"meta", b"ilst"]).unwrap();
+    let data_pos = ilst.start + 8 + 8;
+    assert_eq!(&buf[data_pos + 4..data_pos + 8], b"data");
+    buf[data_pos + 8] = 1; // unknown version
+    fs::write(target_file, &buf).unwrap();
+    assert!(Tag::read_from_path(target_file).is_err());
+    let (info, _) = Tag::read_audio_info_from_path(target_file).unwrap();
+    assert_eq!(&info, full.audio_info());
+}
 
-    write_tag(&tag, target_file);
+#[test]
+fn moov_early_exit() {
+    let target_file = use_sample_file("files/sample-multi-track.3gp", "target/moov_early_exit.3gp");
+    write_tag(&get_tag_1(), target_file);
+    let data = fs::read(target_file).unwrap();
+
+    // move the user data directly behind the movie header, before the 4 tracks
+    let moov = find_atom(&data, &[b"moov"]).unwrap();
+    let atoms = child_atoms(&data, moov.start + 8, moov.end);
+    assert_eq!(atoms.iter().filter(|(f, _)| f == b"trak").count(), 4);
+    let mut reordered = data[..moov.start + 8].to_vec();
+    for fourcc in [b"mvhd", b"udta"] {
+        let (_, range) = atoms.iter().find(|(f, _)| f == fourcc).unwrap();
+        reordered.extend(&data[range.clone()]);
+    }
+    for (_, range) in atoms.iter().filter(|(f, _)| !matches!(f, b"mvhd" | b"udta")) {
+        reordered.extend(&data[range.clone()]);
+    }
+    reordered.extend(&data[moov.end..]);
+    assert_eq!(reordered.len(), data.len());
 
-    let file = std::fs::File::open(target_file).unwrap();
-    let new_size = file.metadata().unwrap().len();
+    let read = |data: &Vec<u8>, cfg: &ReadConfig| {
+        let len = data.len() as u64;
+        let mut reader = CountingReader { data: data.clone(), len, pos: 0, read: 0 };
+        let tag = Tag::read_with(&mut reader, cfg).unwrap();
+        assert_tag_1(&tag);
+        reader.read
+    };
 
-    assert_eq!(prev_size, new_size);
+    // the track heads behind the user data aren't even read
+    let meta_read = read(&data, &ReadConfig::METADATA_ONLY);
+    let reordered_read = read(&reordered, &ReadConfig::METADATA_ONLY);
+    assert_eq!(reordered_read + 4 * 8, meta_read);
 
-    let tag = read_tag(target_file);
-    assert_eq!(tag.chapter_track(), chapters);
+    // the tracks are parsed for the audio and video information
+    let full_read = read(&reordered, &ReadConfig::DEFAULT);
+    assert!(meta_read < full_read / 2, "read {meta_read} of {full_read} bytes");
 }
 
 #[test]
-fn bench() {
-    let Some(in_file) = std::env::args().skip_while(|a| a != "bench").nth(1) else {
-        println!("Skipping bench test since no input file was provided.");
-        return;
+fn video_info() {
+    let target_file = use_sample_file("files/sample.m4v", "target/video_info.m4v");
+
+    let mut tag = read_tag(target_file);
+    let assert_video_info = |tag: &Tag| {
+        let info = tag.video_info().unwrap();
+        assert_eq!(info.codec, Fourcc(*b"avc1"));
+        assert_eq!((info.width, info.height), (720, 480));
+        // 32:27 pixel aspect ratio
+        assert_eq!(info.display_width, 853);
+        assert_eq!(info.frame_rate, Some(30000.0 / 1001.0));
+        assert_eq!(info.duration, Some(Duration::from_nanos(333_666_666)));
     };
-    println!("Running bench with file: {in_file}");
+    assert_video_info(&tag);
+    assert_eq!(tag.codec(), None);
 
-    let target_file = use_sample_file(&in_file, "target/bench.m4a");
-    let in_tag = read_tag(target_file);
+    tag.set_title("TEST TITLE");
+    write_tag(&tag, target_file);
 
-    let start = std::time::Instant::now();
-    for _ in 0..300 {
-        std::fs::copy(&in_file, target_file).unwrap();
+    let tag = read_tag(target_file);
+    assert_eq!(tag.title(), Some("TEST TITLE"));
+    assert_video_info(&tag);
 
-        Tag::default().write_to_path(target_file).unwrap();
-        let tag = Tag::read_from_path(target_file).unwrap();
-        assert!(tag.is_empty());
-        assert_eq!(tag.audio_info(), in_tag.audio_info());
+    let cfg = ReadConfig { read_video_info: false, ..ReadConfig::DEFAULT };
+    let tag = Tag::read_with_path(target_file, &cfg).unwrap();
+    assert_eq!(tag.video_info(), None);
+}
 
-        get_tag_1().write_to_path(target_file).unwrap();
-        let tag = Tag::read_from_path(target_file).unwrap();
-        assert_tag_1(&tag);
-        assert_eq!(tag.audio_info(), in_tag.audio_info());
+#[test]
+fn track_info() {
+    let tag = read_tag("files/sample-multi-track.3gp");
+    let tracks = tag.tracks();
+    assert_eq!(tracks.len(), 4);
+    let handlers: Vec<_> = tracks.iter().map(|t| t.handler_type).collect();
+    assert_eq!(handlers, [b"soun", b"vide", b"hint", b"hint"].map(|h| Some(Fourcc(*h))));
+    assert_eq!(tracks.iter().map(|t| t.id).collect::<Vec<_>>(), [1, 2, 3, 4]);
+    assert!(tracks.iter().all(|t| t.enabled && t.language.as_deref() == Some("eng")));
+
+    let audio = tracks[0].audio.as_ref().unwrap();
+    assert_eq!(audio.codec, Some(Codec::Other(Fourcc(*b"samr"))));
+    assert_eq!(audio.duration, tracks[0].duration);
+    assert_eq!(tracks[0].video, None);
+    let video = tracks[1].video.as_ref().unwrap();
+    assert_eq!(video.codec, Fourcc(*b"mp4v"));
+    assert_eq!((video.width, video.height), (176, 144));
+    assert_eq!(video.frame_rate, Some(15.0));
+    assert_eq!(tracks[1].audio, None);
+    assert!(tracks[2..].iter().all(|t| t.audio.is_none() && t.video.is_none()));
+
+    // the tag level information is the one of the first audio and video track
+    assert_eq!(tag.codec(), Some(Codec::Other(Fourcc(*b"samr"))));
+    assert_eq!(tag.video_info(), Some(video));
+
+    // a disabled chapter text track alongside the audio track
+    let tag = read_tag("files/sample-chaptered.m4a");
+    let tracks = tag.tracks();
+    assert_eq!(tracks.len(), 2);
+    assert_eq!(tracks[0].handler_type, Some(Fourcc(*b"soun")));
+    assert!(tracks[0].enabled);
+    assert_eq!(tracks[0].language.as_deref(), Some("und"));
+    assert_eq!(tracks[0].audio.as_ref().unwrap().codec, Some(Codec::AacLc));
+    assert_eq!(tracks[1].handler_type, Some(Fourcc(*b"text")));
+    assert!(!tracks[1].enabled);
+    assert_eq!(tracks[1].language, None);
+    assert_eq!(tracks[1].audio, None);
+
+    let cfg = ReadConfig {
+        read_audio_info: false,
+        read_video_info: false,
+        ..ReadConfig::DEFAULT
+    };
+    let tag = Tag::read_with_path("files/sample-chaptered.m4a", &cfg).unwrap();
+    assert!(tag.tracks().is_empty());
+}
 
-        get_tag_2().write_to_path(target_file).unwrap();
-        let tag = Tag::read_from_path(target_file).unwrap();
-        assert_tag_2(&tag);
-        assert_eq!(tag.audio_info(), in_tag.audio_info());
+#[test]
+fn audio_language() {
+    let target_file = use_sample_file("files/sample.m4a", "target/audio_language.m4a");
+    let buf = fs::read(target_file).unwrap();
+    let mdhd = find_atom(&buf, &[b"moov", b"trak", b"mdia", b"mdhd"]).unwrap();
+
+    let mut tag = read_tag(target_file);
+    assert_eq!(tag.audio_language(), Some("und"));
+    assert_eq!(tag.language(), None);
+
+    for language in ["de", "deutsch", "DEU", "de1", "dé"] {
+        let err = tag.set_audio_language(language).unwrap_err();
+        assert!(matches!(err.kind, ErrorKind::InvalidData));
     }
-    let end = std::time::Instant::now();
-    let millis = end.duration_since(start).as_millis();
-    println!("took: {millis}ms");
+    tag.set_audio_language("deu").unwrap();
+    write_tag(&tag, target_file);
+
+    // only the packed language code (1 bit padding, 3 times 5 bits) of the mdhd atom changes
+    let new_buf = fs::read(target_file).unwrap();
+    assert_eq!(new_buf.len(), buf.len());
+    let lang_pos = mdhd.start + 28;
+    assert_eq!(new_buf[lang_pos..lang_pos + 2], 0x10b5u16.to_be_bytes());
+    assert_eq!(new_buf[..lang_pos], buf[..lang_pos]);
+    assert_eq!(new_buf[lang_pos + 2..], buf[lang_pos + 2..]);
+
+    let tag = read_tag(target_file);
+    assert_eq!(tag.audio_language(), Some("deu"));
+    assert_eq!(tag.language().as_deref(), Some("deu"));
+    assert_eq!(tag.tracks()[0].language.as_deref(), Some("deu"));
+
+    // only the language of the audio track of a multi track file is changed
+    let target_file = use_sample_file("files/sample-multi-track.3gp", "target/audio_language.3gp");
+    let mut tag = read_tag(target_file);
+    assert_eq!(tag.audio_language(), Some("eng"));
+    tag.set_audio_language("fra").unwrap();
+    write_tag(&tag, target_file);
+
+    let tag = read_tag(target_file);
+    assert_eq!(tag.audio_language(), Some("fra"));
+    assert_eq!(tag.language().as_deref(), Some("fra"));
+    let languages: Vec<_> = tag.tracks().iter().map(|t| t.language.as_deref()).collect();
+    assert_eq!(languages, [Some("fra"), Some("eng"), Some("eng"), Some("eng")]);
 }
 
 #[test]
-fn read_sample() {
-    let tag = read_tag("files/sample.m4a");
-    assert_tag_1(&tag);
-    assert_readonly(&tag);
+fn language() {
+    let tag = read_tag("files/sample-multi-track.3gp");
+    assert_eq!(tag.language().as_deref(), Some("eng"));
+    assert_eq!(tag.tracks()[0].extended_language, None);
+
+    // an undetermined language
+    let mut tag = read_tag("files/sample.m4a");
+    assert_eq!(tag.audio_language(), Some("und"));
+    assert_eq!(tag.language(), None);
+    tag.set_audio_language("deu").unwrap();
+    assert_eq!(tag.audio_language(), Some("deu"));
+    assert_eq!(tag.language().as_deref(), Some("deu"));
+    tag.set_audio_language("und").unwrap();
+    assert_eq!(tag.audio_language(), Some("und"));
+    assert_eq!(tag.language(), None);
+
+    // the extended language tag (elng) takes precedence, the media data of this file precedes the
+    // movie atom so no chunk offsets have to be updated
+    let mut buf = fs::read("files/sample-chaptered.m4a").unwrap();
+    let mdhd = find_atom(&buf, &[b"moov", b"trak", b"mdia", b"mdhd"]).unwrap();
+    let elng = b"\0\0\0\x12elng\0\0\0\0en-US\0";
+    buf.splice(mdhd.end..mdhd.end, elng.iter().copied());
+    for path in [&[b"moov"][..], &[b"moov", b"trak"], &[b"moov", b"trak", b"mdia"]] {
+        let pos = find_atom(&buf, path).unwrap().start;
+        let len = u32::from_be_bytes(buf[pos..pos + 4].try_into().unwrap()) + elng.len() as u32;
+        buf[pos..pos + 4].copy_from_slice(&len.to_be_bytes());
+    }
+
+    let tag = Tag::read_from(&mut std::io::Cursor::new(&buf)).unwrap();
+    assert_eq!(tag.tracks()[0].extended_language.as_deref(), Some("en-US"));
+    assert_eq!(tag.language().as_deref(), Some("en-US"));
 }
 
 #[test]
-fn read_sample_multi_data() {
-    let tag = read_tag("files/sample-multi-data.m4a");
-    assert_tag_3(&tag);
-    assert_readonly(&tag);
+fn exact_duration() {
+    // without audio information the movie duration (`mvhd`) with its coarse timescale is used
+    let cfg = ReadConfig { read_audio_info: false, ..ReadConfig::DEFAULT };
+    let tag = Tag::read_with_path("files/sample.m4a", &cfg).unwrap();
+    assert_eq!(tag.duration(), Duration::from_millis(486));
+    assert_eq!(tag.audio_info().duration_ticks, 486);
+    assert_eq!(tag.audio_info().timescale, 1000);
+
+    let tag = read_tag("files/sample-chaptered.m4a");
+    assert_eq!(tag.audio_info().duration_ticks, 21398);
+    assert_eq!(tag.audio_info().timescale, 44100);
+
+    // a version 1 media header with a 64 bit duration, the free atom following the moov atom
+    // absorbs the 12 additional bytes
+    let target_file = use_sample_file("files/sample.m4a", "target/exact_duration_v1.m4a");
+    let mut buf = fs::read(target_file).unwrap();
+    let be_u32 = |buf: &[u8], pos: usize| u32::from_be_bytes(buf[pos..pos + 4].try_into().unwrap());
+    let set_be_u32 = |buf: &mut [u8], pos: usize, val: u32| {
+        buf[pos..pos + 4].copy_from_slice(&val.to_be_bytes());
+    };
+    let mdhd = find_atom(&buf, &[b"moov", b"trak", b"mdia", b"mdhd"]).unwrap();
+    let parents = [&[b"moov"][..], &[b"moov", b"trak"], &[b"moov", b"trak", b"mdia"]]
+        .map(|path| find_atom(&buf, path).unwrap().start);
+    let free = find_atom(&buf, &[b"moov"]).unwrap().end;
+    assert_eq!(&buf[free + 4..free + 8], b"free");
+
+    let duration = (1u64 << 32) + 21399;
+    let mut mdhd_v1 = Vec::new();
+    mdhd_v1.extend(44u32.to_be_bytes());
+    mdhd_v1.extend(b"mdhd\x01\0\0\0");
+    mdhd_v1.extend([0; 16]);
+    mdhd_v1.extend(44100u32.to_be_bytes());
+    mdhd_v1.extend(duration.to_be_bytes());
+    mdhd_v1.extend(&buf[mdhd.end - 4..mdhd.end]);
+
+    let free_len = be_u32(&buf, free) - 12;
+    set_be_u32(&mut buf, free, free_len);
+    buf.drain(free + 8..free + 8 + 12);
+    buf.splice(mdhd, mdhd_v1);
+    for pos in parents {
+        let len = be_u32(&buf, pos) + 12;
+        set_be_u32(&mut buf, pos, len);
+    }
+    fs::write(target_file, &buf).unwrap();
+
+    let tag = read_tag(target_file);
+    assert_eq!(tag.audio_info().duration_ticks, duration);
+    assert_eq!(tag.audio_info().timescale, 44100);
+    assert_eq!(tag.duration(), Duration::from_nanos(duration * 1_000_000_000 / 44100));
 }
 
 #[test]
-fn write() {
-    let target_file = use_sample_file("files/sample.m4a", "target/write.m4a");
+fn movie_timestamps() {
+    let target_file = use_sample_file("files/sample.m4a", "target/movie_timestamps.m4a");
+    let buf = fs::read(target_file).unwrap();
+    let mvhd = find_atom(&buf, &[b"moov", b"mvhd"]).unwrap();
+    let old_mvhd = buf[mvhd.clone()].to_vec();
+    let be_u32 = |buf: &[u8], pos: usize| u32::from_be_bytes(buf[pos..pos + 4].try_into().unwrap());
+
+    let mut tag = read_tag(target_file);
+    let created = be_u32(&buf, mvhd.start + 12) as u64;
+    let modified = be_u32(&buf, mvhd.start + 16) as u64;
+    assert_eq!(tag.created_at(), Some(Timestamp::from_secs(created)));
+    assert_eq!(tag.modified_at(), Some(Timestamp::from_secs(modified)));
+
+    // version 0, only the timestamps are rewritten
+    let fixed = Timestamp::from_system_time(UNIX_EPOCH + Duration::from_secs(1_000)).unwrap();
+    assert_eq!(fixed.secs(), 2_082_845_800);
+    tag.set_created_at(fixed);
+    tag.set_modified_at(fixed);
+    assert_eq!(tag.created_at(), Some(fixed));
+    write_tag(&tag, target_file);
 
-    let tag = get_tag_2();
+    let new_buf = fs::read(target_file).unwrap();
+    assert_eq!(new_buf.len(), buf.len());
+    let new_mvhd = &new_buf[mvhd.clone()];
+    assert_eq!(new_mvhd[..12], old_mvhd[..12]);
+    assert_eq!(new_mvhd[20..], old_mvhd[20..]);
+    let tag = read_tag(target_file);
+    assert_eq!(tag.created_at(), Some(fixed));
+    assert_eq!(tag.modified_at(), Some(fixed));
+
+    // a version 0 header can't store 64 bit timestamps
+    let mut userdata = Userdata::default();
+    userdata.set_modified_at(Timestamp::from_secs(1 << 32));
+    let err = userdata.write_to_path(target_file).unwrap_err();
+    assert!(matches!(err.kind, ErrorKind::InvalidData));
+
+    // a version 1 header with 64 bit timestamps, the free atom following the moov atom absorbs
+    // the 12 additional bytes
+    let mut buf = buf;
+    let set_be_u32 = |buf: &mut [u8], pos: usize, val: u32| {
+        buf[pos..pos + 4].copy_from_slice(&val.to_be_bytes());
+    };
+    let moov = find_atom(&buf, &[b"moov"]).unwrap();
+    let free = moov.end;
+    assert_eq!(&buf[free + 4..free + 8], b"free");
+
+    let created = 1_000_000u64;
+    let modified = (1u64 << 32) + 7;
+    let mut mvhd_v1 = Vec::new();
+    mvhd_v1.extend(120u32.to_be_bytes());
+    mvhd_v1.extend(b"mvhd\x01\0\0\0");
+    mvhd_v1.extend(created.to_be_bytes());
+    mvhd_v1.extend(modified.to_be_bytes());
+    mvhd_v1.extend(&buf[mvhd.start + 20..mvhd.start + 24]);
+    mvhd_v1.extend((be_u32(&buf, mvhd.start + 24) as u64).to_be_bytes());
+    mvhd_v1.extend(&buf[mvhd.start + 28..mvhd.end]);
+    let rest = buf[mvhd.start + 28..mvhd.end].to_vec();
+
+    let free_len = be_u32(&buf, free) - 12;
+    set_be_u32(&mut buf, free, free_len);
+    buf.drain(free + 8..free + 8 + 12);
+    buf.splice(mvhd, mvhd_v1);
+    let moov_len = be_u32(&buf, moov.start) + 12;
+    set_be_u32(&mut buf, moov.start, moov_len);
+    fs::write(target_file, &buf).unwrap();
+
+    let mut tag = read_tag(target_file);
+    assert_eq!(tag.duration(), Duration::from_nanos(485_238_095));
+    let created_at = tag.created_at().unwrap();
+    assert_eq!(created_at.secs(), created);
+    assert_eq!(tag.modified_at(), Some(Timestamp::from_secs(modified)));
+
+    // dates before 1970 don't panic
+    let system_time = created_at.to_system_time().unwrap();
+    let before = UNIX_EPOCH.duration_since(system_time).unwrap();
+    assert_eq!(before, Duration::from_secs(2_082_844_800 - created));
+    assert_eq!(Timestamp::from_system_time(system_time), Some(created_at));
+
+    tag.set_created_at(Timestamp::from_secs(modified + 1));
     write_tag(&tag, target_file);
 
+    let new_buf = fs::read(target_file).unwrap();
+    assert_eq!(new_buf.len(), buf.len());
+    let mvhd = find_atom(&new_buf, &[b"moov", b"mvhd"]).unwrap();
+    assert_eq!(new_buf[mvhd.start + 8], 1);
+    assert_eq!(new_buf[mvhd.start + 40..mvhd.end], rest[..]);
     let tag = read_tag(target_file);
-    assert_tag_2(&tag);
-    assert_readonly(&tag);
+    assert_eq!(tag.created_at(), Some(Timestamp::from_secs(modified + 1)));
+    assert_eq!(tag.modified_at(), Some(Timestamp::from_secs(modified)));
 }
 
 #[test]
-fn write_same() {
-    let target_file = use_sample_file("files/sample.m4a", "target/write_same.m4a");
+fn sample_counts() {
+    let target_file = use_sample_file("files/sample.m4a", "target/sample_counts.m4a");
 
-    let tag = get_tag_1();
+    let tag = read_tag(target_file);
+    let info = tag.audio_info();
+    assert_eq!(info.total_samples, Some(21399));
+    assert_eq!(info.frame_count, Some(21));
+    assert_eq!((info.encoder_delay, info.encoder_padding), (None, None));
+    assert_eq!(info.pcm_length(), Some(21399));
+
+    let mut tag = tag;
+    let smpb = " 00000000 00000840 000001E4 00000000000049B3 00000000 00000000";
+    tag.set_data(ident::ITUNES_SMPB, Data::Utf8(smpb.into()));
     write_tag(&tag, target_file);
 
     let tag = read_tag(target_file);
-    assert_tag_1(&tag);
-    assert_readonly(&tag);
+    let info = tag.audio_info();
+    assert_eq!(info.encoder_delay, Some(2112));
+    assert_eq!(info.encoder_padding, Some(484));
+    assert_eq!(info.pcm_length(), Some(21399 - 2112 - 484));
+
+    // the counts don't depend on the chapter track being read
+    let cfg = ReadConfig { read_chapter_track: false, ..ReadConfig::DEFAULT };
+    let tag = Tag::read_with_path("files/sample-chaptered.m4a", &cfg).unwrap();
+    assert_eq!(tag.audio_info().total_samples, Some(21398));
+    assert_eq!(tag.audio_info().frame_count, Some(21));
 }
 
 #[test]
-fn write_bigger() {
-    let target_file = use_sample_file("files/sample.m4a", "target/write_bigger.m4a");
+fn gapless_info() {
+    let target_file = use_sample_file("files/sample.m4a", "target/gapless_info.m4a");
+    let mut tag = read_tag(target_file);
+    let info = tag.audio_info();
+    assert_eq!(
+        (info.encoder_delay, info.encoder_padding, info.exact_sample_count),
+        (None, None, None)
+    );
+    assert_eq!(info.gapless_duration(), None);
 
-    let mut tag = get_tag_2();
-    let data: Vec<u8> = (0..512 * 1024).map(|n| n as u8).collect();
-    tag.add_data(Fourcc(*b"test"), Data::Reserved(data));
+    // as written by iTunes: 2112 samples delay, 420 samples padding and 18867 samples in total
+    let smpb = " 00000000 00000840 000001A4 00000000000049B3 00000000 00000000 00000000 00000000 \
+                00000000 00000000 00000000 00000000";
+    tag.set_data(ident::ITUNES_SMPB, Data::Utf8(smpb.into()));
     write_tag(&tag, target_file);
 
     let tag = read_tag(target_file);
-    assert_tag_2(&tag);
-    assert_readonly(&tag);
+    let info = tag.audio_info();
+    assert_eq!(info.encoder_delay, Some(2112));
+    assert_eq!(info.encoder_padding, Some(420));
+    assert_eq!(info.exact_sample_count, Some(18867));
+    assert_eq!(info.pcm_length(), Some(18867));
+    assert_eq!(info.gapless_duration(), Some(Duration::from_nanos(427_823_129)));
+
+    // an unknown sample count is computed from the sample table
+    let mut info = info.clone();
+    info.exact_sample_count = None;
+    assert_eq!(info.gapless_duration(), Some(Duration::from_nanos(427_823_129)));
+
+    // the samples of HE-AAC are counted at the output sample rate, which is twice the sample
+    // rate of the AAC core
+    let target_file =
+        use_sample_file("files/sample-he-aac-v2.m4a", "target/gapless_info_he_aac.m4a");
+    let mut tag = read_tag(target_file);
+    tag.set_data(ident::ITUNES_SMPB, Data::Utf8(smpb.into()));
+    write_tag(&tag, target_file);
+
+    let tag = read_tag(target_file);
+    let mut info = tag.audio_info().clone();
+    assert_eq!(info.sample_rate, Some(SampleRate::Hz22050));
+    assert_eq!(info.effective_sample_rate(), Some(44100));
+    assert_eq!(info.exact_sample_count, Some(18867));
+    assert_eq!(info.gapless_duration(), Some(Duration::from_nanos(427_823_129)));
+    info.exact_sample_count = None;
+    assert_eq!(info.gapless_duration(), Some(Duration::from_nanos(427_823_129)));
 }
 
 #[test]
-fn write_empty() {
-    let target_file = use_sample_file("files/sample.m4a", "target/write_empty.m4a");
-
-    let tag = Tag::default();
-    write_tag(&tag, target_file);
+fn estimated_bitrate() {
+    // the declared bitrate matches the estimate from the media data
+    let tag = read_tag("files/sample.m4a");
+    assert_eq!(tag.mdat_len(), 3929);
+    assert_eq!(tag.avg_bitrate(), Some(64776));
+    assert!(!tag.audio_info().bitrate_estimated);
+
+    // the alac sample entry doesn't declare a bitrate
+    let tag = read_tag("files/sample-alac.m4a");
+    assert_eq!(tag.mdat_len(), 26472);
+    assert_eq!(tag.avg_bitrate(), Some(2117760));
+    assert!(tag.audio_info().bitrate_estimated);
+
+    // the media data of multiple mdat atoms is summed up
+    let target_file = use_sample_file("files/sample-alac.m4a", "target/estimated_bitrate.m4a");
+    let mut file = fs::OpenOptions::new().append(true).open(target_file).unwrap();
+    let mut mdat = 26480u32.to_be_bytes().to_vec();
+    mdat.extend(b"mdat");
+    mdat.extend([0; 26472]);
+    file.write_all(&mdat).unwrap();
+    drop(file);
 
     let tag = read_tag(target_file);
-    assert!(tag.is_empty());
-    assert_readonly(&tag);
+    assert_eq!(tag.mdat_len(), 2 * 26472);
+    assert_eq!(tag.avg_bitrate(), Some(2 * 2117760));
+    assert!(tag.audio_info().bitrate_estimated);
+
+    // the media data of files containing video isn't only audio
+    let tag = read_tag("files/sample-multi-track.3gp");
+    assert_eq!(tag.mdat_len(), 561861);
+    assert_eq!(tag.avg_bitrate(), None);
+    assert!(!tag.audio_info().bitrate_estimated);
+}
+
+#[test]
+fn bitrate_kind() {
+    // the maximum bitrate exceeds the average one
+    let tag = read_tag("files/sample.m4a");
+    assert_eq!(tag.audio_info().bitrate_kind, BitrateKind::Variable);
+
+    // the alac sample entry doesn't declare a maximum bitrate
+    let tag = read_tag("files/sample-alac.m4a");
+    assert_eq!(tag.audio_info().bitrate_kind, BitrateKind::Unknown);
+
+    // declare a maximum bitrate equal to the average one in the decoder config descriptor
+    let mut data = fs::read("files/sample.m4a").unwrap();
+    let stsd = &[b"moov", b"trak", b"mdia", b"minf", b"stbl", b"stsd"];
+    let stsd = find_atom(&data, stsd).unwrap();
+    let bitrates = [69000u32.to_be_bytes(), 64776u32.to_be_bytes()].concat();
+    let pos = data[stsd.clone()].windows(8).position(|w| w == bitrates).unwrap();
+    let pos = stsd.start + pos;
+    data[pos..pos + 4].copy_from_slice(&64776u32.to_be_bytes());
+
+    let tag = Tag::read_from(&mut std::io::Cursor::new(&data)).unwrap();
+    assert_eq!(tag.max_bitrate(), Some(64776));
+    assert_eq!(tag.avg_bitrate(), Some(64776));
+    assert_eq!(tag.audio_info().bitrate_kind, BitrateKind::Constant);
+}
+
+/// A reader over a file followed by a large virtual media data (`mdat`) atom of zeros, which
+/// counts the bytes that are actually read.
+struct CountingReader {
+    data: Vec<u8>,
+    len: u64,
+    pos: u64,
+    read: u64,
+}
+
+impl Read for CountingReader {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let n = buf.len().min(self.len.saturating_sub(self.pos) as usize);
+        for (i, b) in buf[..n].iter_mut().enumerate() {
+            *b = self.data.get(self.pos as usize + i).copied().unwrap_or(0);
+        }
+        self.pos += n as u64;
+        self.read += n as u64;
+        Ok(n)
+    }
+}
+
+impl Seek for CountingReader {
+    fn seek(&mut self, pos: SeekFrom) -> std::io::Result<u64> {
+        self.pos = match pos {
+            SeekFrom::Start(p) => p,
+            SeekFrom::End(p) => self.len.checked_add_signed(p).unwrap(),
+            SeekFrom::Current(p) => self.pos.checked_add_signed(p).unwrap(),
+        };
+        Ok(self.pos)
+    }
+}
+
+/// A reader counting the calls to [`Read::read`].
+struct ReadCallCounter<R> {
+    inner: R,
+    calls: usize,
+}
+
+impl<R: Read> Read for ReadCallCounter<R> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        self.calls += 1;
+        self.inner.read(buf)
+    }
+}
+
+impl<R: Seek> Seek for ReadCallCounter<R> {
+    fn seek(&mut self, pos: SeekFrom) -> std::io::Result<u64> {
+        self.inner.seek(pos)
+    }
+}
+
+/// A reader that records the positions at which it is read.
+struct ReadPositions<R> {
+    inner: R,
+    positions: Vec<u64>,
+}
+
+impl<R: Read + Seek> Read for ReadPositions<R> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        self.positions.push(self.inner.stream_position()?);
+        self.inner.read(buf)
+    }
+}
+
+impl<R: Seek> Seek for ReadPositions<R> {
+    fn seek(&mut self, pos: SeekFrom) -> std::io::Result<u64> {
+        self.inner.seek(pos)
+    }
+}
+
+/// Returns the fourcc and position of all atoms nested inside the movie (`moov`) atom.
+fn moov_atoms(buf: &[u8]) -> Vec<([u8; 4], usize)> {
+    fn visit(buf: &[u8], start: usize, end: usize, atoms: &mut Vec<([u8; 4], usize)>) {
+        for (fourcc, range) in child_atoms(buf, start, end) {
+            atoms.push((fourcc, range.start));
+            match &fourcc {
+                b"trak" | b"mdia" | b"minf" | b"stbl" | b"udta" => {
+                    visit(buf, range.start + 8, range.end, atoms)
+                }
+                b"meta" => visit(buf, range.start + 12, range.end, atoms),
+                _ => (),
+            }
+        }
+    }
+
+    let moov = find_atom(buf, &[b"moov"]).unwrap();
+    let mut atoms = Vec::new();
+    visit(buf, moov.start + 8, moov.end, &mut atoms);
+    atoms
+}
+
+#[test]
+fn early_exit() {
+    // the remaining atoms of a parent are skipped once all needed ones have been parsed
+    let buf = fs::read("files/sample-multi-track.3gp").unwrap();
+    let atoms = moov_atoms(&buf);
+    let visited = |cfg: &ReadConfig| {
+        let mut reader = ReadPositions {
+            inner: std::io::Cursor::new(&buf),
+            positions: Vec::new(),
+        };
+        Tag::read_with(&mut reader, cfg).unwrap();
+        let positions = reader.positions;
+        atoms.iter().filter(|(_, pos)| positions.contains(&(*pos as u64))).collect::<Vec<_>>()
+    };
+
+    let metadata_only = visited(&ReadConfig::METADATA_ONLY);
+    let all = visited(&ReadConfig::DEFAULT);
+    println!("{} of {} atoms visited", metadata_only.len(), atoms.len());
+    assert!(metadata_only.len() < all.len());
+    for (fourcc, _) in metadata_only.iter() {
+        assert!(!matches!(fourcc, b"stts" | b"stss" | b"stsc" | b"stsz" | b"stco"));
+    }
+
+    let audio_info = visited(&ReadConfig::AUDIO_INFO_ONLY);
+    assert!(audio_info.iter().any(|(f, _)| f == b"stsz"));
+    assert!(audio_info.iter().all(|(f, _)| f != b"stco"));
+}
+
+#[test]
+fn buffered_reads() {
+    // skipping atoms doesn't discard the buffer of a buffered reader
+    let files = [
+        "files/sample.m4a",
+        "files/sample-alac.m4a",
+        "files/sample-chaptered.m4a",
+        "files/sample.m4v",
+        "files/sample-multi-track.3gp",
+    ];
+    for path in files {
+        let mut unbuffered = ReadCallCounter { inner: File::open(path).unwrap(), calls: 0 };
+        let unbuffered_tag = Tag::read_from(&mut unbuffered).unwrap();
+
+        let reader = ReadCallCounter { inner: File::open(path).unwrap(), calls: 0 };
+        let mut buffered = BufReader::new(reader);
+        let buffered_tag = Tag::read_from(&mut buffered).unwrap();
+        let buffered = buffered.into_inner();
+
+        assert_eq!(buffered_tag.title(), unbuffered_tag.title());
+        assert!(
+            buffered.calls * 10 < unbuffered.calls,
+            "{path}: {} buffered and {} unbuffered read calls",
+            buffered.calls,
+            unbuffered.calls,
+        );
+    }
+}
+
+#[test]
+fn skip_large_atoms() {
+    const MDAT_LEN: u64 = 3 << 29; // 1.5 GiB
+
+    let mut data = fs::read("files/sample-alac.m4a").unwrap();
+    let file_len = data.len() as u64;
+    data.extend(1u32.to_be_bytes());
+    data.extend(b"mdat");
+    data.extend((16 + MDAT_LEN).to_be_bytes());
+    let mut reader = CountingReader {
+        data,
+        len: file_len + 16 + MDAT_LEN,
+        pos: 0,
+        read: 0,
+    };
+
+    let tag = Tag::read_from(&mut reader).unwrap();
+    assert_eq!(tag.mdat_len(), 26472 + MDAT_LEN);
+    assert_eq!(tag.title(), Some("TEST TITLE"));
+    // the contents of the media data are skipped by seeking
+    assert!(reader.read < file_len, "read {} bytes", reader.read);
+}
+
+#[test]
+fn skip_artwork() {
+    let mut png = fs::read("files/artwork.png").unwrap();
+    png.resize(1 << 20, 0);
+    let target_file = use_sample_file("files/sample.m4a", "target/skip_artwork.m4a");
+    let mut tag = get_tag_1();
+    tag.set_artwork(Img::png(png.clone()));
+    write_tag(&tag, target_file);
+
+    // the payload isn't read, but its presence and size are recorded
+    let data = fs::read(target_file).unwrap();
+    let len = data.len() as u64;
+    let mut reader = CountingReader { data: data.clone(), len, pos: 0, read: 0 };
+    Tag::read_from(&mut reader).unwrap();
+    let full_read = reader.read;
+
+    let cfg = ReadConfig { skip_artwork: true, ..Default::default() };
+    let mut reader = CountingReader { data, len, pos: 0, read: 0 };
+    let mut tag = Tag::read_with(&mut reader, &cfg).unwrap();
+    assert!(reader.read < full_read / 100, "read {} of {full_read} bytes", reader.read);
+    assert_eq!(tag.artworks().count(), 0);
+    assert_eq!(tag.artwork_count(), 1);
+    assert_eq!(tag.artwork_len(), png.len() as u64);
+    assert!(matches!(tag.data_of(&ident::ARTWORK).next(), Some(Data::Skipped(d)) if d.is_image()));
+    assert_eq!(tag.title(), Some("TEST TITLE"));
+
+    // the skipped payload is copied from the file when writing
+    tag.set_title("NEW TITLE");
+    write_tag(&tag, target_file);
+    let tag = read_tag(target_file);
+    assert_eq!(tag.title(), Some("NEW TITLE"));
+    assert_eq!(tag.artwork(), Some(Img::png(png.as_slice())));
+
+    // other items, including freeform ones
+    let cfg = ReadConfig {
+        skip_idents: vec![ident::LYRICS.into(), ident::ISRC.into()],
+        ..Default::default()
+    };
+    let mut tag = Tag::read_with_path(target_file, &cfg).unwrap();
+    assert_eq!(tag.lyrics(), None);
+    assert_eq!(tag.isrc(), None);
+    assert_eq!(tag.lyricist(), Some("TEST LYRICIST"));
+    assert_eq!(tag.artwork(), Some(Img::png(png.as_slice())));
+    tag.set_album("NEW ALBUM");
+    write_tag(&tag, target_file);
+    let tag = read_tag(target_file);
+    assert_eq!(tag.album(), Some("NEW ALBUM"));
+    assert_eq!(tag.lyrics(), Some("TEST LYRICS"));
+    assert_eq!(tag.isrc(), Some("TEST ISRC"));
+
+    // skipped data can't be written to another file
+    let cfg = ReadConfig { skip_artwork: true, ..Default::default() };
+    let tag = Tag::read_with_path(target_file, &cfg).unwrap();
+    let other_file = use_sample_file("files/sample.m4a", "target/skip_artwork_other.m4a");
+    let err = tag.write_to_path(other_file).unwrap_err();
+    assert!(matches!(err.kind, ErrorKind::InvalidData), "{err:?}");
+    assert_eq!(fs::read(other_file).unwrap(), fs::read("files/sample.m4a").unwrap());
+}
+
+#[test]
+fn scan_idents() {
+    let mut png = fs::read("files/artwork.png").unwrap();
+    png.resize(1 << 20, 0);
+    let target_file = use_sample_file("files/sample.m4a", "target/scan_idents.m4a");
+    let mut tag = get_tag_1();
+    tag.set_artwork(Img::png(png.clone()));
+    write_tag(&tag, target_file);
+    let tag = read_tag(target_file);
+
+    // the identifiers and payload lengths match a full read, in the order of the file
+    let data = fs::read(target_file).unwrap();
+    let len = data.len() as u64;
+    let mut reader = CountingReader { data, len, pos: 0, read: 0 };
+    let idents = Tag::scan_idents_from(&mut reader).unwrap();
+    assert!(reader.read < len / 100, "read {} of {len} bytes", reader.read);
+
+    let mut expected: Vec<(DataIdent, usize)> = Vec::new();
+    for (ident, data) in tag.data() {
+        match expected.last_mut() {
+            Some((i, len)) if i == ident => *len += data.data_len() as usize,
+            _ => expected.push((ident.clone(), data.data_len() as usize)),
+        }
+    }
+    assert_eq!(idents, expected);
+    assert!(idents.contains(&(ident::ARTWORK.into(), png.len())));
+    assert!(idents.contains(&(ident::ISRC.into(), "TEST ISRC".len())));
+
+    // the result can drive a subsequent read
+    let skip_idents = idents.into_iter().filter(|(_, len)| *len > 1024).map(|(i, _)| i);
+    let cfg = ReadConfig {
+        skip_idents: skip_idents.collect(),
+        ..Default::default()
+    };
+    let tag = Tag::read_with_path(target_file, &cfg).unwrap();
+    assert_eq!(tag.artworks().count(), 0);
+    assert_eq!(tag.artwork_len(), png.len() as u64);
+    assert_eq!(tag.title(), Some("TEST TITLE"));
+
+    // files without metadata
+    let target_file = use_sample_file("files/sample.m4a", "target/scan_idents_empty.m4a");
+    Tag::remove_from_path(target_file).unwrap();
+    assert_eq!(Tag::scan_idents_from_path(target_file).unwrap(), []);
+}
+
+#[test]
+fn he_aac_codec() {
+    // same as sample.m4a, but the backward compatible signaling reports SBR to be present
+    let tag = read_tag("files/sample-he-aac.m4a");
+    assert_eq!(tag.codec(), Some(Codec::HeAac));
+    assert_eq!(tag.sample_rate(), Some(SampleRate::Hz44100));
+    assert_eq!(tag.channel_config(), Some(ChannelConfig::Mono));
+}
+
+#[test]
+fn he_aac_v2_audio_info() {
+    // same as sample.m4a, but with explicit signaling of SBR and PS, at a core sample rate of
+    // 22.05kHz and an extension sample rate of 44.1kHz
+    let tag = read_tag("files/sample-he-aac-v2.m4a");
+    let info = tag.audio_info();
+    assert_eq!(info.codec, Some(Codec::HeAacV2));
+    assert_eq!(info.spectral_band_replication, Some(true));
+    assert_eq!(info.parametric_stereo, Some(true));
+    assert_eq!(info.sample_rate, Some(SampleRate::Hz22050));
+    assert_eq!(info.extension_sample_rate, Some(44100));
+    assert_eq!(tag.effective_sample_rate(), Some(44100));
+    assert_eq!(tag.channel_config(), Some(ChannelConfig::Mono));
+
+    // backward compatible signaling of absent SBR
+    let info = read_tag("files/sample.m4a").audio_info().clone();
+    assert_eq!(info.spectral_band_replication, Some(false));
+    assert_eq!(info.parametric_stereo, Some(false));
+    assert_eq!(info.effective_sample_rate(), Some(44100));
+
+    // no signaling in the audio specific config of ALAC
+    let info = read_tag("files/sample-alac.m4a").audio_info().clone();
+    assert_eq!(info.spectral_band_replication, None);
+    assert_eq!(info.effective_sample_rate(), Some(44100));
+}
+
+#[test]
+fn alac_audio_info() {
+    // the sample entry reports a sample size of 16, which the magic cookie takes precedence over
+    let tag = read_tag("files/sample-alac.m4a");
+    assert_eq!(tag.bit_depth(), Some(24));
+    assert_eq!(tag.codec(), Some(Codec::Alac));
+    assert_eq!(tag.frames_per_packet(), Some(4096));
+    let cookie = tag.alac_cookie().unwrap();
+    assert_eq!(cookie.len(), 24);
+    assert_eq!(cookie[5], 24);
+    assert_eq!(cookie[9], 2);
+    assert_eq!(tag.channel_config(), Some(ChannelConfig::Stereo));
+    assert_eq!(tag.sample_rate(), Some(SampleRate::Hz44100));
+    assert_eq!(tag.duration(), Duration::from_millis(100));
+}
+
+#[test]
+fn chaptered() {
+    let tag = read_tag("files/sample-chaptered.m4a");
+    let chapters = [
+        Chapter::new(Duration::ZERO, "The Pledge"),
+        Chapter::new(Duration::new(7 * 60 + 28, 1_000_000), "The Turn"),
+        Chapter::new(Duration::new(64 * 60 + 44, 0), "The Prestige"),
+    ];
+    assert_eq!(tag.chapter_list(), chapters);
+    assert_eq!(tag.chapter_track(), chapters);
+    assert_eq!(tag.chapters_source(), Some(ChapterSource::List));
+}
+
+#[test]
+fn collection() {
+    if let Some(path) = std::env::args().skip_while(|a| a != "collection").nth(1) {
+        println!("Testing collection at {}", &path);
+        read_dir(&path, |_, _| {});
+    } else {
+        println!("Skipping collection test since no path was provided.");
+    }
+}
+
+#[test]
+fn read_from_slice() {
+    read_dir("files", |p, t| {
+        let data = fs::read(p).unwrap();
+        assert_eq!(&Tag::read_from_slice(&data).unwrap(), t);
+
+        // the same bounds checks apply to a truncated movie atom
+        let pos = data.windows(4).position(|w| w == b"moov").unwrap() - 4;
+        let len = u32::from_be_bytes(data[pos..pos + 4].try_into().unwrap()) as usize;
+        let err = Tag::read_from_slice(&data[..pos + len - 1]).unwrap_err();
+        assert!(matches!(err.kind, ErrorKind::AtomSizeOutOfBounds | ErrorKind::Io(_)), "{err:?}");
+    });
+}
+
+#[cfg(feature = "mmap")]
+#[test]
+fn read_from_mmap() {
+    read_dir("files", |p, t| {
+        // SAFETY: the sample files aren't modified by any test
+        let tag = unsafe { Tag::read_from_mmap(p) }.unwrap();
+        assert_eq!(&tag, t);
+    });
+}
+
+#[cfg(feature = "tokio")]
+#[tokio::test]
+async fn read_from_path_async() {
+    for d in WalkDir::new("files").into_iter().filter_map(|e| e.ok()) {
+        let p = d.path();
+        if !EXTENSIONS.iter().any(|e| p.to_string_lossy().ends_with(e)) {
+            continue;
+        }
+        println!("reading {} asynchronously...", p.display());
+        let expected = Tag::read_from_path(p).unwrap();
+        let tag = Tag::read_from_path_async(p).await.unwrap();
+        assert_eq!(tag, expected);
+        assert_eq!(tag.chapters(), expected.chapters());
+    }
+}
+
+#[cfg(feature = "tokio")]
+#[tokio::test]
+async fn read_small_atom_async() {
+    // a 12 byte free atom, so the 16 bytes fetched for its head overlap the head of the next atom
+    let mut buf = fs::read("files/sample.m4a").unwrap();
+    let free = find_atom(&buf, &[b"free"]).unwrap();
+    assert_eq!(free.len(), 8);
+    buf[free.start + 3] = 12;
+    buf.splice(free.end..free.end, [0; 4]);
+    let target_file = "target/read_small_atom_async.m4a";
+    fs::write(target_file, &buf).unwrap();
+
+    let expected = Tag::read_from_path(target_file).unwrap();
+    let tag = Tag::read_from_path_async(target_file).await.unwrap();
+    assert_eq!(tag, expected);
+    assert_tag_1(&tag);
+}
+
+#[cfg(feature = "tokio")]
+#[tokio::test]
+async fn write_to_path_async() {
+    let target_file = use_sample_file("files/sample.m4a", "target/write_to_path_async.m4a");
+
+    let mut tag = Tag::read_from_path(target_file).unwrap();
+    tag.set_title("async title");
+    tag.write_to_path_async(target_file).await.unwrap();
+
+    let tag = Tag::read_from_path_async(target_file).await.unwrap();
+    assert_eq!(tag.title(), Some("async title"));
+    assert!(!Path::new("target/.write_to_path_async.m4a.tmp").exists());
+
+    // the same file is written as by the sync writer, the media data is copied in chunks
+    let faststart = WriteConfig { faststart: true, ..WriteConfig::DEFAULT };
+    let backup = WriteConfig {
+        backup: Some(BackupMode::Sibling(".bak".into())),
+        padding: 0,
+        ..WriteConfig::DEFAULT
+    };
+    for (sample_file, cfg) in [
+        ("files/sample-chaptered.m4a", WriteConfig::DEFAULT),
+        ("files/sample-chaptered.m4a", faststart),
+        ("files/sample-64.mp4", backup),
+    ] {
+        let expected_file = use_sample_file(sample_file, "target/write_to_path_async-expected");
+        let target_file = use_sample_file(sample_file, "target/write_to_path_async");
+        let mut tag = Tag::read_from_path(target_file).unwrap();
+        tag.set_title("async title");
+        tag.add_artwork(Img::png(fs::read("files/artwork.png").unwrap()));
+
+        let expected_outcome = tag.write_with_path(expected_file, &cfg).unwrap();
+        let outcome = tag.write_with_path_async(target_file, &cfg).await.unwrap();
+        assert_eq!(outcome, expected_outcome, "{sample_file}");
+        assert!(
+            fs::read(target_file).unwrap() == fs::read(expected_file).unwrap(),
+            "{sample_file}"
+        );
+        if cfg.backup.is_some() {
+            let backup = fs::read("target/write_to_path_async.bak").unwrap();
+            assert!(backup == fs::read(sample_file).unwrap());
+        }
+    }
+}
+
+#[cfg(feature = "tokio")]
+#[tokio::test]
+async fn write_to_path_async_cancelled() {
+    let target_file =
+        use_sample_file("files/sample.m4a", "target/write_to_path_async_cancelled.m4a");
+    let temp_file = Path::new("target/.write_to_path_async_cancelled.m4a.tmp");
+    let original = fs::read(target_file).unwrap();
+
+    let mut tag = Tag::read_from_path(target_file).unwrap();
+    tag.set_title("cancelled title");
+    // poll until the temporary file has been created, then drop the future. blocking tasks may
+    // complete before they are polled, so the write is retried if it completed anyway.
+    let mut cancelled = false;
+    for _ in 0..100 {
+        let mut task = tokio_test::task::spawn(tag.write_to_path_async(target_file));
+        for _ in 0..1000 {
+            if task.poll().is_ready() || temp_file.exists() {
+                break;
+            }
+            std::thread::sleep(Duration::from_millis(1));
+        }
+        if temp_file.exists() {
+            drop(task);
+            cancelled = true;
+            break;
+        }
+        fs::write(target_file, &original).unwrap();
+    }
+    assert!(cancelled, "the write always completed before it could be cancelled");
+
+    assert_eq!(fs::read(target_file).unwrap(), original);
+    assert!(!temp_file.exists());
+}
+
+#[test]
+fn sample_files() {
+    let _ = fs::remove_dir_all("target/files");
+    let _ = fs::create_dir("target/files");
+
+    read_dir("files", |p, t| {
+        let file_name = p.file_name().unwrap().to_owned();
+        let mut path = PathBuf::from("target/files");
+        path.push(file_name);
+
+        println!("copying {} to {}...", p.display(), path.display());
+        fs::copy(p, &path).unwrap();
+
+        println!("writing empty tag");
+        Tag::default().write_to_path(&path).unwrap();
+        println!("reading empty tag");
+        let tag = Tag::read_from_path(&path).unwrap();
+        assert!(tag.is_empty());
+        assert_eq!(tag.audio_info(), t.audio_info());
+
+        println!("writing sample tag 1");
+        get_tag_1().write_to_path(&path).unwrap();
+        println!("reading sample tag 1");
+        let tag = Tag::read_from_path(&path).unwrap();
+        assert_tag_1(&tag);
+        assert_eq!(tag.audio_info(), t.audio_info());
+
+        println!("writing sample tag 2");
+        get_tag_2().write_to_path(&path).unwrap();
+        println!("reading sample tag 2");
+        let tag = Tag::read_from_path(&path).unwrap();
+        assert_tag_2(&tag);
+        // the chapter track samples written to the media data change an estimated bitrate
+        let declared_info = |t: &Tag| {
+            let mut info = t.audio_info().clone();
+            if info.bitrate_estimated {
+                info.avg_bitrate = None;
+            }
+            info
+        };
+        assert_eq!(declared_info(&tag), declared_info(t));
+        println!();
+    });
+}
+
+#[test]
+fn chapter_list_title_truncation() {
+    let target_file = use_sample_file("files/sample.m4a", "target/chapter_list_title.m4a");
+
+    let mut tag = Userdata::default();
+    tag.chapter_list_mut().extend([
+        Chapter::new(Duration::ZERO, "a".repeat(255) + "truncated"),
+        Chapter::new(Duration::from_millis(20), "after"),
+    ]);
+    write_tag(&tag, target_file);
+
+    let tag = read_tag(target_file);
+    assert_eq!(
+        tag.chapter_list(),
+        [
+            Chapter::new(Duration::ZERO, "a".repeat(255)),
+            Chapter::new(Duration::from_millis(20), "after"),
+        ],
+    );
+}
+
+#[test]
+fn chapter_list_sorted() {
+    let target_file = use_sample_file("files/sample.m4a", "target/chapter_list_sorted.m4a");
+
+    let mut tag = Userdata::default();
+    tag.set_chapter_list([
+        Chapter::new(Duration::from_millis(20), "second"),
+        Chapter::new(Duration::ZERO, "first"),
+    ]);
+    write_tag(&tag, target_file);
+
+    // version 0, 2 entries, 100ns timestamps, length prefixed titles
+    let buf = fs::read(target_file).unwrap();
+    let chpl = find_atom(&buf, &[b"moov", b"udta", b"chpl"]).unwrap();
+    #[rustfmt::skip]
+    let expected = [
+        &[0, 0, 0, 0, 2][..],
+        &0u64.to_be_bytes(), &[5], b"first",
+        &200_000u64.to_be_bytes(), &[6], b"second",
+    ]
+    .concat();
+    assert_eq!(buf[chpl.start + 8..chpl.end], expected);
+
+    let tag = read_tag(target_file);
+    assert_eq!(
+        tag.chapter_list(),
+        [Chapter::new(Duration::ZERO, "first"), Chapter::new(Duration::from_millis(20), "second"),],
+    );
+
+    let mut tag = Userdata::default();
+    tag.set_chapter_list([
+        Chapter::new(Duration::ZERO, "first"),
+        Chapter::new(Duration::from_millis(20), "second"),
+        Chapter::new(Duration::ZERO, "duplicate"),
+    ]);
+    let err = tag.write_to_path(target_file).unwrap_err();
+    assert!(matches!(err.kind, ErrorKind::InvalidChapterList));
+    assert_eq!(fs::read(target_file).unwrap(), buf);
+}
+
+#[test]
+fn chapter_list_invalid_utf8_title() {
+    let target_file = use_sample_file("files/sample.m4a", "target/chapter_list_invalid_utf8.m4a");
+
+    let mut tag = Userdata::default();
+    tag.chapter_list_mut().extend([
+        Chapter::new(Duration::ZERO, "Caf\u{e9}"),
+        Chapter::new(Duration::from_millis(20), "after"),
+    ]);
+    write_tag(&tag, target_file);
+
+    // replace the utf-8 encoded 'é' with a latin-1 encoded one, as written by legacy tools
+    let mut buf = fs::read(target_file).unwrap();
+    let chpl = find_atom(&buf, &[b"moov", b"udta", b"chpl"]).unwrap();
+    let pos = chpl.start + buf[chpl.clone()].windows(2).position(|w| w == [0xc3, 0xa9]).unwrap();
+    buf.splice(pos..pos + 2, [0xe9, b'_']);
+    fs::write(target_file, buf).unwrap();
+
+    let tag = read_tag(target_file);
+    assert_eq!(
+        tag.chapter_list(),
+        [
+            Chapter::new(Duration::ZERO, "Caf\u{fffd}_"),
+            Chapter::new(Duration::from_millis(20), "after"),
+        ],
+    );
+}
+
+#[test]
+fn chapter_track_title_truncation() {
+    let target_file = use_sample_file("files/sample.m4a", "target/chapter_track_title.m4a");
+
+    let mut tag = Userdata::default();
+    tag.chapter_track_mut().extend([
+        Chapter::new(Duration::ZERO, "a".repeat(65535) + "truncated"),
+        Chapter::new(Duration::from_millis(20), "after"),
+    ]);
+    write_tag(&tag, target_file);
+
+    let tag = read_tag(target_file);
+    assert_eq!(
+        tag.chapter_track(),
+        [
+            Chapter::new(Duration::ZERO, "a".repeat(65535)),
+            Chapter::new(Duration::from_millis(20), "after"),
+        ],
+    );
+}
+
+#[test]
+fn normalize_chapters() {
+    let target_file = use_sample_file("files/sample.m4a", "target/normalize_chapters.m4a");
+
+    let mut tag = Userdata::default();
+    tag.set_chapter_list([
+        Chapter::new(Duration::from_millis(135), "The Turn"),
+        Chapter::new(Duration::ZERO, "The Pledge"),
+    ]);
+    write_tag(&tag, target_file);
+
+    let mut tag = read_tag(target_file);
+    assert!(tag.chapter_track().is_empty());
+    tag.normalize_chapters(ChapterFormat::Both);
+    tag.validate_chapters().unwrap();
+    write_tag(&tag, target_file);
+
+    let tag = read_tag(target_file);
+    let chapters = [
+        Chapter::new(Duration::ZERO, "The Pledge"),
+        Chapter::new(Duration::from_millis(135), "The Turn"),
+    ];
+    assert_eq!(tag.chapter_list(), chapters);
+    assert_eq!(tag.chapter_track(), chapters);
+    assert_eq!(tag.chapter_ends(), [Duration::from_millis(135), tag.duration()]);
+}
+
+#[test]
+fn chapter_track_structure() {
+    let target_file = use_sample_file("files/sample.m4a", "target/chapter_track_structure.m4a");
+
+    let mut tag = Userdata::default();
+    tag.chapter_track_mut().extend([
+        Chapter::new(Duration::ZERO, "first"),
+        Chapter::new(Duration::from_millis(20), "second"),
+    ]);
+    write_tag(&tag, target_file);
+
+    let buf = fs::read(target_file).unwrap();
+    let be_u32 = |pos: usize| u32::from_be_bytes(buf[pos..pos + 4].try_into().unwrap());
+    let moov = find_atom(&buf, &[b"moov"]).unwrap();
+    let traks: Vec<_> = (child_atoms(&buf, moov.start + 8, moov.end).into_iter())
+        .filter(|(f, _)| f == b"trak")
+        .map(|(_, r)| r)
+        .collect();
+    let [audio_trak, chapter_trak] = traks.as_slice() else {
+        panic!("expected an audio and a chapter track");
+    };
+    let child = |parent: &Range<usize>, path: &[&[u8; 4]]| {
+        let mut range = parent.clone();
+        for fourcc in path {
+            let atoms = child_atoms(&buf, range.start + 8, range.end);
+            range = atoms.into_iter().find(|(f, _)| f == *fourcc).unwrap().1;
+        }
+        range
+    };
+
+    // the chapter track is disabled and referenced by the audio track
+    let tkhd = child(chapter_trak, &[b"tkhd"]);
+    assert_eq!(be_u32(tkhd.start + 8) & 0xffffff, 0);
+    let chapter_id = be_u32(tkhd.start + 20);
+    let chap = child(audio_trak, &[b"tref", b"chap"]);
+    assert_eq!(be_u32(chap.start + 8), chapter_id);
+
+    // the text samples are stored inside the media data
+    let hdlr = child(chapter_trak, &[b"mdia", b"hdlr"]);
+    assert_eq!(&buf[hdlr.start + 16..hdlr.start + 20], b"text");
+    let co64 = child(chapter_trak, &[b"mdia", b"minf", b"stbl", b"co64"]);
+    let offset = u64::from_be_bytes(buf[co64.start + 16..co64.start + 24].try_into().unwrap());
+    let mdat = find_atom(&buf, &[b"mdat"]).unwrap();
+    assert!(mdat.contains(&(offset as usize)));
+    assert_eq!(&buf[offset as usize..][..7], b"\0\x05first");
+}
+
+#[test]
+fn previous_chapter_track_media_data_is_removed() {
+    let target_file = use_sample_file("files/sample.m4a", "target/chapter_track_doesnt_grow.m4a");
+
+    let chapters = [
+        Chapter::new(Duration::ZERO, "The Pledge"),
+        Chapter::new(Duration::from_millis(135), "The Turn"),
+        Chapter::new(Duration::from_millis(324), "The Prestige"),
+    ];
+
+    let mut tag = Userdata::default();
+    tag.chapter_track_mut().extend(chapters.clone());
+    write_tag(&tag, target_file);
+
+    let file = std::fs::File::open(target_file).unwrap();
+    let prev_size = file.metadata().unwrap().len();
+
+    write_tag(&tag, target_file);
+
+    let file = std::fs::File::open(target_file).unwrap();
+    let new_size = file.metadata().unwrap().len();
+
+    assert_eq!(prev_size, new_size);
+
+    let tag = read_tag(target_file);
+    assert_eq!(tag.chapter_track(), chapters);
+}
+
+#[test]
+fn bench() {
+    let Some(in_file) = std::env::args().skip_while(|a| a != "bench").nth(1) else {
+        println!("Skipping bench test since no input file was provided.");
+        return;
+    };
+    println!("Running bench with file: {in_file}");
+
+    let target_file = use_sample_file(&in_file, "target/bench.m4a");
+    let in_tag = read_tag(target_file);
+
+    let start = std::time::Instant::now();
+    for _ in 0..300 {
+        std::fs::copy(&in_file, target_file).unwrap();
+
+        Tag::default().write_to_path(target_file).unwrap();
+        let tag = Tag::read_from_path(target_file).unwrap();
+        assert!(tag.is_empty());
+        assert_eq!(tag.audio_info(), in_tag.audio_info());
+
+        get_tag_1().write_to_path(target_file).unwrap();
+        let tag = Tag::read_from_path(target_file).unwrap();
+        assert_tag_1(&tag);
+        assert_eq!(tag.audio_info(), in_tag.audio_info());
+
+        get_tag_2().write_to_path(target_file).unwrap();
+        let tag = Tag::read_from_path(target_file).unwrap();
+        assert_tag_2(&tag);
+        assert_eq!(tag.audio_info(), in_tag.audio_info());
+    }
+    let end = std::time::Instant::now();
+    let millis = end.duration_since(start).as_millis();
+    println!("took: {millis}ms");
+}
+
+#[test]
+fn bench_read() {
+    if !std::env::args().any(|a| a == "bench_read") {
+        println!("Skipping read bench test since it wasn't explicitly requested.");
+        return;
+    }
+
+    let files = std::cell::RefCell::new(Vec::new());
+    read_dir("files", |p, _| files.borrow_mut().push(p.to_owned()));
+    let files = files.into_inner();
+    let data: Vec<_> = files.iter().map(|p| fs::read(p).unwrap()).collect();
+
+    let start = std::time::Instant::now();
+    for _ in 0..300 {
+        for path in files.iter() {
+            Tag::read_from_path(path).unwrap();
+        }
+    }
+    println!("read_from_path took: {}ms", start.elapsed().as_millis());
+
+    let start = std::time::Instant::now();
+    for _ in 0..300 {
+        for data in data.iter() {
+            Tag::read_from_slice(data).unwrap();
+        }
+    }
+    println!("read_from_slice took: {}ms", start.elapsed().as_millis());
+
+    #[cfg(feature = "mmap")]
+    {
+        let start = std::time::Instant::now();
+        for _ in 0..300 {
+            for path in files.iter() {
+                // SAFETY: the sample files aren't modified by any test
+                unsafe { Tag::read_from_mmap(path) }.unwrap();
+            }
+        }
+        println!("read_from_mmap took: {}ms", start.elapsed().as_millis());
+    }
+}
+
+#[test]
+fn read_sample() {
+    let tag = read_tag("files/sample.m4a");
+    assert_tag_1(&tag);
+    assert_readonly(&tag);
+}
+
+#[cfg(feature = "encoding_rs")]
+#[test]
+fn read_fallback_encoding() {
+    let target_file = use_sample_file("files/sample.m4a", "target/read_fallback_encoding.m4a");
+
+    let mut tag = Userdata::default();
+    tag.set_title("TITLE");
+    write_tag(&tag, target_file);
+
+    // replace the title with a Shift-JIS encoded one
+    let mut buf = fs::read(target_file).unwrap();
+    let pos = buf.windows(5).position(|w| w == b"TITLE").unwrap();
+    buf[pos..pos + 5].copy_from_slice(b"\x93\xfa\x96\x7b!");
+    fs::write(target_file, buf).unwrap();
+
+    let tag = Tag::read_from_path(target_file).unwrap();
+    assert_eq!(tag.title(), None);
+
+    let cfg = ReadConfig {
+        fallback_encoding: Some(mp4ameta::FallbackEncoding::new(encoding_rs::SHIFT_JIS)),
+        ..ReadConfig::DEFAULT
+    };
+    let tag = Tag::read_with_path(target_file, &cfg).unwrap();
+    assert_eq!(tag.title(), Some("\u{65e5}\u{672c}!"));
+}
+
+#[test]
+fn read_encrypted() {
+    let stsd: &[&[u8; 4]] = &[b"moov", b"trak", b"mdia", b"minf", b"stbl", b"stsd"];
+    let buf = fs::read("files/sample.m4a").unwrap();
+    let stsd = find_atom(&buf, stsd).unwrap();
+    let mp4a = stsd.start + 16;
+    assert_eq!(&buf[mp4a + 4..mp4a + 8], b"mp4a");
+    let esds = mp4a + 8 + 28;
+    assert_eq!(&buf[esds + 4..esds + 8], b"esds");
+
+    // a protected sample entry, and a sample entry with protection scheme information
+    for (pos, fourcc) in [(mp4a, b"drms"), (esds, b"sinf")] {
+        let target_file = "target/read_encrypted.m4a";
+        let mut buf = buf.clone();
+        buf[pos + 4..pos + 8].copy_from_slice(fourcc);
+        fs::write(target_file, &buf).unwrap();
+
+        // the metadata can still be read
+        let tag = Tag::read_from_path(target_file).unwrap();
+        assert!(tag.is_encrypted());
+        assert_eq!(tag.title(), Some("TEST TITLE"));
+
+        // the sample descriptions aren't parsed if only the metadata is requested
+        let cfg = ReadConfig { read_meta_items: true, ..ReadConfig::NONE };
+        let tag = Tag::read_with_path(target_file, &cfg).unwrap();
+        assert!(!tag.is_encrypted());
+        assert_eq!(tag.title(), Some("TEST TITLE"));
+    }
+
+    let tag = read_tag("files/sample.m4a");
+    assert!(!tag.is_encrypted());
+}
+
+#[test]
+fn size_report() {
+    let tag = read_tag("files/sample.m4a");
+    let report = tag.size_report();
+    assert_eq!(ident::ARTWORK, report[0].0);
+    assert!(report.windows(2).all(|w| w[0].1 >= w[1].1));
+
+    let buf = fs::read("files/sample.m4a").unwrap();
+    let ilst = find_atom(&buf, &[b"moov", b"udta", b"meta", b"ilst"]).unwrap();
+    assert_eq!(tag.total_metadata_bytes(), ilst.len() as u64 - 8);
+}
+
+#[test]
+fn read_artwork_only() {
+    for file in ["files/sample.m4a", "files/sample-multi-data.m4a", "files/sample-64.mp4"] {
+        let tag = read_tag(file);
+        let artworks: Vec<_> = tag.artworks().map(|i| Img::new(i.fmt, i.data.to_vec())).collect();
+        assert_eq!(Tag::read_artwork_from_path(file).unwrap(), artworks);
+    }
+
+    let target_file = use_sample_file("files/sample.m4a", "target/read_artwork_only.m4a");
+    let mut tag = get_tag_1().userdata;
+    tag.add_artwork(Img::jpeg(b"second".to_vec()));
+    write_tag(&tag, target_file);
+    let artworks = Tag::read_artwork_from_path(target_file).unwrap();
+    assert_eq!(artworks.len(), 2);
+    assert_eq!(
+        artworks,
+        tag.artworks().map(|i| Img::new(i.fmt, i.data.to_vec())).collect::<Vec<_>>()
+    );
+}
+
+#[test]
+fn read_sample_multi_data() {
+    let tag = read_tag("files/sample-multi-data.m4a");
+    assert_tag_3(&tag);
+    assert_readonly(&tag);
+}
+
+#[test]
+fn read_sample_freeform_swapped() {
+    // the `name` atom precedes the `mean` atom, and once even the `data` atom
+    let tag = read_tag("files/sample-freeform-swapped.m4a");
+    assert_tag_1(&tag);
+    assert_readonly(&tag);
+    let length = FreeformIdent::new_static("com.apple.iTunes", "~length");
+    assert_eq!(tag.strings_of(&length).next(), Some("0:00"));
+
+    // written in the order of the spec
+    let target_file =
+        use_sample_file("files/sample-freeform-swapped.m4a", "target/freeform_swapped.m4a");
+    write_tag(&tag, target_file);
+    assert_eq!(fs::read(target_file).unwrap(), fs::read("files/sample.m4a").unwrap());
+}
+
+#[test]
+fn musicbrainz_ids() {
+    const ALBUM_ID: &str = "9e8c8c2a-5b1f-4a36-9a0e-4c3b2f1e0d9c";
+    let target_file = use_sample_file("files/sample.m4a", "target/musicbrainz_ids.m4a");
+    let mut tag = read_tag(target_file);
+    assert_eq!(tag.musicbrainz_album_id(), None);
+
+    tag.set_musicbrainz_album_id(ALBUM_ID);
+    tag.add_musicbrainz_artist_id("artist 1");
+    tag.add_musicbrainz_artist_id("artist 2");
+    write_tag(&tag, target_file);
+
+    let mut tag = read_tag(target_file);
+    assert_tag_1(&tag);
+    assert_eq!(tag.musicbrainz_album_id(), Some(ALBUM_ID));
+    assert_eq!(tag.freeform("com.apple.iTunes", "MusicBrainz Album Id"), Some(ALBUM_ID));
+    assert_eq!(tag.musicbrainz_artist_ids().collect::<Vec<_>>(), ["artist 1", "artist 2"]);
+    assert_eq!(tag.musicbrainz_track_id(), None);
+    assert_eq!(tag.validate(), []);
+
+    tag.remove_musicbrainz_album_id();
+    write_tag(&tag, target_file);
+    let tag = read_tag(target_file);
+    assert_eq!(tag.musicbrainz_album_id(), None);
+    assert_eq!(tag.musicbrainz_artist_ids().count(), 2);
+}
+
+#[test]
+fn copy_metadata() {
+    // the destination has no user data, which is created
+    let target_file = use_sample_file("files/sample.m4v", "target/copy_metadata.m4v");
+    let mdat = |path: &str| {
+        let buf = fs::read(path).unwrap();
+        buf[find_atom(&buf, &[b"mdat"]).unwrap()][8..].to_vec()
+    };
+    let media_data = mdat(target_file);
+    let video_info = read_tag(target_file).video_info;
+
+    Tag::copy_metadata("files/sample.m4a", target_file).unwrap();
+
+    assert_eq!(mdat(target_file), media_data);
+    let tag = read_tag(target_file);
+    assert_tag_1(&tag);
+    assert_eq!(tag.video_info, video_info);
+    assert_eq!(tag.duration(), Duration::from_millis(333));
+
+    // the chapters of the destination are kept
+    let target_file = use_sample_file("files/sample-chaptered.m4a", "target/copy_metadata.m4a");
+    let chapters = read_tag(target_file).chapters().to_vec();
+    assert!(!chapters.is_empty());
+
+    Tag::copy_metadata("files/sample.m4a", target_file).unwrap();
+
+    let tag = read_tag(target_file);
+    assert_tag_1(&tag);
+    assert_eq!(tag.chapters(), chapters);
+}
+
+#[test]
+fn write() {
+    let target_file = use_sample_file("files/sample.m4a", "target/write.m4a");
+
+    let tag = get_tag_2();
+    write_tag(&tag, target_file);
+
+    let tag = read_tag(target_file);
+    assert_tag_2(&tag);
+    assert_readonly(&tag);
+}
+
+#[test]
+fn write_same() {
+    let target_file = use_sample_file("files/sample.m4a", "target/write_same.m4a");
+
+    let tag = get_tag_1();
+    write_tag(&tag, target_file);
+
+    let tag = read_tag(target_file);
+    assert_tag_1(&tag);
+    assert_readonly(&tag);
+}
+
+#[test]
+fn write_bigger() {
+    let target_file = use_sample_file("files/sample.m4a", "target/write_bigger.m4a");
+
+    let mut tag = get_tag_2();
+    let data: Vec<u8> = (0..512 * 1024).map(|n| n as u8).collect();
+    tag.add_data(Fourcc(*b"test"), Data::Reserved(data));
+    write_tag(&tag, target_file);
+
+    let tag = read_tag(target_file);
+    assert_tag_2(&tag);
+    assert_readonly(&tag);
+}
+
+#[test]
+fn write_in_place() {
+    let target_file = use_sample_file("files/sample.m4a", "target/write_in_place.m4a");
+    let prev_len = fs::metadata(target_file).unwrap().len();
+    let prev_mdat = read_mdat(target_file);
+
+    let mut tag = get_tag_1();
+    tag.set_title("A SOMEWHAT LONGER TEST TITLE THAT STILL FITS INTO THE PADDING");
+    assert_eq!(write_tag(&tag, target_file), WriteOutcome::InPlace);
+
+    assert_eq!(fs::metadata(target_file).unwrap().len(), prev_len);
+    assert_eq!(read_mdat(target_file), prev_mdat);
+
+    let tag = read_tag(target_file);
+    assert_eq!(tag.title(), Some("A SOMEWHAT LONGER TEST TITLE THAT STILL FITS INTO THE PADDING"));
+    assert_readonly(&tag);
+}
+
+#[test]
+fn write_in_place_moov_at_end() {
+    let target_file =
+        use_sample_file("files/sample-chaptered.m4a", "target/write_in_place_moov_at_end.m4a");
+    let prev_mdat = read_mdat(target_file);
+
+    let cfg = WriteConfig { write_chapter_track: false, ..WriteConfig::DEFAULT };
+    let mut tag = read_tag(target_file);
+    tag.set_album("TEST ALBUM");
+    let outcome = tag.write_with_path(target_file, &cfg).unwrap();
+    assert_eq!(outcome, WriteOutcome::InPlace);
+
+    assert_eq!(read_mdat(target_file), prev_mdat);
+
+    let tag = read_tag(target_file);
+    assert_eq!(tag.album(), Some("TEST ALBUM"));
+    assert_eq!(tag.chapter_track().len(), 3);
+}
+
+#[test]
+fn write_rewritten() {
+    let target_file = use_sample_file("files/sample.m4a", "target/write_rewritten.m4a");
+    let (prev_mdat_pos, _) = read_mdat(target_file);
+
+    let mut tag = get_tag_1();
+    tag.add_data(Fourcc(*b"test"), Data::Reserved(vec![0; 16 * 1024]));
+    assert_eq!(write_tag(&tag, target_file), WriteOutcome::Rewritten);
+
+    let (mdat_pos, _) = read_mdat(target_file);
+    assert!(mdat_pos > prev_mdat_pos);
+
+    let tag = read_tag(target_file);
+    assert_tag_1(&tag);
+    assert_readonly(&tag);
+}
+
+/// Asserts that only the `udta` atom, the sizes of its ancestors, padding and the chunk offsets
+/// differ between the files.
+#[track_caller]
+fn assert_only_udta_changed(old: &[u8], new: &[u8]) {
+    let (_, old_mdat) =
+        child_atoms(old, 0, old.len()).into_iter().find(|(f, _)| f == b"mdat").unwrap();
+    let (_, new_mdat) =
+        child_atoms(new, 0, new.len()).into_iter().find(|(f, _)| f == b"mdat").unwrap();
+    let offset_shift = new_mdat.start as i64 - old_mdat.start as i64;
+
+    assert_children_unchanged(old, 0..old.len(), new, 0..new.len(), offset_shift);
+}
+
+#[track_caller]
+fn assert_children_unchanged(
+    old: &[u8],
+    old_range: Range<usize>,
+    new: &[u8],
+    new_range: Range<usize>,
+    offset_shift: i64,
+) {
+    let filter = |(f, _): &([u8; 4], Range<usize>)| f != b"udta" && f != b"free";
+    let old_atoms: Vec<_> =
+        child_atoms(old, old_range.start, old_range.end).into_iter().filter(filter).collect();
+    let new_atoms: Vec<_> =
+        child_atoms(new, new_range.start, new_range.end).into_iter().filter(filter).collect();
+
+    let old_fourccs: Vec<_> = old_atoms.iter().map(|(f, _)| f).collect();
+    let new_fourccs: Vec<_> = new_atoms.iter().map(|(f, _)| f).collect();
+    assert_eq!(old_fourccs, new_fourccs);
+
+    for ((fourcc, old_range), (_, new_range)) in old_atoms.into_iter().zip(new_atoms) {
+        match &fourcc {
+            b"moov" | b"trak" | b"mdia" | b"minf" | b"stbl" => {
+                let old_content = old_range.start + 8..old_range.end;
+                let new_content = new_range.start + 8..new_range.end;
+                assert_children_unchanged(old, old_content, new, new_content, offset_shift);
+            }
+            b"stco" | b"co64" => {
+                let int_len = if &fourcc == b"stco" { 4 } else { 8 };
+                assert_eq!(
+                    old[old_range.start..old_range.start + 16],
+                    new[new_range.start..new_range.start + 16]
+                );
+                assert_eq!(old_range.len(), new_range.len());
+
+                let old_offsets = old[old_range.start + 16..old_range.end].chunks(int_len);
+                let new_offsets = new[new_range.start + 16..new_range.end].chunks(int_len);
+                for (o, n) in old_offsets.zip(new_offsets) {
+                    let to_u64 = |b: &[u8]| b.iter().fold(0, |acc, b| (acc << 8) | *b as u64);
+                    assert_eq!(to_u64(o) as i64 + offset_shift, to_u64(n) as i64);
+                }
+            }
+            _ => assert!(old[old_range] == new[new_range], "{} changed", fourcc.escape_ascii()),
+        }
+    }
+}
+
+#[test]
+fn write_only_udta_changed() {
+    let files = [
+        ("files/sample.m4a", "target/write_only_udta_changed.m4a"),
+        ("files/sample-chaptered.m4a", "target/write_only_udta_changed-chaptered.m4a"),
+        ("files/sample-64.mp4", "target/write_only_udta_changed-64.mp4"),
+    ];
+    for (sample_file, target_file) in files {
+        let target_file = use_sample_file(sample_file, target_file);
+        let old = fs::read(target_file).unwrap();
+
+        let mut tag = read_tag(target_file);
+        tag.set_title("TEST TITLE");
+        tag.add_data(Fourcc(*b"test"), Data::Reserved(vec![0; 16 * 1024]));
+        tag.write_to_path(target_file).unwrap();
+
+        let new = fs::read(target_file).unwrap();
+        assert_only_udta_changed(&old, &new);
+    }
+}
+
+#[test]
+fn write_copy() {
+    let files = [
+        ("files/sample.m4a", "target/write_copy"),
+        ("files/sample-chaptered.m4a", "target/write_copy-chaptered"),
+        ("files/sample-64.mp4", "target/write_copy-64"),
+    ];
+    for (sample_file, target_prefix) in files {
+        for (name, big) in [("small", false), ("big", true)] {
+            let src_file = format!("{target_prefix}-{name}-src");
+            let dst_file = format!("{target_prefix}-{name}-dst");
+            let in_place_file = format!("{target_prefix}-{name}-in-place");
+            use_sample_file(sample_file, &src_file);
+            use_sample_file(sample_file, &in_place_file);
+
+            let src_bytes = fs::read(&src_file).unwrap();
+            let src_mtime = fs::metadata(&src_file).unwrap().modified().unwrap();
+
+            let mut tag = get_tag_1().userdata;
+            if big {
+                tag.add_data(Fourcc(*b"test"), Data::Reserved(vec![0; 16 * 1024]));
+            }
+            tag.write_copy_to_path(&src_file, &dst_file).unwrap();
+            write_tag(&tag, &in_place_file);
+
+            assert_eq!(fs::read(&src_file).unwrap(), src_bytes);
+            assert_eq!(fs::metadata(&src_file).unwrap().modified().unwrap(), src_mtime);
+
+            // the copy has the same layout as a file that was written in place
+            assert!(fs::read(&dst_file).unwrap() == fs::read(&in_place_file).unwrap());
+
+            let tag = read_tag(&dst_file);
+            assert_tag_1(&tag);
+            assert_eq!(read_mdat(&dst_file).1, read_mdat(&in_place_file).1);
+        }
+    }
+
+    let target_file = use_sample_file("files/sample.m4a", "target/write_copy-same");
+    let err = get_tag_1().write_copy_to_path(target_file, target_file).unwrap_err();
+    assert!(matches!(err.kind, ErrorKind::Io(_)));
+}
+
+/// A writer that can't seek, only recording the written bytes.
+struct PipeWriter(Vec<u8>);
+
+impl Write for PipeWriter {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.0.write(buf)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+#[test]
+fn stream_to() {
+    for sample_file in ["files/sample.m4a", "files/sample-chaptered.m4a", "files/sample.m4b"] {
+        let in_place_file = use_sample_file(sample_file, "target/stream_to-in-place");
+        let mut tag = get_tag_1().userdata;
+        tag.chapter_track_mut().push(Chapter::new(Duration::ZERO, "The Pledge"));
+        write_tag(&tag, in_place_file);
+
+        let mut pipe = PipeWriter(Vec::new());
+        tag.stream_to(File::open(sample_file).unwrap(), &mut pipe).unwrap();
+        assert!(pipe.0 == fs::read(in_place_file).unwrap(), "{sample_file}");
+    }
+}
+
+/// An in-memory file only storing the written chunks that contain non-zero bytes, reading zeros
+/// everywhere else, so huge media data doesn't take up memory or disk space.
+#[derive(Default)]
+struct SparseFile {
+    chunks: Vec<(u64, Vec<u8>)>,
+    len: u64,
+    pos: u64,
+}
+
+impl Read for SparseFile {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let pos = self.pos;
+        let chunk = self.chunks.iter().find(|(p, c)| *p <= pos && pos < p + c.len() as u64);
+        let n = match chunk {
+            Some((p, c)) => {
+                let data = &c[(pos - p) as usize..];
+                let n = buf.len().min(data.len());
+                buf[..n].copy_from_slice(&data[..n]);
+                n
+            }
+            None => {
+                let next = self.chunks.iter().map(|(p, _)| *p).filter(|p| *p > pos).min();
+                let n = buf.len().min((next.unwrap_or(self.len).max(pos) - pos) as usize);
+                buf[..n].fill(0);
+                n
+            }
+        };
+        self.pos += n as u64;
+        Ok(n)
+    }
+}
+
+impl Write for SparseFile {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        static ZEROS: [u8; 64 * 1024] = [0; 64 * 1024];
+        let buf = &buf[..buf.len().min(ZEROS.len())];
+        if buf != &ZEROS[..buf.len()] {
+            self.chunks.push((self.pos, buf.to_vec()));
+        }
+        self.pos += buf.len() as u64;
+        self.len = self.len.max(self.pos);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+impl Seek for SparseFile {
+    fn seek(&mut self, pos: SeekFrom) -> std::io::Result<u64> {
+        self.pos = match pos {
+            SeekFrom::Start(p) => p,
+            SeekFrom::End(p) => self.len.checked_add_signed(p).unwrap(),
+            SeekFrom::Current(p) => self.pos.checked_add_signed(p).unwrap(),
+        };
+        Ok(self.pos)
+    }
+}
+
+#[test]
+fn write_extended_mdat_len() {
+    // grow the media data to just below 4 GiB, without storing actual data
+    let mut src_bytes = fs::read("files/sample.m4a").unwrap();
+    let mdat = find_atom(&src_bytes, &[b"mdat"]).unwrap();
+    let mdat_len = u32::MAX as u64 - 16;
+    src_bytes[mdat.start..mdat.start + 4].copy_from_slice(&(mdat_len as u32).to_be_bytes());
+    let mut src = SparseFile {
+        chunks: vec![(0, src_bytes.clone())],
+        ..Default::default()
+    };
+    src.len = mdat.start as u64 + mdat_len;
+
+    // the appended chapter track media data doesn't fit into a 32 bit length anymore
+    let chapters = [
+        Chapter::new(Duration::ZERO, "The Pledge"),
+        Chapter::new(Duration::from_millis(135), "The Turn"),
+        Chapter::new(Duration::from_millis(324), "The Prestige"),
+    ];
+    let mut tag = get_tag_1().userdata;
+    tag.chapter_track_mut().extend(chapters.clone());
+    let mut dst = SparseFile::default();
+    tag.write_copy(&mut src, &mut dst).unwrap();
+    drop(src);
+
+    dst.seek(SeekFrom::Start(0)).unwrap();
+    let tag = Tag::read_from(&mut dst).unwrap();
+    assert_tag_1(&tag);
+    assert_eq!(tag.chapter_track(), chapters);
+
+    let mut head = vec![0; 64 * 1024];
+    dst.seek(SeekFrom::Start(0)).unwrap();
+    dst.read_exact(&mut head).unwrap();
+    let dst_len = dst.len;
+
+    let atoms = child_atoms(&head, 0, head.len());
+    let (_, new_mdat) = atoms.iter().find(|(f, _)| f == b"mdat").unwrap();
+    assert_eq!(u32::from_be_bytes(head[new_mdat.start..][..4].try_into().unwrap()), 1);
+    assert_eq!(new_mdat.end as u64, dst_len);
+    assert!(new_mdat.len() as u64 > u32::MAX as u64);
+
+    // the audio chunk offsets account for the extended head
+    let shift = new_mdat.start - mdat.start + 8;
+    assert_eq!(first_chunk_offset(&head), first_chunk_offset(&src_bytes) + shift);
+    let offset = first_chunk_offset(&src_bytes);
+    assert_eq!(head[offset + shift..][..64], src_bytes[offset..][..64]);
+}
+
+#[test]
+fn plan_write() {
+    let mut small_tag = get_tag_1().userdata;
+    small_tag.set_title("TEST TITLE");
+    let mut big_tag = small_tag.clone();
+    big_tag.add_data(Fourcc(*b"test"), Data::Reserved(vec![0; 16 * 1024]));
+
+    let scenarios = [
+        // fits into the existing padding
+        ("files/sample.m4a", "target/plan_write-padding.m4a", &small_tag, WriteOutcome::InPlace),
+        // the media data has to be shifted
+        ("files/sample.m4a", "target/plan_write-shift.m4a", &big_tag, WriteOutcome::Rewritten),
+        // the moov atom is located at the end and can simply grow
+        (
+            "files/sample-chaptered.m4a",
+            "target/plan_write-end.m4a",
+            &big_tag,
+            WriteOutcome::InPlace,
+        ),
+    ];
+    for (sample_file, target_file, tag, expected_outcome) in scenarios {
+        let target_file = use_sample_file(sample_file, target_file);
+        let old = fs::read(target_file).unwrap();
+
+        let plan = tag.plan_write_with_path(target_file, &WriteConfig::DEFAULT).unwrap();
+        assert!(fs::read(target_file).unwrap() == old);
+
+        assert_eq!(plan.outcome, expected_outcome);
+        assert!(plan.rewritten_len > 0);
+        match expected_outcome {
+            WriteOutcome::InPlace => assert_eq!(plan.chunk_offset_shift, 0),
+            WriteOutcome::Rewritten => {
+                assert!(plan.chunk_offset_shift > 0);
+                assert!(plan.metadata_len > plan.available_padding);
+            }
+        }
+
+        let prev_chunk_offset = first_chunk_offset(&old);
+        assert_eq!(write_tag(tag, target_file), plan.outcome);
+
+        let new = fs::read(target_file).unwrap();
+        let new_udta = find_atom(&new, &[b"moov", b"udta"]).unwrap();
+        assert_eq!(new_udta.len() as u64, plan.metadata_len);
+        let chunk_offset = first_chunk_offset(&new);
+        assert_eq!(chunk_offset as i64 - prev_chunk_offset as i64, plan.chunk_offset_shift);
+
+        // only a rewrite moves the media data
+        let (_, mdat) = read_mdat(target_file);
+        match expected_outcome {
+            WriteOutcome::InPlace => assert!(plan.rewritten_len <= (new.len() - mdat.len()) as u64),
+            WriteOutcome::Rewritten => assert!(plan.rewritten_len > mdat.len() as u64),
+        }
+    }
+}
+
+#[test]
+fn write_backup() {
+    let target_file = use_sample_file("files/sample.m4a", "target/write_backup.m4a");
+    let backup_file = "target/write_backup.m4a.bak";
+    let _ = fs::remove_file(backup_file);
+    let old = fs::read(target_file).unwrap();
+
+    let tag = get_tag_1();
+    let cfg = WriteConfig {
+        backup: Some(BackupMode::Sibling(".bak".into())),
+        ..WriteConfig::DEFAULT
+    };
+    tag.write_with_path(target_file, &cfg).unwrap();
+    assert!(fs::read(backup_file).unwrap() == old);
+    assert_tag_1(&read_tag(target_file));
+
+    // an up to date backup, with the same size and modification time, isn't overwritten
+    let current = fs::read(target_file).unwrap();
+    let modified = fs::metadata(target_file).unwrap().modified().unwrap();
+    fs::write(backup_file, vec![0; current.len()]).unwrap();
+    fs::File::options().write(true).open(backup_file).unwrap().set_modified(modified).unwrap();
+    tag.write_with_path(target_file, &cfg).unwrap();
+    assert!(fs::read(backup_file).unwrap() == vec![0; current.len()]);
+
+    // backup directory
+    let target_file = use_sample_file("files/sample.m4a", "target/write_backup-dir.m4a");
+    let backup_dir = "target/write_backup-dir";
+    let _ = fs::remove_dir_all(backup_dir);
+    let cfg = WriteConfig {
+        backup: Some(BackupMode::Directory(backup_dir.into())),
+        ..WriteConfig::DEFAULT
+    };
+    tag.write_with_path(target_file, &cfg).unwrap();
+    assert!(fs::read(format!("{backup_dir}/write_backup-dir.m4a")).unwrap() == old);
+
+    // a failing backup aborts the write
+    let target_file = use_sample_file("files/sample.m4a", "target/write_backup-fail.m4a");
+    let cfg = WriteConfig {
+        backup: Some(BackupMode::Directory(target_file.into())),
+        ..WriteConfig::DEFAULT
+    };
+    tag.write_with_path(target_file, &cfg).unwrap_err();
+    assert!(fs::read(target_file).unwrap() == old);
+}
+
+#[test]
+fn write_stripped() {
+    for (padding, expected_outcome) in [
+        (128 * 1024, WriteOutcome::InPlace),
+        (WriteConfig::DEFAULT_PADDING, WriteOutcome::Rewritten),
+        (0, WriteOutcome::Rewritten),
+    ] {
+        let target_file = use_sample_file("files/sample.m4a", "target/write_stripped.m4a");
+
+        let mut tag = get_tag_1().userdata;
+        tag.add_artwork(Img::jpeg(vec![0xff; 64 * 1024]));
+        tag.set_title_sort_order("title");
+        tag.set_data(ident::CATALOG_ID, Data::u32_be(1234));
+        tag.set_data(Fourcc(*b"\xa9nam"), Data::Utf8("TEST TITLE".into()));
+        write_tag(&tag, target_file);
+        let prev_len = fs::metadata(target_file).unwrap().len();
+        let (prev_mdat_pos, _) = read_mdat(target_file);
+
+        tag.remove_artworks();
+        tag.clear_sort_tags();
+        tag.clear_itunes_store_data();
+        tag.clear_all_freeform();
+        let cfg = WriteConfig { padding, ..WriteConfig::DEFAULT };
+        assert_eq!(tag.write_with_path(target_file, &cfg).unwrap(), expected_outcome);
+
+        let len = fs::metadata(target_file).unwrap().len();
+        let (mdat_pos, _) = read_mdat(target_file);
+        match expected_outcome {
+            // the freed space is kept as padding, if it doesn't exceed the configured padding
+            WriteOutcome::InPlace => {
+                assert_eq!(len, prev_len);
+                assert_eq!(mdat_pos, prev_mdat_pos);
+            }
+            // otherwise the file shrinks, keeping only the configured padding
+            WriteOutcome::Rewritten => {
+                assert!(len < prev_len - 64 * 1024 + padding as u64, "{len} >= {prev_len}");
+                assert!(mdat_pos < prev_mdat_pos);
+            }
+        }
+
+        let read = read_tag(target_file);
+        assert_eq!(read.userdata, tag);
+        assert_eq!(read.artworks().count(), 0);
+        assert_eq!(read.title(), Some("TEST TITLE"));
+        assert_readonly(&read);
+    }
+}
+
+#[test]
+fn write_padding() {
+    let target_file = use_sample_file("files/sample.m4a", "target/write_padding.m4a");
+
+    let cfg = WriteConfig { padding: 4096, ..WriteConfig::DEFAULT };
+    let mut tag = get_tag_1();
+    tag.add_data(Fourcc(*b"test"), Data::Reserved(vec![0; 16 * 1024]));
+    let outcome = tag.write_with_path(target_file, &cfg).unwrap();
+    assert_eq!(outcome, WriteOutcome::Rewritten);
+
+    let buf = fs::read(target_file).unwrap();
+    let free = find_atom(&buf, &[b"moov", b"udta", b"free"]).unwrap();
+    assert_eq!(free.len(), 4096);
+    assert!(buf[free.start + 8..free.end].iter().all(|b| *b == 0));
+
+    // a subsequent small edit fits into the padding
+    let prev_mdat = read_mdat(target_file);
+    tag.set_title("A NEW TITLE");
+    assert_eq!(write_tag(&tag, target_file), WriteOutcome::InPlace);
+    assert_eq!(read_mdat(target_file), prev_mdat);
+
+    let tag = read_tag(target_file);
+    assert_eq!(tag.title(), Some("A NEW TITLE"));
+    assert_readonly(&tag);
+}
+
+#[test]
+fn write_padding_after_moov() {
+    let target_file = use_sample_file("files/sample.m4a", "target/write_padding_after_moov.m4a");
+
+    let cfg = WriteConfig {
+        padding: 1000,
+        padding_position: PaddingPosition::AfterMoov,
+        ..WriteConfig::DEFAULT
+    };
+    let mut tag = get_tag_1();
+    tag.add_data(Fourcc(*b"test"), Data::Reserved(vec![0; 16 * 1024]));
+    let outcome = tag.write_with_path(target_file, &cfg).unwrap();
+    assert_eq!(outcome, WriteOutcome::Rewritten);
+
+    let buf = fs::read(target_file).unwrap();
+    let atoms = child_atoms(&buf, 0, buf.len());
+    let moov_idx = atoms.iter().position(|(f, _)| f == b"moov").unwrap();
+    let (fourcc, free) = &atoms[moov_idx + 1];
+    assert_eq!(fourcc, b"free");
+    assert_eq!(free.len(), 1000);
+    assert_eq!(find_atom(&buf, &[b"moov", b"udta", b"free"]), None);
+
+    let tag = read_tag(target_file);
+    assert_tag_1(&tag);
+    assert_readonly(&tag);
+}
+
+#[test]
+fn write_no_padding() {
+    let target_file = use_sample_file("files/sample.m4a", "target/write_no_padding.m4a");
+
+    let cfg = WriteConfig { padding: 0, ..WriteConfig::DEFAULT };
+    let mut tag = get_tag_1();
+    tag.add_data(Fourcc(*b"test"), Data::Reserved(vec![0; 16 * 1024]));
+    let outcome = tag.write_with_path(target_file, &cfg).unwrap();
+    assert_eq!(outcome, WriteOutcome::Rewritten);
+
+    let buf = fs::read(target_file).unwrap();
+    assert_eq!(find_atom(&buf, &[b"moov", b"udta", b"free"]), None);
+
+    let tag = read_tag(target_file);
+    assert_tag_1(&tag);
+    assert_readonly(&tag);
+}
+
+#[test]
+fn remove_metadata() {
+    let target_file = use_sample_file("files/sample.m4a", "target/remove_metadata.m4a");
+    let prev_buf = fs::read(target_file).unwrap();
+    let (prev_mdat_pos, prev_mdat) = read_mdat(target_file);
+    let prev_chunk_offset = first_chunk_offset(&prev_buf);
+
+    Tag::remove_from_path(target_file).unwrap();
+
+    let buf = fs::read(target_file).unwrap();
+    assert_eq!(find_atom(&buf, &[b"moov", b"udta"]), None);
+
+    // chunk offsets still point to the same media data
+    let (mdat_pos, mdat) = read_mdat(target_file);
+    assert_eq!(mdat, prev_mdat);
+    assert_eq!(first_chunk_offset(&buf) - mdat_pos, prev_chunk_offset - prev_mdat_pos);
+
+    let tag = read_tag(target_file);
+    assert!(tag.is_empty());
+    assert_readonly(&tag);
+}
+
+#[test]
+fn write_cleared() {
+    let target_file = use_sample_file("files/sample.m4a", "target/write_cleared.m4a");
+
+    let mut tag = read_tag(target_file);
+    tag.clear();
+    write_tag(&tag, target_file);
+
+    let buf = fs::read(target_file).unwrap();
+    assert_eq!(find_atom(&buf, &[b"moov", b"udta", b"meta"]), None);
+
+    let tag = read_tag(target_file);
+    assert!(tag.is_empty());
+    assert_readonly(&tag);
+}
+
+#[test]
+fn write_encoder() {
+    let target_file = use_sample_file("files/sample.m4a", "target/write_encoder.m4a");
+
+    let mut tag = read_tag(target_file);
+    assert_eq!(tag.encoder(), Some("Lavf58.29.100"));
+    tag.set_encoder("mp4ameta");
+    write_tag(&tag, target_file);
+
+    let tag = read_tag(target_file);
+    assert_eq!(tag.encoder(), Some("mp4ameta"));
+}
+
+#[test]
+fn write_preserve_encoder() {
+    let target_file = use_sample_file("files/sample.m4a", "target/write_preserve_encoder.m4a");
+
+    let cfg = WriteConfig { preserve_encoder: true, ..WriteConfig::DEFAULT };
+    let mut tag = Tag::default();
+    tag.set_title("TEST TITLE");
+    tag.write_with_path(target_file, &cfg).unwrap();
+
+    let tag = read_tag(target_file);
+    assert_eq!(tag.title(), Some("TEST TITLE"));
+    assert_eq!(tag.encoder(), Some("Lavf58.29.100"));
+
+    // an explicitly set encoder still takes precedence
+    let mut tag = Tag::default();
+    tag.set_encoder("mp4ameta");
+    tag.write_with_path(target_file, &cfg).unwrap();
+
+    let tag = read_tag(target_file);
+    assert_eq!(tag.encoder(), Some("mp4ameta"));
+}
+
+#[test]
+fn write_faststart() {
+    for (in_file, target_file) in [
+        ("files/sample-chaptered.m4a", "target/write_faststart.m4a"),
+        ("files/sample-64.mp4", "target/write_faststart.mp4"),
+    ] {
+        let target_file = use_sample_file(in_file, target_file);
+        let prev_buf = fs::read(target_file).unwrap();
+        let (prev_mdat_pos, prev_mdat) = read_mdat(target_file);
+        let prev_tag = read_tag(target_file);
+
+        let cfg = WriteConfig {
+            faststart: true,
+            write_chapter_track: false,
+            ..WriteConfig::DEFAULT
+        };
+        let outcome = prev_tag.write_with_path(target_file, &cfg).unwrap();
+        assert_eq!(outcome, WriteOutcome::Rewritten);
+
+        let buf = fs::read(target_file).unwrap();
+        let atoms: Vec<_> = child_atoms(&buf, 0, buf.len()).into_iter().map(|(f, _)| f).collect();
+        assert_eq!(atoms, [*b"ftyp", *b"moov", *b"mdat"]);
+
+        // chunk offsets still point to the same media data
+        let (mdat_pos, mdat) = read_mdat(target_file);
+        assert_eq!(mdat, prev_mdat);
+        assert_eq!(
+            first_chunk_offset(&buf) - mdat_pos,
+            first_chunk_offset(&prev_buf) - prev_mdat_pos
+        );
+
+        let tag = read_tag(target_file);
+        assert_eq!(tag.userdata, prev_tag.userdata);
+        assert_eq!(tag.audio_info(), prev_tag.audio_info());
+    }
+}
+
+#[test]
+fn write_itunes_canonical_order() {
+    let target_file =
+        use_sample_file("files/sample.m4a", "target/write_itunes_canonical_order.m4a");
+
+    let mut tag = Userdata::default();
+    tag.add_data(Fourcc(*b"test"), Data::Utf8("unknown".into()));
+    tag.set_artwork(Img::png(b"artwork".to_vec()));
+    tag.set_isrc("isrc");
+    tag.set_track(1, 2);
+    tag.set_album("album");
+    tag.set_custom_genre("genre");
+    tag.set_artist("artist");
+    tag.set_title("title");
+
+    let cfg = WriteConfig {
+        item_order: ItemOrder::ItunesCanonical,
+        ..WriteConfig::DEFAULT
+    };
+    tag.write_with_path(target_file, &cfg).unwrap();
+
+    let tag = read_tag(target_file);
+    let idents: Vec<_> = tag.data().map(|(ident, _)| ident.clone()).collect();
+    let expected: [DataIdent; 8] = [
+        Fourcc(*b"\xa9nam").into(),
+        Fourcc(*b"\xa9ART").into(),
+        Fourcc(*b"\xa9alb").into(),
+        Fourcc(*b"\xa9gen").into(),
+        Fourcc(*b"trkn").into(),
+        Fourcc(*b"covr").into(),
+        DataIdent::freeform("com.apple.iTunes", "ISRC"),
+        Fourcc(*b"test").into(),
+    ];
+    assert_eq!(idents, expected);
+
+    // the order is preserved by default
+    let mut tag = tag.userdata;
+    tag.remove_title();
+    tag.set_title("title");
+    write_tag(&tag, target_file);
+
+    let tag = read_tag(target_file);
+    let idents: Vec<_> = tag.data().map(|(ident, _)| ident.clone()).collect();
+    assert_eq!(idents[..7], expected[1..]);
+    assert_eq!(idents[7], expected[0]);
+}
+
+#[test]
+fn write_custom_item_order() {
+    let target_file = use_sample_file("files/sample.m4a", "target/write_custom_item_order.m4a");
+
+    let mut tag = Userdata::default();
+    tag.set_artwork(Img::png(b"artwork".to_vec()));
+    tag.set_isrc("isrc");
+    tag.set_title("title");
+    tag.add_data(Fourcc(*b"test"), Data::Utf8("unknown".into()));
+    tag.set_artist("artist");
+    tag.set_album("album");
+
+    // the listed items first, followed by the unlisted ones keeping their relative order
+    let order = vec![ident::ARTIST.into(), ident::ALBUM.into(), ident::ARTWORK.into()];
+    let mut cfg = WriteConfig {
+        item_order: ItemOrder::Custom(order),
+        ..WriteConfig::DEFAULT
+    };
+    tag.write_with_path(target_file, &cfg).unwrap();
+
+    let idents = |tag: &Tag| tag.data().map(|(ident, _)| ident.clone()).collect::<Vec<_>>();
+    let tag = read_tag(target_file);
+    let expected: [DataIdent; 6] = [
+        Fourcc(*b"\xa9ART").into(),
+        Fourcc(*b"\xa9alb").into(),
+        Fourcc(*b"covr").into(),
+        DataIdent::freeform("com.apple.iTunes", "ISRC"),
+        Fourcc(*b"\xa9nam").into(),
+        Fourcc(*b"test").into(),
+    ];
+    assert_eq!(idents(&tag), expected);
+
+    cfg.item_order = ItemOrder::Alphabetical;
+    tag.write_with_path(target_file, &cfg).unwrap();
+
+    let tag = read_tag(target_file);
+    let expected: [DataIdent; 6] = [
+        DataIdent::freeform("com.apple.iTunes", "ISRC"),
+        Fourcc(*b"covr").into(),
+        Fourcc(*b"test").into(),
+        Fourcc(*b"\xa9ART").into(),
+        Fourcc(*b"\xa9alb").into(),
+        Fourcc(*b"\xa9nam").into(),
+    ];
+    assert_eq!(idents(&tag), expected);
+}
+
+#[test]
+fn read_multiple_category_atoms() {
+    let target_file =
+        use_sample_file("files/sample.m4a", "target/read_multiple_category_atoms.m4a");
+
+    let mut tag = Userdata::default();
+    tag.add_category("podcast");
+    tag.add_data(Fourcc(*b"xxxx"), Data::Utf8("news".into()));
+    write_tag(&tag, target_file);
+
+    // turn the placeholder into a second, separate `catg` atom
+    let mut buf = fs::read(target_file).unwrap();
+    let ilst = find_atom(&buf, &[b"moov", b"udta", b"meta", b"ilst"]).unwrap();
+    let atoms = child_atoms(&buf, ilst.start + 8, ilst.end);
+    let (_, placeholder) = atoms.iter().find(|(f, _)| f == b"xxxx").unwrap();
+    buf[placeholder.start + 4..placeholder.start + 8].copy_from_slice(b"catg");
+    fs::write(target_file, &buf).unwrap();
+
+    let tag = read_tag(target_file);
+    assert_eq!(tag.category(), Some("podcast"));
+    assert_eq!(tag.categories().collect::<Vec<_>>(), ["podcast", "news"]);
+
+    let mut tag = tag.userdata;
+    tag.set_categories(["arts".to_owned(), "music".to_owned()]);
+    write_tag(&tag, target_file);
+
+    let tag = read_tag(target_file);
+    assert_eq!(tag.categories().collect::<Vec<_>>(), ["arts", "music"]);
+}
+
+#[test]
+fn write_empty() {
+    let target_file = use_sample_file("files/sample.m4a", "target/write_empty.m4a");
+
+    let tag = Tag::default();
+    write_tag(&tag, target_file);
+
+    let tag = read_tag(target_file);
+    assert!(tag.is_empty());
+    assert_readonly(&tag);
+}
+
+#[test]
+fn artwork_data_out_of_bounds() {
+    let mut buf = fs::read("files/sample.m4a").unwrap();
+    let covr_pos = buf.windows(4).position(|w| w == b"covr").unwrap() - 4;
+    let covr_len = u32::from_be_bytes(buf[covr_pos..covr_pos + 4].try_into().unwrap());
+    let data_pos = covr_pos + 8;
+    assert_eq!(&buf[data_pos + 4..data_pos + 8], b"data");
+
+    // the data atom now claims to extend beyond the covr atom
+    buf[data_pos..data_pos + 4].copy_from_slice(&(covr_len + 16).to_be_bytes());
+
+    let err = Tag::read_from(&mut std::io::Cursor::new(buf)).unwrap_err();
+    assert!(matches!(err.kind, ErrorKind::InvalidAtomSize), "{err:?}");
+}
+
+#[test]
+fn chapter_track_urls() {
+    let target_file = use_sample_file("files/sample.m4a", "target/chapter_track_urls.m4a");
+
+    let mut tag = Userdata::default();
+    tag.chapter_track_mut().extend([
+        Chapter {
+            url: Some("https://example.com/first".into()),
+            ..Chapter::new(Duration::ZERO, "first")
+        },
+        Chapter::new(Duration::from_millis(20), "second"),
+    ]);
+    write_tag(&tag, target_file);
+
+    let tag = read_tag(target_file);
+    assert_eq!(tag.chapter_track()[0].url.as_deref(), Some("https://example.com/first"));
+    assert_eq!(tag.chapter_track()[1].url, None);
+
+    // urls are truncated to 255 bytes
+    let mut tag = Userdata::default();
+    tag.chapter_track_mut().push(Chapter {
+        url: Some("a".repeat(300)),
+        ..Chapter::new(Duration::ZERO, "first")
+    });
+    write_tag(&tag, target_file);
+    let tag = read_tag(target_file);
+    assert_eq!(tag.chapter_track()[0].url, Some("a".repeat(255)));
+}
+
+#[test]
+fn chapter_track_images() {
+    let target_file = use_sample_file("files/sample.m4a", "target/chapter_track_images.m4a");
+
+    let mut tag = Userdata::default();
+    tag.chapter_track_mut().extend([
+        Chapter::new(Duration::ZERO, "first"),
+        Chapter::new(Duration::from_millis(20), "second"),
+    ]);
+    write_tag(&tag, target_file);
+
+    // derive a jpeg image track from the text track, which uses audio data as images
+    let mut buf = fs::read(target_file).unwrap();
+    let be_u32 = |buf: &[u8], pos: usize| u32::from_be_bytes(buf[pos..pos + 4].try_into().unwrap());
+    let add_len = |buf: &mut [u8], pos: usize, diff: i64| {
+        let len = (be_u32(buf, pos) as i64 + diff) as u32;
+        buf[pos..pos + 4].copy_from_slice(&len.to_be_bytes());
+    };
+    let moov = find_atom(&buf, &[b"moov"]).unwrap();
+    let traks: Vec<_> = (child_atoms(&buf, moov.start + 8, moov.end).into_iter())
+        .filter(|(f, _)| f == b"trak")
+        .map(|(_, r)| r)
+        .collect();
+    let [audio_trak, chapter_trak] = traks.as_slice() else {
+        panic!("expected an audio and a chapter track");
+    };
+    let tref = child_atoms(&buf, audio_trak.start + 8, audio_trak.end)
+        .into_iter()
+        .find(|(f, _)| f == b"tref")
+        .unwrap()
+        .1;
+    let chap = child_atoms(&buf, tref.start + 8, tref.end)[0].1.clone();
+    let image_id = be_u32(&buf, chap.end - 4) + 1;
+
+    let mut image_trak = buf[chapter_trak.clone()].to_vec();
+    let tkhd = child_atoms(&image_trak, 8, image_trak.len())[0].1.clone();
+    image_trak[tkhd.start + 20..tkhd.start + 24].copy_from_slice(&image_id.to_be_bytes());
+    let stsd = find_atom(&image_trak[8..], &[b"mdia", b"minf", b"stbl", b"stsd"]).unwrap();
+    image_trak[8 + stsd.start + 20..8 + stsd.start + 24].copy_from_slice(b"jpeg");
+    let co64 = find_atom(&image_trak[8..], &[b"mdia", b"minf", b"stbl", b"co64"]).unwrap();
+    let mdat = find_atom(&buf, &[b"mdat"]).unwrap();
+    let image_offset = mdat.start as u64 + 8;
+    image_trak[8 + co64.start + 16..8 + co64.start + 24]
+        .copy_from_slice(&image_offset.to_be_bytes());
+    // both samples are stored in one chunk and include an encd atom of 12 bytes
+    let image_data = &buf[mdat.start + 8..];
+    let expected_images = vec![
+        Img::new(ImgFmt::Jpeg, image_data[..2 + 5 + 12].to_vec()),
+        Img::new(ImgFmt::Jpeg, image_data[2 + 5 + 12..][..2 + 6 + 12].to_vec()),
+    ];
+
+    // the free atom following the moov atom absorbs the added bytes, so the offsets stay valid
+    let added_len = 4 + image_trak.len() as u32;
+    let free = moov.end;
+    assert_eq!(&buf[free + 4..free + 8], b"free");
+    assert!(be_u32(&buf, free) >= added_len + 8);
+    add_len(&mut buf, free, -(added_len as i64));
+    buf.drain(free + 8..free + 8 + added_len as usize);
+    buf.splice(moov.end..moov.end, image_trak);
+    buf.splice(chap.end..chap.end, image_id.to_be_bytes());
+    for pos in [moov.start, audio_trak.start, tref.start, chap.start] {
+        add_len(&mut buf, pos, 4);
+    }
+    add_len(&mut buf, moov.start, added_len as i64 - 4);
+    fs::write(target_file, &buf).unwrap();
+
+    let images = |tag: &Tag| {
+        (tag.chapter_track().iter()).map(|c| c.image.clone().unwrap()).collect::<Vec<_>>()
+    };
+    let tag = read_tag(target_file);
+    assert_eq!(images(&tag), expected_images);
+
+    // the image track is preserved when the text track is rewritten
+    let mut tag = tag.userdata;
+    tag.chapter_track_mut()[1].title = "changed".into();
+    write_tag(&tag, target_file);
+    let tag = read_tag(target_file);
+    assert_eq!(tag.chapter_track()[1].title, "changed");
+    assert_eq!(images(&tag), expected_images);
+
+    // removing the chapter track also removes the image track
+    let mut tag = tag.userdata;
+    tag.chapter_track_mut().clear();
+    write_tag(&tag, target_file);
+    let buf = fs::read(target_file).unwrap();
+    let moov = find_atom(&buf, &[b"moov"]).unwrap();
+    let traks = child_atoms(&buf, moov.start + 8, moov.end);
+    assert_eq!(traks.iter().filter(|(f, _)| f == b"trak").count(), 1);
+}
+
+#[test]
+fn unknown_atoms_are_preserved() {
+    let target_file = use_sample_file("files/sample.m4a", "target/unknown_atoms.m4a");
+
+    // insert an unknown atom into the user data atom, the free atom following the moov atom
+    // absorbs the added bytes, so the chunk offsets stay valid
+    let mut buf = fs::read(target_file).unwrap();
+    let be_u32 = |buf: &[u8], pos: usize| u32::from_be_bytes(buf[pos..pos + 4].try_into().unwrap());
+    let set_be_u32 = |buf: &mut [u8], pos: usize, val: u32| {
+        buf[pos..pos + 4].copy_from_slice(&val.to_be_bytes());
+    };
+    let udta_atom = b"\0\0\0\x14xtra\x01\x02\x03\x04\x05\x06\x07\x08\x09\x0a\x0b\x0c";
+    let moov = find_atom(&buf, &[b"moov"]).unwrap();
+    let udta = find_atom(&buf, &[b"moov", b"udta"]).unwrap();
+    let free = moov.end;
+    assert_eq!(&buf[free + 4..free + 8], b"free");
+    let free_len = be_u32(&buf, free) - udta_atom.len() as u32;
+    set_be_u32(&mut buf, free, free_len);
+    buf.drain(free + 8..free + 8 + udta_atom.len());
+    buf.splice(udta.end..udta.end, *udta_atom);
+    for pos in [moov.start, udta.start] {
+        let len = be_u32(&buf, pos) + udta_atom.len() as u32;
+        set_be_u32(&mut buf, pos, len);
+    }
+
+    // append an unknown top level atom
+    let top_level_atom = b"\0\0\0\x10uuid\xde\xad\xbe\xef\xde\xad\xbe\xef";
+    buf.extend(top_level_atom);
+    fs::write(target_file, &buf).unwrap();
+
+    write_tag(&get_tag_1(), target_file);
+    assert_tag_1(&read_tag(target_file));
+
+    let buf = fs::read(target_file).unwrap();
+    let xtra = find_atom(&buf, &[b"moov", b"udta", b"xtra"]).unwrap();
+    assert_eq!(&buf[xtra], udta_atom);
+    let uuid = find_atom(&buf, &[b"uuid"]).unwrap();
+    assert_eq!(&buf[uuid], top_level_atom);
+}
+
+#[test]
+fn chapter_ticks_are_preserved() {
+    let target_file = use_sample_file("files/sample-chaptered.m4a", "target/chapter_ticks.m4a");
+
+    // Use timescales of 3 for the chapter list and track, a third of a second can't be
+    // represented exactly by a `Duration`.
+    let timescale = ChplTimescale::Fixed(NonZeroU32::new(3).unwrap());
+    let mut buf = fs::read(target_file).unwrap();
+    let child = |buf: &[u8], path: &[&[u8; 4]]| {
+        let moov = find_atom(buf, &[b"moov"]).unwrap();
+        let chapter_trak = (child_atoms(buf, moov.start + 8, moov.end).into_iter())
+            .filter(|(f, _)| f == b"trak")
+            .nth(1)
+            .unwrap()
+            .1;
+        let range = find_atom(&buf[chapter_trak.start + 8..chapter_trak.end], path).unwrap();
+        chapter_trak.start + 8 + range.start..chapter_trak.start + 8 + range.end
+    };
+    let mdhd = child(&buf, &[b"mdia", b"mdhd"]);
+    buf[mdhd.start + 20..mdhd.start + 24].copy_from_slice(&3u32.to_be_bytes());
+    buf[mdhd.start + 24..mdhd.start + 28].copy_from_slice(&3u32.to_be_bytes());
+    let stts = child(&buf, &[b"mdia", b"minf", b"stbl", b"stts"]);
+    for i in 0..3 {
+        let pos = stts.start + 20 + i * 8;
+        buf[pos..pos + 4].copy_from_slice(&1u32.to_be_bytes());
+    }
+    fs::write(target_file, &buf).unwrap();
+
+    let read_cfg = ReadConfig { chpl_timescale: timescale, ..ReadConfig::DEFAULT };
+    let mut tag = Tag::read_with_path(target_file, &read_cfg).unwrap();
+    assert_eq!(tag.chapter_track()[1].start, Duration::from_nanos(333_333_333));
+    tag.set_title("NEW TITLE");
+    tag.chapter_track_mut()[1].title = "NEW CHAPTER TITLE".into();
+
+    let write_cfg = WriteConfig { chpl_timescale: timescale, ..WriteConfig::DEFAULT };
+    tag.write_with_path(target_file, &write_cfg).unwrap();
+
+    let new_buf = fs::read(target_file).unwrap();
+    let chpl = find_atom(&buf, &[b"moov", b"udta", b"chpl"]).unwrap();
+    let new_chpl = find_atom(&new_buf, &[b"moov", b"udta", b"chpl"]).unwrap();
+    // the original chpl atom is of version 1 containing 4 additional reserved bytes, so only the
+    // chapter entries following the chapter count are compared
+    assert_eq!(buf[chpl.start + 8], 1);
+    assert_eq!(new_buf[new_chpl.start + 8], 0);
+    assert_eq!(new_buf[new_chpl.start + 13..new_chpl.end], buf[chpl.start + 17..chpl.end]);
+    let new_stts = child(&new_buf, &[b"mdia", b"minf", b"stbl", b"stts"]);
+    assert_eq!(new_buf[new_stts], buf[stts]);
+
+    let tag = Tag::read_with_path(target_file, &read_cfg).unwrap();
+    assert_eq!(tag.chapter_track()[1].title, "NEW CHAPTER TITLE");
+}
+
+#[test]
+fn bool_flags_are_omitted_when_false() {
+    let target_file = use_sample_file("files/sample.m4a", "target/bool_flags.m4a");
+    let mut tag = read_tag(target_file);
+    tag.set_compilation();
+    tag.set_gapless_playback();
+    tag.set_show_movement();
+    write_tag(&tag, target_file);
+
+    let buf = fs::read(target_file).unwrap();
+    for fourcc in [b"cpil", b"pgap", b"shwm"] {
+        let atom = find_atom(&buf, &[b"moov", b"udta", b"meta", b"ilst", fourcc]).unwrap();
+        // a single data atom with the value 1
+        assert_eq!(atom.len(), 8 + 16 + 1);
+        assert_eq!(buf[atom.end - 1], 1);
+    }
+
+    let mut tag = read_tag(target_file);
+    tag.remove_compilation();
+    tag.remove_gapless_playback();
+    tag.remove_show_movement();
+    write_tag(&tag, target_file);
+
+    let buf = fs::read(target_file).unwrap();
+    for fourcc in [b"cpil", b"pgap", b"shwm"] {
+        assert_eq!(find_atom(&buf, &[b"moov", b"udta", b"meta", b"ilst", fourcc]), None);
+    }
+    let tag = read_tag(target_file);
+    assert!(!tag.compilation());
+    assert!(!tag.gapless_playback());
+    assert!(!tag.show_movement());
+}
+
+#[test]
+fn bmp_artwork() {
+    let target_file = use_sample_file("files/sample.m4a", "target/bmp_artwork.m4a");
+    let bmp = b"BM\x1e\0\0\0\0\0\0\0\x1a\0\0\0".to_vec();
+
+    let mut tag = read_tag(target_file);
+    tag.set_artwork(Img::bmp(bmp.clone()));
+    write_tag(&tag, target_file);
+
+    // the data atom of the artwork has the type code 27
+    let buf = fs::read(target_file).unwrap();
+    let data = find_atom(&buf, &[b"moov", b"udta", b"meta", b"ilst", b"covr", b"data"]).unwrap();
+    assert_eq!(&buf[data.start + 8..data.start + 12], &27u32.to_be_bytes());
+    assert_eq!(&buf[data.start + 16..data.end], &bmp[..]);
+
+    let tag = read_tag(target_file);
+    assert_eq!(tag.artwork(), Some(Img::bmp(&bmp[..])));
+    assert_eq!(tag.artwork().unwrap().fmt, ImgFmt::Bmp);
+}
+
+#[test]
+fn validate_tag() {
+    let mut tag = read_tag("files/sample.m4a");
+    assert_eq!(tag.validate(), []);
+
+    let issue =
+        |ident: Option<DataIdent>, msg: &str| ValidationIssue { ident, message: msg.to_owned() };
+
+    // integer with a wrong width
+    tag.set_data(ident::BPM, Data::BeSigned(vec![0, 0, 120]));
+    tag.set_data(ident::COMPILATION, Data::Utf8("1".into()));
+    assert_eq!(
+        tag.validate(),
+        [
+            issue(Some(ident::COMPILATION.into()), "Expected an integer, found data type code 1"),
+            issue(Some(ident::BPM.into()), "Integer has a width of 3 bytes, expected [2]"),
+        ]
+    );
+    tag.remove_bpm();
+    tag.remove_compilation();
+
+    // text with invalid utf-8
+    tag.set_data(ident::TITLE, Data::Reserved(vec![b'a', 0xff, 0xfe]));
+    tag.set_data(ident::ALBUM, Data::Reserved(b"album".to_vec()));
+    assert_eq!(
+        tag.validate(),
+        [
+            issue(Some(ident::TITLE.into()), "Text contains invalid utf-8"),
+            issue(Some(ident::ALBUM.into()), "Expected utf-8 text, found data type code 0"),
+        ]
+    );
+    tag.set_title("title");
+    tag.set_album("album");
+
+    // artwork type code mismatching the image data
+    tag.set_data(ident::ARTWORK, Data::Jpeg(b"\x89PNG\r\n\x1a\n".to_vec()));
+    assert_eq!(
+        tag.validate(),
+        [issue(Some(ident::ARTWORK.into()), "Image is declared as Jpeg, but the data is Png")]
+    );
+    tag.set_artwork(Img::png(b"\x89PNG\r\n\x1a\n".to_vec()));
+    assert_eq!(tag.validate(), []);
+
+    // both standard and custom genre
+    tag.set_standard_genre(1);
+    tag.set_custom_genre("genre");
+    assert_eq!(
+        tag.validate(),
+        [issue(
+            Some(ident::STANDARD_GENRE.into()),
+            "Both a standard genre (gnre) and a custom genre (©gen) are present"
+        )]
+    );
+    tag.remove_custom_genres();
+    assert_eq!(tag.validate(), []);
+
+    // missing meta/hdlr, renamed to a free atom
+    let target_file = use_sample_file("files/sample.m4a", "target/validate_tag.m4a");
+    let mut buf = fs::read(target_file).unwrap();
+    let hdlr = find_atom(&buf, &[b"moov", b"udta", b"meta", b"hdlr"]).unwrap();
+    buf[hdlr.start + 4..hdlr.start + 8].copy_from_slice(b"free");
+    fs::write(target_file, &buf).unwrap();
+
+    let tag = read_tag(target_file);
+    assert_eq!(
+        tag.validate(),
+        [issue(None, "The metadata atom (meta) is missing the handler reference atom (hdlr)")]
+    );
+    assert_eq!(
+        tag.validate()[0].to_string(),
+        "The metadata atom (meta) is missing the handler reference atom (hdlr)"
+    );
+
+    // a title containing invalid utf-8 is read as is, and preserved when writing
+    let target_file = use_sample_file("files/sample.m4a", "target/validate_tag.m4a");
+    let mut buf = fs::read(target_file).unwrap();
+    let pos = buf.windows(10).position(|w| w == b"TEST TITLE").unwrap();
+    buf[pos + 4] = 0xff;
+    fs::write(target_file, &buf).unwrap();
+
+    let mut tag = read_tag(target_file);
+    assert_eq!(tag.title(), None);
+    assert_eq!(tag.validate(), [issue(Some(ident::TITLE.into()), "Text contains invalid utf-8")]);
+    tag.set_album("album");
+    write_tag(&tag, target_file);
+    let tag = read_tag(target_file);
+    assert_eq!(tag.validate(), [issue(Some(ident::TITLE.into()), "Text contains invalid utf-8")]);
+}
+
+#[test]
+fn read_for_update() {
+    for sample_file in ["files/sample.m4a", "files/sample-chaptered.m4a", "files/sample.m4b"] {
+        let expected_file = use_sample_file(sample_file, "target/read_for_update-expected");
+        let mut tag = Tag::read_from_path(expected_file).unwrap();
+        tag.set_title("Updated");
+        tag.set_artist("Someone");
+        write_tag(&tag, expected_file);
+        let expected = fs::read(expected_file).unwrap();
+
+        let target_file = use_sample_file(sample_file, "target/read_for_update");
+        let mut tag = Tag::read_for_update(target_file).unwrap();
+        assert_eq!(tag, Tag::read_from_path(target_file).unwrap());
+        tag.set_title("Updated");
+        tag.set_artist("Someone");
+        write_tag(&tag, target_file);
+        assert!(fs::read(target_file).unwrap() == expected, "{sample_file}");
+
+        // the file is modified after the layout was cached, so it's parsed again
+        let stale_file = use_sample_file(sample_file, "target/read_for_update-stale");
+        let mut stale_tag = Tag::read_for_update(stale_file).unwrap();
+        let mut other = Tag::read_from_path(stale_file).unwrap();
+        other.set_comment("A much longer comment that changes the length of the file");
+        write_tag(&other, stale_file);
+        stale_tag.set_title("Updated");
+        stale_tag.set_artist("Someone");
+        write_tag(&stale_tag, stale_file);
+        assert!(fs::read(stale_file).unwrap() == expected, "{sample_file}");
+    }
+
+    // the file is replaced by one of the same length, whose movie atom starts 8 bytes earlier
+    let buf = fs::read("files/sample.m4a").unwrap();
+    let free = find_atom(&buf, &[b"free"]).unwrap();
+    let moov = find_atom(&buf, &[b"moov"]).unwrap();
+    assert_eq!(free.end, moov.start);
+    let mut moved = buf[..free.start].to_vec();
+    moved.extend(&buf[moov.clone()]);
+    moved.extend(&buf[free]);
+    moved.extend(&buf[moov.end..]);
+
+    let expected_file = "target/read_for_update-expected";
+    fs::write(expected_file, &moved).unwrap();
+    let mut tag = Tag::read_from_path(expected_file).unwrap();
+    tag.set_title("Updated");
+    write_tag(&tag, expected_file);
+    let expected = fs::read(expected_file).unwrap();
+
+    let stale_file = use_sample_file("files/sample.m4a", "target/read_for_update-stale");
+    let modified = fs::metadata(stale_file).unwrap().modified().unwrap();
+    let mut stale_tag = Tag::read_for_update(stale_file).unwrap();
+    let file = fs::File::create(stale_file).unwrap();
+    (&file).write_all(&moved).unwrap();
+    file.set_modified(modified).unwrap();
+    drop(file);
+
+    // the heads of the atoms at the cached positions reveal that the layout is stale
+    stale_tag.set_title("Updated");
+    write_tag(&stale_tag, stale_file);
+    assert!(fs::read(stale_file).unwrap() == expected);
+}
+
+#[test]
+fn video_credits() {
+    let target_file = use_sample_file("files/sample.m4v", "target/video_credits.m4v");
+
+    let mut tag = read_tag(target_file);
+    tag.set_director("Director");
+    tag.set_producers(["Producer 1".to_owned(), "Producer 2".to_owned()]);
+    tag.set_author("Author");
+    write_tag(&tag, target_file);
+
+    let buf = fs::read(target_file).unwrap();
+    assert!(find_atom(&buf, &[b"moov", b"udta", b"meta", b"ilst", b"\xa9dir"]).is_some());
+
+    let tag = read_tag(target_file);
+    assert_eq!(tag.director(), Some("Director"));
+    assert_eq!(tag.producers().collect::<Vec<_>>(), ["Producer 1", "Producer 2"]);
+    assert_eq!(tag.author(), Some("Author"));
+    assert!(tag.validate().is_empty());
+    let display = tag.to_string();
+    assert!(display.contains("director: Director"), "{display}");
+    assert!(tag.video_info().is_some());
+}
+
+#[test]
+fn wide_atom() {
+    // a `wide` placeholder atom directly precedes the media data (mdat) atom
+    let buf = fs::read("files/sample-wide.m4a").unwrap();
+    let wide = find_atom(&buf, &[b"wide"]).unwrap();
+    let mdat = find_atom(&buf, &[b"mdat"]).unwrap();
+    assert_eq!(wide.len(), 8);
+    assert_eq!(wide.end, mdat.start);
+
+    let tag = read_tag("files/sample-wide.m4a");
+    let sample = read_tag("files/sample-chaptered.m4a");
+    assert_eq!(tag, sample);
+    assert_eq!(tag.mdat_len(), sample.mdat_len());
+
+    for title in ["short", "a considerably longer title that forces the metadata to be rewritten"] {
+        let target_file = use_sample_file("files/sample-wide.m4a", "target/wide_atom.m4a");
+        let mut tag = read_tag(target_file);
+        tag.set_title(title);
+        tag.set_artwork(Img::png(vec![0; 4096]));
+        write_tag(&tag, target_file);
+
+        let buf = fs::read(target_file).unwrap();
+        let wide = find_atom(&buf, &[b"wide"]).unwrap();
+        let mdat = find_atom(&buf, &[b"mdat"]).unwrap();
+        assert_eq!(wide.len(), 8);
+        assert_eq!(wide.end, mdat.start);
+
+        let tag = read_tag(target_file);
+        assert_eq!(tag.title(), Some(title));
+        assert_eq!(tag.audio_info(), sample.audio_info());
+        assert_eq!(tag.mdat_len(), sample.mdat_len());
+    }
+
+    // moving the moov atom to the front keeps the wide atom in front of the media data
+    let target_file = use_sample_file("files/sample-wide.m4a", "target/wide_atom-faststart.m4a");
+    let mut tag = read_tag(target_file);
+    tag.set_title("faststart");
+    tag.write_with_path(target_file, &WriteConfig { faststart: true, ..WriteConfig::DEFAULT })
+        .unwrap();
+    let buf = fs::read(target_file).unwrap();
+    let wide = find_atom(&buf, &[b"wide"]).unwrap();
+    assert_eq!(wide.end, find_atom(&buf, &[b"mdat"]).unwrap().start);
+    let tag = read_tag(target_file);
+    assert_eq!(tag.audio_info(), sample.audio_info());
+    // the chapter track samples are read using the shifted chunk offsets
+    assert_eq!(tag.chapter_track(), sample.chapter_track());
 }